@@ -2,8 +2,16 @@ use colored::*;
 use siko_compiler::compiler::Compiler;
 use siko_compiler::compiler::CompilerInput;
 use siko_compiler::config::Config;
+use siko_compiler::debug_adapter::DebugAdapter;
+use siko_compiler::debugger::CommandLineDebugger;
+use siko_compiler::repl::Repl;
+use siko_doc::page::DocFormat;
+use siko_interpreter::limits;
+use siko_interpreter::limits::Limits;
+use siko_interpreter::limits::DEFAULT_MAX_DEPTH;
 use std::env;
 use std::path::Path;
+use std::thread;
 use walkdir::WalkDir;
 
 fn process_dir(arg: String, inputs: &mut Vec<CompilerInput>) -> bool {
@@ -36,13 +44,420 @@ fn process_dir(arg: String, inputs: &mut Vec<CompilerInput>) -> bool {
     true
 }
 
+fn print_limits_usage() {
+    println!("\t--fuel <n> stop after evaluating n expressions");
+    println!(
+        "\t--max-depth <n> stop when n function calls are nested, defaults to {}",
+        DEFAULT_MAX_DEPTH
+    );
+    println!("\t--max-heap <bytes> stop when the live values take about this much memory");
+}
+
 fn print_usage() {
     println!("arguments: OPTIONS FILENAME... ");
     println!("Options:");
     println!("\t-c <path> compile");
-    println!("\t-m measure durations");
+    println!("\t-m profile the program");
+    println!("\t-f <path> profile and write the folded call stacks to path");
     println!("\t-i visualize");
+    print_limits_usage();
+    println!("\t-s <path> path to std");
+    println!("arguments: fmt [--check] FILENAME...");
+    println!("\t--check only report the files that are not formatted");
+    println!("arguments: doc OPTIONS FILENAME...");
+    println!("\t-o <path> output directory, defaults to doc");
+    println!("\t-s <path> path to std");
+    println!("\t--html generate html instead of markdown");
+    println!("arguments: repl OPTIONS [FILENAME...]");
+    print_limits_usage();
+    println!("\t-s <path> path to std");
+    println!("arguments: debug OPTIONS FILENAME...");
+    print_limits_usage();
     println!("\t-s <path> path to std");
+    println!("arguments: cover OPTIONS FILENAME...");
+    println!("\t-o <path> output directory, defaults to coverage");
+    print_limits_usage();
+    println!("\t-s <path> path to std");
+    println!("arguments: dap");
+    println!("\tserve the debug adapter protocol on stdin/stdout");
+}
+
+// Reads the value of the limit option at index, false if it is not a number
+fn process_limit_arg(args: &[String], index: usize, limits: &mut Limits) -> bool {
+    let arg = args[index].as_str();
+    let limit = match args.get(index + 1).map(|limit| limit.parse::<u64>()) {
+        Some(Ok(limit)) => limit,
+        _ => {
+            eprintln!("{} expected a number after {}", "ERROR:".red(), arg);
+            return false;
+        }
+    };
+    match arg {
+        "--fuel" => limits.fuel = Some(limit),
+        "--max-depth" => limits.max_depth = limit as usize,
+        _ => limits.max_heap = Some(limit),
+    }
+    true
+}
+
+// Runs f on a thread with enough stack for the nested calls allowed by the
+// limits, the default stack is not enough for them
+fn run_with_stack(limits: &Limits, f: impl FnOnce() + Send + 'static) {
+    let stack_size = limits.get_stack_size();
+    let guarded = move || {
+        limits::set_stack_size(stack_size);
+        f()
+    };
+    let thread = match thread::Builder::new().stack_size(stack_size).spawn(guarded) {
+        Ok(thread) => thread,
+        Err(_) => {
+            eprintln!(
+                "{} cannot allocate {} bytes of stack for the call depth limit",
+                "ERROR:".red(),
+                stack_size
+            );
+            std::process::exit(1);
+        }
+    };
+    if thread.join().is_err() {
+        std::process::exit(1);
+    }
+}
+
+fn process_doc_args(args: &[String]) -> (Vec<CompilerInput>, String, DocFormat, bool) {
+    let mut inputs = Vec::new();
+    let mut success = true;
+    let mut std_path = format!("std");
+    let mut output_dir = format!("doc");
+    let mut format = DocFormat::Markdown;
+    let mut file_given = false;
+    let mut index = 0;
+    while index < args.len() {
+        let arg = args[index].as_ref();
+        match arg {
+            "-o" | "-s" => {
+                if index + 1 >= args.len() {
+                    eprintln!("{} missing path after {}", "ERROR:".red(), arg);
+                    success = false;
+                    break;
+                } else {
+                    if arg == "-o" {
+                        output_dir = args[index + 1].to_string();
+                    } else {
+                        std_path = args[index + 1].to_string();
+                    }
+                    index += 1;
+                }
+            }
+            "--html" => {
+                format = DocFormat::Html;
+            }
+            "-h" => {
+                success = false;
+            }
+            _ => {
+                file_given = true;
+                if !process_dir(arg.to_string(), &mut inputs) {
+                    success = false;
+                    break;
+                }
+            }
+        }
+        index += 1;
+    }
+    if !file_given {
+        if success {
+            eprintln!("no file given to document");
+        }
+        success = false;
+    }
+    if success && !process_dir(std_path, &mut inputs) {
+        success = false;
+    }
+    if !success {
+        print_usage();
+    }
+    (inputs, output_dir, format, success)
+}
+
+fn document(args: &[String]) {
+    let (inputs, output_dir, format, success) = process_doc_args(args);
+
+    if !success {
+        std::process::exit(1);
+    }
+
+    let mut compiler = Compiler::new(Config::new());
+
+    if let Err(e) = compiler.document(inputs, &output_dir, format) {
+        compiler.report_error(e);
+        std::process::exit(1);
+    }
+}
+
+fn process_fmt_args(args: &[String]) -> (Vec<CompilerInput>, bool, bool) {
+    let mut inputs = Vec::new();
+    let mut check = false;
+    let mut success = true;
+    let mut file_given = false;
+    for arg in args {
+        match arg.as_ref() {
+            "--check" => {
+                check = true;
+            }
+            "-h" => {
+                success = false;
+            }
+            _ => {
+                file_given = true;
+                if !process_dir(arg.to_string(), &mut inputs) {
+                    success = false;
+                    break;
+                }
+            }
+        }
+    }
+    if !file_given {
+        if success {
+            eprintln!("no file given to format");
+        }
+        success = false;
+    }
+    if !success {
+        print_usage();
+    }
+    (inputs, check, success)
+}
+
+fn format(args: &[String]) {
+    let (inputs, check, success) = process_fmt_args(args);
+
+    if !success {
+        std::process::exit(1);
+    }
+
+    let mut compiler = Compiler::new(Config::new());
+
+    match compiler.format(inputs, check) {
+        Ok(changed) => {
+            if check && !changed.is_empty() {
+                for file_path in changed {
+                    eprintln!("{} is not formatted", file_path.path.yellow());
+                }
+                std::process::exit(1);
+            }
+        }
+        Err(e) => {
+            compiler.report_error(e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn process_session_args(args: &[String]) -> (Vec<CompilerInput>, Vec<CompilerInput>, Limits, bool) {
+    let mut std_inputs = Vec::new();
+    let mut project_inputs = Vec::new();
+    let mut limits = Limits::new();
+    let mut success = true;
+    let mut std_path = format!("std");
+    let mut index = 0;
+    while index < args.len() {
+        let arg = args[index].as_ref();
+        match arg {
+            "-s" => {
+                if index + 1 >= args.len() {
+                    eprintln!("{} missing path after -s", "ERROR:".red(),);
+                    success = false;
+                    break;
+                } else {
+                    std_path = args[index + 1].to_string();
+                    index += 1;
+                }
+            }
+            "--fuel" | "--max-depth" | "--max-heap" => {
+                if !process_limit_arg(&args, index, &mut limits) {
+                    success = false;
+                    break;
+                }
+                index += 1;
+            }
+            "-h" => {
+                success = false;
+            }
+            _ => {
+                if !process_dir(arg.to_string(), &mut project_inputs) {
+                    success = false;
+                    break;
+                }
+            }
+        }
+        index += 1;
+    }
+    if success && !process_dir(std_path, &mut std_inputs) {
+        success = false;
+    }
+    if !success {
+        print_usage();
+    }
+    (std_inputs, project_inputs, limits, success)
+}
+
+fn repl(args: &[String]) {
+    let (std_inputs, project_inputs, limits, success) = process_session_args(args);
+
+    if !success {
+        std::process::exit(1);
+    }
+
+    run_with_stack(&limits.clone(), move || {
+        match Repl::new(std_inputs, project_inputs, limits) {
+            Ok(mut repl) => repl.run(),
+            Err(e) => {
+                Compiler::new(Config::new()).report_error(e);
+                std::process::exit(1);
+            }
+        }
+    });
+}
+
+fn debug(args: &[String]) {
+    let (std_inputs, mut inputs, limits, mut success) = process_session_args(args);
+
+    if success && inputs.is_empty() {
+        eprintln!("no file given to debug");
+        print_usage();
+        success = false;
+    }
+
+    if !success {
+        std::process::exit(1);
+    }
+
+    inputs.extend(std_inputs);
+
+    let mut config = Config::new();
+    config.limits = limits;
+
+    run_with_stack(&config.limits.clone(), move || {
+        let mut compiler = Compiler::new(config);
+
+        let result = compiler.check_program(inputs).and_then(|ir_program| {
+            compiler.debug(ir_program, Box::new(CommandLineDebugger::new()))
+        });
+        if let Err(e) = result {
+            compiler.report_error(e);
+            std::process::exit(1);
+        }
+    });
+}
+
+fn dap() {
+    run_with_stack(&Config::new().limits, serve_dap);
+}
+
+fn serve_dap() {
+    let mut adapter = DebugAdapter::new();
+
+    let launch = match adapter.wait_for_launch() {
+        Some(launch) => launch,
+        None => return,
+    };
+
+    let mut inputs = Vec::new();
+    if !process_dir(launch.program, &mut inputs) || !process_dir(launch.std, &mut inputs) {
+        adapter.fail_launch("program or std not found");
+        std::process::exit(1);
+    }
+
+    if !adapter.run(inputs) {
+        std::process::exit(1);
+    }
+}
+
+fn process_cover_args(args: &[String]) -> (Vec<CompilerInput>, Vec<String>, String, Limits, bool) {
+    let mut inputs = Vec::new();
+    let mut limits = Limits::new();
+    let mut success = true;
+    let mut std_path = format!("std");
+    let mut output_dir = format!("coverage");
+    let mut index = 0;
+    while index < args.len() {
+        let arg = args[index].as_ref();
+        match arg {
+            "-o" | "-s" => {
+                if index + 1 >= args.len() {
+                    eprintln!("{} missing path after {}", "ERROR:".red(), arg);
+                    success = false;
+                    break;
+                } else {
+                    if arg == "-o" {
+                        output_dir = args[index + 1].to_string();
+                    } else {
+                        std_path = args[index + 1].to_string();
+                    }
+                    index += 1;
+                }
+            }
+            "--fuel" | "--max-depth" | "--max-heap" => {
+                if !process_limit_arg(&args, index, &mut limits) {
+                    success = false;
+                    break;
+                }
+                index += 1;
+            }
+            "-h" => {
+                success = false;
+            }
+            _ => {
+                if !process_dir(arg.to_string(), &mut inputs) {
+                    success = false;
+                    break;
+                }
+            }
+        }
+        index += 1;
+    }
+    // only the given files are reported, std is not
+    let files: Vec<_> = inputs
+        .iter()
+        .filter_map(|input| match input {
+            CompilerInput::File { name } => Some(name.clone()),
+            CompilerInput::Memory { .. } => None,
+        })
+        .collect();
+    if files.is_empty() {
+        if success {
+            eprintln!("no file given to cover");
+        }
+        success = false;
+    }
+    if success && !process_dir(std_path, &mut inputs) {
+        success = false;
+    }
+    if !success {
+        print_usage();
+    }
+    (inputs, files, output_dir, limits, success)
+}
+
+fn cover(args: &[String]) {
+    let (inputs, files, output_dir, limits, success) = process_cover_args(args);
+
+    if !success {
+        std::process::exit(1);
+    }
+
+    let mut config = Config::new();
+    config.limits = limits;
+
+    run_with_stack(&config.limits.clone(), move || {
+        let mut compiler = Compiler::new(config);
+
+        if let Err(e) = compiler.cover(inputs, files, &output_dir) {
+            compiler.report_error(e);
+            std::process::exit(1);
+        }
+    });
 }
 
 fn process_args(args: Vec<String>) -> (Config, Vec<CompilerInput>, bool) {
@@ -70,9 +485,27 @@ fn process_args(args: Vec<String>) -> (Config, Vec<CompilerInput>, bool) {
             "-m" => {
                 config.measure_durations = true;
             }
+            "-f" => {
+                if index + 1 >= args.len() {
+                    eprintln!("{} missing path after -f", "ERROR:".red(),);
+                    success = false;
+                    break;
+                } else {
+                    config.measure_durations = true;
+                    config.folded_stacks = Some(args[index + 1].to_string());
+                    index += 1;
+                }
+            }
             "-i" => {
                 config.visualize = true;
             }
+            "--fuel" | "--max-depth" | "--max-heap" => {
+                if !process_limit_arg(&args, index, &mut config.limits) {
+                    success = false;
+                    break;
+                }
+                index += 1;
+            }
             "-s" => {
                 if index + 1 >= args.len() {
                     eprintln!("{} missing path after -s", "ERROR:".red(),);
@@ -117,16 +550,48 @@ fn process_args(args: Vec<String>) -> (Config, Vec<CompilerInput>, bool) {
 fn main() {
     let args: Vec<_> = env::args().skip(1).collect();
 
+    if args.first().map(|arg| arg.as_ref()) == Some("fmt") {
+        format(&args[1..]);
+        return;
+    }
+
+    if args.first().map(|arg| arg.as_ref()) == Some("doc") {
+        document(&args[1..]);
+        return;
+    }
+
+    if args.first().map(|arg| arg.as_ref()) == Some("repl") {
+        repl(&args[1..]);
+        return;
+    }
+
+    if args.first().map(|arg| arg.as_ref()) == Some("debug") {
+        debug(&args[1..]);
+        return;
+    }
+
+    if args.first().map(|arg| arg.as_ref()) == Some("cover") {
+        cover(&args[1..]);
+        return;
+    }
+
+    if args.first().map(|arg| arg.as_ref()) == Some("dap") {
+        dap();
+        return;
+    }
+
     let (config, inputs, success) = process_args(args);
 
     if !success {
         std::process::exit(1);
     }
 
-    let mut compiler = Compiler::new(config);
+    run_with_stack(&config.limits.clone(), move || {
+        let mut compiler = Compiler::new(config);
 
-    if let Err(e) = compiler.compile(inputs) {
-        compiler.report_error(e);
-        std::process::exit(1);
-    }
+        if let Err(e) = compiler.compile(inputs) {
+            compiler.report_error(e);
+            std::process::exit(1);
+        }
+    });
 }
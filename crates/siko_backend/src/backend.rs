@@ -1,3 +1,4 @@
+use crate::error::BackendError;
 use crate::function_queue::CallContext;
 use crate::function_queue::FunctionQueue;
 use crate::function_queue::FunctionQueueItem;
@@ -10,7 +11,17 @@ use siko_mir::program::Program as MirProgram;
 pub struct Backend {}
 
 impl Backend {
-    pub fn compile(ir_program: &mut IrProgram) -> Result<MirProgram, ()> {
+    pub fn compile(ir_program: &mut IrProgram) -> Result<MirProgram, Vec<BackendError>> {
+        // actors only run in the interpreter
+        let errors: Vec<_> = ir_program
+            .actors
+            .items
+            .values()
+            .map(|actor| BackendError::ActorNotSupported(actor.name.clone(), actor.location_id))
+            .collect();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
         let mut mir_program = MirProgram::new();
         let mut function_queue = FunctionQueue::new();
         let mut typedef_store = TypeDefStore::new();
@@ -6,6 +6,7 @@ use siko_mir::program::Program;
 use siko_mir::walker::walk_expr;
 use siko_mir::walker::Visitor;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
 enum VarRef {
@@ -16,6 +17,7 @@ enum VarRef {
 struct VarRefCollector<'a> {
     program: &'a mut Program,
     refs: BTreeMap<VarRef, Vec<ExprId>>,
+    guards: Vec<ExprId>,
 }
 
 impl<'a> Visitor for VarRefCollector<'a> {
@@ -29,9 +31,6 @@ impl<'a> Visitor for VarRefCollector<'a> {
                     .refs
                     .entry(VarRef::Arg(*index))
                     .or_insert_with(|| Vec::new());
-                if !refs.is_empty() {
-                    panic!("Arg ref {} used multiple times");
-                }
                 refs.push(expr_id);
             }
             Expr::ExprValue(_, pattern) => {
@@ -44,21 +43,41 @@ impl<'a> Visitor for VarRefCollector<'a> {
             _ => {}
         }
     }
-    fn visit_pattern(&mut self, _: PatternId, _: &Pattern) {}
+    fn visit_pattern(&mut self, _: PatternId, pattern: &Pattern) {
+        if let Pattern::Guarded(_, guard_expr_id) = pattern {
+            self.guards.push(*guard_expr_id);
+        }
+    }
 }
 
 pub fn insert_clone_pass(expr_id: &ExprId, program: &mut Program) {
     let mut collector = VarRefCollector {
         program: program,
         refs: BTreeMap::new(),
+        guards: Vec::new(),
     };
     walk_expr(expr_id, &mut collector);
     let refs = collector.refs;
-    for (_, exprs) in refs {
-        if exprs.len() == 1 {
-            continue;
+    let guards = collector.guards;
+    // guards only borrow the matched value, so every reference inside them must be cloned
+    let mut guard_refs = BTreeSet::new();
+    for guard_expr_id in guards {
+        let mut collector = VarRefCollector {
+            program: program,
+            refs: BTreeMap::new(),
+            guards: Vec::new(),
+        };
+        walk_expr(&guard_expr_id, &mut collector);
+        for (_, exprs) in collector.refs {
+            guard_refs.extend(exprs);
         }
+    }
+    for (_, exprs) in refs {
+        let single = exprs.len() == 1;
         for expr_id in exprs {
+            if single && !guard_refs.contains(&expr_id) {
+                continue;
+            }
             let location = program.exprs.get(&expr_id).location_id;
             let new_ref = program.exprs.get(&expr_id).item.clone();
             let ty = program.get_expr_type(&expr_id).clone();
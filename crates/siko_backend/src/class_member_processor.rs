@@ -108,12 +108,15 @@ pub fn generate_auto_derived_instance_member(
                         type_signature: None,
                         module: adt.module.clone(),
                         name: format!("{:?}", derived_class),
+                        constraints: Vec::new(),
+                        doc: None,
                     };
                     let function_info = FunctionInfo::NamedFunction(info);
                     let function = IrFunction {
                         id: function_id,
                         arg_count: arg_count,
                         arg_locations: vec![location],
+                        arg_names: vec![None],
                         info: function_info,
                     };
                     ir_program.function_types.insert(function_id, function_type);
@@ -186,12 +189,15 @@ pub fn generate_auto_derived_instance_member(
                         type_signature: None,
                         module: record.module.clone(),
                         name: format!("{:?}", derived_class),
+                        constraints: Vec::new(),
+                        doc: None,
                     };
                     let function_info = FunctionInfo::NamedFunction(info);
                     let function = IrFunction {
                         id: function_id,
                         arg_count: arg_count,
                         arg_locations: vec![location],
+                        arg_names: vec![None],
                         info: function_info,
                     };
                     ir_program.function_types.insert(function_id, function_type);
@@ -0,0 +1,6 @@
+use siko_location_info::location_id::LocationId;
+
+#[derive(Debug)]
+pub enum BackendError {
+    ActorNotSupported(String, LocationId),
+}
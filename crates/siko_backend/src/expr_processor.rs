@@ -326,6 +326,7 @@ pub fn process_expr(
                 .collect();
             MirExpr::RecordUpdate(mir_receiver_expr_id, mir_updates)
         }
+        IrExpr::Hole(_) => unreachable!(),
         IrExpr::StaticFunctionCall(func_id, args) => {
             let mut arg_types: Vec<_> = args
                 .iter()
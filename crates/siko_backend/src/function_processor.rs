@@ -4,6 +4,7 @@ use crate::function_queue::FunctionQueueItem;
 use crate::type_processor::process_type;
 use crate::typedef_store::TypeDefStore;
 use crate::util::get_call_unifier;
+use crate::util::mangle_operator_name;
 use crate::util::preprocess_ir;
 use siko_ir::data::TypeDef;
 use siko_ir::function::FunctionId as IrFunctionId;
@@ -126,7 +127,11 @@ pub fn process_function(
                 MirFunctionInfo::Extern(info.name.clone())
             };
             let mir_function = MirFunction {
-                name: format!("{}_{}", info.name, mir_function_id.id),
+                name: format!(
+                    "{}_{}",
+                    mangle_operator_name(&info.name),
+                    mir_function_id.id
+                ),
                 module: info.module.clone(),
                 info: mir_function_info,
                 arg_count: function.arg_count,
@@ -150,10 +155,11 @@ pub fn process_function(
                 &mut expr_id_map,
                 &mut pattern_id_map,
             );
-            let lambda_name = format!("{}", info);
+            let lambda_name = format!("{}_{}", info, mir_function_id.id);
             let lambda_name = lambda_name.replace("/", "_");
             let lambda_name = lambda_name.replace(".", "_");
             let lambda_name = lambda_name.replace("#", "_");
+            let lambda_name = mangle_operator_name(&lambda_name);
             let mir_function = MirFunction {
                 name: lambda_name,
                 module: info.module.clone(),
@@ -165,20 +171,46 @@ pub fn process_function(
                 .functions
                 .add_item(mir_function_id, mir_function);
         }
+        FunctionInfo::LocalFunction(info) => {
+            preprocess_ir(info.body, ir_program);
+            let mut expr_id_map = BTreeMap::new();
+            let mut pattern_id_map = BTreeMap::new();
+            let mir_body = process_expr(
+                &info.body,
+                ir_program,
+                mir_program,
+                &call_unifier,
+                function_queue,
+                typedef_store,
+                &mut expr_id_map,
+                &mut pattern_id_map,
+            );
+            let local_function_name = format!("{}_{}", info, mir_function_id.id);
+            let local_function_name = local_function_name.replace("/", "_");
+            let local_function_name = local_function_name.replace(".", "_");
+            let local_function_name = local_function_name.replace("#", "_");
+            let local_function_name = mangle_operator_name(&local_function_name);
+            let mir_function = MirFunction {
+                name: local_function_name,
+                module: info.module.clone(),
+                function_type: mir_function_type,
+                arg_count: function.arg_count,
+                info: MirFunctionInfo::Normal(mir_body),
+            };
+            mir_program
+                .functions
+                .add_item(mir_function_id, mir_function);
+        }
         FunctionInfo::VariantConstructor(info) => {
             let adt = ir_program.typedefs.get(&info.type_id).get_adt();
             let variant = &adt.variants[info.index];
             let module = adt.module.clone();
             let result_ty = function_type.get_result_type(function.arg_count);
             let mir_typedef_id = typedef_store.add_type(result_ty, ir_program, mir_program);
-            let name = if adt.type_args.is_empty() {
-                format!("{}_{}_ctor{}", adt.name, variant.name, info.index)
-            } else {
-                format!(
-                    "{}_{}_ctor{}_{}",
-                    adt.name, variant.name, info.index, mir_typedef_id.id
-                )
-            };
+            let name = format!(
+                "{}_{}_ctor{}_{}",
+                adt.name, variant.name, info.index, mir_function_id.id
+            );
             let mir_function = MirFunction {
                 name: name,
                 module: module,
@@ -190,6 +222,9 @@ pub fn process_function(
                 .functions
                 .add_item(mir_function_id, mir_function);
         }
+        FunctionInfo::ActorConstructor(_) => {
+            unreachable!("Actors are rejected by the backend")
+        }
         FunctionInfo::RecordConstructor(info) => {
             let record = ir_program.typedefs.get(&info.type_id).get_record();
             let module = record.module.clone();
@@ -1,6 +1,7 @@
 pub mod backend;
 pub mod backend_passes;
 pub mod class_member_processor;
+pub mod error;
 pub mod expr_processor;
 pub mod format_rewriter;
 pub mod function_processor;
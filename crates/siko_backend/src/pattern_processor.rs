@@ -56,6 +56,51 @@ pub fn process_pattern(
         IrPattern::IntegerLiteral(v) => MirPattern::IntegerLiteral(v.clone()),
         IrPattern::CharLiteral(v) => MirPattern::CharLiteral(v.clone()),
         IrPattern::CharRange(start, end) => MirPattern::CharRange(start.clone(), end.clone()),
+        IrPattern::IntegerRange(start, end) => MirPattern::IntegerRange(*start, *end),
+        IrPattern::FloatLiteral(v) => MirPattern::FloatLiteral(*v),
+        IrPattern::List(items, rest) => {
+            let mir_items: Vec<_> = items
+                .iter()
+                .map(|item| {
+                    process_pattern(
+                        item,
+                        ir_program,
+                        mir_program,
+                        unifier,
+                        function_queue,
+                        typedef_store,
+                        expr_id_map,
+                        pattern_id_map,
+                    )
+                })
+                .collect();
+            let mir_rest = rest.map(|rest| {
+                process_pattern(
+                    &rest,
+                    ir_program,
+                    mir_program,
+                    unifier,
+                    function_queue,
+                    typedef_store,
+                    expr_id_map,
+                    pattern_id_map,
+                )
+            });
+            MirPattern::List(mir_items, mir_rest)
+        }
+        IrPattern::As(sub, name) => {
+            let mir_sub = process_pattern(
+                sub,
+                ir_program,
+                mir_program,
+                unifier,
+                function_queue,
+                typedef_store,
+                expr_id_map,
+                pattern_id_map,
+            );
+            MirPattern::As(mir_sub, name.clone())
+        }
         IrPattern::Record(_, items) => {
             let mir_typedef_id = typedef_store.add_type(ir_pattern_ty, ir_program, mir_program);
             let mir_items: Vec<_> = items
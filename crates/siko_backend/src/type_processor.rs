@@ -13,6 +13,7 @@ pub fn process_type(
     match ir_type {
         IrType::FixedTypeArg(..) => unreachable!(),
         IrType::Var(..) => unreachable!(),
+        IrType::Application(..) => unreachable!(),
         IrType::Function(from, to) => {
             let from = process_type(from, typedef_store, ir_program, mir_program);
             let to = process_type(to, typedef_store, ir_program, mir_program);
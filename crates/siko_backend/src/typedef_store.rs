@@ -109,6 +109,7 @@ impl TypeDefStore {
                                 module: ir_adt.module.clone(),
                                 name: format!("{}_{}", ir_adt.name, mir_typedef_id.id),
                                 variants: variants,
+                                newtype: ir_adt.newtype,
                             };
                             let mir_typedef = MirTypeDef::Adt(mir_adt);
                             mir_program.typedefs.add_item(mir_typedef_id, mir_typedef);
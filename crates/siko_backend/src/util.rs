@@ -44,3 +44,57 @@ pub fn preprocess_ir(body: IrExprId, ir_program: &mut IrProgram) {
     let mut rewriter = FormatRewriter::new(ir_program);
     walk_expr(&body, &mut rewriter);
 }
+
+fn get_operator_char_name(c: char) -> Option<&'static str> {
+    let name = match c {
+        '<' => "lt",
+        '>' => "gt",
+        '=' => "eq",
+        '+' => "plus",
+        '-' => "minus",
+        '*' => "star",
+        '/' => "slash",
+        '!' => "bang",
+        '&' => "amp",
+        '|' => "bar",
+        '.' => "dot",
+        ':' => "colon",
+        '^' => "caret",
+        '$' => "dollar",
+        '~' => "tilde",
+        '?' => "question",
+        '%' => "percent",
+        '\\' => "backslash",
+        _ => return None,
+    };
+    Some(name)
+}
+
+// User defined operators cannot be used as rust identifiers as is, every operator character is
+// replaced by its name, e.g. <+> becomes op_lt_plus_gt
+pub fn mangle_operator_name(name: &str) -> String {
+    let mut result = String::new();
+    let mut prev_operator = false;
+    for c in name.chars() {
+        match get_operator_char_name(c) {
+            Some(char_name) => {
+                if result.is_empty() {
+                    result.push_str("op");
+                }
+                if !result.ends_with('_') {
+                    result.push('_');
+                }
+                result.push_str(char_name);
+                prev_operator = true;
+            }
+            None => {
+                if prev_operator && c != '_' {
+                    result.push('_');
+                }
+                result.push(c);
+                prev_operator = false;
+            }
+        }
+    }
+    result
+}
@@ -1,54 +1,54 @@
 use crate::config::Config;
+use crate::coverage::CoverageReport;
 use crate::error::Error;
 use siko_backend::backend::Backend;
+use siko_doc::generator::DocGenerator;
+use siko_doc::page::DocFormat;
+use siko_formatter::error::FormatterError;
+use siko_formatter::formatter::format_source;
+use siko_interpreter::debugger::DebugFrontend;
 use siko_interpreter::interpreter::Interpreter;
+use siko_ir::program::Program as IrProgram;
 use siko_location_info::error_context::ErrorContext;
 use siko_location_info::file_manager::FileManager;
+use siko_location_info::file_manager::LineOrigin;
 use siko_location_info::filepath::FilePath;
 use siko_location_info::location_info::LocationInfo;
 use siko_name_resolver::resolver::Resolver;
+use siko_parser::doc_comment::DocComments;
+use siko_parser::fixity::FixityTable;
 use siko_parser::lexer::Lexer;
 use siko_parser::parser::Parser;
+use siko_parser::token::TokenInfo;
 use siko_syntax::program::Program;
 use siko_transpiler::transpiler::Transpiler;
 use siko_type_checker::typechecker::Typechecker;
+use std::collections::BTreeMap;
+use std::path::Path;
 
+#[derive(Clone)]
 pub enum CompilerInput {
-    File {
-        name: String,
-    },
-    #[allow(unused)]
-    Memory {
-        name: String,
-        content: String,
-    },
+    File { name: String },
+    Memory { name: String, content: String },
 }
 
-fn parse(
-    content: &str,
-    file_path: FilePath,
-    program: &mut Program,
-    location_info: &mut LocationInfo,
-) -> Result<(), Error> {
+fn lex(content: &str, file_path: FilePath) -> Result<(Vec<TokenInfo>, DocComments), Error> {
     //println!("Compiling {}", file_path.path);
-    let mut lexer = Lexer::new(content, file_path.clone());
+    let mut lexer = Lexer::new(content, file_path);
     let mut errors = Vec::new();
-    let tokens = match lexer.process(&mut errors) {
+    match lexer.process(&mut errors) {
         Ok(tokens) => {
             if errors.is_empty() {
-                tokens
+                Ok((tokens, lexer.take_doc_comments()))
             } else {
-                return Err(Error::LexerError(errors));
+                Err(Error::LexerError(errors))
             }
         }
         Err(e) => {
             errors.push(e);
-            return Err(Error::LexerError(errors));
+            Err(Error::LexerError(errors))
         }
-    };
-    let mut parser = Parser::new(file_path, &tokens[..], program, location_info);
-    parser.parse()?;
-    Ok(())
+    }
 }
 
 pub struct Compiler {
@@ -66,8 +66,11 @@ impl Compiler {
         }
     }
 
-    pub fn compile(&mut self, inputs: Vec<CompilerInput>) -> Result<(), Error> {
-        let mut program = Program::new();
+    pub fn add_line_origins(&mut self, file_path: FilePath, origins: BTreeMap<usize, LineOrigin>) {
+        self.file_manager.add_line_origins(file_path, origins);
+    }
+
+    fn read_inputs(&mut self, inputs: Vec<CompilerInput>) -> Result<(), Error> {
         for input in inputs.iter() {
             match input {
                 CompilerInput::File { name } => {
@@ -79,34 +82,188 @@ impl Compiler {
                 }
             }
         }
+        Ok(())
+    }
+
+    fn parse(&mut self) -> Result<Program, Error> {
+        let mut program = Program::new();
+        let mut token_streams = Vec::new();
+        let mut lexer_errors = Vec::new();
         for (file_path, content) in self.file_manager.files.iter() {
-            parse(
-                content,
-                file_path.clone(),
+            match lex(content, file_path.clone()) {
+                Ok((tokens, doc_comments)) => {
+                    token_streams.push((file_path.clone(), tokens, doc_comments))
+                }
+                Err(Error::LexerError(errors)) => lexer_errors.extend(errors),
+                Err(err) => return Err(err),
+            }
+        }
+        if !lexer_errors.is_empty() {
+            return Err(Error::LexerError(lexer_errors));
+        }
+        let mut parse_errors = Vec::new();
+        // fixity declarations are global, they must be known before parsing any expression
+        let mut fixity_table = FixityTable::new();
+        for (file_path, tokens, _) in token_streams.iter() {
+            if let Err(err) = fixity_table.collect(&tokens[..], file_path) {
+                parse_errors.push(err);
+            }
+        }
+        for (file_path, tokens, doc_comments) in token_streams {
+            let mut parser = Parser::new(
+                file_path,
+                &tokens[..],
                 &mut program,
                 &mut self.location_info,
-            )?;
+                &fixity_table,
+                &doc_comments,
+            );
+            if let Err(errors) = parser.parse() {
+                parse_errors.extend(errors);
+            }
         }
+        if !parse_errors.is_empty() {
+            return Err(Error::ParseError(parse_errors));
+        }
+        Ok(program)
+    }
+
+    fn check(
+        &mut self,
+        inputs: Vec<CompilerInput>,
+        typechecker: Typechecker,
+    ) -> Result<(IrProgram, Resolver), Error> {
+        self.read_inputs(inputs)?;
+        let program = self.parse()?;
 
         let mut resolver = Resolver::new();
         let mut ir_program = resolver.resolve(&program)?;
 
-        let typechecker = Typechecker::new();
-
         typechecker.check(&mut ir_program)?;
 
+        Ok((ir_program, resolver))
+    }
+
+    // Checks the modules of a REPL session, they have no main function
+    pub fn check_session(&mut self, inputs: Vec<CompilerInput>) -> Result<IrProgram, Error> {
+        let (ir_program, _) = self.check(inputs, Typechecker::without_main())?;
+        Ok(ir_program)
+    }
+
+    // Checks a program that is going to be run, it must have a main function
+    pub fn check_program(&mut self, inputs: Vec<CompilerInput>) -> Result<IrProgram, Error> {
+        let (ir_program, _) = self.check(inputs, Typechecker::new())?;
+        Ok(ir_program)
+    }
+
+    pub fn debug(
+        &self,
+        ir_program: IrProgram,
+        frontend: Box<dyn DebugFrontend>,
+    ) -> Result<(), Error> {
+        Interpreter::debug(
+            ir_program,
+            self.location_info.clone(),
+            frontend,
+            self.config.limits.clone(),
+        )
+        .map_err(Error::RuntimeError)?;
+        Ok(())
+    }
+
+    pub fn compile(&mut self, inputs: Vec<CompilerInput>) -> Result<(), Error> {
+        let (mut ir_program, _) = self.check(inputs, Typechecker::new())?;
+
         if let Some(compile_target) = &self.config.compile {
-            let mir_program = Backend::compile(&mut ir_program);
-            let mir_program = mir_program.expect("TODO");
+            let mir_program = Backend::compile(&mut ir_program).map_err(Error::BackendError)?;
             Transpiler::process(&mir_program, compile_target).expect("Transpiler failed");
+        } else if self.config.measure_durations {
+            let (result, profile) = Interpreter::profile(ir_program, self.config.limits.clone());
+            eprint!("{}", profile.format_table());
+            if let Some(path) = &self.config.folded_stacks {
+                std::fs::write(path, profile.format_folded_stacks())?;
+            }
+            result.map_err(Error::RuntimeError)?;
         } else {
-            Interpreter::run(ir_program, self.context());
+            Interpreter::run(ir_program, self.config.limits.clone())
+                .map_err(Error::RuntimeError)?;
         }
 
         //println!("Result {}", value);
         Ok(())
     }
 
+    // Runs the program counting the evaluations of its expressions, the
+    // coverage of the given files is shown and written into output_dir
+    pub fn cover(
+        &mut self,
+        inputs: Vec<CompilerInput>,
+        files: Vec<String>,
+        output_dir: &str,
+    ) -> Result<(), Error> {
+        let ir_program = self.check_program(inputs)?;
+        let (result, data) = Interpreter::cover(ir_program, self.config.limits.clone());
+        let report = CoverageReport::new(&data, &self.location_info, &self.file_manager, &files);
+        report.print();
+        report.write(output_dir)?;
+        result.map_err(Error::RuntimeError)?;
+        Ok(())
+    }
+
+    // Writes one documentation page per module and an index page into output_dir
+    pub fn document(
+        &mut self,
+        inputs: Vec<CompilerInput>,
+        output_dir: &str,
+        format: DocFormat,
+    ) -> Result<(), Error> {
+        let (ir_program, resolver) = self.check(inputs, Typechecker::new())?;
+        let generator = DocGenerator::new(&ir_program, resolver.get_modules());
+        std::fs::create_dir_all(output_dir)?;
+        for (filename, content) in generator.generate(format) {
+            let path = Path::new(output_dir).join(filename);
+            std::fs::write(path, content)?;
+        }
+        Ok(())
+    }
+
+    // Returns the files whose content differs from the canonical format.
+    // Unless check is set, these files are rewritten in place.
+    pub fn format(
+        &mut self,
+        inputs: Vec<CompilerInput>,
+        check: bool,
+    ) -> Result<Vec<FilePath>, Error> {
+        self.read_inputs(inputs)?;
+        let mut lexer_errors = Vec::new();
+        let mut changed = Vec::new();
+        for (file_path, content) in self.file_manager.files.iter() {
+            match format_source(content, file_path) {
+                Ok(formatted) => {
+                    if formatted != *content {
+                        changed.push((file_path.clone(), formatted));
+                    }
+                }
+                Err(FormatterError::LexerError(errors)) => lexer_errors.extend(errors),
+                Err(FormatterError::MeaningChanged(file_path)) => {
+                    return Err(Error::FormatError(file_path));
+                }
+            }
+        }
+        if !lexer_errors.is_empty() {
+            return Err(Error::LexerError(lexer_errors));
+        }
+        if !check {
+            for (file_path, formatted) in changed.iter() {
+                std::fs::write(&file_path.path, formatted)?;
+            }
+        }
+        Ok(changed
+            .into_iter()
+            .map(|(file_path, _)| file_path)
+            .collect())
+    }
+
     fn context(&self) -> ErrorContext {
         ErrorContext {
             file_manager: self.file_manager.clone(),
@@ -1,15 +1,21 @@
+use siko_interpreter::limits::Limits;
+
 pub struct Config {
     pub measure_durations: bool,
+    pub folded_stacks: Option<String>,
     pub visualize: bool,
     pub compile: Option<String>,
+    pub limits: Limits,
 }
 
 impl Config {
     pub fn new() -> Config {
         Config {
             measure_durations: false,
+            folded_stacks: None,
             visualize: false,
             compile: None,
+            limits: Limits::new(),
         }
     }
 }
@@ -0,0 +1,322 @@
+use colored::*;
+use siko_interpreter::coverage::CoverageData;
+use siko_location_info::file_manager::FileManager;
+use siko_location_info::filepath::FilePath;
+use siko_location_info::location_info::LocationInfo;
+use std::collections::BTreeMap;
+use std::io::Error as IoError;
+use std::path::Path;
+
+fn escape_html(s: &str) -> String {
+    let mut result = String::new();
+    for c in s.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+fn percent(hit: usize, found: usize) -> String {
+    if found == 0 {
+        format!("-")
+    } else {
+        format!("{:.1}%", hit as f64 * 100.0 / found as f64)
+    }
+}
+
+#[derive(Default)]
+struct LineCoverage {
+    // the most times an expression starting on this line was evaluated, lines
+    // without a starting expression are not counted
+    count: Option<u64>,
+    // character ranges of the expressions never evaluated
+    uncovered: Vec<(usize, usize)>,
+}
+
+struct Branch {
+    // starts from 0
+    line: usize,
+    count: u64,
+    arms: Vec<u64>,
+}
+
+struct FileCoverage {
+    path: String,
+    source: Vec<String>,
+    // the lines having an expression on them, they start from 0
+    lines: BTreeMap<usize, LineCoverage>,
+    branches: Vec<Branch>,
+}
+
+impl FileCoverage {
+    fn get_line_counts(&self) -> (usize, usize) {
+        let counts = self.lines.values().filter_map(|line| line.count);
+        let hit = counts.clone().filter(|count| *count > 0).count();
+        (hit, counts.count())
+    }
+
+    fn get_branch_counts(&self) -> (usize, usize) {
+        let arms = self.branches.iter().flat_map(|branch| branch.arms.iter());
+        let hit = arms.clone().filter(|count| **count > 0).count();
+        (hit, arms.count())
+    }
+
+    // Splits a line into parts, the flag of a part is set if it was not evaluated
+    fn split_line(&self, index: usize) -> Vec<(String, bool)> {
+        let chars: Vec<_> = self.source[index].chars().collect();
+        let mut uncovered = vec![false; chars.len()];
+        if let Some(line) = self.lines.get(&index) {
+            if line.count == Some(0) {
+                uncovered = vec![true; chars.len()];
+            }
+            for (start, end) in &line.uncovered {
+                for flag in uncovered.iter_mut().take(*end).skip(*start) {
+                    *flag = true;
+                }
+            }
+        }
+        let mut parts: Vec<(String, bool)> = Vec::new();
+        for (c, flag) in chars.into_iter().zip(uncovered.into_iter()) {
+            match parts.last_mut() {
+                Some((part, last_flag)) if *last_flag == flag => part.push(c),
+                _ => parts.push((c.to_string(), flag)),
+            }
+        }
+        parts
+    }
+
+    fn get_html_name(&self) -> String {
+        format!(
+            "{}.html",
+            self.path.trim_start_matches("./").replace('/', "_")
+        )
+    }
+}
+
+// Source lines annotated with the number of evaluations of the expressions
+// on them, in the terminal, in html and in the LCOV format
+pub struct CoverageReport {
+    files: Vec<FileCoverage>,
+}
+
+impl CoverageReport {
+    pub fn new(
+        data: &CoverageData,
+        location_info: &LocationInfo,
+        file_manager: &FileManager,
+        files: &Vec<String>,
+    ) -> CoverageReport {
+        let mut coverages: BTreeMap<String, FileCoverage> = BTreeMap::new();
+        for file in files {
+            let source = file_manager.content(&FilePath::new(file.clone()));
+            coverages.insert(
+                file.clone(),
+                FileCoverage {
+                    path: file.clone(),
+                    source: source.lines().map(|line| line.to_string()).collect(),
+                    lines: BTreeMap::new(),
+                    branches: Vec::new(),
+                },
+            );
+        }
+        for expr in &data.exprs {
+            let location_set = location_info.get_item_location(&expr.location_id);
+            if let Some(coverage) = coverages.get_mut(&location_set.file_path.path) {
+                let first = location_set.lines.keys().next().cloned();
+                for (index, ranges) in &location_set.lines {
+                    let line = coverage.lines.entry(*index).or_default();
+                    if Some(*index) == first {
+                        line.count = Some(std::cmp::max(line.count.unwrap_or(0), expr.count));
+                    }
+                    if expr.count == 0 {
+                        line.uncovered
+                            .extend(ranges.iter().map(|range| (range.start, range.end)));
+                    }
+                }
+            }
+        }
+        for branch in &data.branches {
+            let location_set = location_info.get_item_location(&branch.location_id);
+            if let Some(coverage) = coverages.get_mut(&location_set.file_path.path) {
+                coverage.branches.push(Branch {
+                    line: location_set.lines.keys().next().cloned().unwrap_or(0),
+                    count: branch.count,
+                    arms: branch.arms.iter().map(|arm| arm.count).collect(),
+                });
+            }
+        }
+        for coverage in coverages.values_mut() {
+            coverage.branches.sort_by_key(|branch| branch.line);
+        }
+        CoverageReport {
+            files: coverages
+                .into_iter()
+                .map(|(_, coverage)| coverage)
+                .collect(),
+        }
+    }
+
+    pub fn print(&self) {
+        for file in &self.files {
+            println!("{}{}", "-- ".blue(), file.path.green());
+            for index in 0..file.source.len() {
+                let count = match file.lines.get(&index).and_then(|line| line.count) {
+                    Some(count) => format!("{}", count),
+                    None => String::new(),
+                };
+                print!("{:>8} {} ", count, "|".blue());
+                for (part, uncovered) in file.split_line(index) {
+                    if uncovered {
+                        print!("{}", part.red());
+                    } else {
+                        print!("{}", part);
+                    }
+                }
+                println!();
+            }
+        }
+        println!("Coverage:");
+        for file in &self.files {
+            let (lines_hit, lines_found) = file.get_line_counts();
+            let (branches_hit, branches_found) = file.get_branch_counts();
+            println!(
+                "    {} lines {}/{} ({}) branches {}/{} ({})",
+                file.path.green(),
+                lines_hit,
+                lines_found,
+                percent(lines_hit, lines_found),
+                branches_hit,
+                branches_found,
+                percent(branches_hit, branches_found)
+            );
+        }
+    }
+
+    pub fn format_lcov(&self) -> String {
+        let mut lcov = String::new();
+        for file in &self.files {
+            let path = match std::fs::canonicalize(&file.path) {
+                Ok(path) => format!("{}", path.display()),
+                Err(_) => file.path.clone(),
+            };
+            lcov += &format!("TN:\nSF:{}\n", path);
+            for (block, branch) in file.branches.iter().enumerate() {
+                for (index, count) in branch.arms.iter().enumerate() {
+                    // - marks the branches that were never reached
+                    let taken = if branch.count == 0 {
+                        format!("-")
+                    } else {
+                        format!("{}", count)
+                    };
+                    lcov += &format!("BRDA:{},{},{},{}\n", branch.line + 1, block, index, taken);
+                }
+            }
+            let (branches_hit, branches_found) = file.get_branch_counts();
+            lcov += &format!("BRF:{}\nBRH:{}\n", branches_found, branches_hit);
+            for (index, line) in &file.lines {
+                if let Some(count) = line.count {
+                    lcov += &format!("DA:{},{}\n", index + 1, count);
+                }
+            }
+            let (lines_hit, lines_found) = file.get_line_counts();
+            lcov += &format!("LF:{}\nLH:{}\n", lines_found, lines_hit);
+            lcov += "end_of_record\n";
+        }
+        lcov
+    }
+
+    fn format_html_page(title: &str, body: Vec<String>) -> String {
+        let mut lines = Vec::new();
+        lines.push(format!("<!DOCTYPE html>"));
+        lines.push(format!("<html>"));
+        lines.push(format!(
+            "<head><meta charset=\"utf-8\"><title>{}</title>",
+            escape_html(title)
+        ));
+        lines.push(format!(
+            "<style>.count {{ color: gray; text-align: right; padding-right: 1em }} \
+             .uncovered {{ background-color: #fcc }} .covered {{ background-color: #cfc }} \
+             td {{ font-family: monospace; white-space: pre }}</style></head>"
+        ));
+        lines.push(format!("<body>"));
+        lines.push(format!("<h1>{}</h1>", escape_html(title)));
+        lines.extend(body);
+        lines.push(format!("</body>"));
+        lines.push(format!("</html>"));
+        lines.push(String::new());
+        lines.join("\n")
+    }
+
+    fn format_html_index(&self) -> String {
+        let mut body = Vec::new();
+        body.push(format!(
+            "<table><tr><th>File</th><th>Lines</th><th>Branches</th></tr>"
+        ));
+        for file in &self.files {
+            let (lines_hit, lines_found) = file.get_line_counts();
+            let (branches_hit, branches_found) = file.get_branch_counts();
+            body.push(format!(
+                "<tr><td><a href=\"{}\">{}</a></td><td>{}/{} ({})</td><td>{}/{} ({})</td></tr>",
+                escape_html(&file.get_html_name()),
+                escape_html(&file.path),
+                lines_hit,
+                lines_found,
+                percent(lines_hit, lines_found),
+                branches_hit,
+                branches_found,
+                percent(branches_hit, branches_found)
+            ));
+        }
+        body.push(format!("</table>"));
+        CoverageReport::format_html_page("Coverage", body)
+    }
+
+    fn format_html_file(file: &FileCoverage) -> String {
+        let mut body = Vec::new();
+        body.push(format!("<table>"));
+        for index in 0..file.source.len() {
+            let line = file.lines.get(&index);
+            let (count, class) = match line.and_then(|line| line.count) {
+                Some(0) => (format!("0"), " class=\"uncovered\""),
+                Some(count) if line.map(|line| line.uncovered.is_empty()) == Some(true) => {
+                    (format!("{}", count), " class=\"covered\"")
+                }
+                Some(count) => (format!("{}", count), ""),
+                None => (String::new(), ""),
+            };
+            let mut code = String::new();
+            for (part, uncovered) in file.split_line(index) {
+                // lines not evaluated at all are marked as a whole
+                if uncovered && class.is_empty() {
+                    code += &format!("<span class=\"uncovered\">{}</span>", escape_html(&part));
+                } else {
+                    code += &escape_html(&part);
+                }
+            }
+            body.push(format!(
+                "<tr><td class=\"count\">{}</td><td{}>{}</td></tr>",
+                count, class, code
+            ));
+        }
+        body.push(format!("</table>"));
+        CoverageReport::format_html_page(&file.path, body)
+    }
+
+    // Writes lcov.info and the html pages into output_dir
+    pub fn write(&self, output_dir: &str) -> Result<(), IoError> {
+        std::fs::create_dir_all(output_dir)?;
+        let dir = Path::new(output_dir);
+        std::fs::write(dir.join("lcov.info"), self.format_lcov())?;
+        std::fs::write(dir.join("index.html"), self.format_html_index())?;
+        for file in &self.files {
+            let content = CoverageReport::format_html_file(file);
+            std::fs::write(dir.join(file.get_html_name()), content)?;
+        }
+        Ok(())
+    }
+}
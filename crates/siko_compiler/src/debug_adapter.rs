@@ -0,0 +1,513 @@
+use crate::compiler::Compiler;
+use crate::compiler::CompilerInput;
+use crate::config::Config;
+use crate::json::Json;
+use siko_interpreter::debugger::Breakpoints;
+use siko_interpreter::debugger::DebugFrontend;
+use siko_interpreter::debugger::Resume;
+use siko_interpreter::debugger::Stop;
+use siko_interpreter::debugger::StopReason;
+use siko_interpreter::debugger::Variable;
+use std::io;
+use std::io::BufRead;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+
+// The interpreter runs a single thread
+const THREAD_ID: i64 = 1;
+const ARGUMENTS_REFERENCE: i64 = 1;
+const LOCALS_REFERENCE: i64 = 2;
+
+pub struct LaunchArgs {
+    pub program: String,
+    pub std: String,
+}
+
+fn number(n: i64) -> Json {
+    Json::Number(n as f64)
+}
+
+fn get_arg<'a>(request: &'a Json, name: &str) -> Option<&'a Json> {
+    request.get("arguments").and_then(|args| args.get(name))
+}
+
+fn variables_to_json(variables: &Vec<Variable>) -> Json {
+    Json::Array(
+        variables
+            .iter()
+            .map(|variable| {
+                Json::object(vec![
+                    ("name", Json::string(&variable.name)),
+                    ("value", Json::string(&variable.value)),
+                    ("type", Json::string(&variable.ty)),
+                    ("variablesReference", number(0)),
+                ])
+            })
+            .collect(),
+    )
+}
+
+fn source_to_json(file: &str) -> Json {
+    let path = match std::fs::canonicalize(file) {
+        Ok(path) => format!("{}", path.display()),
+        Err(_) => file.to_string(),
+    };
+    let name = Path::new(file)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| file.to_string());
+    Json::object(vec![
+        ("name", Json::String(name)),
+        ("path", Json::String(path)),
+    ])
+}
+
+struct Connection {
+    input: io::BufReader<io::Stdin>,
+    seq: i64,
+}
+
+impl Connection {
+    fn new() -> Connection {
+        Connection {
+            input: io::BufReader::new(io::stdin()),
+            seq: 1,
+        }
+    }
+
+    // Returns None if the client closed the connection
+    fn read(&mut self) -> Option<Json> {
+        let mut length = None;
+        loop {
+            let mut header = String::new();
+            if self.input.read_line(&mut header).ok()? == 0 {
+                return None;
+            }
+            let header = header.trim();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(value) = header.strip_prefix("Content-Length:") {
+                length = value.trim().parse().ok();
+            }
+        }
+        let mut body = vec![0; length?];
+        self.input.read_exact(&mut body).ok()?;
+        Json::parse(&String::from_utf8_lossy(&body))
+    }
+
+    fn send(&mut self, kind: &str, mut items: Vec<(&str, Json)>) {
+        items.insert(0, ("seq", number(self.seq)));
+        items.insert(1, ("type", Json::string(kind)));
+        self.seq += 1;
+        let message = format!("{}", Json::object(items));
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        write!(
+            stdout,
+            "Content-Length: {}\r\n\r\n{}",
+            message.len(),
+            message
+        )
+        .and_then(|_| stdout.flush())
+        .expect("Writing debug adapter message failed");
+    }
+
+    fn respond(&mut self, request: &Json, body: Json) {
+        let request_seq = request.get("seq").cloned().unwrap_or(Json::Null);
+        let command = request.get("command").cloned().unwrap_or(Json::Null);
+        self.send(
+            "response",
+            vec![
+                ("request_seq", request_seq),
+                ("success", Json::Bool(true)),
+                ("command", command),
+                ("body", body),
+            ],
+        );
+    }
+
+    fn respond_error(&mut self, request: &Json, message: &str) {
+        let request_seq = request.get("seq").cloned().unwrap_or(Json::Null);
+        let command = request.get("command").cloned().unwrap_or(Json::Null);
+        self.send(
+            "response",
+            vec![
+                ("request_seq", request_seq),
+                ("success", Json::Bool(false)),
+                ("command", command),
+                ("message", Json::string(message)),
+            ],
+        );
+    }
+
+    fn event(&mut self, event: &str, body: Json) {
+        self.send(
+            "event",
+            vec![("event", Json::string(event)), ("body", body)],
+        );
+    }
+}
+
+// Implements the debug adapter protocol over the standard input and output
+pub struct DebugAdapter {
+    connection: Connection,
+    launch_request: Json,
+    stop_on_entry: bool,
+}
+
+impl Default for DebugAdapter {
+    fn default() -> DebugAdapter {
+        DebugAdapter::new()
+    }
+}
+
+impl DebugAdapter {
+    pub fn new() -> DebugAdapter {
+        DebugAdapter {
+            connection: Connection::new(),
+            launch_request: Json::Null,
+            stop_on_entry: false,
+        }
+    }
+
+    // Handles the requests until the client asks to launch a program
+    pub fn wait_for_launch(&mut self) -> Option<LaunchArgs> {
+        loop {
+            let request = self.connection.read()?;
+            match request.get("command").and_then(|c| c.as_str()) {
+                Some("initialize") => {
+                    let capabilities = Json::object(vec![
+                        ("supportsConfigurationDoneRequest", Json::Bool(true)),
+                        ("supportsEvaluateForHovers", Json::Bool(true)),
+                    ]);
+                    self.connection.respond(&request, capabilities);
+                }
+                Some("launch") => {
+                    let program = get_arg(&request, "program").and_then(|p| p.as_str());
+                    let program = match program {
+                        Some(program) => program.to_string(),
+                        None => {
+                            self.connection
+                                .respond_error(&request, "no program given to debug");
+                            continue;
+                        }
+                    };
+                    if let Some(cwd) = get_arg(&request, "cwd").and_then(|c| c.as_str()) {
+                        if std::env::set_current_dir(cwd).is_err() {
+                            let message = format!("cannot change directory to {}", cwd);
+                            self.connection.respond_error(&request, &message);
+                            continue;
+                        }
+                    }
+                    let std = get_arg(&request, "std")
+                        .and_then(|s| s.as_str())
+                        .unwrap_or("std")
+                        .to_string();
+                    self.stop_on_entry = get_arg(&request, "stopOnEntry")
+                        .and_then(|s| s.as_bool())
+                        .unwrap_or(false);
+                    self.launch_request = request;
+                    return Some(LaunchArgs {
+                        program: program,
+                        std: std,
+                    });
+                }
+                Some("disconnect") => {
+                    self.connection.respond(&request, Json::object(vec![]));
+                    return None;
+                }
+                _ => self
+                    .connection
+                    .respond_error(&request, "the program is not launched yet"),
+            }
+        }
+    }
+
+    pub fn fail_launch(mut self, message: &str) {
+        let request = self.launch_request.clone();
+        self.connection.respond_error(&request, message);
+        self.connection.event("terminated", Json::object(vec![]));
+    }
+
+    // Compiles and runs the launched program, compilation errors are
+    // reported on the standard error
+    pub fn run(mut self, inputs: Vec<CompilerInput>) -> bool {
+        let mut compiler = Compiler::new(Config::new());
+        match compiler.check_program(inputs) {
+            Ok(ir_program) => {
+                let request = self.launch_request.clone();
+                self.connection.respond(&request, Json::object(vec![]));
+                self.connection.event("initialized", Json::object(vec![]));
+                compiler.debug(ir_program, Box::new(self)).is_ok()
+            }
+            Err(err) => {
+                compiler.report_error(err);
+                self.fail_launch("compilation failed, run the program with siko for the details");
+                false
+            }
+        }
+    }
+
+    fn find_variable<'a>(stop: &'a Stop, name: &str) -> Option<&'a Variable> {
+        stop.locals
+            .iter()
+            .chain(stop.arguments.iter())
+            .find(|variable| variable.name == name)
+    }
+
+    fn set_breakpoints(&mut self, request: &Json, breakpoints: &mut Breakpoints) {
+        let file = get_arg(request, "source")
+            .and_then(|s| s.get("path"))
+            .and_then(|p| p.as_str())
+            .unwrap_or("")
+            .to_string();
+        breakpoints.clear_file(&file);
+        let requested = get_arg(request, "breakpoints")
+            .and_then(|b| b.as_array())
+            .cloned()
+            .unwrap_or_else(Vec::new);
+        let mut results = Vec::new();
+        for breakpoint in requested {
+            let line = breakpoint.get("line").and_then(|l| l.as_i64()).unwrap_or(0);
+            let result = match breakpoints.add(&file, line as usize) {
+                Some(position) => Json::object(vec![
+                    ("verified", Json::Bool(true)),
+                    ("line", number(position.line as i64)),
+                ]),
+                None => Json::object(vec![
+                    ("verified", Json::Bool(false)),
+                    ("line", number(line)),
+                    ("message", Json::string("no code at this line")),
+                ]),
+            };
+            results.push(result);
+        }
+        let body = Json::object(vec![("breakpoints", Json::Array(results))]);
+        self.connection.respond(request, body);
+    }
+
+    fn stack_trace(&mut self, request: &Json, stop: Option<&Stop>) {
+        let mut frames = Vec::new();
+        if let Some(stop) = stop {
+            for (index, frame) in stop.frames.iter().enumerate() {
+                let mut items = vec![
+                    ("id", number(index as i64)),
+                    ("name", Json::string(&frame.function)),
+                    ("column", number(1)),
+                ];
+                match &frame.position {
+                    Some(position) => {
+                        items.push(("source", source_to_json(&position.file)));
+                        items.push(("line", number(position.line as i64)));
+                    }
+                    None => items.push(("line", number(0))),
+                }
+                frames.push(Json::object(items));
+            }
+        }
+        let total = number(frames.len() as i64);
+        let body = Json::object(vec![
+            ("stackFrames", Json::Array(frames)),
+            ("totalFrames", total),
+        ]);
+        self.connection.respond(request, body);
+    }
+
+    // Only the innermost frame has variables
+    fn scopes(&mut self, request: &Json, stop: Option<&Stop>) {
+        let frame_id = get_arg(request, "frameId").and_then(|f| f.as_i64());
+        let scopes = if stop.is_some() && frame_id == Some(0) {
+            vec![
+                Json::object(vec![
+                    ("name", Json::string("Arguments")),
+                    ("variablesReference", number(ARGUMENTS_REFERENCE)),
+                    ("expensive", Json::Bool(false)),
+                ]),
+                Json::object(vec![
+                    ("name", Json::string("Locals")),
+                    ("variablesReference", number(LOCALS_REFERENCE)),
+                    ("expensive", Json::Bool(false)),
+                ]),
+            ]
+        } else {
+            Vec::new()
+        };
+        let body = Json::object(vec![("scopes", Json::Array(scopes))]);
+        self.connection.respond(request, body);
+    }
+
+    fn variables(&mut self, request: &Json, stop: Option<&Stop>) {
+        let reference = get_arg(request, "variablesReference").and_then(|r| r.as_i64());
+        let variables = match (stop, reference) {
+            (Some(stop), Some(ARGUMENTS_REFERENCE)) => variables_to_json(&stop.arguments),
+            (Some(stop), Some(LOCALS_REFERENCE)) => variables_to_json(&stop.locals),
+            _ => Json::Array(Vec::new()),
+        };
+        let body = Json::object(vec![("variables", variables)]);
+        self.connection.respond(request, body);
+    }
+
+    // Expressions cannot be evaluated, only the variables can be looked up
+    fn evaluate(&mut self, request: &Json, stop: Option<&Stop>) {
+        let expression = get_arg(request, "expression")
+            .and_then(|e| e.as_str())
+            .unwrap_or("")
+            .trim();
+        match stop.and_then(|stop| DebugAdapter::find_variable(stop, expression)) {
+            Some(variable) => {
+                let body = Json::object(vec![
+                    ("result", Json::string(&variable.value)),
+                    ("type", Json::string(&variable.ty)),
+                    ("variablesReference", number(0)),
+                ]);
+                self.connection.respond(request, body);
+            }
+            None => {
+                let message = format!("no variable named {}", expression);
+                self.connection.respond_error(request, &message);
+            }
+        }
+    }
+
+    // Handles the requests until one of them resumes the program
+    fn serve(&mut self, stop: Option<&Stop>, breakpoints: &mut Breakpoints) -> Resume {
+        loop {
+            let request = match self.connection.read() {
+                Some(request) => request,
+                None => std::process::exit(0),
+            };
+            let command = request
+                .get("command")
+                .and_then(|c| c.as_str())
+                .unwrap_or("")
+                .to_string();
+            let resume = match command.as_ref() {
+                "continue" => Resume::Continue,
+                "next" => Resume::StepOver,
+                "stepIn" => Resume::StepIn,
+                "stepOut" => Resume::StepOut,
+                "configurationDone" if stop.is_none() => {
+                    self.connection.respond(&request, Json::object(vec![]));
+                    if self.stop_on_entry {
+                        return Resume::StepIn;
+                    } else {
+                        return Resume::Continue;
+                    }
+                }
+                "setBreakpoints" => {
+                    self.set_breakpoints(&request, breakpoints);
+                    continue;
+                }
+                "setExceptionBreakpoints" => {
+                    let body = Json::object(vec![("breakpoints", Json::Array(Vec::new()))]);
+                    self.connection.respond(&request, body);
+                    continue;
+                }
+                "threads" => {
+                    let thread = Json::object(vec![
+                        ("id", number(THREAD_ID)),
+                        ("name", Json::string("main")),
+                    ]);
+                    let body = Json::object(vec![("threads", Json::Array(vec![thread]))]);
+                    self.connection.respond(&request, body);
+                    continue;
+                }
+                "stackTrace" => {
+                    self.stack_trace(&request, stop);
+                    continue;
+                }
+                "scopes" => {
+                    self.scopes(&request, stop);
+                    continue;
+                }
+                "variables" => {
+                    self.variables(&request, stop);
+                    continue;
+                }
+                "evaluate" => {
+                    self.evaluate(&request, stop);
+                    continue;
+                }
+                "disconnect" | "terminate" => {
+                    self.connection.respond(&request, Json::object(vec![]));
+                    std::process::exit(0);
+                }
+                _ => {
+                    let message = format!("unsupported request {}", command);
+                    self.connection.respond_error(&request, &message);
+                    continue;
+                }
+            };
+            if stop.is_none() {
+                self.connection
+                    .respond_error(&request, "the program is not stopped");
+                continue;
+            }
+            let body = Json::object(vec![("allThreadsContinued", Json::Bool(true))]);
+            self.connection.respond(&request, body);
+            return resume;
+        }
+    }
+}
+
+impl DebugFrontend for DebugAdapter {
+    fn start(&mut self, breakpoints: &mut Breakpoints) -> Resume {
+        self.serve(None, breakpoints)
+    }
+
+    fn stopped(&mut self, stop: &Stop, breakpoints: &mut Breakpoints) -> Resume {
+        let reason = match stop.reason {
+            StopReason::Entry => "entry",
+            StopReason::Breakpoint => "breakpoint",
+            StopReason::Step => "step",
+        };
+        let body = Json::object(vec![
+            ("reason", Json::string(reason)),
+            ("threadId", number(THREAD_ID)),
+            ("allThreadsStopped", Json::Bool(true)),
+        ]);
+        self.connection.event("stopped", body);
+        self.serve(Some(stop), breakpoints)
+    }
+
+    fn output(&mut self, text: &str) {
+        let body = Json::object(vec![
+            ("category", Json::string("stdout")),
+            ("output", Json::string(text)),
+        ]);
+        self.connection.event("output", body);
+    }
+
+    // The adapter exits when the client disconnects
+    fn finished(&mut self, error: Option<String>) {
+        let exit_code = match &error {
+            Some(error) => {
+                let body = Json::object(vec![
+                    ("category", Json::string("stderr")),
+                    ("output", Json::String(format!("ERROR: {}\n", error))),
+                ]);
+                self.connection.event("output", body);
+                1
+            }
+            None => 0,
+        };
+        self.connection.event(
+            "exited",
+            Json::object(vec![("exitCode", number(exit_code))]),
+        );
+        self.connection.event("terminated", Json::object(vec![]));
+        while let Some(request) = self.connection.read() {
+            match request.get("command").and_then(|c| c.as_str()) {
+                Some("disconnect") | Some("terminate") => {
+                    self.connection.respond(&request, Json::object(vec![]));
+                    return;
+                }
+                _ => self
+                    .connection
+                    .respond_error(&request, "the program has finished"),
+            }
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use siko_interpreter::debugger::Breakpoints;
+use siko_interpreter::debugger::DebugFrontend;
+use siko_interpreter::debugger::Position;
+use siko_interpreter::debugger::Resume;
+use siko_interpreter::debugger::Stop;
+use siko_interpreter::debugger::StopReason;
+use siko_interpreter::debugger::Variable;
+use std::collections::BTreeMap;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+
+// Number of lines shown around the current line by list
+const LIST_CONTEXT: usize = 5;
+
+fn print_help(running: bool) {
+    println!("Commands:");
+    println!("\tbreak <file>:<line>     set a breakpoint");
+    println!("\tdelete [<file>:<line>]  delete a breakpoint or all of them");
+    println!("\tbreakpoints             list the breakpoints");
+    if running {
+        println!("\tcontinue                run until the next breakpoint");
+        println!("\tstep                    step into the called function");
+        println!("\tnext                    step over the called function");
+        println!("\tfinish                  run until the current function returns");
+        println!("\tbacktrace               show the call stack");
+        println!("\tvars                    show the arguments and variables");
+        println!("\tprint <name>            show a variable");
+        println!("\tlist                    show the source around the current line");
+    } else {
+        println!("\trun                     run until the first breakpoint");
+        println!("\tstep                    stop at the first line");
+    }
+    println!("\thelp                    show this help");
+    println!("\tquit                    stop debugging");
+}
+
+fn print_variable(variable: &Variable) {
+    println!("{} :: {} = {}", variable.name, variable.ty, variable.value);
+}
+
+fn parse_position(arg: &str, stop: Option<&Stop>) -> Option<(String, usize)> {
+    // a line without a file refers to the file of the current line
+    match arg.rfind(':') {
+        Some(index) => {
+            let line = arg[index + 1..].parse().ok()?;
+            Some((arg[..index].to_string(), line))
+        }
+        None => {
+            let line = arg.parse().ok()?;
+            let file = stop?.frames.first()?.position.as_ref()?.file.clone();
+            Some((file, line))
+        }
+    }
+}
+
+// A line oriented debugger reading its commands from the standard input
+pub struct CommandLineDebugger {
+    sources: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for CommandLineDebugger {
+    fn default() -> CommandLineDebugger {
+        CommandLineDebugger::new()
+    }
+}
+
+impl CommandLineDebugger {
+    pub fn new() -> CommandLineDebugger {
+        CommandLineDebugger {
+            sources: BTreeMap::new(),
+        }
+    }
+
+    fn get_line(&mut self, position: &Position) -> Option<String> {
+        let lines = self
+            .sources
+            .entry(position.file.clone())
+            .or_insert_with(|| match std::fs::read_to_string(&position.file) {
+                Ok(content) => content.lines().map(|line| line.to_string()).collect(),
+                Err(_) => Vec::new(),
+            });
+        lines.get(position.line - 1).cloned()
+    }
+
+    fn print_source(&mut self, position: &Position, context: usize) {
+        let first = if position.line > context {
+            position.line - context
+        } else {
+            1
+        };
+        for line in first..=position.line + context {
+            let current = Position {
+                file: position.file.clone(),
+                line: line,
+            };
+            if let Some(source) = self.get_line(&current) {
+                let marker = if line == position.line { ">" } else { " " };
+                println!("{} {:4} | {}", marker, line, source);
+            }
+        }
+    }
+
+    fn print_stop(&mut self, stop: &Stop) {
+        let frame = match stop.frames.first() {
+            Some(frame) => frame,
+            None => return,
+        };
+        let position = match &frame.position {
+            Some(position) => position.clone(),
+            None => return,
+        };
+        let reason = match stop.reason {
+            StopReason::Entry => "Entry",
+            StopReason::Breakpoint => "Breakpoint",
+            StopReason::Step => "Step",
+        };
+        println!(
+            "{}: {} at {}:{}",
+            reason, frame.function, position.file, position.line
+        );
+        self.print_source(&position, 0);
+    }
+
+    fn add_breakpoint(&mut self, arg: &str, stop: Option<&Stop>, breakpoints: &mut Breakpoints) {
+        match parse_position(arg, stop) {
+            Some((file, line)) => match breakpoints.add(&file, line) {
+                Some(position) => println!("breakpoint set at {}:{}", position.file, position.line),
+                None => println!("no code at {}:{}", file, line),
+            },
+            None => println!("expected <file>:<line>"),
+        }
+    }
+
+    fn delete_breakpoint(&mut self, arg: &str, stop: Option<&Stop>, breakpoints: &mut Breakpoints) {
+        if arg.is_empty() {
+            for position in breakpoints.get_all() {
+                breakpoints.remove(&position);
+            }
+            return;
+        }
+        let removed = match parse_position(arg, stop) {
+            Some((file, line)) => match breakpoints.resolve_file(&file) {
+                Some(file) => breakpoints.remove(&Position {
+                    file: file,
+                    line: line,
+                }),
+                None => false,
+            },
+            None => false,
+        };
+        if !removed {
+            println!("no breakpoint at {}", arg);
+        }
+    }
+
+    // Reads commands until one of them resumes the program
+    fn prompt(&mut self, stop: Option<&Stop>, breakpoints: &mut Breakpoints) -> Resume {
+        let stdin = io::stdin();
+        loop {
+            print!("(sdb) ");
+            io::stdout().flush().expect("stdout flush failed");
+            let mut line = String::new();
+            match stdin.lock().read_line(&mut line) {
+                Ok(0) | Err(_) => std::process::exit(0),
+                Ok(_) => {}
+            }
+            let mut words = line.trim().splitn(2, char::is_whitespace);
+            let command = words.next().unwrap_or("");
+            let arg = words.next().unwrap_or("").trim();
+            match (command, stop) {
+                ("", _) => {}
+                ("break", _) | ("b", _) => self.add_breakpoint(arg, stop, breakpoints),
+                ("delete", _) | ("d", _) => self.delete_breakpoint(arg, stop, breakpoints),
+                ("breakpoints", _) => {
+                    for position in breakpoints.get_all() {
+                        println!("{}:{}", position.file, position.line);
+                    }
+                }
+                ("run", _) | ("r", _) | ("continue", _) | ("c", _) => {
+                    return Resume::Continue;
+                }
+                ("step", _) | ("s", _) => return Resume::StepIn,
+                ("next", Some(_)) | ("n", Some(_)) => return Resume::StepOver,
+                ("finish", Some(_)) | ("f", Some(_)) => return Resume::StepOut,
+                ("backtrace", Some(stop)) | ("bt", Some(stop)) => {
+                    for (index, frame) in stop.frames.iter().enumerate() {
+                        match &frame.position {
+                            Some(position) => println!(
+                                "#{} {} at {}:{}",
+                                index, frame.function, position.file, position.line
+                            ),
+                            None => println!("#{} {}", index, frame.function),
+                        }
+                    }
+                }
+                ("vars", Some(stop)) | ("v", Some(stop)) => {
+                    for variable in stop.arguments.iter().chain(stop.locals.iter()) {
+                        print_variable(variable);
+                    }
+                }
+                ("print", Some(stop)) | ("p", Some(stop)) => {
+                    match stop
+                        .locals
+                        .iter()
+                        .chain(stop.arguments.iter())
+                        .find(|variable| variable.name == arg)
+                    {
+                        Some(variable) => print_variable(variable),
+                        None => println!("no variable named {}", arg),
+                    }
+                }
+                ("list", Some(stop)) | ("l", Some(stop)) => {
+                    if let Some(position) = stop.frames.first().and_then(|f| f.position.clone()) {
+                        self.print_source(&position, LIST_CONTEXT);
+                    }
+                }
+                ("help", _) | ("h", _) => print_help(stop.is_some()),
+                ("quit", _) | ("q", _) => std::process::exit(0),
+                _ => println!("unknown command {}, try help", command),
+            }
+        }
+    }
+}
+
+impl DebugFrontend for CommandLineDebugger {
+    fn start(&mut self, breakpoints: &mut Breakpoints) -> Resume {
+        println!("Set breakpoints and run the program, try help for the commands");
+        self.prompt(None, breakpoints)
+    }
+
+    fn stopped(&mut self, stop: &Stop, breakpoints: &mut Breakpoints) -> Resume {
+        self.print_stop(stop);
+        self.prompt(Some(stop), breakpoints)
+    }
+
+    fn output(&mut self, text: &str) {
+        print!("{}", text);
+    }
+
+    fn finished(&mut self, error: Option<String>) {
+        match error {
+            Some(error) => println!("program stopped: {}", error),
+            None => println!("program finished"),
+        }
+    }
+}
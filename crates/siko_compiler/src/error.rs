@@ -1,17 +1,20 @@
 use colored::*;
+use siko_backend::error::BackendError;
 use siko_constants::MAIN_FUNCTION;
 use siko_constants::MAIN_MODULE_NAME;
+use siko_interpreter::error::RuntimeError;
 use siko_location_info::error_context::ErrorContext;
 use siko_location_info::file_manager::FileManager;
 use siko_location_info::filepath::FilePath;
 use siko_location_info::location::Location;
-use siko_location_info::location_id::LocationId;
 use siko_location_info::location_set::LocationSet;
+use siko_location_info::location_set::Range;
 use siko_name_resolver::error::Error as ResolverErrorContainer;
 use siko_name_resolver::error::ResolverError;
 use siko_parser::error::LexerError;
 use siko_parser::error::ParseError;
 use siko_type_checker::error::Error as TypecheckErrorContainer;
+use siko_type_checker::error::MismatchTrace;
 use siko_type_checker::error::TypecheckError;
 use siko_util::format_list;
 use std::cmp;
@@ -26,43 +29,47 @@ fn s_from_range(chars: &[char], start: usize, end: usize) -> String {
 }
 
 fn print_location_set(file_manager: &FileManager, location_set: &LocationSet) {
-    let input = file_manager.content(&location_set.file_path);
-    let lines: Vec<_> = input.lines().collect();
-    let mut first = true;
+    let file_path = &location_set.file_path;
     let pipe = "|";
+    let first_line = location_set.lines.keys().next().cloned().unwrap_or(0);
     let mut last_line = 0;
     for (line_index, ranges) in &location_set.lines {
         last_line = *line_index;
-        if first {
-            first = false;
+        if *line_index == first_line {
             eprintln!(
-                "{}{}:{}",
+                "{}{}",
                 "-- ".blue(),
-                location_set.file_path.path.green(),
-                format!("{}", line_index + 1).green()
+                file_manager.get_line_name(file_path, *line_index).green()
             );
             if *line_index != 0 {
-                let line = &lines[*line_index - 1];
-                eprintln!("{} {}", pipe.blue(), line);
+                if let Some((line, _)) =
+                    file_manager.get_shown_line(file_path, *line_index - 1, first_line)
+                {
+                    eprintln!("{} {}", pipe.blue(), line);
+                }
             }
         }
-        let line = &lines[*line_index];
+        let (line, column) = match file_manager.get_shown_line(file_path, *line_index, first_line) {
+            Some(shown) => shown,
+            None => continue,
+        };
         let chars: Vec<_> = line.chars().collect();
-        let first = s_from_range(&chars[..], 0, ranges[0].start);
+        let start = |range: &Range| cmp::min(range.start.saturating_sub(column), chars.len());
+        let end = |range: &Range| cmp::min(range.end.saturating_sub(column), chars.len());
+        let first = s_from_range(&chars[..], 0, start(&ranges[0]));
         eprint!("{} {}", pipe.blue(), first);
         for (index, range) in ranges.iter().enumerate() {
-            let s = s_from_range(&chars[..], range.start, range.end);
+            let s = s_from_range(&chars[..], start(range), end(range));
             eprint!("{}", s.yellow());
             if index < ranges.len() - 1 {
-                let s = s_from_range(&chars[..], range.end, ranges[index + 1].start);
+                let s = s_from_range(&chars[..], end(range), start(&ranges[index + 1]));
                 eprint!("{}", s);
             }
         }
-        let last = s_from_range(&chars[..], ranges[ranges.len() - 1].end, chars.len());
+        let last = s_from_range(&chars[..], end(&ranges[ranges.len() - 1]), chars.len());
         eprintln!("{}", last);
     }
-    if last_line + 1 < lines.len() {
-        let line = &lines[last_line + 1];
+    if let Some((line, _)) = file_manager.get_shown_line(file_path, last_line + 1, first_line) {
         eprintln!("{} {}", pipe.blue(), line);
     }
 }
@@ -71,28 +78,31 @@ fn print_location_set(file_manager: &FileManager, location_set: &LocationSet) {
 pub enum Error {
     IoError(IoError),
     LexerError(Vec<LexerError>),
-    ParseError(ParseError),
+    ParseError(Vec<ParseError>),
     ResolverError(ResolverErrorContainer),
     TypecheckError(TypecheckErrorContainer),
-    RuntimeError(String, LocationId),
+    RuntimeError(RuntimeError),
+    BackendError(Vec<BackendError>),
+    FormatError(FilePath),
 }
 
 impl Error {
     fn report_location(file_manager: &FileManager, file_path: &FilePath, location: &Location) {
-        let input = file_manager.content(file_path);
-        let lines: Vec<_> = input.lines().collect();
         eprintln!(
-            "--{}:{}",
-            file_path.path.green(),
-            format!("{}", location.line + 1).green()
+            "--{}",
+            file_manager.get_line_name(file_path, location.line).green()
         );
-        let line = &lines[location.line];
+        let (line, column) = file_manager
+            .get_shown_line(file_path, location.line, location.line)
+            .unwrap_or(("", 0));
         let chars: Vec<_> = line.chars().collect();
-        let first = s_from_range(&chars[..], 0, location.span.start);
+        let start = cmp::min(location.span.start.saturating_sub(column), chars.len());
+        let end = cmp::min(location.span.end.saturating_sub(column), chars.len());
+        let first = s_from_range(&chars[..], 0, start);
         eprint!("{}", first);
-        let s = s_from_range(&chars[..], location.span.start, location.span.end);
+        let s = s_from_range(&chars[..], start, end);
         eprint!("{}", s.red());
-        let last = s_from_range(&chars[..], location.span.end, chars.len());
+        let last = s_from_range(&chars[..], end, chars.len());
         eprintln!("{}", last);
     }
 
@@ -133,8 +143,10 @@ impl Error {
                     }
                 }
             }
-            Error::ParseError(err) => {
-                Error::report_error_base(&err.msg, file_manager, &err.file_path, &err.location);
+            Error::ParseError(errors) => {
+                for err in errors {
+                    Error::report_error_base(&err.msg, file_manager, &err.file_path, &err.location);
+                }
             }
             Error::ResolverError(errs) => {
                 for err in &errs.errors {
@@ -184,15 +196,6 @@ impl Error {
                             let location_set = location_info.get_item_location(id);
                             print_location_set(file_manager, location_set);
                         }
-                        ResolverError::ArgumentConflict(args, id) => {
-                            eprintln!(
-                                "{} argument(s) are not unique: {}",
-                                error.red(),
-                                format_list(args).yellow()
-                            );
-                            let location_set = location_info.get_item_location(id);
-                            print_location_set(file_manager, location_set);
-                        }
                         ResolverError::LambdaArgumentConflict(args, id) => {
                             eprintln!(
                                 "{} lambda argument(s) {} are not unique",
@@ -472,6 +475,17 @@ impl Error {
                                 print_location_set(file_manager, location_set);
                             }
                         }
+                        ResolverError::ConflictingLocalFunctions(name, locations) => {
+                            eprintln!(
+                                "{} conflicting local functions named {}",
+                                error.red(),
+                                name.yellow(),
+                            );
+                            for id in locations {
+                                let location_set = location_info.get_item_location(id);
+                                print_location_set(file_manager, location_set);
+                            }
+                        }
                         ResolverError::ConflictingFunctionTypesInInstance(name, locations) => {
                             eprintln!(
                                 "{} conflicting function types named {} in instance",
@@ -538,13 +552,160 @@ impl Error {
                             let location_set = location_info.get_item_location(id);
                             print_location_set(file_manager, location_set);
                         }
+                        ResolverError::NotAProtocolName(name, id) => {
+                            eprintln!("{} {} is not a protocol", error.red(), name.yellow());
+                            let location_set = location_info.get_item_location(id);
+                            print_location_set(file_manager, location_set);
+                        }
+                        ResolverError::ConflictingProtocolHandlers(actor, protocol, id) => {
+                            eprintln!(
+                                "{} protocol {} is handled more than once in actor {}",
+                                error.red(),
+                                protocol.yellow(),
+                                actor.yellow()
+                            );
+                            let location_set = location_info.get_item_location(id);
+                            print_location_set(file_manager, location_set);
+                        }
+                        ResolverError::FunctionClauseArgCountMismatch(name, locations) => {
+                            eprintln!(
+                                "{} clauses of function {} have different number of arguments",
+                                error.red(),
+                                name.yellow(),
+                            );
+                            for id in locations {
+                                let location_set = location_info.get_item_location(id);
+                                print_location_set(file_manager, location_set);
+                            }
+                        }
+                        ResolverError::CyclicTypeAlias(names, locations) => {
+                            eprintln!(
+                                "{} cyclic type alias definition: {}",
+                                error.red(),
+                                format_list(&names[..]).yellow(),
+                            );
+                            for id in locations {
+                                let location_set = location_info.get_item_location(id);
+                                print_location_set(file_manager, location_set);
+                            }
+                        }
+                        ResolverError::ExternFunctionWithMultipleClauses(name, locations) => {
+                            eprintln!(
+                                "{} extern function {} has multiple clauses",
+                                error.red(),
+                                name.yellow(),
+                            );
+                            for id in locations {
+                                let location_set = location_info.get_item_location(id);
+                                print_location_set(file_manager, location_set);
+                            }
+                        }
+                        ResolverError::KindMismatch(name, expected, found, id) => {
+                            eprintln!("{} kind mismatch for {}", error.red(), name.yellow());
+                            eprintln!("Expected: {}", expected.yellow());
+                            eprintln!("Found:    {}", found.yellow());
+                            let location_set = location_info.get_item_location(id);
+                            print_location_set(file_manager, location_set);
+                        }
+                        ResolverError::IncorrectClassArgumentCount(
+                            class_name,
+                            expected,
+                            found,
+                            id,
+                        ) => {
+                            eprintln!(
+                                "{} incorrect argument count for class {}",
+                                error.red(),
+                                class_name.yellow(),
+                            );
+                            let expected = format!("{}", expected);
+                            let found = format!("{}", found);
+                            eprintln!("Expected: {}", expected.yellow());
+                            eprintln!("Found:    {}", found.yellow());
+                            let location_set = location_info.get_item_location(id);
+                            print_location_set(file_manager, location_set);
+                        }
+                        ResolverError::SuperclassOfMultiParamClass(class_name, id) => {
+                            eprintln!(
+                                "{} multi parameter class {} cannot have superclass constraints",
+                                error.red(),
+                                class_name.yellow(),
+                            );
+                            let location_set = location_info.get_item_location(id);
+                            print_location_set(file_manager, location_set);
+                        }
+                        ResolverError::EmptyRangePattern(range, id) => {
+                            eprintln!("{} range pattern {} is empty", error.red(), range.yellow());
+                            let location_set = location_info.get_item_location(id);
+                            print_location_set(file_manager, location_set);
+                        }
                     }
                 }
             }
-            Error::RuntimeError(err, id) => {
+            Error::IoError(err) => {
                 eprintln!("{} {}", error.red(), err);
-                let location_set = location_info.get_item_location(id);
-                print_location_set(file_manager, location_set);
+            }
+            Error::BackendError(errors) => {
+                for err in errors {
+                    match err {
+                        BackendError::ActorNotSupported(name, id) => {
+                            eprintln!(
+                                "{} actor {} is not supported when compiling, it can only be interpreted",
+                                error.red(),
+                                name.yellow()
+                            );
+                            let location_set = location_info.get_item_location(id);
+                            print_location_set(file_manager, location_set);
+                        }
+                    }
+                }
+            }
+            Error::FormatError(file_path) => {
+                eprintln!(
+                    "{} formatting would change the meaning of {}, file left untouched",
+                    error.red(),
+                    file_path.path.yellow()
+                );
+            }
+            Error::RuntimeError(err) => {
+                eprintln!("{} {}", error.red(), err.message);
+                if let Some(id) = &err.location {
+                    let location_set = location_info.get_item_location(id);
+                    print_location_set(file_manager, location_set);
+                }
+                eprintln!("Stack trace:");
+                let mut index = 0;
+                while index < err.stack.len() {
+                    let frame = &err.stack[index];
+                    // deep recursion is shown as a single frame
+                    let mut repeated = err.stack[index + 1..]
+                        .iter()
+                        .take_while(|f| {
+                            f.function == frame.function && f.location == frame.location
+                        })
+                        .count();
+                    if repeated < 2 {
+                        repeated = 0;
+                    }
+                    match &frame.location {
+                        Some(id) => {
+                            let location_set = location_info.get_item_location(id);
+                            let line = location_set.lines.keys().next().cloned().unwrap_or(0);
+                            eprintln!(
+                                "    {} at {}",
+                                frame.function.yellow(),
+                                file_manager
+                                    .get_line_name(&location_set.file_path, line)
+                                    .green()
+                            );
+                        }
+                        None => eprintln!("    {}", frame.function.yellow()),
+                    }
+                    if repeated > 0 {
+                        eprintln!("    (repeated {} more times)", repeated);
+                    }
+                    index += repeated + 1;
+                }
             }
             Error::TypecheckError(errs) => {
                 for err in &errs.errors {
@@ -632,12 +793,29 @@ impl Error {
                                 "Main".yellow()
                             );
                         }
-                        TypecheckError::TypeMismatch(id, expected, found) => {
+                        TypecheckError::TypeMismatch(id, expected, found, trace) => {
                             eprintln!("{} type mismatch in expression", error.red());
                             eprintln!("Expected: {}", expected.yellow());
                             eprintln!("Found:    {}", found.yellow());
                             let location_set = location_info.get_item_location(id);
                             print_location_set(file_manager, location_set);
+                            for item in trace {
+                                let id = match item {
+                                    MismatchTrace::Signature(name, id) => {
+                                        eprintln!(
+                                            "Expected by the signature of {}:",
+                                            name.yellow()
+                                        );
+                                        id
+                                    }
+                                    MismatchTrace::Binding(ty, id) => {
+                                        eprintln!("Type {} fixed here:", ty.yellow());
+                                        id
+                                    }
+                                };
+                                let location_set = location_info.get_item_location(id);
+                                print_location_set(file_manager, location_set);
+                            }
                         }
                         TypecheckError::FunctionArgumentMismatch(id, args, func) => {
                             eprintln!("{} invalid argument(s)", error.red());
@@ -692,6 +870,38 @@ impl Error {
                             let location_set = location_info.get_item_location(id);
                             print_location_set(file_manager, location_set);
                         }
+                        TypecheckError::AmbiguousInstance(constraint, id, candidates) => {
+                            eprintln!(
+                                "{} ambiguous instance selection for {}",
+                                error.red(),
+                                constraint.yellow()
+                            );
+                            let location_set = location_info.get_item_location(id);
+                            print_location_set(file_manager, location_set);
+                            if !candidates.is_empty() {
+                                eprintln!("Candidate instances:");
+                                for candidate in candidates {
+                                    let location_set = location_info.get_item_location(candidate);
+                                    print_location_set(file_manager, location_set);
+                                }
+                            }
+                        }
+                        TypecheckError::TypedHole(name, id, ty, fits) => {
+                            eprintln!(
+                                "{} found hole {} :: {}",
+                                error.red(),
+                                name.yellow(),
+                                ty.yellow()
+                            );
+                            let location_set = location_info.get_item_location(id);
+                            print_location_set(file_manager, location_set);
+                            if !fits.is_empty() {
+                                eprintln!("Valid hole fits:");
+                                for (fit_name, fit_ty) in fits {
+                                    eprintln!("    {} :: {}", fit_name, fit_ty);
+                                }
+                            }
+                        }
                         TypecheckError::ClassNotAutoDerivable(class, id) => {
                             eprintln!(
                                 "{} class {} is not auto derivable",
@@ -713,20 +923,58 @@ impl Error {
                             let location_set = location_info.get_item_location(id);
                             print_location_set(file_manager, location_set);
                         }
-                        TypecheckError::UnreachablePattern(id) => {
+                        TypecheckError::UnreachablePattern(id, covering_id) => {
                             eprintln!("{} unreachable pattern", error.red(),);
                             let location_set = location_info.get_item_location(id);
                             print_location_set(file_manager, location_set);
+                            eprintln!("Already matched by:");
+                            let location_set = location_info.get_item_location(covering_id);
+                            print_location_set(file_manager, location_set);
                         }
-                        TypecheckError::NonExhaustivePattern(id) => {
+                        TypecheckError::NonExhaustivePattern(id, missing) => {
                             eprintln!("{} non exhaustive pattern", error.red(),);
+                            eprintln!("Missing: {}", format_list(missing).yellow());
+                            let location_set = location_info.get_item_location(id);
+                            print_location_set(file_manager, location_set);
+                        }
+                        TypecheckError::ActorHandlerTypeMismatch(id, expected, found) => {
+                            eprintln!("{} actor handler type mismatch", error.red());
+                            eprintln!("Expected: {}", expected.yellow());
+                            eprintln!("Found:    {}", found.yellow());
+                            let location_set = location_info.get_item_location(id);
+                            print_location_set(file_manager, location_set);
+                        }
+                        TypecheckError::SendTargetNotActor(id, ty) => {
+                            eprintln!(
+                                "{} message sent to {} which is not an actor",
+                                error.red(),
+                                ty.yellow()
+                            );
+                            let location_set = location_info.get_item_location(id);
+                            print_location_set(file_manager, location_set);
+                        }
+                        TypecheckError::SendMessageNotProtocol(id, ty) => {
+                            eprintln!(
+                                "{} message of type {} is not a protocol",
+                                error.red(),
+                                ty.yellow()
+                            );
+                            let location_set = location_info.get_item_location(id);
+                            print_location_set(file_manager, location_set);
+                        }
+                        TypecheckError::ProtocolNotHandled(id, actor, protocol) => {
+                            eprintln!(
+                                "{} actor {} does not handle protocol {}",
+                                error.red(),
+                                actor.yellow(),
+                                protocol.yellow()
+                            );
                             let location_set = location_info.get_item_location(id);
                             print_location_set(file_manager, location_set);
                         }
                     }
                 }
             }
-            _ => unimplemented!(),
         }
     }
 }
@@ -737,12 +985,6 @@ impl From<IoError> for Error {
     }
 }
 
-impl From<ParseError> for Error {
-    fn from(e: ParseError) -> Error {
-        Error::ParseError(e)
-    }
-}
-
 impl From<ResolverErrorContainer> for Error {
     fn from(e: ResolverErrorContainer) -> Error {
         Error::ResolverError(e)
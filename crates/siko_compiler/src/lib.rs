@@ -1,3 +1,8 @@
 pub mod compiler;
 pub mod config;
+pub mod coverage;
+pub mod debug_adapter;
+pub mod debugger;
 pub mod error;
+pub mod json;
+pub mod repl;
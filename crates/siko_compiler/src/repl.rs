@@ -0,0 +1,322 @@
+use crate::compiler::Compiler;
+use crate::compiler::CompilerInput;
+use crate::config::Config;
+use crate::error::Error;
+use siko_interpreter::interpreter::Interpreter;
+use siko_interpreter::limits::Limits;
+use siko_ir::function::FunctionId;
+use siko_ir::function::FunctionInfo;
+use siko_ir::program::Program as IrProgram;
+use siko_ir::types::Type;
+use siko_location_info::file_manager::LineOrigin;
+use siko_location_info::filepath::FilePath;
+use std::collections::BTreeMap;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+
+const REPL_MODULE_NAME: &str = "Repl";
+const REPL_FILE_NAME: &str = "<repl>";
+const REPL_EXPRESSION_NAME: &str = "replExpression";
+
+// Items starting with these keywords are named by the word after the keyword
+const NAMED_ITEM_KEYWORDS: &[&str] = &["data", "newtype", "type", "class", "protocol", "actor"];
+// Items starting with these keywords are named by their whole first line
+const UNNAMED_ITEM_KEYWORDS: &[&str] = &["instance", "infix", "infixl", "infixr"];
+
+fn print_help() {
+    println!("Commands:");
+    println!("\t<expr>           evaluate an expression");
+    println!("\t<definition>     add or replace a definition");
+    println!("\timport <module>  import a module");
+    println!("\t:type <expr>     show the type of an expression");
+    println!("\t:reload          read the project modules again");
+    println!("\t:{{ ... :}}        enter multiple lines");
+    println!("\t:help            show this help");
+    println!("\t:quit            leave the repl");
+}
+
+// The name a definition is replaced by when it is entered again
+fn get_definition_name(definition: &str) -> String {
+    let first_line = definition.lines().next().unwrap_or("").trim();
+    let mut words = first_line.split_whitespace();
+    let first = words.next().unwrap_or("");
+    if NAMED_ITEM_KEYWORDS.contains(&first) {
+        format!("{} {}", first, words.next().unwrap_or(""))
+    } else if UNNAMED_ITEM_KEYWORDS.contains(&first) {
+        first_line.to_string()
+    } else {
+        first.to_string()
+    }
+}
+
+fn is_keyword_definition(input: &str) -> bool {
+    let first = input.split_whitespace().next().unwrap_or("");
+    NAMED_ITEM_KEYWORDS.contains(&first) || UNNAMED_ITEM_KEYWORDS.contains(&first)
+}
+
+fn read_inputs(inputs: &Vec<CompilerInput>) -> Result<Vec<CompilerInput>, Error> {
+    let mut loaded = Vec::new();
+    for input in inputs {
+        match input {
+            CompilerInput::File { name } => {
+                let content = std::fs::read_to_string(name)?;
+                loaded.push(CompilerInput::Memory {
+                    name: name.clone(),
+                    content: content,
+                });
+            }
+            CompilerInput::Memory { .. } => loaded.push(input.clone()),
+        }
+    }
+    Ok(loaded)
+}
+
+// Appends the lines of an entry to the generated source indented by the given
+// number of columns, errors are reported at the entered lines
+fn add_entry(
+    source: &mut String,
+    origins: &mut BTreeMap<usize, LineOrigin>,
+    text: &str,
+    entry: usize,
+    column: usize,
+) {
+    for (index, line) in text.lines().enumerate() {
+        let origin = LineOrigin {
+            entry: entry,
+            line: index,
+            column: column,
+        };
+        origins.insert(source.matches('\n').count(), origin);
+        *source += &format!("{}{}\n", " ".repeat(column), line);
+    }
+}
+
+fn get_expression_function(program: &IrProgram) -> FunctionId {
+    for (id, function) in &program.functions.items {
+        if let FunctionInfo::NamedFunction(info) = &function.info {
+            if info.module == REPL_MODULE_NAME && info.name == REPL_EXPRESSION_NAME {
+                return *id;
+            }
+        }
+    }
+    unreachable!()
+}
+
+// An interactive session. The std and project modules are read once, every
+// input is checked together with them in a generated module holding the
+// imports and definitions entered so far.
+pub struct Repl {
+    std_inputs: Vec<CompilerInput>,
+    project_inputs: Vec<CompilerInput>,
+    loaded_project_inputs: Vec<CompilerInput>,
+    imports: Vec<String>,
+    definitions: Vec<(String, String)>,
+    limits: Limits,
+}
+
+impl Repl {
+    pub fn new(
+        std_inputs: Vec<CompilerInput>,
+        project_inputs: Vec<CompilerInput>,
+        limits: Limits,
+    ) -> Result<Repl, Error> {
+        let std_inputs = read_inputs(&std_inputs)?;
+        let loaded_project_inputs = read_inputs(&project_inputs)?;
+        Ok(Repl {
+            std_inputs: std_inputs,
+            project_inputs: project_inputs,
+            loaded_project_inputs: loaded_project_inputs,
+            imports: Vec::new(),
+            definitions: Vec::new(),
+            limits: limits,
+        })
+    }
+
+    // The generated module and the origin of its lines, every import,
+    // definition and the expression is a separate entry
+    fn get_source(
+        &self,
+        imports: &Vec<String>,
+        definitions: &Vec<(String, String)>,
+        expr: Option<&str>,
+    ) -> (String, BTreeMap<usize, LineOrigin>) {
+        let mut source = format!("module {} where\n\n", REPL_MODULE_NAME);
+        let mut origins = BTreeMap::new();
+        let mut entry = 0;
+        for import in imports {
+            add_entry(&mut source, &mut origins, import, entry, 0);
+            entry += 1;
+        }
+        for (_, definition) in definitions {
+            source += "\n";
+            add_entry(&mut source, &mut origins, definition, entry, 0);
+            entry += 1;
+        }
+        if let Some(expr) = expr {
+            source += &format!("\n{} =\n", REPL_EXPRESSION_NAME);
+            add_entry(&mut source, &mut origins, expr, entry, 4);
+        }
+        (source, origins)
+    }
+
+    fn check(
+        &self,
+        imports: &Vec<String>,
+        definitions: &Vec<(String, String)>,
+        expr: Option<&str>,
+    ) -> (Compiler, Result<IrProgram, Error>) {
+        let mut inputs = self.std_inputs.clone();
+        inputs.extend(self.loaded_project_inputs.iter().cloned());
+        let (source, origins) = self.get_source(imports, definitions, expr);
+        inputs.push(CompilerInput::Memory {
+            name: REPL_FILE_NAME.to_string(),
+            content: source,
+        });
+        let mut compiler = Compiler::new(Config::new());
+        compiler.add_line_origins(FilePath::new(REPL_FILE_NAME.to_string()), origins);
+        let result = compiler.check_session(inputs);
+        (compiler, result)
+    }
+
+    fn evaluate(&self, expr: &str) {
+        let (compiler, result) = self.check(&self.imports, &self.definitions, Some(expr));
+        let program = match result {
+            Ok(program) => program,
+            Err(err) => {
+                compiler.report_error(err);
+                return;
+            }
+        };
+        let function_id = get_expression_function(&program);
+        let function_type = program.get_function_type(&function_id);
+        let type_string = function_type.get_resolved_type_string(&program);
+        let is_function = if let Type::Function(..) = function_type {
+            true
+        } else {
+            false
+        };
+        match Interpreter::eval(program, function_id, self.limits.clone()) {
+            Ok(Some(value)) => println!("{} :: {}", value, type_string),
+            Ok(None) if is_function => println!("<function> :: {}", type_string),
+            Ok(None) => println!("<value> :: {}", type_string),
+            Err(err) => compiler.report_error(Error::RuntimeError(err)),
+        }
+    }
+
+    fn show_type(&self, expr: &str) {
+        let (compiler, result) = self.check(&self.imports, &self.definitions, Some(expr));
+        match result {
+            Ok(program) => {
+                let function_id = get_expression_function(&program);
+                let function_type = program.get_function_type(&function_id);
+                println!(
+                    "{} :: {}",
+                    expr.trim(),
+                    function_type.get_resolved_type_string(&program)
+                );
+            }
+            Err(err) => compiler.report_error(err),
+        }
+    }
+
+    fn add_import(&mut self, import: &str) {
+        let mut imports = self.imports.clone();
+        imports.push(import.trim().to_string());
+        let (compiler, result) = self.check(&imports, &self.definitions, None);
+        match result {
+            Ok(_) => self.imports = imports,
+            Err(err) => compiler.report_error(err),
+        }
+    }
+
+    // Inputs that do not parse as definitions are evaluated as expressions
+    fn add_definition_or_evaluate(&mut self, input: &str) {
+        let name = get_definition_name(input);
+        let mut definitions = self.definitions.clone();
+        match definitions.iter_mut().find(|(n, _)| *n == name) {
+            Some(definition) => definition.1 = input.to_string(),
+            None => definitions.push((name, input.to_string())),
+        }
+        let (compiler, result) = self.check(&self.imports, &definitions, None);
+        match result {
+            Ok(_) => self.definitions = definitions,
+            Err(Error::ParseError(_)) if !is_keyword_definition(input) => self.evaluate(input),
+            Err(err) => compiler.report_error(err),
+        }
+    }
+
+    fn reload(&mut self) {
+        match read_inputs(&self.project_inputs) {
+            Ok(loaded) => self.loaded_project_inputs = loaded,
+            Err(err) => {
+                Compiler::new(Config::new()).report_error(err);
+                return;
+            }
+        }
+        let (compiler, result) = self.check(&self.imports, &self.definitions, None);
+        match result {
+            Ok(_) => println!("reloaded {} file(s)", self.project_inputs.len()),
+            Err(err) => compiler.report_error(err),
+        }
+    }
+
+    // Returns false if the session is over
+    fn process(&mut self, input: &str) -> bool {
+        let trimmed = input.trim();
+        if trimmed.is_empty() {
+            return true;
+        }
+        let mut words = trimmed.splitn(2, char::is_whitespace);
+        let first = words.next().unwrap_or("");
+        let rest = words.next().unwrap_or("").trim();
+        match first {
+            ":quit" | ":q" => return false,
+            ":help" | ":h" => print_help(),
+            ":reload" | ":r" => self.reload(),
+            ":type" | ":t" => self.show_type(rest),
+            "import" => self.add_import(trimmed),
+            _ if first.starts_with(':') => {
+                eprintln!("unknown command {}, try :help", first);
+            }
+            _ => self.add_definition_or_evaluate(input),
+        }
+        true
+    }
+
+    pub fn run(&mut self) {
+        let stdin = io::stdin();
+        let mut lines = stdin.lock().lines();
+        loop {
+            print!("siko> ");
+            io::stdout().flush().expect("stdout flush failed");
+            let line = match lines.next() {
+                Some(Ok(line)) => line,
+                _ => break,
+            };
+            let input = if line.trim() == ":{" {
+                let mut block = Vec::new();
+                loop {
+                    print!("siko| ");
+                    io::stdout().flush().expect("stdout flush failed");
+                    match lines.next() {
+                        Some(Ok(line)) => {
+                            if line.trim() == ":}" {
+                                break;
+                            }
+                            block.push(line);
+                        }
+                        _ => break,
+                    }
+                }
+                block.join("\n")
+            } else {
+                line
+            };
+            if !self.process(&input) {
+                break;
+            }
+        }
+        println!("");
+    }
+}
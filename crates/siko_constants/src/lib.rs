@@ -38,6 +38,38 @@ impl BuiltinOperator {
             _ => panic!("Op {:?} has no func name", self),
         }
     }
+
+    pub fn get_symbol(&self) -> &'static str {
+        match self {
+            BuiltinOperator::Add => "+",
+            BuiltinOperator::Sub => "-",
+            BuiltinOperator::Mul => "*",
+            BuiltinOperator::Div => "/",
+            BuiltinOperator::PipeForward => "|>",
+            BuiltinOperator::And => "&&",
+            BuiltinOperator::Or => "||",
+            BuiltinOperator::Equals => "==",
+            BuiltinOperator::NotEquals => "!=",
+            BuiltinOperator::LessThan => "<",
+            BuiltinOperator::GreaterThan => ">",
+            BuiltinOperator::LessOrEqualThan => "<=",
+            BuiltinOperator::GreaterOrEqualThan => ">=",
+            BuiltinOperator::Not => "!",
+            BuiltinOperator::Minus => "-",
+            BuiltinOperator::Bind => "<-",
+            BuiltinOperator::Arrow => "->",
+        }
+    }
+
+    pub fn is_binary(&self) -> bool {
+        match self {
+            BuiltinOperator::Not
+            | BuiltinOperator::Minus
+            | BuiltinOperator::Bind
+            | BuiltinOperator::Arrow => false,
+            _ => true,
+        }
+    }
 }
 
 pub const MAIN_MODULE_NAME: &str = "Main";
@@ -63,6 +95,11 @@ pub const STRING_TYPE_NAME: &str = "String";
 pub const LIST_MODULE_NAME: &str = "List";
 pub const LIST_TYPE_NAME: &str = "List";
 pub const ITERATOR_MODULE_NAME: &str = "Iterator";
+pub const LIST_ITER_NAME: &str = "iter";
+pub const LIST_TO_LIST_NAME: &str = "toList";
+pub const ITERATOR_RANGE_NAME: &str = "range";
+pub const ITERATOR_RANGE_FROM_NAME: &str = "rangeFrom";
+pub const ITERATOR_FLAT_MAP_NAME: &str = "flatMap";
 pub const TRUE_NAME: &str = "True";
 pub const FALSE_NAME: &str = "False";
 pub const SOME_NAME: &str = "Some";
@@ -79,6 +116,9 @@ pub const ORD_CLASS_NAME: &str = "Ord";
 pub const ORD_OP_NAME: &str = "cmp";
 pub const STD_OPS_MODULE_NAME: &str = "Std.Ops";
 pub const STD_UTIL_BASIC_MODULE_NAME: &str = "Std.Util.Basic";
+pub const STD_ACTOR_MODULE_NAME: &str = "Std.Actor";
+pub const ACTOR_SEND_NAME: &str = "send";
+pub const ACTOR_HANDLES_CLASS_NAME: &str = "Handles";
 pub const MIR_INTERNAL_MODULE_NAME: &str = "__siko__";
 pub const MIR_FUNCTION_TRAIT_NAME: &str = "Function";
 
@@ -99,6 +139,7 @@ pub fn get_implicit_module_list() -> Vec<&'static str> {
         ITERATOR_MODULE_NAME,
         STD_UTIL_BASIC_MODULE_NAME,
         STD_OPS_MODULE_NAME,
+        STD_ACTOR_MODULE_NAME,
     ];
     implicit_modules
 }
@@ -0,0 +1,388 @@
+use crate::page::DocFormat;
+use crate::page::Entry;
+use crate::page::Page;
+use crate::page::Section;
+use siko_ir::class::ClassId;
+use siko_ir::class::InstanceId;
+use siko_ir::data::TypeAliasId;
+use siko_ir::data::TypeDef;
+use siko_ir::data::TypeDefId;
+use siko_ir::function::FunctionId;
+use siko_ir::function::FunctionInfo;
+use siko_ir::instance_resolver::InstanceInfo;
+use siko_ir::program::Program;
+use siko_ir::type_signature::TypeSignature;
+use siko_ir::types::ResolverContext;
+use siko_ir::types::Type;
+use siko_name_resolver::item::DataMember;
+use siko_name_resolver::item::Item;
+use siko_name_resolver::module::Module;
+use siko_type_checker::util::process_type_signature;
+use std::collections::BTreeMap;
+
+fn needs_parens(type_str: &str) -> bool {
+    let wrapped = (type_str.starts_with('(') && type_str.ends_with(')'))
+        || (type_str.starts_with('[') && type_str.ends_with(']'));
+    type_str.contains(' ') && !wrapped
+}
+
+pub struct DocGenerator<'a> {
+    program: &'a Program,
+    modules: &'a BTreeMap<String, Module>,
+}
+
+impl<'a> DocGenerator<'a> {
+    pub fn new(program: &'a Program, modules: &'a BTreeMap<String, Module>) -> DocGenerator<'a> {
+        DocGenerator {
+            program: program,
+            modules: modules,
+        }
+    }
+
+    fn type_string(&self, ty: &Type) -> String {
+        ty.get_resolved_type_string(self.program)
+    }
+
+    fn function_type_string(&self, function_id: &FunctionId) -> String {
+        match self.program.function_types.get(function_id) {
+            Some(ty) => self.type_string(ty),
+            None => format!("?"),
+        }
+    }
+
+    fn class_name(&self, class_id: &ClassId) -> String {
+        self.program.classes.get(class_id).name.clone()
+    }
+
+    // Returns the constraint prefix and the instance type separately, like (Show a) => and Option a
+    fn instance_type_parts(&self, ty: &Type) -> (String, String) {
+        let type_str = self.type_string(ty);
+        let (prefix, type_str) = match type_str.find(" => ") {
+            Some(index) => (
+                format!("{} ", &type_str[..index + 3]),
+                type_str[index + 4..].to_string(),
+            ),
+            None => (String::new(), type_str),
+        };
+        if needs_parens(&type_str) {
+            (prefix, format!("({})", type_str))
+        } else {
+            (prefix, type_str)
+        }
+    }
+
+    fn instance_types(&self, class_id: &ClassId) -> Vec<(Option<InstanceId>, Type)> {
+        let mut result = Vec::new();
+        if let Some(instances) = self.program.instance_resolver.instance_map.get(class_id) {
+            for infos in instances.values() {
+                for info in infos {
+                    let instance_id = match info {
+                        InstanceInfo::UserDefined(_, id, _) => Some(*id),
+                        InstanceInfo::AutoDerived(_) => None,
+                    };
+                    let ty = info.get_type(&self.program.instance_resolver);
+                    result.push((instance_id, ty.clone()));
+                }
+            }
+        }
+        result
+    }
+
+    // Classes implemented by user defined instances for the given type
+    fn instances_of_type(&self, typedef_id: TypeDefId) -> Vec<String> {
+        let mut result = Vec::new();
+        for class_id in self.program.classes.items.keys() {
+            for (instance_id, ty) in self.instance_types(class_id) {
+                if instance_id.is_none() {
+                    continue;
+                }
+                if let Type::Named(_, id, _) = ty {
+                    if id == typedef_id {
+                        result.push(self.class_name(class_id));
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    fn add_type_notes(&self, entry: &mut Entry, typedef_id: TypeDefId, derived: Vec<ClassId>) {
+        if !derived.is_empty() {
+            let names: Vec<_> = derived.iter().map(|id| self.class_name(id)).collect();
+            entry.notes.push(format!("Derives: {}", names.join(", ")));
+        }
+        let instances = self.instances_of_type(typedef_id);
+        if !instances.is_empty() {
+            entry
+                .notes
+                .push(format!("Instances: {}", instances.join(", ")));
+        }
+    }
+
+    fn type_entry(&self, module: &Module, typedef_id: &TypeDefId) -> Entry {
+        match self.program.typedefs.get(typedef_id) {
+            TypeDef::Adt(adt) => {
+                let type_str = match self.program.adt_type_info_map.get(typedef_id) {
+                    Some(info) => self.type_string(&info.adt_type),
+                    None => adt.name.clone(),
+                };
+                let keyword = if adt.newtype { "newtype" } else { "data" };
+                let mut entry = Entry::new(format!("{} {}", keyword, type_str), adt.doc.clone());
+                let derived = adt.derived_classes.iter().map(|d| d.class_id).collect();
+                self.add_type_notes(&mut entry, *typedef_id, derived);
+                for variant in &adt.variants {
+                    let exported = match module.exported_members.get(&variant.name) {
+                        Some(members) => members.iter().any(|m| match m {
+                            DataMember::Variant(v) => {
+                                module.items[&variant.name].iter().any(|item| match item {
+                                    Item::Variant(adt_id, _, id, _) => {
+                                        *adt_id == v.adt_id && id == typedef_id
+                                    }
+                                    _ => false,
+                                })
+                            }
+                            DataMember::RecordField(_) => false,
+                        }),
+                        None => false,
+                    };
+                    if exported {
+                        let signature = format!(
+                            "{} :: {}",
+                            variant.name,
+                            self.function_type_string(&variant.constructor)
+                        );
+                        entry
+                            .children
+                            .push(Entry::new(signature, variant.doc.clone()));
+                    }
+                }
+                entry
+            }
+            TypeDef::Record(record) => {
+                let info = self.program.record_type_info_map.get(typedef_id);
+                let type_str = match info {
+                    Some(info) => self.type_string(&info.record_type),
+                    None => record.name.clone(),
+                };
+                let mut entry = Entry::new(format!("data {}", type_str), record.doc.clone());
+                let derived = record.derived_classes.iter().map(|d| d.class_id).collect();
+                self.add_type_notes(&mut entry, *typedef_id, derived);
+                for (index, field) in record.fields.iter().enumerate() {
+                    let exported = match module.exported_members.get(&field.name) {
+                        Some(members) => members.iter().any(|m| match m {
+                            DataMember::RecordField(f) => {
+                                f.ir_typedef_id == *typedef_id && f.index == index
+                            }
+                            DataMember::Variant(_) => false,
+                        }),
+                        None => false,
+                    };
+                    if exported {
+                        let type_str = match info {
+                            Some(info) => self.type_string(&info.field_types[index].0),
+                            None => format!("?"),
+                        };
+                        let signature = format!("{} :: {}", field.name, type_str);
+                        entry
+                            .children
+                            .push(Entry::new(signature, field.doc.clone()));
+                    }
+                }
+                entry
+            }
+        }
+    }
+
+    fn type_alias_entry(&self, type_alias_id: &TypeAliasId) -> Entry {
+        let type_alias = self.program.type_aliases.get(type_alias_id);
+        let mut context = ResolverContext::new(self.program);
+        // resolving the body names the type arguments of the alias
+        let type_str = match type_alias.type_signature {
+            Some(id) => {
+                let mut type_var_generator = self.program.type_var_generator.clone();
+                let ty = process_type_signature(id, self.program, &mut type_var_generator);
+                ty.get_resolved_type_string_with_context(&mut context)
+            }
+            None => format!("?"),
+        };
+        let mut parts = vec![type_alias.name.clone()];
+        for index in &type_alias.type_args {
+            parts.push(context.get_type_arg_name(*index));
+        }
+        let signature = format!("type {} = {}", parts.join(" "), type_str);
+        Entry::new(signature, type_alias.doc.clone())
+    }
+
+    fn class_entry(&self, class_id: &ClassId) -> Entry {
+        let class = self.program.classes.get(class_id);
+        let type_arg_name = |id| match &self.program.type_signatures.get(id).item {
+            TypeSignature::TypeArgument(_, name, _) => name.clone(),
+            _ => format!("a"),
+        };
+        let arg = match class.type_signature {
+            Some(id) => match &self.program.type_signatures.get(&id).item {
+                TypeSignature::Tuple(items) => {
+                    let names: Vec<_> = items.iter().map(type_arg_name).collect();
+                    names.join(" ")
+                }
+                _ => type_arg_name(&id),
+            },
+            None => format!("a"),
+        };
+        let constraints: Vec<_> = class
+            .constraints
+            .iter()
+            .map(|id| format!("{} {}", self.class_name(id), arg))
+            .collect();
+        let signature = if constraints.is_empty() {
+            format!("class {} {}", class.name, arg)
+        } else {
+            format!(
+                "class ({}) => {} {}",
+                constraints.join(", "),
+                class.name,
+                arg
+            )
+        };
+        let mut entry = Entry::new(signature, class.doc.clone());
+        let instances: Vec<_> = self
+            .instance_types(class_id)
+            .iter()
+            .map(|(_, ty)| match ty {
+                // instances of multi parameter classes list their arguments
+                Type::Tuple(items) if class.is_multi_param() => {
+                    let mut context = ResolverContext::new(self.program);
+                    let items: Vec<_> = items
+                        .iter()
+                        .map(|item| {
+                            let type_str = item.get_resolved_type_string_with_context(&mut context);
+                            if needs_parens(&type_str) {
+                                format!("({})", type_str)
+                            } else {
+                                type_str
+                            }
+                        })
+                        .collect();
+                    items.join(" ")
+                }
+                _ => self.instance_type_parts(ty).1,
+            })
+            .collect();
+        if !instances.is_empty() {
+            entry
+                .notes
+                .push(format!("Instances: {}", instances.join(", ")));
+        }
+        for (name, member_id) in &class.members {
+            let member = self.program.class_members.get(member_id);
+            let type_str = match self.program.class_member_types.get(member_id) {
+                Some((ty, _)) => self.type_string(ty),
+                None => format!("?"),
+            };
+            let mut child = Entry::new(format!("{} :: {}", name, type_str), member.doc.clone());
+            if member.default_implementation.is_some() {
+                child.notes.push(format!("Has default implementation"));
+            }
+            entry.children.push(child);
+        }
+        entry
+    }
+
+    fn function_entry(&self, name: &str, function_id: &FunctionId) -> Entry {
+        let function = self.program.functions.get(function_id);
+        let doc = match &function.info {
+            FunctionInfo::NamedFunction(info) => info.doc.clone(),
+            _ => None,
+        };
+        let signature = format!("{} :: {}", name, self.function_type_string(function_id));
+        Entry::new(signature, doc)
+    }
+
+    fn instance_entries(&self, module: &Module) -> Vec<Entry> {
+        let mut entries = Vec::new();
+        for class_id in self.program.classes.items.keys() {
+            for (instance_id, ty) in self.instance_types(class_id) {
+                let instance_id = match instance_id {
+                    Some(id) => id,
+                    None => continue,
+                };
+                let instance = self.program.instances.get(&instance_id);
+                if instance.module != module.name {
+                    continue;
+                }
+                let (prefix, type_str) = self.instance_type_parts(&ty);
+                let name = match &instance.name {
+                    Some(name) => format!("as {} ", name),
+                    None => String::new(),
+                };
+                let signature = format!(
+                    "instance {}{}{} {}",
+                    name,
+                    prefix,
+                    self.class_name(class_id),
+                    type_str
+                );
+                entries.push(Entry::new(signature, instance.doc.clone()));
+            }
+        }
+        entries
+    }
+
+    fn module_page(&self, module: &Module) -> Page {
+        let mut types = Vec::new();
+        let mut classes = Vec::new();
+        let mut functions = Vec::new();
+        for (name, items) in &module.exported_items {
+            for item in items {
+                match item {
+                    Item::Adt(_, id) | Item::Record(_, id) => {
+                        types.push(self.type_entry(module, id));
+                    }
+                    Item::TypeAlias(_, id) => types.push(self.type_alias_entry(id)),
+                    Item::Class(_, id) => classes.push(self.class_entry(id)),
+                    Item::Function(_, id) => functions.push(self.function_entry(name, id)),
+                    _ => {}
+                }
+            }
+        }
+        let sections = vec![
+            Section {
+                title: format!("Types"),
+                entries: types,
+            },
+            Section {
+                title: format!("Classes"),
+                entries: classes,
+            },
+            Section {
+                title: format!("Functions"),
+                entries: functions,
+            },
+            Section {
+                title: format!("Instances"),
+                entries: self.instance_entries(module),
+            },
+        ];
+        Page {
+            title: format!("Module {}", module.name),
+            sections: sections,
+        }
+    }
+
+    // Returns the generated files with their contents, one page per module and an index page
+    pub fn generate(&self, format: DocFormat) -> Vec<(String, String)> {
+        let mut files = Vec::new();
+        let mut index = Vec::new();
+        for (name, module) in self.modules {
+            let filename = format!("{}.{}", name, format.extension());
+            let page = self.module_page(module);
+            files.push((filename.clone(), page.render(format)));
+            index.push((name.clone(), filename));
+        }
+        files.push((
+            format!("index.{}", format.extension()),
+            Page::render_index(&index, format),
+        ));
+        files
+    }
+}
@@ -0,0 +1,198 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DocFormat {
+    Markdown,
+    Html,
+}
+
+impl DocFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DocFormat::Markdown => "md",
+            DocFormat::Html => "html",
+        }
+    }
+}
+
+pub struct Entry {
+    pub signature: String,
+    pub doc: Option<String>,
+    pub notes: Vec<String>,
+    pub children: Vec<Entry>,
+}
+
+impl Entry {
+    pub fn new(signature: String, doc: Option<String>) -> Entry {
+        Entry {
+            signature: signature,
+            doc: doc,
+            notes: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+}
+
+pub struct Section {
+    pub title: String,
+    pub entries: Vec<Entry>,
+}
+
+pub struct Page {
+    pub title: String,
+    pub sections: Vec<Section>,
+}
+
+fn escape_html(s: &str) -> String {
+    let mut result = String::new();
+    for c in s.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+fn code_span(s: &str) -> String {
+    if s.contains('`') {
+        format!("`` {} ``", s)
+    } else {
+        format!("`{}`", s)
+    }
+}
+
+impl Page {
+    pub fn render(&self, format: DocFormat) -> String {
+        match format {
+            DocFormat::Markdown => self.render_markdown(),
+            DocFormat::Html => self.render_html(),
+        }
+    }
+
+    pub fn render_index(modules: &[(String, String)], format: DocFormat) -> String {
+        match format {
+            DocFormat::Markdown => {
+                let mut lines = vec![format!("# Modules"), String::new()];
+                for (name, filename) in modules {
+                    lines.push(format!("- [{}]({})", name, filename));
+                }
+                lines.push(String::new());
+                lines.join("\n")
+            }
+            DocFormat::Html => {
+                let mut lines = vec![
+                    format!("<!DOCTYPE html>"),
+                    format!("<html>"),
+                    format!("<head><meta charset=\"utf-8\"><title>Modules</title></head>"),
+                    format!("<body>"),
+                    format!("<h1>Modules</h1>"),
+                    format!("<ul>"),
+                ];
+                for (name, filename) in modules {
+                    lines.push(format!(
+                        "<li><a href=\"{}\">{}</a></li>",
+                        escape_html(filename),
+                        escape_html(name)
+                    ));
+                }
+                lines.push(format!("</ul>"));
+                lines.push(format!("</body>"));
+                lines.push(format!("</html>"));
+                lines.push(String::new());
+                lines.join("\n")
+            }
+        }
+    }
+
+    fn render_markdown(&self) -> String {
+        let mut lines = Vec::new();
+        lines.push(format!("# {}", self.title));
+        for section in &self.sections {
+            if section.entries.is_empty() {
+                continue;
+            }
+            lines.push(String::new());
+            lines.push(format!("## {}", section.title));
+            for entry in &section.entries {
+                lines.push(String::new());
+                lines.push(format!("### {}", code_span(&entry.signature)));
+                if let Some(doc) = &entry.doc {
+                    lines.push(String::new());
+                    lines.push(doc.clone());
+                }
+                for note in &entry.notes {
+                    lines.push(String::new());
+                    lines.push(format!("*{}*", note));
+                }
+                if !entry.children.is_empty() {
+                    lines.push(String::new());
+                }
+                for child in &entry.children {
+                    lines.push(format!("- {}", code_span(&child.signature)));
+                    if let Some(doc) = &child.doc {
+                        for line in doc.lines() {
+                            lines.push(format!("  {}", line));
+                        }
+                    }
+                    for note in &child.notes {
+                        lines.push(format!("  *{}*", note));
+                    }
+                }
+            }
+        }
+        lines.push(String::new());
+        lines.join("\n")
+    }
+
+    fn render_html(&self) -> String {
+        let mut lines = Vec::new();
+        lines.push(format!("<!DOCTYPE html>"));
+        lines.push(format!("<html>"));
+        lines.push(format!(
+            "<head><meta charset=\"utf-8\"><title>{}</title></head>",
+            escape_html(&self.title)
+        ));
+        lines.push(format!("<body>"));
+        lines.push(format!("<h1>{}</h1>", escape_html(&self.title)));
+        for section in &self.sections {
+            if section.entries.is_empty() {
+                continue;
+            }
+            lines.push(format!("<h2>{}</h2>", escape_html(&section.title)));
+            for entry in &section.entries {
+                lines.push(format!(
+                    "<h3><code>{}</code></h3>",
+                    escape_html(&entry.signature)
+                ));
+                if let Some(doc) = &entry.doc {
+                    lines.push(format!("<p>{}</p>", escape_html(doc)));
+                }
+                for note in &entry.notes {
+                    lines.push(format!("<p><em>{}</em></p>", escape_html(note)));
+                }
+                if entry.children.is_empty() {
+                    continue;
+                }
+                lines.push(format!("<ul>"));
+                for child in &entry.children {
+                    let mut item = format!("<li><code>{}</code>", escape_html(&child.signature));
+                    if let Some(doc) = &child.doc {
+                        item += &format!("<p>{}</p>", escape_html(doc));
+                    }
+                    for note in &child.notes {
+                        item += &format!("<p><em>{}</em></p>", escape_html(note));
+                    }
+                    item += "</li>";
+                    lines.push(item);
+                }
+                lines.push(format!("</ul>"));
+            }
+        }
+        lines.push(format!("</body>"));
+        lines.push(format!("</html>"));
+        lines.push(String::new());
+        lines.join("\n")
+    }
+}
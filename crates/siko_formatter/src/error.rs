@@ -0,0 +1,8 @@
+use siko_location_info::filepath::FilePath;
+use siko_parser::error::LexerError;
+
+#[derive(Debug)]
+pub enum FormatterError {
+    LexerError(Vec<LexerError>),
+    MeaningChanged(FilePath),
+}
@@ -0,0 +1,602 @@
+use crate::error::FormatterError;
+use siko_constants::BuiltinOperator;
+use siko_location_info::filepath::FilePath;
+use siko_parser::lexer::Lexer;
+use siko_parser::lexer::SourceToken;
+use siko_parser::token::TokenInfo;
+use siko_parser::token::TokenKind;
+use std::collections::BTreeMap;
+
+pub const MAX_LINE_WIDTH: usize = 100;
+const INDENT: usize = 4;
+
+#[derive(Clone, Copy, PartialEq)]
+enum BlockKind {
+    Module,
+    Let,
+    CaseOf,
+    Other,
+}
+
+struct Block {
+    id: usize,
+    kind: BlockKind,
+    // column of the items in the source
+    column: usize,
+    // column of the items in the output
+    indent: usize,
+    // indentation of the line that opened the block
+    owner_indent: usize,
+}
+
+struct PendingBlock {
+    kind: BlockKind,
+    indent: usize,
+    owner_indent: usize,
+}
+
+struct OutputLine {
+    indent: usize,
+    text: String,
+    block_id: Option<usize>,
+    arrow: Option<usize>,
+    import: bool,
+}
+
+impl OutputLine {
+    fn new(indent: usize, text: String) -> OutputLine {
+        OutputLine {
+            indent: indent,
+            text: text,
+            block_id: None,
+            arrow: None,
+            import: false,
+        }
+    }
+
+    fn is_blank(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    fn column_of(&self, index: usize) -> usize {
+        self.indent + self.text[..index].chars().count()
+    }
+}
+
+struct SourceLine<'a> {
+    tokens: Vec<&'a SourceToken>,
+    blank_before: bool,
+}
+
+fn kind(token: &SourceToken) -> TokenKind {
+    token.info.token.kind()
+}
+
+fn column(token: &SourceToken) -> usize {
+    token.info.location.span.start
+}
+
+fn get_block_kind(token: &SourceToken) -> Option<BlockKind> {
+    match kind(token) {
+        TokenKind::KeywordWhere | TokenKind::KeywordDo => Some(BlockKind::Other),
+        TokenKind::KeywordOf => Some(BlockKind::CaseOf),
+        TokenKind::KeywordLet => Some(BlockKind::Let),
+        _ => None,
+    }
+}
+
+fn needs_space(prev: &SourceToken, next: &SourceToken) -> bool {
+    match (kind(prev), kind(next)) {
+        (_, TokenKind::Comment) => true,
+        (TokenKind::LParen, _) | (TokenKind::LBracket, _) => false,
+        (_, TokenKind::RParen) | (_, TokenKind::RBracket) | (_, TokenKind::Comma) => false,
+        (TokenKind::Comma, _) => true,
+        // everything else keeps the original decision, removing a space could merge tokens
+        _ => prev.end_line != next.info.location.line || prev.end_offset < column(next),
+    }
+}
+
+fn render(tokens: &[&SourceToken]) -> String {
+    let mut text = String::new();
+    for (index, token) in tokens.iter().enumerate() {
+        if index > 0 && needs_space(tokens[index - 1], token) {
+            text.push(' ');
+        }
+        text.push_str(&token.text);
+    }
+    text
+}
+
+// Moves the continuation lines of a multi-line comment by the same number of
+// columns as its first line, so the comment keeps its shape
+fn shift_continuation_lines(text: &str, from: usize, to: usize) -> String {
+    let mut lines = text.split('\n');
+    let mut result = lines.next().unwrap_or("").to_string();
+    for line in lines {
+        result.push('\n');
+        let leading = line.len() - line.trim_start_matches(' ').len();
+        if to >= from {
+            result.push_str(&" ".repeat(to - from));
+            result.push_str(line);
+        } else {
+            result.push_str(&line[leading.min(from - to)..]);
+        }
+    }
+    result
+}
+
+fn is_plain(tokens: &[&SourceToken]) -> bool {
+    tokens
+        .iter()
+        .all(|t| kind(t) != TokenKind::Comment && get_block_kind(t).is_none())
+}
+
+// Returns the tokens at paren depth 0
+fn top_level_kinds(tokens: &[&SourceToken]) -> Vec<(usize, TokenKind)> {
+    let mut depth = 0;
+    let mut result = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        match kind(token) {
+            TokenKind::LParen | TokenKind::LBracket => depth += 1,
+            TokenKind::RParen | TokenKind::RBracket => depth -= 1,
+            k => {
+                if depth == 0 {
+                    result.push((index, k));
+                }
+            }
+        }
+    }
+    result
+}
+
+fn is_signature(tokens: &[&SourceToken]) -> bool {
+    match kind(tokens[0]) {
+        TokenKind::VarIdentifier | TokenKind::LParen => {}
+        _ => return false,
+    }
+    if !is_plain(tokens) {
+        return false;
+    }
+    let kinds = top_level_kinds(tokens);
+    kinds
+        .iter()
+        .any(|(_, k)| *k == TokenKind::KeywordDoubleColon)
+        && kinds
+            .iter()
+            .all(|(_, k)| !matches!(k, TokenKind::Equal | TokenKind::Op(BuiltinOperator::Bind)))
+}
+
+// Long signatures are broken before every top level arrow and after the constraints
+fn wrap_signature(tokens: &[&SourceToken]) -> Vec<String> {
+    let mut segments = Vec::new();
+    let mut current = Vec::new();
+    let mut break_after = false;
+    let top_level = top_level_kinds(tokens);
+    for (index, token) in tokens.iter().enumerate() {
+        let is_top_level = top_level.iter().any(|(i, _)| *i == index);
+        let is_arrow = kind(token) == TokenKind::Op(BuiltinOperator::Arrow);
+        if (break_after || (is_top_level && is_arrow)) && !current.is_empty() {
+            segments.push(render(&current));
+            current.clear();
+        }
+        break_after = is_top_level && kind(token) == TokenKind::KeywordConstraint;
+        current.push(*token);
+    }
+    if !current.is_empty() {
+        segments.push(render(&current));
+    }
+    segments
+}
+
+fn split_lines(tokens: &[SourceToken]) -> Vec<SourceLine<'_>> {
+    let mut lines: Vec<SourceLine> = Vec::new();
+    let mut prev: Option<&SourceToken> = None;
+    for token in tokens {
+        let line = token.info.location.line;
+        match prev {
+            Some(prev) if prev.end_line == line => {
+                lines.last_mut().unwrap().tokens.push(token);
+            }
+            _ => {
+                let blank_before = match prev {
+                    Some(prev) => line > prev.end_line + 1,
+                    None => false,
+                };
+                lines.push(SourceLine {
+                    tokens: vec![token],
+                    blank_before: blank_before,
+                });
+            }
+        }
+        prev = Some(token);
+    }
+    lines
+}
+
+struct Formatter {
+    output: Vec<OutputLine>,
+    blocks: Vec<Block>,
+    pending: Option<PendingBlock>,
+    next_block_id: usize,
+    last_item_line: Option<usize>,
+}
+
+impl Formatter {
+    fn new() -> Formatter {
+        Formatter {
+            output: Vec::new(),
+            blocks: Vec::new(),
+            pending: None,
+            next_block_id: 0,
+            last_item_line: None,
+        }
+    }
+
+    fn push_block(&mut self, kind: BlockKind, column: usize, indent: usize, owner_indent: usize) {
+        let block = Block {
+            id: self.next_block_id,
+            kind: kind,
+            column: column,
+            indent: indent,
+            owner_indent: owner_indent,
+        };
+        self.next_block_id += 1;
+        self.blocks.push(block);
+    }
+
+    // The in keyword closes the innermost let block, regardless of its column
+    fn pop_let(&mut self) -> Option<Block> {
+        if !self.blocks.iter().any(|b| b.kind == BlockKind::Let) {
+            return None;
+        }
+        while let Some(block) = self.blocks.pop() {
+            if block.kind == BlockKind::Let {
+                return Some(block);
+            }
+        }
+        None
+    }
+
+    fn comment_indent(&self, column: usize) -> usize {
+        if let Some(pending) = &self.pending {
+            match self.blocks.last() {
+                Some(block) if column <= block.column => {}
+                _ => return pending.indent,
+            }
+        }
+        for block in self.blocks.iter().rev() {
+            if block.column == column {
+                return block.indent;
+            }
+            if block.column < column {
+                return block.indent + INDENT;
+            }
+        }
+        0
+    }
+
+    fn add_blank_line(&mut self) {
+        match self.output.last() {
+            Some(line) if !line.is_blank() => {
+                self.output.push(OutputLine::new(0, String::new()));
+            }
+            _ => {}
+        }
+    }
+
+    fn format_comment_line(&mut self, tokens: Vec<&SourceToken>) {
+        let first_column = column(tokens[0]);
+        let indent = self.comment_indent(first_column);
+        let text = shift_continuation_lines(&render(&tokens), first_column, indent);
+        self.output.push(OutputLine::new(indent, text));
+    }
+
+    fn format_line(&mut self, tokens: Vec<&SourceToken>, signature: bool) {
+        let first = tokens[0];
+        let first_column = column(first);
+        let module_line = kind(first) == TokenKind::KeywordModule;
+        if module_line {
+            self.blocks.clear();
+            self.pending = None;
+        }
+        if let Some(pending) = self.pending.take() {
+            self.push_block(
+                pending.kind,
+                first_column,
+                pending.indent,
+                pending.owner_indent,
+            );
+        }
+        let mut in_indent = None;
+        if kind(first) == TokenKind::KeywordIn {
+            if let Some(block) = self.pop_let() {
+                in_indent = Some(block.owner_indent + INDENT);
+            }
+        }
+        while let Some(block) = self.blocks.last() {
+            if block.column > first_column {
+                self.blocks.pop();
+            } else {
+                break;
+            }
+        }
+        let mut item_block = None;
+        let indent = match (in_indent, self.blocks.last()) {
+            (Some(indent), _) => indent,
+            (None, Some(block)) if block.column == first_column => {
+                item_block = Some((block.id, block.kind));
+                block.indent
+            }
+            (None, Some(block)) => block.indent + INDENT,
+            (None, None) => {
+                if module_line {
+                    0
+                } else {
+                    INDENT
+                }
+            }
+        };
+        match item_block {
+            Some(_) => {
+                self.last_item_line = Some(self.output.len());
+            }
+            None => {
+                if let Some(index) = self.last_item_line {
+                    self.output[index].import = false;
+                }
+            }
+        }
+        if signature {
+            let text = render(&tokens);
+            if indent + text.chars().count() > MAX_LINE_WIDTH {
+                let segments = wrap_signature(&tokens);
+                for (index, segment) in segments.into_iter().enumerate() {
+                    if index == 0 {
+                        let mut line = OutputLine::new(indent, segment);
+                        line.block_id = item_block.map(|(id, _)| id);
+                        self.output.push(line);
+                    } else {
+                        self.output.push(OutputLine::new(indent + INDENT, segment));
+                    }
+                }
+                return;
+            }
+        }
+        let last_code_index = tokens
+            .iter()
+            .rposition(|t| kind(t) != TokenKind::Comment)
+            .expect("Line without code");
+        let mut text = String::new();
+        let mut column = indent;
+        let mut depth: usize = 0;
+        let mut arrow = None;
+        let mut alignable = true;
+        let mut inline_block = None;
+        for (index, token) in tokens.iter().enumerate() {
+            if index > 0 && needs_space(tokens[index - 1], token) {
+                text.push(' ');
+                column += 1;
+            }
+            let token_kind = kind(token);
+            if token_kind != TokenKind::Comment {
+                if let Some((block_kind, owner_indent)) = inline_block.take() {
+                    self.push_block(block_kind, self::column(token), column, owner_indent);
+                    if arrow.is_some() {
+                        alignable = false;
+                    }
+                }
+            }
+            match token_kind {
+                TokenKind::LParen | TokenKind::LBracket => depth += 1,
+                TokenKind::RParen | TokenKind::RBracket => depth = depth.saturating_sub(1),
+                TokenKind::Op(BuiltinOperator::Arrow) => {
+                    let case_arm = matches!(item_block, Some((_, BlockKind::CaseOf)));
+                    if case_arm && depth == 0 && arrow.is_none() {
+                        arrow = Some(text.len());
+                    }
+                }
+                TokenKind::KeywordIn if index > 0 => {
+                    self.pop_let();
+                }
+                _ => {}
+            }
+            text.push_str(&token.text);
+            match token.text.rfind('\n') {
+                Some(pos) => column = token.text[pos + 1..].chars().count(),
+                None => column += token.text.chars().count(),
+            }
+            if let Some(block_kind) = get_block_kind(token) {
+                let block_kind = if module_line && token_kind == TokenKind::KeywordWhere {
+                    BlockKind::Module
+                } else {
+                    block_kind
+                };
+                if index == last_code_index {
+                    let block_indent = match block_kind {
+                        BlockKind::Module => 0,
+                        BlockKind::Let => indent + 2 * INDENT,
+                        _ => indent + INDENT,
+                    };
+                    self.pending = Some(PendingBlock {
+                        kind: block_kind,
+                        indent: block_indent,
+                        owner_indent: indent,
+                    });
+                } else {
+                    inline_block = Some((block_kind, indent));
+                }
+            }
+        }
+        let mut line = OutputLine::new(indent, text);
+        if let Some((id, block_kind)) = item_block {
+            line.block_id = Some(id);
+            line.import =
+                block_kind == BlockKind::Module && kind(first) == TokenKind::KeywordImport;
+        }
+        if alignable {
+            line.arrow = arrow;
+        }
+        self.output.push(line);
+    }
+
+    fn align_case_arms(&mut self) {
+        let mut widths = BTreeMap::new();
+        for line in &self.output {
+            if let (Some(id), Some(arrow)) = (line.block_id, line.arrow) {
+                let column = line.column_of(arrow);
+                let width = widths.entry(id).or_insert(column);
+                if *width < column {
+                    *width = column;
+                }
+            }
+        }
+        for line in &mut self.output {
+            if let (Some(id), Some(arrow)) = (line.block_id, line.arrow) {
+                let padding = widths[&id] - line.column_of(arrow);
+                line.text.insert_str(arrow, &" ".repeat(padding));
+            }
+        }
+    }
+
+    fn sort_imports(&mut self) {
+        let mut index = 0;
+        while index < self.output.len() {
+            if !self.output[index].import {
+                index += 1;
+                continue;
+            }
+            let start = index;
+            while index < self.output.len() && self.output[index].import {
+                index += 1;
+            }
+            self.output[start..index].sort_by(|a, b| a.text.cmp(&b.text));
+        }
+    }
+
+    fn finish(mut self, sort_imports: bool) -> String {
+        self.align_case_arms();
+        if sort_imports {
+            self.sort_imports();
+        }
+        while let Some(line) = self.output.last() {
+            if line.is_blank() {
+                self.output.pop();
+            } else {
+                break;
+            }
+        }
+        let mut result = String::new();
+        for line in self.output {
+            if !line.is_blank() {
+                for _ in 0..line.indent {
+                    result.push(' ');
+                }
+                result.push_str(line.text.trim_end());
+            }
+            result.push('\n');
+        }
+        result
+    }
+}
+
+fn format_lines(tokens: &[SourceToken], sort_imports: bool) -> String {
+    let lines = split_lines(tokens);
+    let mut formatter = Formatter::new();
+    let mut index = 0;
+    while index < lines.len() {
+        let line = &lines[index];
+        index += 1;
+        if line.blank_before {
+            formatter.add_blank_line();
+        }
+        if line.tokens.iter().all(|t| kind(t) == TokenKind::Comment) {
+            formatter.format_comment_line(line.tokens.clone());
+            continue;
+        }
+        let mut tokens = line.tokens.clone();
+        let signature = is_signature(&tokens);
+        if signature {
+            // multi line signatures are joined, they are wrapped again if they are too long
+            let first_column = column(tokens[0]);
+            while index < lines.len() {
+                let next = &lines[index];
+                if next.blank_before
+                    || column(next.tokens[0]) <= first_column
+                    || !is_plain(&next.tokens)
+                {
+                    break;
+                }
+                tokens.extend(next.tokens.iter());
+                index += 1;
+            }
+        }
+        formatter.format_line(tokens, signature);
+    }
+    formatter.finish(sort_imports)
+}
+
+pub fn format_tokens(tokens: &[SourceToken]) -> String {
+    format_lines(tokens, true)
+}
+
+fn lex_source(content: &str, file_path: &FilePath) -> Result<Vec<SourceToken>, FormatterError> {
+    let mut lexer = Lexer::new(content, file_path.clone());
+    let mut errors = Vec::new();
+    match lexer.process_source(&mut errors) {
+        Ok(tokens) => {
+            if errors.is_empty() {
+                Ok(tokens)
+            } else {
+                Err(FormatterError::LexerError(errors))
+            }
+        }
+        Err(e) => {
+            errors.push(e);
+            Err(FormatterError::LexerError(errors))
+        }
+    }
+}
+
+fn lex_layout(content: &str, file_path: &FilePath) -> Result<Vec<TokenInfo>, FormatterError> {
+    let mut lexer = Lexer::new(content, file_path.clone());
+    let mut errors = Vec::new();
+    match lexer.process(&mut errors) {
+        Ok(tokens) => {
+            if errors.is_empty() {
+                Ok(tokens)
+            } else {
+                Err(FormatterError::LexerError(errors))
+            }
+        }
+        Err(e) => {
+            errors.push(e);
+            Err(FormatterError::LexerError(errors))
+        }
+    }
+}
+
+fn same_tokens(a: &[TokenInfo], b: &[TokenInfo]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b.iter())
+            .all(|(a, b)| format!("{:?}", a.token) == format!("{:?}", b.token))
+}
+
+// The formatted source must produce exactly the same token stream after layout processing,
+// otherwise the formatter would change the meaning of the program. Sorting the imports reorders
+// tokens, so the check is done on the unsorted output.
+pub fn format_source(content: &str, file_path: &FilePath) -> Result<String, FormatterError> {
+    let original = lex_layout(content, file_path)?;
+    let tokens = lex_source(content, file_path)?;
+    let unsorted = format_lines(&tokens, false);
+    let same = match lex_layout(&unsorted, file_path) {
+        Ok(result) => same_tokens(&original, &result),
+        Err(_) => false,
+    };
+    let formatted = format_tokens(&tokens);
+    if same && lex_layout(&formatted, file_path).is_ok() {
+        Ok(formatted)
+    } else {
+        Err(FormatterError::MeaningChanged(file_path.clone()))
+    }
+}
@@ -0,0 +1,69 @@
+use crate::value::Value;
+use siko_ir::actor::ActorId;
+use std::collections::VecDeque;
+
+pub struct ActorInstance {
+    pub actor_id: ActorId,
+    pub state: Value,
+    mailbox: VecDeque<Value>,
+}
+
+pub struct ActorSystem {
+    instances: Vec<ActorInstance>,
+    ready_queue: VecDeque<usize>,
+}
+
+impl Default for ActorSystem {
+    fn default() -> ActorSystem {
+        ActorSystem::new()
+    }
+}
+
+impl ActorSystem {
+    pub fn new() -> ActorSystem {
+        ActorSystem {
+            instances: Vec::new(),
+            ready_queue: VecDeque::new(),
+        }
+    }
+
+    pub fn spawn(&mut self, actor_id: ActorId, state: Value) -> usize {
+        let index = self.instances.len();
+        let instance = ActorInstance {
+            actor_id: actor_id,
+            state: state,
+            mailbox: VecDeque::new(),
+        };
+        self.instances.push(instance);
+        index
+    }
+
+    pub fn get_instance(&self, index: usize) -> &ActorInstance {
+        &self.instances[index]
+    }
+
+    pub fn enqueue(&mut self, index: usize, message: Value) {
+        let instance = &mut self.instances[index];
+        if instance.mailbox.is_empty() {
+            self.ready_queue.push_back(index);
+        }
+        instance.mailbox.push_back(message);
+    }
+
+    pub fn next_message(&mut self) -> Option<(usize, Value)> {
+        let index = self.ready_queue.pop_front()?;
+        let instance = &mut self.instances[index];
+        let message = instance
+            .mailbox
+            .pop_front()
+            .expect("Empty mailbox in ready queue");
+        if !instance.mailbox.is_empty() {
+            self.ready_queue.push_back(index);
+        }
+        Some((index, message))
+    }
+
+    pub fn update_state(&mut self, index: usize, state: Value) {
+        self.instances[index].state = state;
+    }
+}
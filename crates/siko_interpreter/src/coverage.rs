@@ -0,0 +1,90 @@
+use siko_ir::expr::Expr;
+use siko_ir::expr::ExprId;
+use siko_ir::program::Program;
+use siko_location_info::location_id::LocationId;
+use std::cell::Cell;
+
+pub struct ExprCoverage {
+    pub location_id: LocationId,
+    pub count: u64,
+}
+
+// A case or an if expression, an arm of an if is one of its branches
+pub struct BranchCoverage {
+    pub location_id: LocationId,
+    pub count: u64,
+    pub arms: Vec<ExprCoverage>,
+}
+
+pub struct CoverageData {
+    pub exprs: Vec<ExprCoverage>,
+    pub branches: Vec<BranchCoverage>,
+}
+
+// Counts how many times each expression was evaluated
+pub struct Coverage {
+    counts: Vec<Cell<u64>>,
+}
+
+impl Coverage {
+    pub fn new(program: &Program) -> Coverage {
+        let size = program
+            .exprs
+            .items
+            .keys()
+            .next_back()
+            .map(|id| id.id + 1)
+            .unwrap_or(0);
+        Coverage {
+            counts: vec![Cell::new(0); size],
+        }
+    }
+
+    pub fn hit(&self, expr_id: ExprId) {
+        let count = &self.counts[expr_id.id];
+        count.set(count.get() + 1);
+    }
+
+    fn get_count(&self, expr_id: &ExprId) -> u64 {
+        self.counts[expr_id.id].get()
+    }
+
+    pub fn get_data(&self, program: &Program) -> CoverageData {
+        let mut exprs = Vec::new();
+        let mut branches = Vec::new();
+        for (expr_id, expr_info) in &program.exprs.items {
+            let count = self.get_count(expr_id);
+            exprs.push(ExprCoverage {
+                location_id: expr_info.location_id,
+                count: count,
+            });
+            let arms = match &expr_info.item {
+                // an arm is located at its pattern, its body runs when it matches
+                Expr::CaseOf(_, cases, _) => cases
+                    .iter()
+                    .map(|case| ExprCoverage {
+                        location_id: program.patterns.get(&case.pattern_id).location_id,
+                        count: self.get_count(&case.body),
+                    })
+                    .collect(),
+                Expr::If(_, true_branch, false_branch) => [true_branch, false_branch]
+                    .iter()
+                    .map(|branch| ExprCoverage {
+                        location_id: program.exprs.get(branch).location_id,
+                        count: self.get_count(branch),
+                    })
+                    .collect(),
+                _ => continue,
+            };
+            branches.push(BranchCoverage {
+                location_id: expr_info.location_id,
+                count: count,
+                arms: arms,
+            });
+        }
+        CoverageData {
+            exprs: exprs,
+            branches: branches,
+        }
+    }
+}
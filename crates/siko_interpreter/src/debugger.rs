@@ -0,0 +1,279 @@
+use siko_ir::program::Program;
+use siko_location_info::location_id::LocationId;
+use siko_location_info::location_info::LocationInfo;
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fs;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub file: String,
+    // starts from 1
+    pub line: usize,
+}
+
+pub struct Variable {
+    pub name: String,
+    pub ty: String,
+    pub value: String,
+}
+
+pub struct Frame {
+    pub function: String,
+    pub position: Option<Position>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StopReason {
+    Entry,
+    Breakpoint,
+    Step,
+}
+
+// The state of the program when it is stopped. The frames start with the
+// innermost one, the variables belong to it.
+pub struct Stop {
+    pub reason: StopReason,
+    pub frames: Vec<Frame>,
+    pub arguments: Vec<Variable>,
+    pub locals: Vec<Variable>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resume {
+    Continue,
+    StepIn,
+    StepOver,
+    StepOut,
+}
+
+// The user interface of the debugger, e.g. a command line or a debug adapter
+pub trait DebugFrontend {
+    // Called before the program starts
+    fn start(&mut self, breakpoints: &mut Breakpoints) -> Resume;
+
+    fn stopped(&mut self, stop: &Stop, breakpoints: &mut Breakpoints) -> Resume;
+
+    // Called with everything the program prints
+    fn output(&mut self, text: &str);
+
+    fn finished(&mut self, error: Option<String>);
+}
+
+// Breakpoints can only be set on lines having an expression on them
+pub struct Breakpoints {
+    lines: BTreeMap<String, BTreeSet<usize>>,
+    active: BTreeSet<Position>,
+}
+
+impl Breakpoints {
+    fn new(lines: BTreeMap<String, BTreeSet<usize>>) -> Breakpoints {
+        Breakpoints {
+            lines: lines,
+            active: BTreeSet::new(),
+        }
+    }
+
+    // Finds the file of the program a path given by the user refers to, it
+    // can be relative to a different directory or just a file name if that
+    // is unique.
+    pub fn resolve_file(&self, file: &str) -> Option<String> {
+        if self.lines.contains_key(file) {
+            return Some(file.to_string());
+        }
+        if let Ok(canonical) = fs::canonicalize(file) {
+            for known in self.lines.keys() {
+                if fs::canonicalize(known).ok().as_ref() == Some(&canonical) {
+                    return Some(known.clone());
+                }
+            }
+        }
+        let suffix = format!("/{}", file.trim_start_matches("./"));
+        let matching: Vec<_> = self
+            .lines
+            .keys()
+            .filter(|known| known.ends_with(&suffix))
+            .collect();
+        if matching.len() == 1 {
+            Some(matching[0].clone())
+        } else {
+            None
+        }
+    }
+
+    // Sets a breakpoint on the first line having an expression starting from
+    // the given line, returns where it was set
+    pub fn add(&mut self, file: &str, line: usize) -> Option<Position> {
+        let file = self.resolve_file(file)?;
+        let line = *self.lines.get(&file)?.range(line..).next()?;
+        let position = Position {
+            file: file,
+            line: line,
+        };
+        self.active.insert(position.clone());
+        Some(position)
+    }
+
+    pub fn remove(&mut self, position: &Position) -> bool {
+        self.active.remove(position)
+    }
+
+    pub fn clear_file(&mut self, file: &str) {
+        if let Some(file) = self.resolve_file(file) {
+            self.active.retain(|position| position.file != file);
+        }
+    }
+
+    pub fn get_all(&self) -> Vec<Position> {
+        self.active.iter().cloned().collect()
+    }
+
+    fn contains(&self, position: &Position) -> bool {
+        self.active.contains(position)
+    }
+}
+
+// Decides whether the execution stops at an expression. It only stops when
+// the evaluation reaches a new line in a function, the position of every
+// function on the call stack is remembered so returning from a call to the
+// line it was made from does not count as a new line.
+struct Stepper {
+    mode: Resume,
+    depth: usize,
+    started: bool,
+    lines: Vec<Option<Position>>,
+}
+
+impl Stepper {
+    fn new() -> Stepper {
+        Stepper {
+            mode: Resume::Continue,
+            depth: 0,
+            started: false,
+            lines: Vec::new(),
+        }
+    }
+
+    fn check(
+        &mut self,
+        position: Position,
+        depth: usize,
+        breakpoints: &Breakpoints,
+    ) -> Option<StopReason> {
+        self.lines.resize(depth + 1, None);
+        if self.lines[depth].as_ref() == Some(&position) {
+            return None;
+        }
+        let is_breakpoint = breakpoints.contains(&position);
+        self.lines[depth] = Some(position);
+        let reason = if is_breakpoint {
+            StopReason::Breakpoint
+        } else {
+            let stop = match self.mode {
+                Resume::Continue => false,
+                Resume::StepIn => true,
+                Resume::StepOver => depth <= self.depth,
+                Resume::StepOut => depth < self.depth,
+            };
+            if !stop {
+                return None;
+            }
+            if self.started {
+                StopReason::Step
+            } else {
+                StopReason::Entry
+            }
+        };
+        self.started = true;
+        Some(reason)
+    }
+
+    fn resume(&mut self, mode: Resume, depth: usize) {
+        self.mode = mode;
+        self.depth = depth;
+    }
+}
+
+pub struct Debugger {
+    location_info: LocationInfo,
+    frontend: RefCell<Box<dyn DebugFrontend>>,
+    breakpoints: RefCell<Breakpoints>,
+    stepper: RefCell<Stepper>,
+    // set while the debugger itself runs Siko code, e.g. to show a value
+    suspended: Cell<bool>,
+}
+
+impl Debugger {
+    pub fn new(
+        program: &Program,
+        location_info: LocationInfo,
+        frontend: Box<dyn DebugFrontend>,
+    ) -> Debugger {
+        let mut lines = BTreeMap::new();
+        for expr in program.exprs.items.values() {
+            let position = get_position(&location_info, &expr.location_id);
+            lines
+                .entry(position.file)
+                .or_insert_with(BTreeSet::new)
+                .insert(position.line);
+        }
+        Debugger {
+            location_info: location_info,
+            frontend: RefCell::new(frontend),
+            breakpoints: RefCell::new(Breakpoints::new(lines)),
+            stepper: RefCell::new(Stepper::new()),
+            suspended: Cell::new(false),
+        }
+    }
+
+    pub fn get_position(&self, location_id: &LocationId) -> Position {
+        get_position(&self.location_info, location_id)
+    }
+
+    pub fn start(&self) {
+        let mut breakpoints = self.breakpoints.borrow_mut();
+        let mode = self.frontend.borrow_mut().start(&mut breakpoints);
+        self.stepper.borrow_mut().resume(mode, 0);
+    }
+
+    pub fn should_stop(&self, location_id: &LocationId, depth: usize) -> Option<StopReason> {
+        if self.suspended.get() {
+            return None;
+        }
+        let position = self.get_position(location_id);
+        self.stepper
+            .borrow_mut()
+            .check(position, depth, &self.breakpoints.borrow())
+    }
+
+    pub fn suspend(&self) {
+        self.suspended.set(true);
+    }
+
+    // Passes the control to the frontend until it resumes the execution
+    pub fn stop(&self, stop: Stop, depth: usize) {
+        let mut breakpoints = self.breakpoints.borrow_mut();
+        let mode = self.frontend.borrow_mut().stopped(&stop, &mut breakpoints);
+        self.stepper.borrow_mut().resume(mode, depth);
+        self.suspended.set(false);
+    }
+
+    pub fn output(&self, text: &str) {
+        self.frontend.borrow_mut().output(text);
+    }
+
+    pub fn finished(&self, error: Option<String>) {
+        self.frontend.borrow_mut().finished(error);
+    }
+}
+
+fn get_position(location_info: &LocationInfo, location_id: &LocationId) -> Position {
+    let location_set = location_info.get_item_location(location_id);
+    let line = location_set.lines.keys().next().cloned().unwrap_or(0);
+    Position {
+        file: location_set.file_path.path.clone(),
+        line: line + 1,
+    }
+}
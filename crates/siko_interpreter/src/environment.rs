@@ -63,4 +63,26 @@ impl<'a> Environment<'a> {
     pub fn get_arg_by_index(&self, index: usize) -> Value {
         return self.args[index].clone();
     }
+
+    pub fn get_args(&self) -> &Vec<Value> {
+        &self.args
+    }
+
+    pub fn get_callable_kind(&self) -> CallableKind {
+        self.callable_kind
+    }
+
+    // Returns the variables of this environment and its parents, the ones
+    // bound in the innermost environment first
+    pub fn get_variables(&self) -> Vec<(PatternId, Value)> {
+        let mut variables: Vec<_> = self
+            .variables
+            .iter()
+            .map(|(id, value)| (*id, value.clone()))
+            .collect();
+        if let Some(parent) = self.parent {
+            variables.extend(parent.get_variables());
+        }
+        variables
+    }
 }
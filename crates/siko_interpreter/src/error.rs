@@ -0,0 +1,14 @@
+use siko_location_info::location_id::LocationId;
+
+#[derive(Debug)]
+pub struct StackFrame {
+    pub function: String,
+    pub location: Option<LocationId>,
+}
+
+#[derive(Debug)]
+pub struct RuntimeError {
+    pub message: String,
+    pub location: Option<LocationId>,
+    pub stack: Vec<StackFrame>,
+}
@@ -64,12 +64,15 @@ impl ExternFunction for IntDiv {
     fn call(
         &self,
         environment: &mut Environment,
-        _: Option<ExprId>,
+        current_expr: Option<ExprId>,
         _: &NamedFunctionKind,
         ty: Type,
     ) -> Value {
         let l = environment.get_arg_by_index(0).core.as_int();
         let r = environment.get_arg_by_index(1).core.as_int();
+        if r == 0 {
+            Interpreter::abort(format!("division by zero"), current_expr);
+        }
         return Value::new(ValueCore::Int(l / r), ty);
     }
 }
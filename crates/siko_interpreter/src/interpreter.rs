@@ -1,11 +1,27 @@
+use crate::actor_system::ActorSystem;
 use crate::char;
+use crate::coverage::Coverage;
+use crate::coverage::CoverageData;
+use crate::debugger::DebugFrontend;
+use crate::debugger::Debugger;
+use crate::debugger::Frame;
+use crate::debugger::Stop;
+use crate::debugger::StopReason;
+use crate::debugger::Variable;
 use crate::environment::Environment;
+use crate::error::RuntimeError;
+use crate::error::StackFrame;
 use crate::extern_function::ExternFunction;
 use crate::float;
 use crate::int;
 use crate::iterator;
+use crate::limits;
+use crate::limits::Limits;
 use crate::list;
 use crate::map;
+use crate::profiler::Profile;
+use crate::profiler::Profiler;
+use crate::std_actor;
 use crate::std_ops;
 use crate::std_util;
 use crate::std_util_basic;
@@ -21,6 +37,7 @@ use siko_constants::OPTION_MODULE_NAME;
 use siko_constants::OPTION_TYPE_NAME;
 use siko_constants::ORDERING_MODULE_NAME;
 use siko_constants::ORDERING_TYPE_NAME;
+use siko_ir::class::ClassId;
 use siko_ir::class::ClassMember;
 use siko_ir::class::ClassMemberId;
 use siko_ir::data::Adt;
@@ -29,6 +46,8 @@ use siko_ir::expr::Expr;
 use siko_ir::expr::ExprId;
 use siko_ir::function::FunctionId;
 use siko_ir::function::FunctionInfo;
+use siko_ir::function::LambdaInfo;
+use siko_ir::function::LocalFunctionInfo;
 use siko_ir::function::NamedFunctionKind;
 use siko_ir::instance_resolver::ResolutionResult;
 use siko_ir::pattern::Pattern;
@@ -36,10 +55,13 @@ use siko_ir::pattern::PatternId;
 use siko_ir::program::Program;
 use siko_ir::types::Type;
 use siko_ir::unifier::Unifier;
-use siko_location_info::error_context::ErrorContext;
+use siko_location_info::location_info::LocationInfo;
+use std::cell::Cell;
 use std::cell::RefCell;
 use std::cmp::Ordering;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::panic;
 use std::thread_local;
 
 thread_local! {
@@ -73,82 +95,352 @@ pub struct TypeDefIdCache {
     pub ordering_variants: VariantCache,
 }
 
+enum Step {
+    Value(Value),
+    TailCall(Value, Vec<Value>, ExprId),
+}
+
+// A function being executed. The frames of the functions that finished with a
+// tail call are dropped, only the last one of them is kept for stack traces.
+struct CallFrame {
+    function_id: FunctionId,
+    // the expression of the caller this function was called from
+    call_expr: Option<ExprId>,
+    // the function that was replaced by this one, its tail call expression and
+    // the number of frames replaced before it
+    tail_caller: Option<(FunctionId, ExprId, usize)>,
+}
+
 pub struct Interpreter {
     program: Program,
-    error_context: ErrorContext,
     typedefid_cache: Option<TypeDefIdCache>,
     extern_functions: BTreeMap<(String, String), Box<dyn ExternFunction>>,
+    actor_system: RefCell<ActorSystem>,
+    call_stack: RefCell<Vec<CallFrame>>,
+    debugger: Option<Debugger>,
+    profiler: Option<Profiler>,
+    coverage: Option<Coverage>,
+    limits: Limits,
+    steps: Cell<u64>,
 }
 
 impl Interpreter {
-    fn new(program: Program, error_context: ErrorContext) -> Interpreter {
+    fn new(program: Program) -> Interpreter {
         Interpreter {
             program: program,
-            error_context: error_context,
             typedefid_cache: None,
             extern_functions: BTreeMap::new(),
+            actor_system: RefCell::new(ActorSystem::new()),
+            call_stack: RefCell::new(Vec::new()),
+            debugger: None,
+            profiler: None,
+            coverage: None,
+            limits: Limits::new(),
+            steps: Cell::new(0),
+        }
+    }
+
+    fn get_frame_name(&self, function_id: &FunctionId) -> String {
+        match &self.program.functions.get(function_id).info {
+            FunctionInfo::NamedFunction(info) => format!("{}", info),
+            FunctionInfo::Lambda(info) => format!("{}", info),
+            FunctionInfo::LocalFunction(info) => format!("{}", info),
+            FunctionInfo::RecordConstructor(info) => {
+                let record = self.program.typedefs.get(&info.type_id).get_record();
+                format!("{}/{}", record.module, record.name)
+            }
+            FunctionInfo::VariantConstructor(info) => {
+                let adt = self.program.typedefs.get(&info.type_id).get_adt();
+                format!("{}/{}", adt.module, adt.variants[info.index].name)
+            }
+            FunctionInfo::ActorConstructor(info) => format!("{}", info),
+        }
+    }
+
+    // Returns the Siko functions being executed, the innermost one first
+    fn get_stack(&self, current_expr: Option<ExprId>) -> Vec<StackFrame> {
+        let call_stack = self.call_stack.borrow();
+        let mut stack = Vec::new();
+        let mut location_expr = current_expr;
+        for frame in call_stack.iter().rev() {
+            // extern functions have no Siko code to point at
+            let location = match self.program.functions.get(&frame.function_id).get_body() {
+                Some(_) => {
+                    location_expr.map(|expr_id| self.program.exprs.get(&expr_id).location_id)
+                }
+                None => None,
+            };
+            stack.push(StackFrame {
+                function: self.get_frame_name(&frame.function_id),
+                location: location,
+            });
+            if let Some((function_id, tail_expr, replaced)) = &frame.tail_caller {
+                stack.push(StackFrame {
+                    function: self.get_frame_name(function_id),
+                    location: Some(self.program.exprs.get(tail_expr).location_id),
+                });
+                if *replaced > 0 {
+                    stack.push(StackFrame {
+                        function: format!("(tail calls omitted: {})", replaced),
+                        location: None,
+                    });
+                }
+            }
+            location_expr = frame.call_expr;
+        }
+        stack
+    }
+
+    // Stops the execution of the program, the error is caught by run together
+    // with the stack of the Siko functions that were being executed.
+    fn runtime_error(&self, message: String, current_expr: Option<ExprId>) -> ! {
+        let stack = self.get_stack(current_expr);
+        let err = RuntimeError {
+            message: message,
+            location: current_expr.map(|expr_id| self.program.exprs.get(&expr_id).location_id),
+            stack: stack,
+        };
+        panic::resume_unwind(Box::new(err));
+    }
+
+    fn get_debug_variable(&self, name: String, value: &Value) -> Variable {
+        let shown = match self.show_value(value) {
+            Some(shown) => shown,
+            None => match value.core {
+                ValueCore::Callable(_) => format!("<function>"),
+                _ => format!("<value>"),
+            },
+        };
+        Variable {
+            name: name,
+            ty: value.ty.get_resolved_type_string(&self.program),
+            value: shown,
+        }
+    }
+
+    fn get_stop(
+        &self,
+        debugger: &Debugger,
+        reason: StopReason,
+        expr_id: ExprId,
+        environment: &Environment,
+    ) -> Stop {
+        let frames = self
+            .get_stack(Some(expr_id))
+            .into_iter()
+            .map(|frame| Frame {
+                function: frame.function,
+                position: frame
+                    .location
+                    .map(|location_id| debugger.get_position(&location_id)),
+            })
+            .collect();
+        // the captured values come before the arguments of the function
+        let args = environment.get_args();
+        let arg_names = match environment.get_callable_kind() {
+            CallableKind::FunctionId(id) => self.program.functions.get(&id).arg_names.clone(),
+            CallableKind::Builtin(_) => Vec::new(),
+        };
+        let capture_count = args.len().saturating_sub(arg_names.len());
+        let arguments = args
+            .iter()
+            .enumerate()
+            .map(|(index, value)| {
+                let name = index
+                    .checked_sub(capture_count)
+                    .and_then(|index| arg_names.get(index).cloned().flatten())
+                    .unwrap_or_else(|| format!("arg#{}", index));
+                self.get_debug_variable(name, value)
+            })
+            .collect();
+        // a variable shadows the variables of the outer environments with the same name
+        let mut names = BTreeSet::new();
+        let mut locals = Vec::new();
+        for (pattern_id, value) in environment.get_variables() {
+            let name = match &self.program.patterns.get(&pattern_id).item {
+                Pattern::Binding(name) | Pattern::As(_, name) => name,
+                _ => continue,
+            };
+            if names.insert(name.clone()) {
+                locals.push((pattern_id, self.get_debug_variable(name.clone(), &value)));
+            }
         }
+        locals.sort_by_key(|(pattern_id, _)| *pattern_id);
+        Stop {
+            reason: reason,
+            frames: frames,
+            arguments: arguments,
+            locals: locals.into_iter().map(|(_, variable)| variable).collect(),
+        }
+    }
+
+    fn debug_hook(&self, expr_id: ExprId, environment: &Environment) {
+        if let Some(debugger) = &self.debugger {
+            let location_id = self.program.exprs.get(&expr_id).location_id;
+            let depth = self.call_stack.borrow().len();
+            if let Some(reason) = debugger.should_stop(&location_id, depth) {
+                // showing the variables runs Siko code, that must not stop
+                debugger.suspend();
+                let stop = self.get_stop(debugger, reason, expr_id, environment);
+                debugger.stop(stop, depth);
+            }
+        }
+    }
+
+    fn check_limits(&self, expr_id: ExprId) {
+        if limits::is_stack_exhausted() {
+            self.runtime_error(
+                format!("stack limit exceeded, expressions are nested too deeply"),
+                Some(expr_id),
+            );
+        }
+        if let Some(fuel) = self.limits.fuel {
+            let steps = self.steps.get() + 1;
+            self.steps.set(steps);
+            if steps > fuel {
+                self.runtime_error(
+                    format!("evaluation step limit of {} exceeded", fuel),
+                    Some(expr_id),
+                );
+            }
+        }
+        if let Some(max_heap) = self.limits.max_heap {
+            if limits::get_heap_used() > max_heap {
+                self.runtime_error(
+                    format!("heap limit of {} bytes exceeded", max_heap),
+                    Some(expr_id),
+                );
+            }
+        }
+    }
+
+    // Everything the program prints goes through the debugger if there is one
+    pub fn write_output(text: &str) {
+        INTERPRETER_CONTEXT.with(|i| {
+            let b = i.borrow();
+            let i = b.as_ref().expect("Interpreter not set");
+            match &i.debugger {
+                Some(debugger) => debugger.output(text),
+                None => print!("{}", text),
+            }
+        })
+    }
+
+    pub fn abort(message: String, current_expr: Option<ExprId>) -> ! {
+        INTERPRETER_CONTEXT.with(|i| {
+            let b = i.borrow();
+            let i = b.as_ref().expect("Interpreter not set");
+            i.runtime_error(message, current_expr)
+        })
     }
 
     fn call(&self, callable_value: Value, args: Vec<Value>, expr_id: Option<ExprId>) -> Value {
-        match callable_value.core {
-            ValueCore::Callable(mut callable) => {
-                let mut callable_func_ty = callable_value.ty;
-                callable.values.extend(args);
-                loop {
-                    let needed_arg_count = match &callable.kind {
-                        CallableKind::Builtin(builtin) => match builtin {
-                            BuiltinCallable::Show => 1,
-                            BuiltinCallable::PartialEq => 2,
-                            BuiltinCallable::PartialOrd => 2,
-                            BuiltinCallable::Ord => 2,
-                        },
-                        CallableKind::FunctionId(function_id) => {
-                            let func = self.program.functions.get(function_id);
-                            func.arg_count
-                        }
+        self.call_with_tail_caller(callable_value, args, expr_id, expr_id, None)
+    }
+
+    // Makes a call on behalf of the function that called the one in
+    // tail_caller, which was called from call_expr
+    fn call_with_tail_caller(
+        &self,
+        callable_value: Value,
+        args: Vec<Value>,
+        expr_id: Option<ExprId>,
+        call_expr: Option<ExprId>,
+        tail_caller: Option<(FunctionId, ExprId, usize)>,
+    ) -> Value {
+        let mut callable_value = callable_value;
+        let mut args = args;
+        let mut expr_id = expr_id;
+        let mut tail_caller = tail_caller;
+        // tail calls of the executed functions are made by this loop
+        'trampoline: loop {
+            let mut callable = match callable_value.core {
+                ValueCore::Callable(callable) => callable,
+                _ => self.runtime_error(
+                    format!(
+                        "value of type {} is not callable",
+                        callable_value.ty.get_resolved_type_string(&self.program)
+                    ),
+                    expr_id,
+                ),
+            };
+            let mut callable_func_ty = callable_value.ty;
+            callable.values.extend(args);
+            loop {
+                let needed_arg_count = match &callable.kind {
+                    CallableKind::Builtin(builtin) => match builtin {
+                        BuiltinCallable::Show => 1,
+                        BuiltinCallable::PartialEq => 2,
+                        BuiltinCallable::PartialOrd => 2,
+                        BuiltinCallable::Ord => 2,
+                    },
+                    CallableKind::FunctionId(function_id) => {
+                        let func = self.program.functions.get(function_id);
+                        func.arg_count
+                    }
+                };
+                if needed_arg_count > callable.values.len() {
+                    callable_func_ty = callable_func_ty.get_result_type(callable.values.len());
+                    return Value::new(ValueCore::Callable(callable), callable_func_ty);
+                } else {
+                    let rest = callable.values.split_off(needed_arg_count);
+                    let mut call_args = Vec::new();
+                    std::mem::swap(&mut call_args, &mut callable.values);
+                    let arg_count = call_args.len();
+                    let mut environment = Environment::new(callable.kind, call_args);
+                    callable_func_ty = callable_func_ty.get_result_type(arg_count);
+                    let step = match &callable.kind {
+                        CallableKind::Builtin(builtin) => Step::Value(self.execute_builtin(
+                            builtin,
+                            &mut environment,
+                            expr_id,
+                            &callable.unifier,
+                            callable_func_ty.clone(),
+                        )),
+                        CallableKind::FunctionId(id) => self.execute(
+                            CallFrame {
+                                function_id: *id,
+                                call_expr: call_expr,
+                                tail_caller: tail_caller,
+                            },
+                            &mut environment,
+                            expr_id,
+                            &callable.unifier,
+                            callable_func_ty.clone(),
+                        ),
                     };
-                    if needed_arg_count > callable.values.len() {
-                        callable_func_ty = callable_func_ty.get_result_type(callable.values.len());
-                        return Value::new(ValueCore::Callable(callable), callable_func_ty);
-                    } else {
-                        let rest = callable.values.split_off(needed_arg_count);
-                        let mut call_args = Vec::new();
-                        std::mem::swap(&mut call_args, &mut callable.values);
-                        let arg_count = call_args.len();
-                        let mut environment = Environment::new(callable.kind, call_args);
-                        callable_func_ty = callable_func_ty.get_result_type(arg_count);
-                        let result = match &callable.kind {
-                            CallableKind::Builtin(builtin) => self.execute_builtin(
-                                builtin,
-                                &mut environment,
-                                expr_id,
-                                &callable.unifier,
-                                callable_func_ty.clone(),
-                            ),
-                            CallableKind::FunctionId(id) => self.execute(
-                                *id,
-                                &mut environment,
-                                expr_id,
-                                &callable.unifier,
-                                callable_func_ty.clone(),
-                            ),
-                        };
-                        if !rest.is_empty() {
-                            if let ValueCore::Callable(new_callable) = result.core {
-                                callable = new_callable;
-                                callable_func_ty = result.ty;
-                                callable.values.extend(rest);
-                            } else {
-                                unreachable!()
+                    let result = match step {
+                        Step::Value(result) => result,
+                        Step::TailCall(tail_callable, mut tail_args, tail_expr_id) => {
+                            // the remaining arguments are applied to the result of
+                            // the tail call, which is the same as passing them along
+                            tail_args.extend(rest);
+                            if let CallableKind::FunctionId(id) = callable.kind {
+                                let replaced = match tail_caller {
+                                    Some((_, _, replaced)) => replaced + 1,
+                                    None => 0,
+                                };
+                                tail_caller = Some((id, tail_expr_id, replaced));
                             }
+                            callable_value = tail_callable;
+                            args = tail_args;
+                            expr_id = Some(tail_expr_id);
+                            continue 'trampoline;
+                        }
+                    };
+                    if !rest.is_empty() {
+                        if let ValueCore::Callable(new_callable) = result.core {
+                            callable = new_callable;
+                            callable_func_ty = result.ty;
+                            callable.values.extend(rest);
                         } else {
-                            return result;
+                            unreachable!()
                         }
+                    } else {
+                        return result;
                     }
                 }
             }
-            _ => panic!("Cannot call {:?}", callable_value),
         }
     }
 
@@ -256,6 +548,53 @@ impl Interpreter {
                 };
                 return r;
             }
+            Pattern::IntegerRange(start, end) => {
+                let r = match &value.core {
+                    ValueCore::Int(v) => {
+                        let range = std::ops::Range { start, end };
+                        range.contains(&v)
+                    }
+                    _ => false,
+                };
+                return r;
+            }
+            Pattern::FloatLiteral(p_v) => {
+                let r = match &value.core {
+                    ValueCore::Float(v) => p_v == v,
+                    _ => false,
+                };
+                return r;
+            }
+            Pattern::List(ids, rest) => match &value.core {
+                ValueCore::List(vs) => {
+                    let length_matches = match rest {
+                        Some(_) => vs.len() >= ids.len(),
+                        None => vs.len() == ids.len(),
+                    };
+                    if !length_matches {
+                        return false;
+                    }
+                    for (index, id) in ids.iter().enumerate() {
+                        let v = &vs[index];
+                        if !self.match_pattern(id, v, environment, unifier) {
+                            return false;
+                        }
+                    }
+                    if let Some(rest) = rest {
+                        let rest_value =
+                            Value::new(ValueCore::List(vs[ids.len()..].to_vec()), value.ty.clone());
+                        return self.match_pattern(rest, &rest_value, environment, unifier);
+                    }
+                    return true;
+                }
+                _ => {
+                    return false;
+                }
+            },
+            Pattern::As(id, _) => {
+                environment.add(*pattern_id, value.clone());
+                return self.match_pattern(id, value, environment, unifier);
+            }
         }
     }
 
@@ -274,6 +613,15 @@ impl Interpreter {
         })
     }
 
+    pub fn get_int_type() -> Type {
+        INTERPRETER_CONTEXT.with(|i| {
+            let b = i.borrow();
+            let i = b.as_ref().expect("Interpreter not set");
+            let int_ty = i.program.get_int_type();
+            int_ty
+        })
+    }
+
     pub fn get_bool_type() -> Type {
         INTERPRETER_CONTEXT.with(|i| {
             let b = i.borrow();
@@ -346,16 +694,98 @@ impl Interpreter {
     }
 
     pub fn call_abort(current_expr: ExprId) {
+        Interpreter::abort(format!("assertion failed"), Some(current_expr));
+    }
+
+    pub fn send_message(actor: Value, message: Value, current_expr: Option<ExprId>) {
         INTERPRETER_CONTEXT.with(|i| {
             let b = i.borrow();
             let i = b.as_ref().expect("Interpreter not set");
-            let location_id = i.program.exprs.get(&current_expr).location_id;
-            i.error_context
-                .report_error(format!("Assertion failed"), location_id);
-            panic!("Abort not implemented");
+            let index = match actor.core {
+                ValueCore::Actor(index) => Some(index),
+                _ => None,
+            };
+            let error = match index {
+                Some(index) => {
+                    let actor_id = i.actor_system.borrow().get_instance(index).actor_id;
+                    let actor = i.program.actors.get(&actor_id);
+                    let handler = match &message.ty {
+                        Type::Named(_, typedef_id, _) => i
+                            .program
+                            .get_protocol_by_typedef(*typedef_id)
+                            .and_then(|protocol| actor.get_handler(protocol.id)),
+                        _ => None,
+                    };
+                    if handler.is_none() {
+                        Some(format!(
+                            "actor {} cannot handle message of type {}",
+                            actor.name,
+                            message.ty.get_resolved_type_string(&i.program)
+                        ))
+                    } else {
+                        None
+                    }
+                }
+                None => Some(format!(
+                    "message sent to {} which is not an actor",
+                    actor.ty.get_resolved_type_string(&i.program)
+                )),
+            };
+            if let Some(msg) = error {
+                i.runtime_error(msg, current_expr);
+            }
+            let index = index.expect("Actor index not found");
+            i.actor_system.borrow_mut().enqueue(index, message);
         })
     }
 
+    fn process_messages(&self) {
+        loop {
+            let next = self.actor_system.borrow_mut().next_message();
+            let (index, message) = match next {
+                Some(next) => next,
+                None => break,
+            };
+            let (actor_id, state) = {
+                let actor_system = self.actor_system.borrow();
+                let instance = actor_system.get_instance(index);
+                (instance.actor_id, instance.state.clone())
+            };
+            let actor = self.program.actors.get(&actor_id);
+            let protocol = self
+                .program
+                .get_protocol_by_typedef(message.ty.get_typedef_id())
+                .expect("Protocol not found");
+            let handler = actor
+                .get_handler(protocol.id)
+                .expect("Protocol handler not found");
+            let payload = match message.core {
+                ValueCore::Variant(_, _, mut items) => items.remove(0),
+                _ => unreachable!(),
+            };
+            let func_ty = self
+                .program
+                .get_function_type(&handler.function_id)
+                .remove_fixed_types();
+            let state_ty = state.ty.clone();
+            let arg_values = vec![state, payload];
+            let call_unifier = self.get_call_unifier(&arg_values, &func_ty, &state_ty);
+            let function_type = call_unifier.apply(&func_ty);
+            let callable = Value::new(
+                ValueCore::Callable(Callable {
+                    kind: CallableKind::FunctionId(handler.function_id),
+                    values: vec![],
+                    unifier: call_unifier,
+                }),
+                function_type,
+            );
+            let new_state = self.call(callable, arg_values, None);
+            self.actor_system
+                .borrow_mut()
+                .update_state(index, new_state);
+        }
+    }
+
     pub fn call_op_eq(arg1: Value, arg2: Value) -> Value {
         let bool_ty = Interpreter::get_bool_type();
         Interpreter::call_specific_class_member(vec![arg1, arg2], "PartialEq", "opEq", bool_ty)
@@ -402,14 +832,23 @@ impl Interpreter {
             println!("{}.argty {}", index, arg);
         }*/
         let mut call_unifier = self.program.get_unifier();
+        if func_ty.is_concrete_type() {
+            return call_unifier;
+        }
         let mut func_ty = func_ty.clone();
         for arg in arg_values {
-            let mut arg_types = Vec::new();
-            func_ty.get_args(&mut arg_types);
-            let r = call_unifier.unify(&arg.ty, &arg_types[0]);
-            assert!(r.is_ok());
-            func_ty.apply(&call_unifier);
-            func_ty = func_ty.get_result_type(1);
+            // the result of a generic function becomes a function type only
+            // after its type variable is bound by the previous arguments
+            if let Type::Var(..) = func_ty {
+                func_ty = call_unifier.apply(&func_ty);
+            }
+            if let Type::Function(from, to) = func_ty {
+                let r = call_unifier.unify(&arg.ty, &from);
+                assert!(r.is_ok());
+                func_ty = *to;
+            } else {
+                unreachable!()
+            }
         }
         //println!("{} {}", expected_result_ty, func_ty);
         let r = call_unifier.unify(&func_ty, expected_result_ty);
@@ -471,9 +910,12 @@ impl Interpreter {
                     ("Std.Ops", "Ord") => {
                         Interpreter::check_member(member, "cmp", BuiltinCallable::Ord)
                     }
-                    _ => panic!(
-                        "Auto derive of {}/{} is not implemented",
-                        class.module, class.name
+                    _ => self.runtime_error(
+                        format!(
+                            "auto derive of {}/{} is not implemented",
+                            class.module, class.name
+                        ),
+                        expr_id,
                     ),
                 };
                 if let Some(kind) = kind {
@@ -511,6 +953,15 @@ impl Interpreter {
                             .default_implementation
                             .expect("Default implementation not found")
                     };
+                // the instance member has its own type arguments (the ones of the
+                // instance), so its body needs a unifier built from its own type
+                let member_function_type = self
+                    .program
+                    .get_function_type(&member_function_id)
+                    .remove_fixed_types();
+                let call_unifier =
+                    self.get_call_unifier(&arg_values, &member_function_type, &expr_ty);
+                let function_type = call_unifier.apply(&member_function_type);
                 let callable = Value::new(
                     ValueCore::Callable(Callable {
                         kind: CallableKind::FunctionId(member_function_id),
@@ -524,16 +975,127 @@ impl Interpreter {
         }
     }
 
+    fn prepare_static_call(
+        &self,
+        function_id: &FunctionId,
+        args: &Vec<ExprId>,
+        environment: &mut Environment,
+        unifier: &Unifier,
+        expr_ty: Type,
+    ) -> (Value, Vec<Value>) {
+        let func_ty = self
+            .program
+            .get_function_type(function_id)
+            .remove_fixed_types();
+        let arg_values: Vec<_> = args
+            .iter()
+            .map(|arg| self.eval_expr(*arg, environment, unifier))
+            .collect();
+        for arg in &arg_values {
+            assert!(arg.ty.is_concrete_type());
+        }
+        let call_unifier = self.get_call_unifier(&arg_values, &func_ty, &expr_ty);
+        let function_type = call_unifier.apply(&func_ty);
+        let callable = Value::new(
+            ValueCore::Callable(Callable {
+                kind: CallableKind::FunctionId(*function_id),
+                values: vec![],
+                unifier: call_unifier,
+            }),
+            function_type,
+        );
+        (callable, arg_values)
+    }
+
+    fn prepare_dynamic_call(
+        &self,
+        function_expr_id: &ExprId,
+        args: &Vec<ExprId>,
+        environment: &mut Environment,
+        unifier: &Unifier,
+    ) -> (Value, Vec<Value>) {
+        let callable = self.eval_expr(*function_expr_id, environment, unifier);
+        let arg_values: Vec<_> = args
+            .iter()
+            .map(|arg| self.eval_expr(*arg, environment, unifier))
+            .collect();
+        (callable, arg_values)
+    }
+
+    // Evaluates the body of a function. Calls in tail position are not made
+    // here but returned to the caller, which makes them in its own loop, so
+    // tail recursion runs in constant Rust stack.
+    fn eval_body(&self, expr_id: ExprId, environment: &mut Environment, unifier: &Unifier) -> Step {
+        self.check_limits(expr_id);
+        self.debug_hook(expr_id, environment);
+        let expr = &self.program.exprs.get(&expr_id).item;
+        if let Some(coverage) = &self.coverage {
+            // the other expressions are counted by eval_expr
+            match expr {
+                Expr::StaticFunctionCall(..)
+                | Expr::DynamicFunctionCall(..)
+                | Expr::Do(..)
+                | Expr::If(..)
+                | Expr::CaseOf(..) => coverage.hit(expr_id),
+                _ => {}
+            }
+        }
+        match expr {
+            Expr::StaticFunctionCall(function_id, args) => {
+                let expr_ty = unifier.apply(self.program.get_expr_type(&expr_id));
+                let (callable, arg_values) =
+                    self.prepare_static_call(function_id, args, environment, unifier, expr_ty);
+                Step::TailCall(callable, arg_values, expr_id)
+            }
+            Expr::DynamicFunctionCall(function_expr_id, args) => {
+                let (callable, arg_values) =
+                    self.prepare_dynamic_call(function_expr_id, args, environment, unifier);
+                Step::TailCall(callable, arg_values, expr_id)
+            }
+            Expr::Do(exprs) => {
+                let mut environment = Environment::block_child(environment);
+                let (last, init) = exprs.split_last().expect("Empty do block");
+                for expr in init {
+                    self.eval_expr(*expr, &mut environment, unifier);
+                }
+                self.eval_body(*last, &mut environment, unifier)
+            }
+            Expr::If(cond, true_branch, false_branch) => {
+                let cond_value = self.eval_expr(*cond, environment, unifier);
+                if cond_value.core.as_bool() {
+                    self.eval_body(*true_branch, environment, unifier)
+                } else {
+                    self.eval_body(*false_branch, environment, unifier)
+                }
+            }
+            Expr::CaseOf(body, cases, _) => {
+                let case_value = self.eval_expr(*body, environment, unifier);
+                for case in cases {
+                    let mut case_env = Environment::block_child(environment);
+                    if self.match_pattern(&case.pattern_id, &case_value, &mut case_env, unifier) {
+                        return self.eval_body(case.body, &mut case_env, unifier);
+                    }
+                }
+                unreachable!()
+            }
+            _ => Step::Value(self.eval_expr(expr_id, environment, unifier)),
+        }
+    }
+
     fn eval_expr(
         &self,
         expr_id: ExprId,
         environment: &mut Environment,
         unifier: &Unifier,
     ) -> Value {
+        self.check_limits(expr_id);
+        self.debug_hook(expr_id, environment);
+        if let Some(coverage) = &self.coverage {
+            coverage.hit(expr_id);
+        }
         let expr = &self.program.exprs.get(&expr_id).item;
         //println!("Eval {} {}", expr_id, expr);
-        let expr_ty = self.program.get_expr_type(&expr_id).clone();
-        let expr_ty = unifier.apply(&expr_ty);
+        let expr_ty = unifier.apply(self.program.get_expr_type(&expr_id));
         match expr {
             Expr::IntegerLiteral(v) => Value::new(ValueCore::Int(*v), expr_ty),
             Expr::StringLiteral(v) => Value::new(ValueCore::String(v.clone()), expr_ty),
@@ -543,47 +1105,14 @@ impl Interpreter {
                 return environment.get_arg(arg_ref);
             }
             Expr::StaticFunctionCall(function_id, args) => {
-                let func_ty = self
-                    .program
-                    .get_function_type(function_id)
-                    .remove_fixed_types();
-                let arg_values: Vec<_> = args
-                    .iter()
-                    .map(|arg| self.eval_expr(*arg, environment, unifier))
-                    .collect();
-                for arg in &arg_values {
-                    assert!(arg.ty.is_concrete_type());
-                    //println!("ARG {}", arg.ty.get_resolved_type_string(&self.program));
-                }
-                /*
-                let f = self.program.functions.get(function_id);
-                println!("Calling {}", f.info);
-                */
-                let call_unifier = self.get_call_unifier(&arg_values, &func_ty, &expr_ty);
-                let function_type = call_unifier.apply(&func_ty);
-                /*
-                println!(
-                    "Function type {}",
-                    function_type.get_resolved_type_string(&self.program)
-                );
-                */
-                let callable = Value::new(
-                    ValueCore::Callable(Callable {
-                        kind: CallableKind::FunctionId(*function_id),
-                        values: vec![],
-                        unifier: call_unifier,
-                    }),
-                    function_type,
-                );
+                let (callable, arg_values) =
+                    self.prepare_static_call(function_id, args, environment, unifier, expr_ty);
                 return self.call(callable, arg_values, Some(expr_id));
             }
             Expr::DynamicFunctionCall(function_expr_id, args) => {
-                let function_expr_id = self.eval_expr(*function_expr_id, environment, unifier);
-                let arg_values: Vec<_> = args
-                    .iter()
-                    .map(|arg| self.eval_expr(*arg, environment, unifier))
-                    .collect();
-                return self.call(function_expr_id, arg_values, Some(expr_id));
+                let (callable, arg_values) =
+                    self.prepare_dynamic_call(function_expr_id, args, environment, unifier);
+                return self.call(callable, arg_values, Some(expr_id));
             }
             Expr::Do(exprs) => {
                 let mut environment = Environment::block_child(environment);
@@ -709,6 +1238,7 @@ impl Interpreter {
                     .collect();
                 return self.call_class_member(class_member_id, arg_values, Some(expr_id), expr_ty);
             }
+            Expr::Hole(_) => unreachable!(),
         }
     }
 
@@ -727,7 +1257,10 @@ impl Interpreter {
         {
             return f.call(environment, current_expr, kind, ty);
         } else {
-            panic!("Unimplemented extern function {} {}", module, name);
+            self.runtime_error(
+                format!("unimplemented extern function {}/{}", module, name),
+                current_expr,
+            );
         }
     }
 
@@ -735,7 +1268,7 @@ impl Interpreter {
         &self,
         builtin: &BuiltinCallable,
         environment: &mut Environment,
-        _: Option<ExprId>,
+        current_expr: Option<ExprId>,
         _: &Unifier,
         _: Type,
     ) -> Value {
@@ -782,7 +1315,13 @@ impl Interpreter {
                         return Interpreter::get_bool_value(true);
                     }
                 }
-                unimplemented!()
+                self.runtime_error(
+                    format!(
+                        "auto derived PartialEq cannot compare values of type {}",
+                        lhs.ty.get_resolved_type_string(&self.program)
+                    ),
+                    current_expr,
+                )
             }
             BuiltinCallable::PartialOrd => {
                 let lhs = environment.get_arg_by_index(0);
@@ -853,7 +1392,13 @@ impl Interpreter {
                         return get_opt_ordering_value(Some(Ordering::Equal));
                     }
                 }
-                unimplemented!()
+                self.runtime_error(
+                    format!(
+                        "auto derived PartialOrd cannot compare values of type {}",
+                        lhs.ty.get_resolved_type_string(&self.program)
+                    ),
+                    current_expr,
+                )
             }
             BuiltinCallable::Ord => {
                 let lhs = environment.get_arg_by_index(0);
@@ -902,39 +1447,74 @@ impl Interpreter {
                         return get_ordering_value(Ordering::Equal);
                     }
                 }
-                unimplemented!()
+                self.runtime_error(
+                    format!(
+                        "auto derived Ord cannot compare values of type {}",
+                        lhs.ty.get_resolved_type_string(&self.program)
+                    ),
+                    current_expr,
+                )
             }
         }
     }
 
     fn execute(
+        &self,
+        frame: CallFrame,
+        environment: &mut Environment,
+        current_expr: Option<ExprId>,
+        unifier: &Unifier,
+        expr_ty: Type,
+    ) -> Step {
+        let id = frame.function_id;
+        let depth = self.call_stack.borrow().len();
+        if depth >= self.limits.max_depth {
+            self.runtime_error(
+                format!("call depth limit of {} exceeded", self.limits.max_depth),
+                current_expr,
+            );
+        }
+        self.call_stack.borrow_mut().push(frame);
+        if let Some(profiler) = &self.profiler {
+            profiler.enter(id);
+        }
+        let step = self.execute_function(id, environment, current_expr, unifier, expr_ty);
+        if let Some(profiler) = &self.profiler {
+            profiler.exit();
+        }
+        self.call_stack.borrow_mut().pop();
+        step
+    }
+
+    fn execute_function(
         &self,
         id: FunctionId,
         environment: &mut Environment,
         current_expr: Option<ExprId>,
         unifier: &Unifier,
         expr_ty: Type,
-    ) -> Value {
+    ) -> Step {
         assert!(expr_ty.is_concrete_type());
         let function = self.program.functions.get(&id);
         match &function.info {
             FunctionInfo::NamedFunction(info) => match info.body {
                 Some(body) => {
-                    return self.eval_expr(body, environment, unifier);
+                    return self.eval_body(body, environment, unifier);
                 }
                 None => {
-                    return self.call_extern(
+                    return Step::Value(self.call_extern(
                         &info.module,
                         &info.name,
                         environment,
                         current_expr,
                         &info.kind,
                         expr_ty,
-                    );
+                    ));
                 }
             },
-            FunctionInfo::Lambda(info) => {
-                return self.eval_expr(info.body, environment, unifier);
+            FunctionInfo::Lambda(LambdaInfo { body, .. })
+            | FunctionInfo::LocalFunction(LocalFunctionInfo { body, .. }) => {
+                return self.eval_body(*body, environment, unifier);
             }
             FunctionInfo::VariantConstructor(info) => {
                 let adt = self.program.typedefs.get(&info.type_id).get_adt();
@@ -944,10 +1524,15 @@ impl Interpreter {
                     let v = environment.get_arg_by_index(index);
                     values.push(v);
                 }
-                return Value::new(
+                return Step::Value(Value::new(
                     ValueCore::Variant(info.type_id, info.index, values),
                     expr_ty,
-                );
+                ));
+            }
+            FunctionInfo::ActorConstructor(info) => {
+                let state = environment.get_arg_by_index(0);
+                let index = self.actor_system.borrow_mut().spawn(info.actor_id, state);
+                return Step::Value(Value::new(ValueCore::Actor(index), expr_ty));
             }
             FunctionInfo::RecordConstructor(info) => {
                 let record = self.program.typedefs.get(&info.type_id).get_record();
@@ -956,7 +1541,7 @@ impl Interpreter {
                     let v = environment.get_arg_by_index(index);
                     values.push(v);
                 }
-                return Value::new(ValueCore::Record(info.type_id, values), expr_ty);
+                return Step::Value(Value::new(ValueCore::Record(info.type_id, values), expr_ty));
             }
         }
     }
@@ -988,17 +1573,77 @@ impl Interpreter {
         })
     }
 
+    fn execute_entry(&self, function_id: FunctionId, unifier: &Unifier, ty: Type) -> Value {
+        let mut environment = Environment::new(CallableKind::FunctionId(function_id), vec![]);
+        let frame = CallFrame {
+            function_id: function_id,
+            call_expr: None,
+            tail_caller: None,
+        };
+        let result = match self.execute(frame, &mut environment, None, unifier, ty) {
+            Step::Value(value) => value,
+            Step::TailCall(callable, args, expr_id) => self.call_with_tail_caller(
+                callable,
+                args,
+                Some(expr_id),
+                None,
+                Some((function_id, expr_id, 0)),
+            ),
+        };
+        self.process_messages();
+        return result;
+    }
+
     fn execute_main(interpreter: &Interpreter) -> Value {
         let main_id = interpreter.program.get_main().expect("Main does not exist");
-        let mut environment = Environment::new(CallableKind::FunctionId(main_id), vec![]);
         let unifier = interpreter.program.get_unifier();
-        return interpreter.execute(
-            main_id,
-            &mut environment,
-            None,
-            &unifier,
-            Type::Tuple(vec![]),
-        );
+        interpreter.execute_entry(main_id, &unifier, Type::Tuple(vec![]))
+    }
+
+    // Evaluates a function without arguments and shows its result if it can be
+    // shown. Type variables left in the type of the function are defaulted to ().
+    fn execute_eval(interpreter: &Interpreter, function_id: FunctionId) -> Option<String> {
+        let function_ty = interpreter
+            .program
+            .get_function_type(&function_id)
+            .remove_fixed_types();
+        let mut vars = Vec::new();
+        function_ty.collect_vars(&mut vars);
+        let mut unifier = interpreter.program.get_unifier();
+        for var in vars {
+            let r = unifier.unify(&Type::Var(var, vec![]), &Type::Tuple(vec![]));
+            assert!(r.is_ok());
+        }
+        let ty = unifier.apply(&function_ty);
+        let value = interpreter.execute_entry(function_id, &unifier, ty);
+        interpreter.show_value(&value)
+    }
+
+    // Tuples have no Show instance, tuples and lists of them are shown item by item
+    fn show_value(&self, value: &Value) -> Option<String> {
+        if self.has_instance(self.program.get_show_class_id(), &value.ty) {
+            return Some(Interpreter::call_show(value.clone()));
+        }
+        match &value.core {
+            ValueCore::Tuple(items) => {
+                let items: Option<Vec<_>> = items.iter().map(|i| self.show_value(i)).collect();
+                items.map(|items| format!("({})", items.join(", ")))
+            }
+            ValueCore::List(items) => {
+                let items: Option<Vec<_>> = items.iter().map(|i| self.show_value(i)).collect();
+                items.map(|items| format!("[{}]", items.join(", ")))
+            }
+            _ => None,
+        }
+    }
+
+    fn has_instance(&self, class_id: ClassId, ty: &Type) -> bool {
+        match self.program.instance_resolver.get_context(class_id, ty) {
+            Some(context) => context
+                .iter()
+                .all(|(class_id, ty)| self.has_instance(*class_id, ty)),
+            None => false,
+        }
     }
 
     pub fn add_extern_function(
@@ -1011,8 +1656,11 @@ impl Interpreter {
             .insert((module.to_string(), name.to_string()), extern_function);
     }
 
-    pub fn run(program: Program, error_context: ErrorContext) -> Value {
-        let mut interpreter = Interpreter::new(program, error_context);
+    fn start(program: Program, debugger: Option<Debugger>, limits: Limits) {
+        let mut interpreter = Interpreter::new(program);
+        interpreter.debugger = debugger;
+        interpreter.limits = limits;
+        limits::reset_heap_used();
         int::register_extern_functions(&mut interpreter);
         char::register_extern_functions(&mut interpreter);
         float::register_extern_functions(&mut interpreter);
@@ -1021,6 +1669,7 @@ impl Interpreter {
         list::register_extern_functions(&mut interpreter);
         std_util_basic::register_extern_functions(&mut interpreter);
         std_util::register_extern_functions(&mut interpreter);
+        std_actor::register_extern_functions(&mut interpreter);
         std_ops::register_extern_functions(&mut interpreter);
         iterator::register_extern_functions(&mut interpreter);
         interpreter.build_typedefid_cache();
@@ -1028,10 +1677,100 @@ impl Interpreter {
             let mut p = c.borrow_mut();
             *p = Some(interpreter);
         });
+    }
+
+    fn execute_in_context<T>(f: impl FnOnce(&Interpreter) -> T) -> Result<T, RuntimeError> {
         INTERPRETER_CONTEXT.with(|c| {
             let p = c.borrow();
             let i = p.as_ref().expect("Interpreter not set");
-            Interpreter::execute_main(i)
+            match panic::catch_unwind(panic::AssertUnwindSafe(|| f(i))) {
+                Ok(value) => Ok(value),
+                Err(payload) => match payload.downcast::<RuntimeError>() {
+                    Ok(err) => Err(*err),
+                    Err(payload) => panic::resume_unwind(payload),
+                },
+            }
         })
     }
+
+    // Runs the program stopping it with an error when it exceeds the limits,
+    // the same holds for every way of running a program below
+    pub fn run(program: Program, limits: Limits) -> Result<Value, RuntimeError> {
+        Interpreter::start(program, None, limits);
+        Interpreter::execute_in_context(Interpreter::execute_main)
+    }
+
+    pub fn eval(
+        program: Program,
+        function_id: FunctionId,
+        limits: Limits,
+    ) -> Result<Option<String>, RuntimeError> {
+        Interpreter::start(program, None, limits);
+        Interpreter::execute_in_context(|i| Interpreter::execute_eval(i, function_id))
+    }
+
+    // Runs the program measuring the time spent in each function, the profile
+    // is returned even if the program stopped with an error
+    pub fn profile(program: Program, limits: Limits) -> (Result<Value, RuntimeError>, Profile) {
+        Interpreter::start(program, None, limits);
+        INTERPRETER_CONTEXT.with(|c| {
+            let mut p = c.borrow_mut();
+            p.as_mut().expect("Interpreter not set").profiler = Some(Profiler::new());
+        });
+        let result = Interpreter::execute_in_context(Interpreter::execute_main);
+        let profile = INTERPRETER_CONTEXT.with(|c| {
+            let p = c.borrow();
+            let i = p.as_ref().expect("Interpreter not set");
+            let profiler = i.profiler.as_ref().expect("Profiler not set");
+            profiler.get_profile(|function_id| i.get_frame_name(function_id))
+        });
+        (result, profile)
+    }
+
+    // Runs the program counting the evaluations of each expression
+    pub fn cover(program: Program, limits: Limits) -> (Result<Value, RuntimeError>, CoverageData) {
+        let coverage = Coverage::new(&program);
+        Interpreter::start(program, None, limits);
+        INTERPRETER_CONTEXT.with(|c| {
+            let mut p = c.borrow_mut();
+            p.as_mut().expect("Interpreter not set").coverage = Some(coverage);
+        });
+        let result = Interpreter::execute_in_context(Interpreter::execute_main);
+        let data = INTERPRETER_CONTEXT.with(|c| {
+            let p = c.borrow();
+            let i = p.as_ref().expect("Interpreter not set");
+            let coverage = i.coverage.as_ref().expect("Coverage not set");
+            coverage.get_data(&i.program)
+        });
+        (result, data)
+    }
+
+    // Runs the program stopping at the breakpoints and steps requested by the frontend
+    pub fn debug(
+        program: Program,
+        location_info: LocationInfo,
+        frontend: Box<dyn DebugFrontend>,
+        limits: Limits,
+    ) -> Result<Value, RuntimeError> {
+        let debugger = Debugger::new(&program, location_info, frontend);
+        Interpreter::start(program, Some(debugger), limits);
+        let result = Interpreter::execute_in_context(|i| {
+            i.debugger.as_ref().expect("Debugger not set").start();
+            Interpreter::execute_main(i)
+        });
+        INTERPRETER_CONTEXT.with(|c| {
+            let p = c.borrow();
+            let i = p.as_ref().expect("Interpreter not set");
+            let debugger = i.debugger.as_ref().expect("Debugger not set");
+            let error = result.as_ref().err().map(|err| match err.location {
+                Some(location_id) => {
+                    let position = debugger.get_position(&location_id);
+                    format!("{} at {}:{}", err.message, position.file, position.line)
+                }
+                None => err.message.clone(),
+            });
+            debugger.finished(error);
+        });
+        result
+    }
 }
@@ -46,7 +46,99 @@ impl ExternFunction for ForEach {
     }
 }
 
+pub struct Filter {}
+
+impl ExternFunction for Filter {
+    fn call(
+        &self,
+        environment: &mut Environment,
+        _: Option<ExprId>,
+        _: &NamedFunctionKind,
+        ty: Type,
+    ) -> Value {
+        let func = environment.get_arg_by_index(0);
+        let iterator = environment.get_arg_by_index(1);
+        return Value::new(
+            ValueCore::IteratorFilter(Box::new(iterator), Box::new(func)),
+            ty,
+        );
+    }
+}
+
+pub struct FlatMap {}
+
+impl ExternFunction for FlatMap {
+    fn call(
+        &self,
+        environment: &mut Environment,
+        _: Option<ExprId>,
+        _: &NamedFunctionKind,
+        ty: Type,
+    ) -> Value {
+        let func = environment.get_arg_by_index(0);
+        let iterator = environment.get_arg_by_index(1);
+        return Value::new(
+            ValueCore::IteratorFlatMap(Box::new(iterator), Box::new(func)),
+            ty,
+        );
+    }
+}
+
+pub struct Take {}
+
+impl ExternFunction for Take {
+    fn call(
+        &self,
+        environment: &mut Environment,
+        _: Option<ExprId>,
+        _: &NamedFunctionKind,
+        ty: Type,
+    ) -> Value {
+        let count = environment.get_arg_by_index(0).core.as_int();
+        let iterator = environment.get_arg_by_index(1);
+        return Value::new(ValueCore::IteratorTake(Box::new(iterator), count), ty);
+    }
+}
+
+pub struct Range {}
+
+impl ExternFunction for Range {
+    fn call(
+        &self,
+        environment: &mut Environment,
+        _: Option<ExprId>,
+        _: &NamedFunctionKind,
+        ty: Type,
+    ) -> Value {
+        let start = environment.get_arg_by_index(0).core.as_int();
+        let step = environment.get_arg_by_index(1).core.as_int();
+        let end = environment.get_arg_by_index(2).core.as_int();
+        return Value::new(ValueCore::IteratorRange(start, step, Some(end)), ty);
+    }
+}
+
+pub struct RangeFrom {}
+
+impl ExternFunction for RangeFrom {
+    fn call(
+        &self,
+        environment: &mut Environment,
+        _: Option<ExprId>,
+        _: &NamedFunctionKind,
+        ty: Type,
+    ) -> Value {
+        let start = environment.get_arg_by_index(0).core.as_int();
+        let step = environment.get_arg_by_index(1).core.as_int();
+        return Value::new(ValueCore::IteratorRange(start, step, None), ty);
+    }
+}
+
 pub fn register_extern_functions(interpreter: &mut Interpreter) {
     interpreter.add_extern_function(ITERATOR_MODULE_NAME, "map", Box::new(Map {}));
     interpreter.add_extern_function(ITERATOR_MODULE_NAME, "forEach", Box::new(ForEach {}));
+    interpreter.add_extern_function(ITERATOR_MODULE_NAME, "filter", Box::new(Filter {}));
+    interpreter.add_extern_function(ITERATOR_MODULE_NAME, "flatMap", Box::new(FlatMap {}));
+    interpreter.add_extern_function(ITERATOR_MODULE_NAME, "take", Box::new(Take {}));
+    interpreter.add_extern_function(ITERATOR_MODULE_NAME, "range", Box::new(Range {}));
+    interpreter.add_extern_function(ITERATOR_MODULE_NAME, "rangeFrom", Box::new(RangeFrom {}));
 }
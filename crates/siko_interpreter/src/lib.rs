@@ -1,12 +1,19 @@
+pub mod actor_system;
 pub mod char;
+pub mod coverage;
+pub mod debugger;
 pub mod environment;
+pub mod error;
 pub mod extern_function;
 pub mod float;
 pub mod int;
 pub mod interpreter;
 pub mod iterator;
+pub mod limits;
 pub mod list;
 pub mod map;
+pub mod profiler;
+pub mod std_actor;
 pub mod std_ops;
 pub mod std_util;
 pub mod std_util_basic;
@@ -0,0 +1,130 @@
+use crate::value::Value;
+use crate::value::ValueCore;
+use std::cell::Cell;
+use std::mem::size_of;
+
+// Stack used by the compiler before the program starts
+const BASE_STACK_SIZE: usize = 64 * 1024 * 1024;
+// Stack used by a function call of the interpreted program, with room for
+// the nested expressions evaluated in it
+#[cfg(debug_assertions)]
+const STACK_SIZE_PER_CALL: usize = 64 * 1024;
+#[cfg(not(debug_assertions))]
+const STACK_SIZE_PER_CALL: usize = 16 * 1024;
+// Deep enough for the usual recursion, stops the runaway ones before they
+// overflow the stack of the host
+pub const DEFAULT_MAX_DEPTH: usize = 10000;
+// Stack kept free for reporting the error when the stack guard stops the program
+const STACK_RESERVE: usize = 1024 * 1024;
+
+thread_local! {
+    static HEAP_USED: Cell<u64> = const { Cell::new(0) };
+    static STACK_END: Cell<usize> = const { Cell::new(0) };
+}
+
+fn get_stack_address() -> usize {
+    let marker = 0u8;
+    &marker as *const u8 as usize
+}
+
+// Called first in the thread running the interpreter with the size of its
+// stack. The stack needed by a call grows with the nesting of its expressions,
+// so the call depth limit alone cannot keep the program from overflowing it.
+pub fn set_stack_size(size: usize) {
+    let end = get_stack_address().saturating_sub(size) + STACK_RESERVE;
+    STACK_END.with(|e| e.set(end));
+}
+
+// The stack grows downwards, it is exhausted when the current frame is below
+// the reserve at its end. Threads without a known stack size are not checked.
+pub fn is_stack_exhausted() -> bool {
+    let end = STACK_END.with(|e| e.get());
+    end != 0 && get_stack_address() < end
+}
+
+fn add_heap_used(size: u64) {
+    HEAP_USED.with(|h| h.set(h.get() + size));
+}
+
+fn remove_heap_used(size: u64) {
+    let _ = HEAP_USED.try_with(|h| h.set(h.get().saturating_sub(size)));
+}
+
+// Owned by every value, keeps an estimate of the memory the value owns
+// counted in the heap usage while the value is alive.
+#[derive(Debug)]
+pub struct HeapUsage {
+    size: u64,
+}
+
+impl HeapUsage {
+    pub fn new(core: &ValueCore) -> HeapUsage {
+        let items = match core {
+            ValueCore::String(s) => s.len(),
+            ValueCore::Tuple(items)
+            | ValueCore::Variant(_, _, items)
+            | ValueCore::Record(_, items)
+            | ValueCore::List(items) => items.len() * size_of::<Value>(),
+            ValueCore::Map(items) => items.len() * 2 * size_of::<Value>(),
+            _ => 0,
+        };
+        let size = (size_of::<Value>() + items) as u64;
+        add_heap_used(size);
+        HeapUsage { size: size }
+    }
+}
+
+impl Clone for HeapUsage {
+    fn clone(&self) -> HeapUsage {
+        add_heap_used(self.size);
+        HeapUsage { size: self.size }
+    }
+}
+
+impl Drop for HeapUsage {
+    fn drop(&mut self) {
+        remove_heap_used(self.size);
+    }
+}
+
+pub fn get_heap_used() -> u64 {
+    HEAP_USED.with(|h| h.get())
+}
+
+pub fn reset_heap_used() {
+    HEAP_USED.with(|h| h.set(0));
+}
+
+// Bounds for running untrusted programs, exceeding any of them stops the
+// program with a runtime error. None means unlimited.
+#[derive(Clone, Debug)]
+pub struct Limits {
+    // number of expressions evaluated
+    pub fuel: Option<u64>,
+    // number of nested function calls, tail calls do not nest
+    pub max_depth: usize,
+    // approximate bytes of the values alive at the same time
+    pub max_heap: Option<u64>,
+}
+
+impl Default for Limits {
+    fn default() -> Limits {
+        Limits {
+            fuel: None,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_heap: None,
+        }
+    }
+}
+
+impl Limits {
+    pub fn new() -> Limits {
+        Limits::default()
+    }
+
+    // The Rust stack needed by the thread running the interpreter to reach
+    // max_depth without overflowing
+    pub fn get_stack_size(&self) -> usize {
+        BASE_STACK_SIZE + self.max_depth.saturating_mul(STACK_SIZE_PER_CALL)
+    }
+}
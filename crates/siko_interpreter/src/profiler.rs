@@ -0,0 +1,239 @@
+use siko_ir::function::FunctionId;
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use std::time::Instant;
+
+thread_local! {
+    static ALLOCATIONS: Cell<u64> = Cell::new(0);
+}
+
+// Called for every created value
+pub fn count_allocation() {
+    ALLOCATIONS.with(|a| a.set(a.get() + 1));
+}
+
+fn get_allocations() -> u64 {
+    ALLOCATIONS.with(|a| a.get())
+}
+
+pub struct FunctionProfile {
+    pub name: String,
+    pub calls: u64,
+    pub inclusive: Duration,
+    pub exclusive: Duration,
+    pub allocations: u64,
+}
+
+pub struct Profile {
+    // sorted by exclusive time, the slowest first
+    pub functions: Vec<FunctionProfile>,
+    // call stacks separated by ; with the exclusive time spent in them
+    pub stacks: Vec<(String, Duration)>,
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+impl Profile {
+    pub fn format_table(&self) -> String {
+        let mut table = format!(
+            "{:>10} {:>14} {:>14} {:>12}  {}\n",
+            "calls", "inclusive ms", "exclusive ms", "allocations", "function"
+        );
+        for function in &self.functions {
+            table += &format!(
+                "{:>10} {:>14.3} {:>14.3} {:>12}  {}\n",
+                function.calls,
+                millis(function.inclusive),
+                millis(function.exclusive),
+                function.allocations,
+                function.name
+            );
+        }
+        table
+    }
+
+    // One line per call stack with the exclusive time in microseconds, the
+    // format read by flamegraph tools
+    pub fn format_folded_stacks(&self) -> String {
+        let mut folded = String::new();
+        for (stack, time) in &self.stacks {
+            let micros = time.as_micros();
+            if micros > 0 {
+                folded += &format!("{} {}\n", stack, micros);
+            }
+        }
+        folded
+    }
+}
+
+#[derive(Default)]
+struct FunctionStats {
+    calls: u64,
+    inclusive: Duration,
+    exclusive: Duration,
+    allocations: u64,
+    // number of calls of the function being executed, only the outermost
+    // one of recursive calls adds to the inclusive time
+    active: usize,
+}
+
+// A node of the tree of the call stacks seen
+struct StackNode {
+    function_id: FunctionId,
+    parent: Option<usize>,
+    children: BTreeMap<FunctionId, usize>,
+    exclusive: Duration,
+}
+
+struct ActiveCall {
+    function_id: FunctionId,
+    node: usize,
+    start: Instant,
+    child_time: Duration,
+    start_allocations: u64,
+    child_allocations: u64,
+}
+
+struct ProfilerState {
+    functions: BTreeMap<FunctionId, FunctionStats>,
+    nodes: Vec<StackNode>,
+    roots: BTreeMap<FunctionId, usize>,
+    active: Vec<ActiveCall>,
+}
+
+pub struct Profiler {
+    state: RefCell<ProfilerState>,
+}
+
+impl Default for Profiler {
+    fn default() -> Profiler {
+        Profiler::new()
+    }
+}
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler {
+            state: RefCell::new(ProfilerState {
+                functions: BTreeMap::new(),
+                nodes: Vec::new(),
+                roots: BTreeMap::new(),
+                active: Vec::new(),
+            }),
+        }
+    }
+
+    pub fn enter(&self, function_id: FunctionId) {
+        let mut state = self.state.borrow_mut();
+        let state = &mut *state;
+        let parent = state.active.last().map(|call| call.node);
+        // direct recursion is shown as a single frame, the stacks would be
+        // as deep as the recursion otherwise
+        let parent = match parent {
+            Some(parent) if state.nodes[parent].function_id == function_id => {
+                state.nodes[parent].parent
+            }
+            parent => parent,
+        };
+        let existing = match parent {
+            Some(parent) => state.nodes[parent].children.get(&function_id),
+            None => state.roots.get(&function_id),
+        };
+        let node = match existing {
+            Some(node) => *node,
+            None => {
+                let node = state.nodes.len();
+                state.nodes.push(StackNode {
+                    function_id: function_id,
+                    parent: parent,
+                    children: BTreeMap::new(),
+                    exclusive: Duration::default(),
+                });
+                match parent {
+                    Some(parent) => state.nodes[parent].children.insert(function_id, node),
+                    None => state.roots.insert(function_id, node),
+                };
+                node
+            }
+        };
+        let stats = state.functions.entry(function_id).or_default();
+        stats.calls += 1;
+        stats.active += 1;
+        state.active.push(ActiveCall {
+            function_id: function_id,
+            node: node,
+            start: Instant::now(),
+            child_time: Duration::default(),
+            start_allocations: get_allocations(),
+            child_allocations: 0,
+        });
+    }
+
+    pub fn exit(&self) {
+        let mut state = self.state.borrow_mut();
+        let call = state.active.pop().expect("No active call");
+        let time = call.start.elapsed();
+        let allocations = get_allocations() - call.start_allocations;
+        let exclusive = time.checked_sub(call.child_time).unwrap_or_default();
+        let stats = state
+            .functions
+            .get_mut(&call.function_id)
+            .expect("Function not profiled");
+        stats.active -= 1;
+        if stats.active == 0 {
+            stats.inclusive += time;
+        }
+        stats.exclusive += exclusive;
+        stats.allocations += allocations - call.child_allocations;
+        state.nodes[call.node].exclusive += exclusive;
+        if let Some(parent) = state.active.last_mut() {
+            parent.child_time += time;
+            parent.child_allocations += allocations;
+        }
+    }
+
+    // The calls interrupted by a runtime error end when the profile is taken
+    pub fn get_profile(&self, get_name: impl Fn(&FunctionId) -> String) -> Profile {
+        while !self.state.borrow().active.is_empty() {
+            self.exit();
+        }
+        let state = self.state.borrow();
+        let mut functions: Vec<_> = state
+            .functions
+            .iter()
+            .map(|(id, stats)| FunctionProfile {
+                name: get_name(id),
+                calls: stats.calls,
+                inclusive: stats.inclusive,
+                exclusive: stats.exclusive,
+                allocations: stats.allocations,
+            })
+            .collect();
+        functions.sort_by(|a, b| b.exclusive.cmp(&a.exclusive));
+        let names: Vec<_> = state
+            .nodes
+            .iter()
+            .map(|node| get_name(&node.function_id).replace(';', ":"))
+            .collect();
+        let mut stacks = Vec::new();
+        for (index, node) in state.nodes.iter().enumerate() {
+            let mut path = vec![names[index].as_str()];
+            let mut parent = node.parent;
+            while let Some(p) = parent {
+                path.push(names[p].as_str());
+                parent = state.nodes[p].parent;
+            }
+            path.reverse();
+            stacks.push((path.join(";"), node.exclusive));
+        }
+        stacks.sort();
+        Profile {
+            functions: functions,
+            stacks: stacks,
+        }
+    }
+}
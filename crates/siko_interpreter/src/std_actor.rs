@@ -0,0 +1,29 @@
+use crate::environment::Environment;
+use crate::extern_function::ExternFunction;
+use crate::interpreter::Interpreter;
+use crate::value::Value;
+use crate::value::ValueCore;
+use siko_ir::expr::ExprId;
+use siko_ir::function::NamedFunctionKind;
+use siko_ir::types::Type;
+
+pub struct Send {}
+
+impl ExternFunction for Send {
+    fn call(
+        &self,
+        environment: &mut Environment,
+        current_expr: Option<ExprId>,
+        _: &NamedFunctionKind,
+        ty: Type,
+    ) -> Value {
+        let actor = environment.get_arg_by_index(0);
+        let message = environment.get_arg_by_index(1);
+        Interpreter::send_message(actor, message, current_expr);
+        return Value::new(ValueCore::Tuple(vec![]), ty);
+    }
+}
+
+pub fn register_extern_functions(interpreter: &mut Interpreter) {
+    interpreter.add_extern_function("Std.Actor", "send", Box::new(Send {}));
+}
@@ -19,7 +19,7 @@ impl ExternFunction for Print {
     ) -> Value {
         let v = environment.get_arg_by_index(0);
         let v = Interpreter::call_show(v);
-        print!("{}", v);
+        Interpreter::write_output(&v);
         return Value::new(ValueCore::Tuple(vec![]), ty);
     }
 }
@@ -36,7 +36,7 @@ impl ExternFunction for PrintLn {
     ) -> Value {
         let v = environment.get_arg_by_index(0);
         let v = Interpreter::call_show(v);
-        println!("{}", v);
+        Interpreter::write_output(&format!("{}\n", v));
         return Value::new(ValueCore::Tuple(vec![]), ty);
     }
 }
@@ -1,4 +1,6 @@
 use crate::interpreter::Interpreter;
+use crate::limits::HeapUsage;
+use crate::profiler;
 use siko_ir::data::TypeDefId;
 use siko_ir::function::FunctionId;
 use siko_ir::program::Program;
@@ -33,11 +35,19 @@ pub struct Callable {
 pub struct Value {
     pub core: ValueCore,
     pub ty: Type,
+    // kept for counting the value in the heap usage until it is dropped
+    _heap_usage: HeapUsage,
 }
 
 impl Value {
     pub fn new(core: ValueCore, ty: Type) -> Value {
-        Value { core: core, ty: ty }
+        profiler::count_allocation();
+        let heap_usage = HeapUsage::new(&core);
+        Value {
+            core: core,
+            ty: ty,
+            _heap_usage: heap_usage,
+        }
     }
 }
 
@@ -87,6 +97,11 @@ pub enum ValueCore {
     Map(BTreeMap<Value, Value>),
     Iterator(Box<Value>),
     IteratorMap(Box<Value>, Box<Value>),
+    IteratorFilter(Box<Value>, Box<Value>),
+    IteratorFlatMap(Box<Value>, Box<Value>),
+    IteratorTake(Box<Value>, i64),
+    IteratorRange(i64, i64, Option<i64>),
+    Actor(usize),
 }
 
 impl ValueCore {
@@ -217,6 +232,42 @@ impl ValueCore {
                     iterator.map(move |x| Interpreter::call_func(func.clone(), vec![x], None));
                 Box::new(iterator)
             }
+            ValueCore::IteratorFilter(v, func) => {
+                let func = *func.clone();
+                let iterator = v.core.as_iterator();
+                let iterator = iterator.filter(move |x| {
+                    Interpreter::call_func(func.clone(), vec![x.clone()], None)
+                        .core
+                        .as_bool()
+                });
+                Box::new(iterator)
+            }
+            ValueCore::IteratorFlatMap(v, func) => {
+                let func = *func.clone();
+                let iterator = v.core.as_iterator();
+                let iterator = iterator.flat_map(move |x| {
+                    Interpreter::call_func(func.clone(), vec![x], None)
+                        .core
+                        .as_iterator()
+                });
+                Box::new(iterator)
+            }
+            ValueCore::IteratorTake(v, count) => {
+                let count = if *count < 0 { 0 } else { *count as usize };
+                Box::new(v.core.as_iterator().take(count))
+            }
+            ValueCore::IteratorRange(start, step, end) => {
+                let step = *step;
+                let end = *end;
+                let int_ty = Interpreter::get_int_type();
+                let iterator = std::iter::successors(Some(*start), move |x| x.checked_add(step))
+                    .take_while(move |x| match end {
+                        Some(end) if step < 0 => *x >= end,
+                        Some(end) => *x <= end,
+                        None => true,
+                    });
+                Box::new(iterator.map(move |x| Value::new(ValueCore::Int(x), int_ty.clone())))
+            }
             _ => unreachable!(),
         }
     }
@@ -255,6 +306,18 @@ impl fmt::Display for ValueCore {
             }
             ValueCore::Iterator(v) => write!(f, "Iterator({})", v.core),
             ValueCore::IteratorMap(v, func) => write!(f, "IteratorMap({}, {})", v.core, func.core),
+            ValueCore::IteratorFilter(v, func) => {
+                write!(f, "IteratorFilter({}, {})", v.core, func.core)
+            }
+            ValueCore::IteratorFlatMap(v, func) => {
+                write!(f, "IteratorFlatMap({}, {})", v.core, func.core)
+            }
+            ValueCore::IteratorTake(v, count) => write!(f, "IteratorTake({}, {})", v.core, count),
+            ValueCore::IteratorRange(start, step, end) => match end {
+                Some(end) => write!(f, "IteratorRange({}, {}, {})", start, step, end),
+                None => write!(f, "IteratorRange({}, {})", start, step),
+            },
+            ValueCore::Actor(index) => write!(f, "Actor({})", index),
         }
     }
 }
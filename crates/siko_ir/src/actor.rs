@@ -0,0 +1,76 @@
+use crate::data::TypeDefId;
+use crate::function::FunctionId;
+use crate::type_signature::TypeSignatureId;
+use siko_location_info::location_id::LocationId;
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub struct Protocol {
+    pub id: ProtocolId,
+    pub name: String,
+    pub module: String,
+    pub typedef_id: TypeDefId,
+    pub type_signature: TypeSignatureId,
+    pub location_id: LocationId,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub struct ProtocolId {
+    pub id: usize,
+}
+
+impl fmt::Display for ProtocolId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}", self.id)
+    }
+}
+
+impl From<usize> for ProtocolId {
+    fn from(id: usize) -> ProtocolId {
+        ProtocolId { id: id }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ActorHandler {
+    pub protocol_id: ProtocolId,
+    pub function_id: FunctionId,
+    pub location_id: LocationId,
+}
+
+#[derive(Debug, Clone)]
+pub struct Actor {
+    pub id: ActorId,
+    pub name: String,
+    pub module: String,
+    pub typedef_id: TypeDefId,
+    pub state_type_signature: TypeSignatureId,
+    pub constructor: FunctionId,
+    pub handlers: Vec<ActorHandler>,
+    pub location_id: LocationId,
+}
+
+impl Actor {
+    pub fn get_handler(&self, protocol_id: ProtocolId) -> Option<&ActorHandler> {
+        self.handlers
+            .iter()
+            .find(|handler| handler.protocol_id == protocol_id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub struct ActorId {
+    pub id: usize,
+}
+
+impl fmt::Display for ActorId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}", self.id)
+    }
+}
+
+impl From<usize> for ActorId {
+    fn from(id: usize) -> ActorId {
+        ActorId { id: id }
+    }
+}
@@ -709,11 +709,14 @@ impl<'a> Builder<'a> {
             type_signature: None,
             module: module.clone(),
             name: format!("{:?}", name),
+            constraints: Vec::new(),
+            doc: None,
         };
         let function_info = FunctionInfo::NamedFunction(info);
         let function = Function {
             id: function_id,
             arg_count: arg_count,
+            arg_names: vec![None; arg_locations.len()],
             arg_locations: arg_locations,
             info: function_info,
         };
@@ -741,12 +744,15 @@ impl<'a> Builder<'a> {
             type_signature: None,
             module: module.clone(),
             name: format!("{:?}", name),
+            constraints: Vec::new(),
+            doc: None,
         };
         let function_info = FunctionInfo::NamedFunction(info);
         let function = Function {
             id: function_id,
             arg_count: 0,
             arg_locations: arg_locations,
+            arg_names: Vec::new(),
             info: function_info,
         };
         self.program
@@ -1,4 +1,5 @@
 use crate::function::FunctionId;
+use crate::kind::Kind;
 use crate::type_signature::TypeSignatureId;
 use siko_location_info::location_id::LocationId;
 use std::collections::BTreeMap;
@@ -10,10 +11,18 @@ pub struct Class {
     pub name: String,
     pub module: String,
     pub type_signature: Option<TypeSignatureId>,
+    pub arg_kinds: Vec<Kind>,
     pub constraints: Vec<ClassId>,
     pub members: BTreeMap<String, ClassMemberId>,
     pub location_id: LocationId,
     pub auto_derivable: bool,
+    pub doc: Option<String>,
+}
+
+impl Class {
+    pub fn is_multi_param(&self) -> bool {
+        self.arg_kinds.len() > 1
+    }
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
@@ -59,6 +68,7 @@ pub struct ClassMember {
     pub type_signature: TypeSignatureId,
     pub default_implementation: Option<FunctionId>,
     pub location_id: LocationId,
+    pub doc: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
@@ -82,10 +92,13 @@ impl From<usize> for InstanceId {
 pub struct Instance {
     pub id: InstanceId,
     pub name: Option<String>,
+    pub module: String,
     pub class_id: ClassId,
     pub type_signature: TypeSignatureId,
+    pub constraints: Vec<ClassConstraint>,
     pub members: BTreeMap<String, InstanceMember>,
     pub location_id: LocationId,
+    pub doc: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -93,3 +106,12 @@ pub struct InstanceMember {
     pub type_signature: TypeSignatureId,
     pub function_id: FunctionId,
 }
+
+// A constraint of a multi parameter class, the type signature is a tuple of
+// the class arguments. Single parameter constraints are stored in the
+// constraint list of the type argument instead.
+#[derive(Debug, Clone)]
+pub struct ClassConstraint {
+    pub class_id: ClassId,
+    pub type_signature: TypeSignatureId,
+}
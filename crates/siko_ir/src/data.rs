@@ -1,5 +1,6 @@
 use crate::class::ClassId;
 use crate::function::FunctionId;
+use crate::kind::Kind;
 use crate::type_signature::TypeSignatureId;
 use siko_location_info::location_id::LocationId;
 use std::fmt;
@@ -14,6 +15,7 @@ pub struct DerivedClass {
 pub struct RecordField {
     pub name: String,
     pub type_signature_id: TypeSignatureId,
+    pub doc: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -22,11 +24,13 @@ pub struct Record {
     pub module: String,
     pub id: TypeDefId,
     pub type_args: Vec<usize>,
+    pub type_arg_kinds: Vec<Kind>,
     pub fields: Vec<RecordField>,
     pub constructor: FunctionId,
     pub location_id: LocationId,
     pub external: bool,
     pub derived_classes: Vec<DerivedClass>,
+    pub doc: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -40,6 +44,7 @@ pub struct Variant {
     pub items: Vec<VariantItem>,
     pub type_signature_id: TypeSignatureId,
     pub constructor: FunctionId,
+    pub doc: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -48,8 +53,11 @@ pub struct Adt {
     pub module: String,
     pub id: TypeDefId,
     pub type_args: Vec<usize>,
+    pub type_arg_kinds: Vec<Kind>,
     pub variants: Vec<Variant>,
     pub derived_classes: Vec<DerivedClass>,
+    pub doc: Option<String>,
+    pub newtype: bool,
 }
 
 impl Adt {
@@ -118,4 +126,33 @@ impl TypeDef {
             unreachable!()
         }
     }
+
+    pub fn get_type_arg_kinds(&self) -> &Vec<Kind> {
+        match self {
+            TypeDef::Adt(adt) => &adt.type_arg_kinds,
+            TypeDef::Record(record) => &record.type_arg_kinds,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TypeAlias {
+    pub name: String,
+    pub module: String,
+    pub id: TypeAliasId,
+    pub type_args: Vec<usize>,
+    pub type_signature: Option<TypeSignatureId>,
+    pub location_id: LocationId,
+    pub doc: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub struct TypeAliasId {
+    pub id: usize,
+}
+
+impl From<usize> for TypeAliasId {
+    fn from(id: usize) -> TypeAliasId {
+        TypeAliasId { id: id }
+    }
 }
@@ -6,7 +6,7 @@ use crate::pattern::PatternId;
 use siko_util::format_list;
 use std::fmt;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct FunctionArgumentRef {
     pub captured: bool,
     pub id: FunctionId,
@@ -94,6 +94,20 @@ impl fmt::Display for RecordUpdateInfo {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct HoleInfo {
+    pub name: String,
+    pub bindings: Vec<(String, ExprId)>,
+    pub functions: Vec<(String, FunctionId)>,
+    pub class_members: Vec<(String, ClassMemberId)>,
+}
+
+impl fmt::Display for HoleInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Expr {
     StaticFunctionCall(FunctionId, Vec<ExprId>),
@@ -116,6 +130,7 @@ pub enum Expr {
     CaseOf(ExprId, Vec<Case>, Vec<BindGroup>),
     RecordInitialization(TypeDefId, Vec<RecordFieldValueExpr>),
     RecordUpdate(ExprId, Vec<RecordUpdateInfo>),
+    Hole(HoleInfo),
 }
 
 impl fmt::Display for Expr {
@@ -163,6 +178,7 @@ impl fmt::Display for Expr {
             Expr::RecordUpdate(expr_id, items) => {
                 write!(f, "RecordUpdate({}, {})", expr_id, format_list(items))
             }
+            Expr::Hole(info) => write!(f, "Hole({})", info),
         }
     }
 }
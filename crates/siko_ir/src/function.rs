@@ -1,3 +1,5 @@
+use crate::actor::ActorId;
+use crate::class::ClassConstraint;
 use crate::class::ClassMemberId;
 use crate::data::TypeDefId;
 use crate::expr::ExprId;
@@ -39,6 +41,8 @@ pub struct NamedFunctionInfo {
     pub type_signature: Option<TypeSignatureId>,
     pub location_id: LocationId,
     pub kind: NamedFunctionKind,
+    pub constraints: Vec<ClassConstraint>,
+    pub doc: Option<String>,
 }
 
 impl fmt::Display for NamedFunctionInfo {
@@ -63,6 +67,21 @@ impl fmt::Display for LambdaInfo {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct LocalFunctionInfo {
+    pub body: ExprId,
+    pub module: String,
+    pub name: String,
+    pub host_info: String,
+    pub location_id: LocationId,
+}
+
+impl fmt::Display for LocalFunctionInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.host_info, self.name)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RecordConstructorInfo {
     pub type_id: TypeDefId,
@@ -86,21 +105,37 @@ impl fmt::Display for VariantConstructorInfo {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct ActorConstructorInfo {
+    pub actor_id: ActorId,
+    pub type_id: TypeDefId,
+}
+
+impl fmt::Display for ActorConstructorInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "actor{}", self.type_id)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum FunctionInfo {
     Lambda(LambdaInfo),
+    LocalFunction(LocalFunctionInfo),
     NamedFunction(NamedFunctionInfo),
     RecordConstructor(RecordConstructorInfo),
     VariantConstructor(VariantConstructorInfo),
+    ActorConstructor(ActorConstructorInfo),
 }
 
 impl fmt::Display for FunctionInfo {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             FunctionInfo::Lambda(i) => write!(f, "lambda{}", i),
+            FunctionInfo::LocalFunction(i) => write!(f, "{}", i),
             FunctionInfo::NamedFunction(i) => write!(f, "{}", i),
             FunctionInfo::RecordConstructor(i) => write!(f, "{}", i),
             FunctionInfo::VariantConstructor(i) => write!(f, "{}", i),
+            FunctionInfo::ActorConstructor(i) => write!(f, "{}", i),
         }
     }
 }
@@ -109,6 +144,8 @@ impl fmt::Display for FunctionInfo {
 pub struct Function {
     pub id: FunctionId,
     pub arg_locations: Vec<LocationId>,
+    // the name every clause binds the argument to, if they agree
+    pub arg_names: Vec<Option<String>>,
     pub arg_count: usize,
     pub info: FunctionInfo,
 }
@@ -124,18 +161,22 @@ impl Function {
     pub fn get_body(&self) -> Option<ExprId> {
         match &self.info {
             FunctionInfo::Lambda(i) => Some(i.body),
+            FunctionInfo::LocalFunction(i) => Some(i.body),
             FunctionInfo::NamedFunction(i) => i.body.clone(),
             FunctionInfo::RecordConstructor(_) => None,
             FunctionInfo::VariantConstructor(_) => None,
+            FunctionInfo::ActorConstructor(_) => None,
         }
     }
 
     pub fn is_typed(&self) -> bool {
         match &self.info {
             FunctionInfo::Lambda(_) => false,
+            FunctionInfo::LocalFunction(_) => false,
             FunctionInfo::NamedFunction(i) => i.type_signature.is_some(),
             FunctionInfo::RecordConstructor(_) => true,
             FunctionInfo::VariantConstructor(_) => true,
+            FunctionInfo::ActorConstructor(_) => true,
         }
     }
 }
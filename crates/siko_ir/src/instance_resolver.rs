@@ -50,6 +50,7 @@ impl InstanceInfo {
 pub struct InstanceResolver {
     pub instance_map: BTreeMap<ClassId, BTreeMap<BaseType, Vec<InstanceInfo>>>,
     pub auto_derived_instances: Vec<AutoDerivedInstance>,
+    pub contexts: BTreeMap<InstanceId, Vec<(ClassId, Type)>>,
     cache: Rc<RefCell<BTreeMap<(ClassId, Type), ResolutionResult>>>,
     pub type_var_generator: TypeVarGenerator,
 }
@@ -59,6 +60,7 @@ impl InstanceResolver {
         InstanceResolver {
             instance_map: BTreeMap::new(),
             auto_derived_instances: Vec::new(),
+            contexts: BTreeMap::new(),
             cache: Rc::new(RefCell::new(BTreeMap::new())),
             type_var_generator: type_var_generator,
         }
@@ -93,6 +95,11 @@ impl InstanceResolver {
         ));
     }
 
+    // multi parameter class constraints of a user defined instance
+    pub fn add_context(&mut self, instance_id: InstanceId, context: Vec<(ClassId, Type)>) {
+        self.contexts.insert(instance_id, context);
+    }
+
     pub fn add_auto_derived(
         &mut self,
         class_id: ClassId,
@@ -116,7 +123,7 @@ impl InstanceResolver {
         index
     }
 
-    fn has_instance(&self, ty: &Type, class_id: ClassId) -> Option<Unifier> {
+    fn has_instance(&self, ty: &Type, class_id: ClassId) -> Option<(Unifier, Option<InstanceId>)> {
         let base_type = ty.get_base_type();
         if let Some(class_instances) = self.instance_map.get(&class_id) {
             if let Some(instances) = class_instances.get(&base_type) {
@@ -131,7 +138,7 @@ impl InstanceResolver {
                                     let mut cache = self.cache.borrow_mut();
                                     cache.insert((class_id, ty.clone()), result);
                                 }
-                                return Some(unifier);
+                                return Some((unifier, None));
                             }
                         }
                         InstanceInfo::UserDefined(instance_ty, instance_id, _) => {
@@ -141,7 +148,7 @@ impl InstanceResolver {
                                     let mut cache = self.cache.borrow_mut();
                                     cache.insert((class_id, ty.clone()), result);
                                 }
-                                return Some(unifier);
+                                return Some((unifier, Some(*instance_id)));
                             }
                         }
                     }
@@ -155,6 +162,39 @@ impl InstanceResolver {
         }
     }
 
+    // Returns the constraints that must hold for the instance matching the
+    // given type, None if there is no such instance.
+    pub fn get_context(&self, class_id: ClassId, ty: &Type) -> Option<Vec<(ClassId, Type)>> {
+        let (unifier, instance_id) = self.has_instance(ty, class_id)?;
+        let mut context: Vec<_> = unifier
+            .get_constraints()
+            .into_iter()
+            .map(|constraint| (constraint.class_id, constraint.ty))
+            .collect();
+        if let Some(instance_context) = instance_id.and_then(|id| self.contexts.get(&id)) {
+            for (class_id, ty) in instance_context {
+                context.push((*class_id, unifier.apply(ty)));
+            }
+        }
+        Some(context)
+    }
+
+    // Locations of all instances that could be selected for the given type.
+    pub fn get_candidates(&self, class_id: ClassId, ty: &Type) -> Vec<LocationId> {
+        let mut candidates = Vec::new();
+        if let Some(class_instances) = self.instance_map.get(&class_id) {
+            for instances in class_instances.values() {
+                for instance in instances {
+                    let mut unifier = Unifier::new(self.type_var_generator.clone());
+                    if unifier.unify(ty, instance.get_type(self)).is_ok() {
+                        candidates.push(instance.get_location(self));
+                    }
+                }
+            }
+        }
+        candidates
+    }
+
     pub fn check_instance(
         &mut self,
         class_id: ClassId,
@@ -177,7 +217,24 @@ impl InstanceResolver {
                 return true;
             }
         }
-        if let Some(unifier) = self.has_instance(&ty, class_id) {
+        if let Some((unifier, instance_id)) = self.has_instance(&ty, class_id) {
+            // contexts over type arguments of the caller are checked together
+            // with the caller's own constraints by the typechecker
+            if let Some(instance_context) = instance_id.and_then(|id| self.contexts.get(&id)) {
+                for (context_class_id, context_ty) in instance_context.clone() {
+                    let context_ty = unifier.apply(&context_ty);
+                    if context_ty.is_concrete_type()
+                        && !self.check_instance(
+                            context_class_id,
+                            &context_ty,
+                            location_id,
+                            unifiers,
+                        )
+                    {
+                        return false;
+                    }
+                }
+            }
             let constraints = unifier.get_substitution().get_constraints();
             for constraint in constraints {
                 if constraint.ty.get_base_type() == BaseType::Generic {
@@ -0,0 +1,25 @@
+use std::fmt;
+
+// Kinds only describe how many type arguments a type needs before it becomes a
+// proper type, `Option` has an arity of one (`* -> *`), `Int` has zero (`*`).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Kind {
+    pub arity: usize,
+}
+
+impl Kind {
+    pub fn new(arity: usize) -> Kind {
+        Kind { arity: arity }
+    }
+
+    pub fn star() -> Kind {
+        Kind::new(0)
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let parts: Vec<_> = (0..=self.arity).map(|_| "*").collect();
+        write!(f, "{}", parts.join(" -> "))
+    }
+}
@@ -1,3 +1,4 @@
+pub mod actor;
 pub mod builder;
 pub mod class;
 pub mod data;
@@ -6,6 +7,7 @@ pub mod expr;
 pub mod function;
 pub mod function_dep_processor;
 pub mod instance_resolver;
+pub mod kind;
 pub mod pattern;
 pub mod program;
 pub mod substitution;
@@ -32,6 +32,10 @@ pub enum Pattern {
     StringLiteral(String),
     CharLiteral(char),
     CharRange(char, char),
+    IntegerRange(i64, i64),
+    FloatLiteral(f64),
+    List(Vec<PatternId>, Option<PatternId>),
+    As(PatternId, String),
     Typed(PatternId, TypeSignatureId),
 }
 
@@ -1,3 +1,7 @@
+use crate::actor::Actor;
+use crate::actor::ActorId;
+use crate::actor::Protocol;
+use crate::actor::ProtocolId;
 use crate::class::Class;
 use crate::class::ClassId;
 use crate::class::ClassMember;
@@ -5,6 +9,8 @@ use crate::class::ClassMemberId;
 use crate::class::Instance;
 use crate::class::InstanceId;
 use crate::data::Adt;
+use crate::data::TypeAlias;
+use crate::data::TypeAliasId;
 use crate::data::TypeDef;
 use crate::data::TypeDefId;
 use crate::data_type_info::AdtTypeInfo;
@@ -24,6 +30,8 @@ use crate::type_signature::TypeSignatureId;
 use crate::type_var_generator::TypeVarGenerator;
 use crate::types::Type;
 use crate::unifier::Unifier;
+use siko_constants::ACTOR_HANDLES_CLASS_NAME;
+use siko_constants::ACTOR_SEND_NAME;
 use siko_constants::BOOL_MODULE_NAME;
 use siko_constants::BOOL_TYPE_NAME;
 use siko_constants::CHAR_MODULE_NAME;
@@ -47,6 +55,7 @@ use siko_constants::PARTIALEQ_CLASS_NAME;
 use siko_constants::PARTIALEQ_OP_NAME;
 use siko_constants::PARTIALORD_CLASS_NAME;
 use siko_constants::SHOW_CLASS_NAME;
+use siko_constants::STD_ACTOR_MODULE_NAME;
 use siko_constants::STRING_MODULE_NAME;
 use siko_constants::STRING_TYPE_NAME;
 use siko_location_info::item::ItemInfo;
@@ -59,10 +68,13 @@ pub struct Program {
     pub exprs: ItemContainer<ExprId, ItemInfo<Expr>>,
     pub functions: ItemContainer<FunctionId, Function>,
     pub typedefs: ItemContainer<TypeDefId, TypeDef>,
+    pub type_aliases: ItemContainer<TypeAliasId, TypeAlias>,
     pub patterns: ItemContainer<PatternId, ItemInfo<Pattern>>,
     pub classes: ItemContainer<ClassId, Class>,
     pub class_members: ItemContainer<ClassMemberId, ClassMember>,
     pub instances: ItemContainer<InstanceId, Instance>,
+    pub protocols: ItemContainer<ProtocolId, Protocol>,
+    pub actors: ItemContainer<ActorId, Actor>,
     pub expr_types: BTreeMap<ExprId, Type>,
     pub pattern_types: BTreeMap<PatternId, Type>,
     pub function_types: BTreeMap<FunctionId, Type>,
@@ -83,10 +95,13 @@ impl Program {
             exprs: ItemContainer::new(),
             functions: ItemContainer::new(),
             typedefs: ItemContainer::new(),
+            type_aliases: ItemContainer::new(),
             patterns: ItemContainer::new(),
             classes: ItemContainer::new(),
             class_members: ItemContainer::new(),
             instances: ItemContainer::new(),
+            protocols: ItemContainer::new(),
+            actors: ItemContainer::new(),
             expr_types: BTreeMap::new(),
             pattern_types: BTreeMap::new(),
             function_types: BTreeMap::new(),
@@ -259,6 +274,42 @@ impl Program {
         (module, name)
     }
 
+    pub fn get_protocol_by_typedef(&self, typedef_id: TypeDefId) -> Option<&Protocol> {
+        self.protocols
+            .items
+            .values()
+            .find(|protocol| protocol.typedef_id == typedef_id)
+    }
+
+    pub fn get_actor_by_typedef(&self, typedef_id: TypeDefId) -> Option<&Actor> {
+        self.actors
+            .items
+            .values()
+            .find(|actor| actor.typedef_id == typedef_id)
+    }
+
+    // The class behind the (a <- p) constraints
+    pub fn get_actor_handles_class_id(&self) -> Option<ClassId> {
+        self.classes
+            .items
+            .iter()
+            .find(|(_, class)| {
+                class.module == STD_ACTOR_MODULE_NAME && class.name == ACTOR_HANDLES_CLASS_NAME
+            })
+            .map(|(id, _)| *id)
+    }
+
+    pub fn get_actor_send_function_id(&self) -> Option<FunctionId> {
+        self.functions.items.iter().find_map(|(id, function)| {
+            if let FunctionInfo::NamedFunction(info) = &function.info {
+                if info.module == STD_ACTOR_MODULE_NAME && info.name == ACTOR_SEND_NAME {
+                    return Some(*id);
+                }
+            }
+            None
+        })
+    }
+
     pub fn get_unifier(&self) -> Unifier {
         Unifier::new(self.type_var_generator.clone())
     }
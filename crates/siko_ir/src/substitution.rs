@@ -32,6 +32,10 @@ impl Substitution {
         self.var_map.is_empty()
     }
 
+    pub fn get_bindings(&self) -> &BTreeMap<usize, Type> {
+        &self.var_map
+    }
+
     pub fn add_constraint(&mut self, class_id: ClassId, ty: Type) {
         let constraints = self
             .constraints
@@ -85,6 +89,10 @@ impl Substitution {
                 Some(ty) => self.apply(ty),
                 None => ty.clone(),
             },
+            Type::Application(head, args) => {
+                let args = args.iter().map(|ty| self.apply(ty)).collect();
+                Type::apply_args(self.apply(head), args)
+            }
         }
     }
 
@@ -17,6 +17,7 @@ pub enum TypeSignature {
     Tuple(Vec<TypeSignatureId>),
     Function(TypeSignatureId, TypeSignatureId),
     TypeArgument(usize, String, Vec<ClassId>),
+    Application(TypeSignatureId, Vec<TypeSignatureId>),
     Named(String, TypeDefId, Vec<TypeSignatureId>),
     Variant(String, Vec<TypeSignatureId>),
     Wildcard,
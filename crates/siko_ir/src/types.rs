@@ -76,9 +76,30 @@ pub enum Type {
     Function(Box<Type>, Box<Type>),
     Var(usize, Vec<ClassId>),
     FixedTypeArg(String, usize, Vec<ClassId>),
+    Application(Box<Type>, Vec<Type>),
 }
 
 impl Type {
+    // Applies a type constructor to further arguments. Once the head is known
+    // to be a named type the application collapses into that named type, so
+    // `f a` with `f = Result e` becomes `Result e a`.
+    pub fn apply_args(head: Type, args: Vec<Type>) -> Type {
+        if args.is_empty() {
+            return head;
+        }
+        match head {
+            Type::Named(name, id, mut items) => {
+                items.extend(args);
+                Type::Named(name, id, items)
+            }
+            Type::Application(head, mut items) => {
+                items.extend(args);
+                Type::Application(head, items)
+            }
+            head => Type::Application(Box::new(head), args),
+        }
+    }
+
     pub fn contains(&self, index: usize) -> bool {
         match self {
             Type::Tuple(items) => {
@@ -112,6 +133,17 @@ impl Type {
             Type::FixedTypeArg(_, i, _) => {
                 return *i == index;
             }
+            Type::Application(head, args) => {
+                if head.contains(index) {
+                    return true;
+                }
+                for arg in args {
+                    if arg.contains(index) {
+                        return true;
+                    }
+                }
+                return false;
+            }
         }
     }
 
@@ -138,6 +170,7 @@ impl Type {
             Type::Function(..) => BaseType::Function,
             Type::Var(..) => BaseType::Generic,
             Type::FixedTypeArg(..) => BaseType::Generic,
+            Type::Application(..) => BaseType::Generic,
         }
     }
 
@@ -158,6 +191,10 @@ impl Type {
             }
             Type::Var(..) => self.clone(),
             Type::FixedTypeArg(_, index, constraints) => Type::Var(*index, constraints.clone()),
+            Type::Application(head, args) => {
+                let args: Vec<_> = args.iter().map(|i| i.remove_fixed_types()).collect();
+                Type::Application(Box::new(head.remove_fixed_types()), args)
+            }
         }
     }
 
@@ -196,6 +233,14 @@ impl Type {
                 let new_index = arg_map.entry(*index).or_insert_with(|| gen.get_new_index());
                 Type::FixedTypeArg(name.clone(), *new_index, constraints.clone())
             }
+            Type::Application(head, args) => {
+                let head = head.duplicate(arg_map, type_var_generator);
+                let args: Vec<_> = args
+                    .iter()
+                    .map(|i| i.duplicate(arg_map, type_var_generator))
+                    .collect();
+                Type::Application(Box::new(head), args)
+            }
         }
     }
 
@@ -206,6 +251,7 @@ impl Type {
             Type::Function(_, to) => 1 + to.get_arg_count(),
             Type::Var(..) => 0,
             Type::FixedTypeArg(..) => 0,
+            Type::Application(..) => 0,
         }
     }
 
@@ -219,6 +265,7 @@ impl Type {
             }
             Type::Var(..) => {}
             Type::FixedTypeArg(..) => {}
+            Type::Application(..) => {}
         }
     }
 
@@ -239,6 +286,7 @@ impl Type {
             }
             Type::Var(..) => self.clone(),
             Type::FixedTypeArg(..) => self.clone(),
+            Type::Application(..) => self.clone(),
         }
     }
 
@@ -271,6 +319,12 @@ impl Type {
                     args.add(*index, *c);
                 }
             }
+            Type::Application(head, items) => {
+                head.collect(args, context);
+                for item in items {
+                    item.collect(args, context);
+                }
+            }
         }
     }
 
@@ -295,6 +349,39 @@ impl Type {
             Type::Function(from, to) => from.is_concrete_type() && to.is_concrete_type(),
             Type::Var(..) => false,
             Type::FixedTypeArg(..) => false,
+            Type::Application(..) => false,
+        }
+    }
+
+    // true if the type still has type variables that are not fixed type
+    // arguments of a signature
+    pub fn has_type_vars(&self) -> bool {
+        match self {
+            Type::Tuple(items) => items.iter().any(|item| item.has_type_vars()),
+            Type::Named(_, _, items) => items.iter().any(|item| item.has_type_vars()),
+            Type::Function(from, to) => from.has_type_vars() || to.has_type_vars(),
+            Type::Var(..) => true,
+            Type::FixedTypeArg(..) => false,
+            Type::Application(head, items) => {
+                head.has_type_vars() || items.iter().any(|item| item.has_type_vars())
+            }
+        }
+    }
+
+    pub fn collect_vars(&self, vars: &mut Vec<usize>) {
+        match self {
+            Type::Tuple(items) => items.iter().for_each(|item| item.collect_vars(vars)),
+            Type::Named(_, _, items) => items.iter().for_each(|item| item.collect_vars(vars)),
+            Type::Function(from, to) => {
+                from.collect_vars(vars);
+                to.collect_vars(vars);
+            }
+            Type::Var(index, _) => vars.push(*index),
+            Type::FixedTypeArg(..) => {}
+            Type::Application(head, items) => {
+                head.collect_vars(vars);
+                items.iter().for_each(|item| item.collect_vars(vars));
+            }
         }
     }
 
@@ -378,12 +465,28 @@ impl Type {
             }
             Type::Var(index, _) => resolver_context.get_type_arg_name(*index),
             Type::FixedTypeArg(name, _, _) => format!("{}", name),
+            Type::Application(head, items) => {
+                let ss: Vec<_> = items
+                    .iter()
+                    .map(|item| item.as_string(true, resolver_context))
+                    .collect();
+                let ty_str = format!(
+                    "{} {}",
+                    head.as_string(true, resolver_context),
+                    ss.join(" ")
+                );
+                if need_parens {
+                    format!("({})", ty_str)
+                } else {
+                    ty_str
+                }
+            }
             Type::Named(name, id, items) => {
                 let ss: Vec<_> = items
                     .iter()
                     .map(|item| item.as_string(true, resolver_context))
                     .collect();
-                if *id == resolver_context.get_list_type_id() {
+                if *id == resolver_context.get_list_type_id() && ss.len() == 1 {
                     assert_eq!(ss.len(), 1);
                     format!("[{}]", ss[0])
                 } else {
@@ -461,6 +564,10 @@ impl fmt::Display for Type {
                 };
                 write!(f, "f${}{}", id, c)
             }
+            Type::Application(head, items) => {
+                let ss: Vec<_> = items.iter().map(|i| format!("{}", i)).collect();
+                write!(f, "{} ({})", head, ss.join(" "))
+            }
         }
     }
 }
@@ -24,8 +24,9 @@ impl Unifier {
         //println!("Unify {} <?> {}", type1, type2);
         match (&type1, &type2) {
             (Type::Named(_, id1, items1), Type::Named(_, id2, items2)) => {
-                if id1 == id2 {
-                    assert_eq!(items1.len(), items2.len());
+                // partially applied named types only meet through type
+                // constructor variables, their lengths can differ
+                if id1 == id2 && items1.len() == items2.len() {
                     for (item1, item2) in items1.iter().zip(items2.iter()) {
                         self.unify(item1, item2)?;
                     }
@@ -93,6 +94,26 @@ impl Unifier {
                 }
                 return self.substitution.add(*index, &type1);
             }
+            (Type::Application(head1, args1), Type::Application(head2, args2)) => {
+                if args1.len() >= args2.len() {
+                    self.unify_application(head1, args1, head2, args2)
+                } else {
+                    self.unify_application(head2, args2, head1, args1)
+                }
+            }
+            (Type::Application(head, args), Type::Named(name, id, items))
+            | (Type::Named(name, id, items), Type::Application(head, args)) => {
+                if items.len() < args.len() {
+                    return Err(Error::Fail);
+                }
+                let (prefix, suffix) = items.split_at(items.len() - args.len());
+                let constructor = Type::Named(name.clone(), *id, prefix.to_vec());
+                self.unify(head, &constructor)?;
+                for (arg, item) in args.iter().zip(suffix.iter()) {
+                    self.unify(arg, item)?;
+                }
+                Ok(())
+            }
             (Type::Tuple(items1), Type::Tuple(items2)) => {
                 if items1.len() != items2.len() {
                     return Err(Error::Fail);
@@ -111,6 +132,25 @@ impl Unifier {
         }
     }
 
+    // Unifies `head1 args1` with `head2 args2` where args1 is not shorter than
+    // args2, the extra leading arguments of the first one belong to the head of
+    // the second one.
+    fn unify_application(
+        &mut self,
+        head1: &Type,
+        args1: &Vec<Type>,
+        head2: &Type,
+        args2: &Vec<Type>,
+    ) -> Result<(), Error> {
+        let (prefix, suffix) = args1.split_at(args1.len() - args2.len());
+        let constructor = Type::apply_args(head1.clone(), prefix.to_vec());
+        self.unify(&constructor, head2)?;
+        for (arg1, arg2) in suffix.iter().zip(args2.iter()) {
+            self.unify(arg1, arg2)?;
+        }
+        Ok(())
+    }
+
     pub fn apply(&self, ty: &Type) -> Type {
         self.substitution.apply(ty)
     }
@@ -56,6 +56,11 @@ pub fn walk_expr(expr_id: &ExprId, visitor: &mut dyn Visitor) {
         }
         Expr::ArgRef(_) => {}
         Expr::ExprValue(_, _) => {}
+        Expr::Hole(info) => {
+            for (_, binding) in &info.bindings {
+                walk_expr(binding, visitor);
+            }
+        }
         Expr::FieldAccess(_, lhs) => {
             walk_expr(lhs, visitor);
         }
@@ -128,6 +133,19 @@ fn walk_pattern(pattern_id: &PatternId, visitor: &mut dyn Visitor) {
         Pattern::StringLiteral(_) => {}
         Pattern::CharLiteral(_) => {}
         Pattern::CharRange(_, _) => {}
+        Pattern::IntegerRange(_, _) => {}
+        Pattern::FloatLiteral(_) => {}
+        Pattern::List(items, rest) => {
+            for item in items {
+                walk_pattern(item, visitor);
+            }
+            if let Some(rest) = rest {
+                walk_pattern(rest, visitor);
+            }
+        }
+        Pattern::As(id, _) => {
+            walk_pattern(id, visitor);
+        }
         Pattern::Typed(id, _) => {
             walk_pattern(id, visitor);
         }
@@ -3,15 +3,26 @@ use std::collections::BTreeMap;
 use std::fs;
 use std::io::Error;
 
+// Where a line of a generated file was entered by the user, the generated
+// file indents it by the given number of columns
+#[derive(Debug, Clone)]
+pub struct LineOrigin {
+    pub entry: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
 #[derive(Clone)]
 pub struct FileManager {
     pub files: BTreeMap<FilePath, String>,
+    line_origins: BTreeMap<FilePath, BTreeMap<usize, LineOrigin>>,
 }
 
 impl FileManager {
     pub fn new() -> FileManager {
         FileManager {
             files: BTreeMap::new(),
+            line_origins: BTreeMap::new(),
         }
     }
 
@@ -29,4 +40,44 @@ impl FileManager {
     pub fn add_from_memory(&mut self, file_path: FilePath, content: String) {
         self.files.insert(file_path, content);
     }
+
+    // Marks the file as generated, only the lines with an origin were entered
+    // by the user
+    pub fn add_line_origins(&mut self, file_path: FilePath, origins: BTreeMap<usize, LineOrigin>) {
+        self.line_origins.insert(file_path, origins);
+    }
+
+    // The file and line shown for a line, the lines of a generated file are
+    // shown at their line in the entry they came from
+    pub fn get_line_name(&self, file_path: &FilePath, line: usize) -> String {
+        match self.line_origins.get(file_path) {
+            Some(origins) => match origins.get(&line) {
+                Some(origin) => format!("{}:{}", file_path.path, origin.line + 1),
+                None => file_path.path.clone(),
+            },
+            None => format!("{}:{}", file_path.path, line + 1),
+        }
+    }
+
+    // The text of a line shown around a reported line and the number of
+    // columns removed from its start. Generated lines are only shown next to
+    // lines of the same entry, without the indentation added by the generator.
+    pub fn get_shown_line(
+        &self,
+        file_path: &FilePath,
+        line: usize,
+        reported_line: usize,
+    ) -> Option<(&str, usize)> {
+        let text = self.content(file_path).lines().nth(line)?;
+        let origins = match self.line_origins.get(file_path) {
+            Some(origins) => origins,
+            None => return Some((text, 0)),
+        };
+        match (origins.get(&line), origins.get(&reported_line)) {
+            (Some(origin), Some(reported)) if origin.entry != reported.entry => None,
+            (Some(origin), _) => Some((text.get(origin.column..).unwrap_or(""), origin.column)),
+            (None, _) if line == reported_line => Some((text, 0)),
+            (None, _) => None,
+        }
+    }
 }
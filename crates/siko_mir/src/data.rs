@@ -45,6 +45,7 @@ pub struct Adt {
     pub module: String,
     pub id: TypeDefId,
     pub variants: Vec<Variant>,
+    pub newtype: bool,
 }
 
 impl Adt {
@@ -29,5 +29,9 @@ pub enum Pattern {
     IntegerLiteral(i64),
     CharLiteral(char),
     CharRange(char, char),
+    IntegerRange(i64, i64),
+    FloatLiteral(f64),
     StringLiteral(String),
+    List(Vec<PatternId>, Option<PatternId>),
+    As(PatternId, String),
 }
@@ -112,6 +112,19 @@ fn walk_pattern(pattern_id: &PatternId, visitor: &mut dyn Visitor) {
         Pattern::StringLiteral(_) => {}
         Pattern::CharLiteral(_) => {}
         Pattern::CharRange(_, _) => {}
+        Pattern::IntegerRange(_, _) => {}
+        Pattern::FloatLiteral(_) => {}
+        Pattern::List(items, rest) => {
+            for item in items {
+                walk_pattern(item, visitor);
+            }
+            if let Some(rest) = rest {
+                walk_pattern(rest, visitor);
+            }
+        }
+        Pattern::As(id, _) => {
+            walk_pattern(id, visitor);
+        }
     }
     visitor.visit_pattern(*pattern_id, pattern);
 }
@@ -4,14 +4,16 @@ use siko_ir::function::FunctionId;
 use siko_ir::pattern::PatternId;
 use std::collections::BTreeMap;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NamedRef {
     FunctionArg(FunctionArgumentRef),
     ExprValue(ExprId, PatternId),
+    LocalFunction(FunctionId),
 }
 
 pub struct Environment<'a> {
     variables: BTreeMap<String, NamedRef>,
+    local_functions: BTreeMap<FunctionId, Vec<(NamedRef, usize)>>,
     parent: Option<&'a Environment<'a>>,
     level: usize,
 }
@@ -20,6 +22,7 @@ impl<'a> Environment<'a> {
     pub fn new() -> Environment<'a> {
         Environment {
             variables: BTreeMap::new(),
+            local_functions: BTreeMap::new(),
             parent: None,
             level: 0,
         }
@@ -37,6 +40,28 @@ impl<'a> Environment<'a> {
         );
     }
 
+    pub fn add_local_function(
+        &mut self,
+        var: String,
+        function_id: FunctionId,
+        captures: Vec<(NamedRef, usize)>,
+    ) {
+        self.variables
+            .insert(var, NamedRef::LocalFunction(function_id));
+        self.local_functions.insert(function_id, captures);
+    }
+
+    pub fn get_local_function_captures(&self, function_id: &FunctionId) -> Vec<(NamedRef, usize)> {
+        if let Some(captures) = self.local_functions.get(function_id) {
+            captures.clone()
+        } else {
+            match self.parent {
+                Some(parent) => parent.get_local_function_captures(function_id),
+                None => panic!("Local function {} not found in env", function_id),
+            }
+        }
+    }
+
     pub fn get_ref(&self, var: &str) -> Option<(NamedRef, usize)> {
         if let Some(named_ref) = self.variables.get(var) {
             return Some((named_ref.clone(), self.level));
@@ -49,9 +74,21 @@ impl<'a> Environment<'a> {
         }
     }
 
+    pub fn get_visible_refs(&self) -> BTreeMap<String, (NamedRef, usize)> {
+        let mut refs = match self.parent {
+            Some(parent) => parent.get_visible_refs(),
+            None => BTreeMap::new(),
+        };
+        for (var, named_ref) in &self.variables {
+            refs.insert(var.clone(), (named_ref.clone(), self.level));
+        }
+        refs
+    }
+
     pub fn child(parent: &'a Environment<'a>) -> Environment<'a> {
         Environment {
             variables: BTreeMap::new(),
+            local_functions: BTreeMap::new(),
             parent: Some(parent),
             level: parent.level + 1,
         }
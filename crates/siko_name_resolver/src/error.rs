@@ -10,7 +10,6 @@ pub enum ResolverError {
     UnknownTypeName(String, LocationId),
     UnknownTypeArg(String, LocationId),
     TypeArgumentConflict(Vec<String>, LocationId),
-    ArgumentConflict(Vec<String>, LocationId),
     LambdaArgumentConflict(Vec<String>, LocationId),
     UnknownFunction(String, LocationId),
     AmbiguousName(String, LocationId),
@@ -40,6 +39,7 @@ pub enum ResolverError {
     DefaultClassMemberWithoutType(String, String, LocationId),
     InstanceMemberWithoutImplementation(String, LocationId),
     ConflictingInstanceMemberFunction(String, Vec<LocationId>),
+    ConflictingLocalFunctions(String, Vec<LocationId>),
     ConflictingFunctionTypesInInstance(String, Vec<LocationId>),
     FunctionTypeWithoutImplementationInModule(String, String, LocationId),
     InvalidClassArgument(LocationId),
@@ -47,6 +47,15 @@ pub enum ResolverError {
     NamedInstancedNotUnique(String, String, LocationId),
     PatternBindConflict(String, Vec<LocationId>),
     PatternBindNotPresent(String, LocationId),
+    NotAProtocolName(String, LocationId),
+    ConflictingProtocolHandlers(String, String, LocationId),
+    FunctionClauseArgCountMismatch(String, Vec<LocationId>),
+    ExternFunctionWithMultipleClauses(String, Vec<LocationId>),
+    CyclicTypeAlias(Vec<String>, Vec<LocationId>),
+    KindMismatch(String, String, String, LocationId),
+    IncorrectClassArgumentCount(String, usize, usize, LocationId),
+    SuperclassOfMultiParamClass(String, LocationId),
+    EmptyRangePattern(String, LocationId),
 }
 
 #[derive(Debug)]
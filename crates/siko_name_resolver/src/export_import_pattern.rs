@@ -129,6 +129,18 @@ fn match_item(name: &str, group: bool, item: &Item, program: &Program) -> bool {
             class.name == name && !group
         }
         Item::ClassMember(_, _, _) => false,
+        Item::Protocol(id, _) | Item::ProtocolConstructor(id, _) => {
+            let protocol = program.protocols.get(&id);
+            protocol.name == name && !group
+        }
+        Item::Actor(id, _) | Item::ActorConstructor(id, _) => {
+            let actor = program.actors.get(&id);
+            actor.name == name && !group
+        }
+        Item::TypeAlias(id, _) => {
+            let type_alias = program.type_aliases.get(&id);
+            type_alias.name == name && !group
+        }
     }
 }
 
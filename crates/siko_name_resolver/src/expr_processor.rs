@@ -16,12 +16,15 @@ use siko_ir::expr::Case as IrCase;
 use siko_ir::expr::Expr as IrExpr;
 use siko_ir::expr::ExprId as IrExprId;
 use siko_ir::expr::FieldAccessInfo;
+use siko_ir::expr::FunctionArgumentRef;
+use siko_ir::expr::HoleInfo;
 use siko_ir::expr::RecordFieldValueExpr;
 use siko_ir::expr::RecordUpdateInfo;
 use siko_ir::function::Function as IrFunction;
 use siko_ir::function::FunctionId as IrFunctionId;
 use siko_ir::function::FunctionInfo;
 use siko_ir::function::LambdaInfo;
+use siko_ir::function::LocalFunctionInfo;
 use siko_ir::pattern::BindGroup;
 use siko_ir::pattern::Pattern as IrPattern;
 use siko_ir::pattern::PatternId as IrPatternId;
@@ -30,6 +33,9 @@ use siko_location_info::item::ItemInfo;
 use siko_location_info::location_id::LocationId;
 use siko_syntax::expr::Expr;
 use siko_syntax::expr::ExprId;
+use siko_syntax::function::Function as AstFunction;
+use siko_syntax::function::FunctionBody;
+use siko_syntax::function::FunctionId;
 use siko_syntax::pattern::Pattern;
 use siko_syntax::pattern::PatternId;
 use siko_syntax::program::Program;
@@ -39,6 +45,7 @@ use std::collections::BTreeSet;
 enum PathResolveResult {
     VariableRef(IrExprId),
     FunctionRef(IrFunctionId),
+    LocalFunctionRef(IrFunctionId, Vec<IrExprId>),
     ClassMemberRef(IrClassMemberId),
 }
 
@@ -54,6 +61,17 @@ fn resolve_item_path(
     location_id: LocationId,
 ) -> PathResolveResult {
     if let Some((named_ref, level)) = environment.get_ref(path) {
+        if let NamedRef::LocalFunction(ir_function_id) = named_ref {
+            let captures = environment.get_local_function_captures(&ir_function_id);
+            let captured_args: Vec<_> = captures
+                .into_iter()
+                .map(|(captured_ref, captured_level)| {
+                    let ir_expr = lambda_helper.process_named_ref(captured_ref, captured_level);
+                    add_expr(ir_expr, id, ir_program, program)
+                })
+                .collect();
+            return PathResolveResult::LocalFunctionRef(ir_function_id, captured_args);
+        }
         let ir_expr = lambda_helper.process_named_ref(named_ref.clone(), level);
         let ir_expr_id = add_expr(ir_expr, id, ir_program, program);
         return PathResolveResult::VariableRef(ir_expr_id);
@@ -82,10 +100,30 @@ fn resolve_item_path(
                 Item::ClassMember(_, _, ir_class_member_id) => {
                     return PathResolveResult::ClassMemberRef(ir_class_member_id);
                 }
+                Item::ProtocolConstructor(_, ir_typedef_id) => {
+                    let ir_adt = ir_program.typedefs.get(&ir_typedef_id).get_adt();
+                    return PathResolveResult::FunctionRef(ir_adt.variants[0].constructor);
+                }
+                Item::ActorConstructor(_, ir_function_id) => {
+                    return PathResolveResult::FunctionRef(ir_function_id);
+                }
                 _ => {}
             },
         }
     }
+    if path.starts_with("_") {
+        let ir_expr = process_hole(
+            path,
+            module,
+            environment,
+            lambda_helper,
+            program,
+            ir_program,
+            id,
+        );
+        let ir_expr_id = add_expr(ir_expr, id, ir_program, program);
+        return PathResolveResult::VariableRef(ir_expr_id);
+    }
     let err = ResolverError::UnknownFunction(path.to_string(), location_id);
     errors.push(err);
     let ir_expr = IrExpr::Tuple(vec![]);
@@ -93,6 +131,52 @@ fn resolve_item_path(
     return PathResolveResult::VariableRef(ir_expr_id);
 }
 
+// A typed hole remembers everything in scope so that the typechecker can
+// suggest the bindings and functions whose type fits the hole.
+fn process_hole(
+    path: &str,
+    module: &Module,
+    environment: &Environment,
+    lambda_helper: LambdaHelper,
+    program: &Program,
+    ir_program: &mut IrProgram,
+    id: ExprId,
+) -> IrExpr {
+    let mut bindings = Vec::new();
+    for (name, (named_ref, level)) in environment.get_visible_refs() {
+        if let NamedRef::LocalFunction(_) = named_ref {
+            continue;
+        }
+        let ir_expr = lambda_helper.process_named_ref(named_ref, level);
+        let ir_expr_id = add_expr(ir_expr, id, ir_program, program);
+        bindings.push((name, ir_expr_id));
+    }
+    let mut functions = Vec::new();
+    let mut class_members = Vec::new();
+    for (name, items) in &module.imported_items {
+        if name.contains('.') {
+            continue;
+        }
+        if let Some(item) = ImportedItemInfo::resolve_ambiguity(items, Namespace::Value) {
+            match item.item {
+                Item::Function(_, ir_function_id) => {
+                    functions.push((name.clone(), ir_function_id));
+                }
+                Item::ClassMember(_, _, ir_class_member_id) => {
+                    class_members.push((name.clone(), ir_class_member_id));
+                }
+                _ => {}
+            }
+        }
+    }
+    IrExpr::Hole(HoleInfo {
+        name: path.to_string(),
+        bindings: bindings,
+        functions: functions,
+        class_members: class_members,
+    })
+}
+
 fn add_expr(
     ir_expr: IrExpr,
     ast_id: ExprId,
@@ -138,6 +222,7 @@ fn process_field_access(
 
 fn resolve_pattern_type_constructor(
     name: &String,
+    program: &Program,
     module: &Module,
     errors: &mut Vec<ResolverError>,
     location_id: LocationId,
@@ -156,8 +241,8 @@ fn resolve_pattern_type_constructor(
                 Item::Record(_, ir_typedef_id) => {
                     return IrPattern::Record(ir_typedef_id, ids);
                 }
-                Item::Variant(_, _, ir_typedef_id, index) => {
-                    if irrefutable {
+                Item::Variant(adt_id, _, ir_typedef_id, index) => {
+                    if irrefutable && !program.adts.get(&adt_id).newtype {
                         let err = ResolverError::NotIrrefutablePattern(location_id);
                         errors.push(err);
                         return IrPattern::Wildcard;
@@ -165,6 +250,9 @@ fn resolve_pattern_type_constructor(
                         return IrPattern::Variant(ir_typedef_id, index, ids);
                     }
                 }
+                Item::ProtocolConstructor(_, ir_typedef_id) => {
+                    return IrPattern::Variant(ir_typedef_id, 0, ids);
+                }
                 _ => {}
             },
         }
@@ -270,7 +358,15 @@ fn process_pattern(
                     )
                 })
                 .collect();
-            resolve_pattern_type_constructor(name, module, errors, location_id, ids, irrefutable)
+            resolve_pattern_type_constructor(
+                name,
+                program,
+                module,
+                errors,
+                location_id,
+                ids,
+                irrefutable,
+            )
         }
         Pattern::Guarded(pattern_id, guard_expr_id) => {
             let ir_pattern_id = process_pattern(
@@ -429,10 +525,99 @@ fn process_pattern(
                 let err = ResolverError::NotIrrefutablePattern(location_id);
                 errors.push(err);
                 IrPattern::Wildcard
+            } else if start >= end {
+                let range = format!("{:?}..{:?}", start, end);
+                let err = ResolverError::EmptyRangePattern(range, location_id);
+                errors.push(err);
+                IrPattern::Wildcard
             } else {
                 IrPattern::CharRange(*start, *end)
             }
         }
+        Pattern::IntegerRange(start, end) => {
+            if irrefutable {
+                let err = ResolverError::NotIrrefutablePattern(location_id);
+                errors.push(err);
+                IrPattern::Wildcard
+            } else if start >= end {
+                let range = format!("{}..{}", start, end);
+                let err = ResolverError::EmptyRangePattern(range, location_id);
+                errors.push(err);
+                IrPattern::Wildcard
+            } else {
+                IrPattern::IntegerRange(*start, *end)
+            }
+        }
+        Pattern::FloatLiteral(v) => {
+            if irrefutable {
+                let err = ResolverError::NotIrrefutablePattern(location_id);
+                errors.push(err);
+                IrPattern::Wildcard
+            } else {
+                IrPattern::FloatLiteral(*v)
+            }
+        }
+        Pattern::List(patterns, rest) => {
+            let ids: Vec<_> = patterns
+                .iter()
+                .map(|id| {
+                    process_pattern(
+                        case_expr_id,
+                        *id,
+                        program,
+                        ir_program,
+                        module,
+                        environment,
+                        bindings,
+                        errors,
+                        lambda_helper.clone(),
+                        irrefutable,
+                        type_arg_resolver,
+                    )
+                })
+                .collect();
+            let rest = rest.map(|id| {
+                process_pattern(
+                    case_expr_id,
+                    id,
+                    program,
+                    ir_program,
+                    module,
+                    environment,
+                    bindings,
+                    errors,
+                    lambda_helper.clone(),
+                    irrefutable,
+                    type_arg_resolver,
+                )
+            });
+            if irrefutable {
+                let err = ResolverError::NotIrrefutablePattern(location_id);
+                errors.push(err);
+                IrPattern::Wildcard
+            } else {
+                IrPattern::List(ids, rest)
+            }
+        }
+        Pattern::As(pattern_id, name) => {
+            let locations = bindings.entry(name.clone()).or_insert_with(|| Vec::new());
+            locations.push(location_id);
+            environment.add_expr_value(name.clone(), case_expr_id, ir_pattern_id);
+            let ir_sub_pattern_id = process_pattern(
+                case_expr_id,
+                *pattern_id,
+                program,
+                ir_program,
+                module,
+                environment,
+                bindings,
+                errors,
+                lambda_helper.clone(),
+                irrefutable,
+                type_arg_resolver,
+            );
+            IrPattern::As(ir_sub_pattern_id, name.clone())
+        }
     };
     let ir_pattern_info = ItemInfo {
         item: ir_pattern,
@@ -507,11 +692,13 @@ pub fn process_expr(
             let captures = local_lambda_helper.captures();
 
             let arg_locations: Vec<_> = args.iter().map(|arg| arg.1).collect();
+            let arg_names: Vec<_> = args.iter().map(|arg| Some(arg.0.clone())).collect();
 
             let ir_function = IrFunction {
                 id: ir_lambda_id,
                 arg_count: arg_locations.len() + captures.len(),
                 arg_locations: arg_locations,
+                arg_names: arg_names,
                 info: FunctionInfo::Lambda(lambda_info),
             };
             ir_program.functions.add_item(ir_lambda_id, ir_function);
@@ -523,6 +710,19 @@ pub fn process_expr(
             let ir_expr = IrExpr::StaticFunctionCall(ir_lambda_id, captured_lambda_args);
             return add_expr(ir_expr, id, ir_program, program);
         }
+        Expr::Let(local_functions, body) => {
+            return process_let(
+                local_functions,
+                *body,
+                program,
+                module,
+                environment,
+                ir_program,
+                errors,
+                lambda_helper,
+                type_arg_resolver,
+            );
+        }
         Expr::FunctionCall(id_expr_id, args) => {
             let ir_args: Vec<IrExprId> = args
                 .iter()
@@ -556,6 +756,11 @@ pub fn process_expr(
                         let ir_expr = IrExpr::StaticFunctionCall(n, ir_args);
                         return add_expr(ir_expr, id, ir_program, program);
                     }
+                    PathResolveResult::LocalFunctionRef(n, mut captured_args) => {
+                        captured_args.extend(ir_args);
+                        let ir_expr = IrExpr::StaticFunctionCall(n, captured_args);
+                        return add_expr(ir_expr, id, ir_program, program);
+                    }
                     PathResolveResult::VariableRef(ir_id_expr_id) => {
                         let ir_expr = IrExpr::DynamicFunctionCall(ir_id_expr_id, ir_args);
                         return add_expr(ir_expr, id, ir_program, program);
@@ -709,6 +914,10 @@ pub fn process_expr(
                     let ir_expr = IrExpr::StaticFunctionCall(n, vec![]);
                     return add_expr(ir_expr, id, ir_program, program);
                 }
+                PathResolveResult::LocalFunctionRef(n, captured_args) => {
+                    let ir_expr = IrExpr::StaticFunctionCall(n, captured_args);
+                    return add_expr(ir_expr, id, ir_program, program);
+                }
                 PathResolveResult::VariableRef(ir_expr_id) => {
                     return ir_expr_id;
                 }
@@ -1052,6 +1261,10 @@ pub fn process_expr(
                     let ir_expr = IrExpr::StaticFunctionCall(n, vec![]);
                     add_expr(ir_expr, id, ir_program, program)
                 }
+                PathResolveResult::LocalFunctionRef(n, captured_args) => {
+                    let ir_expr = IrExpr::StaticFunctionCall(n, captured_args);
+                    add_expr(ir_expr, id, ir_program, program)
+                }
                 PathResolveResult::VariableRef(ir_expr_id) => ir_expr_id,
                 PathResolveResult::ClassMemberRef(n) => {
                     let ir_expr = IrExpr::ClassFunctionCall(n, vec![]);
@@ -1127,3 +1340,546 @@ pub fn process_expr(
         }
     }
 }
+
+fn add_expr_with_location(
+    ir_expr: IrExpr,
+    location_id: LocationId,
+    ir_program: &mut IrProgram,
+) -> IrExprId {
+    let expr_id = ir_program.exprs.get_id();
+    let expr_info = ItemInfo::new(ir_expr, location_id);
+    ir_program.exprs.add_item(expr_id, expr_info);
+    expr_id
+}
+
+fn add_pattern_with_location(
+    ir_pattern: IrPattern,
+    location_id: LocationId,
+    ir_program: &mut IrProgram,
+) -> IrPatternId {
+    let pattern_id = ir_program.patterns.get_id();
+    let pattern_info = ItemInfo::new(ir_pattern, location_id);
+    ir_program.patterns.add_item(pattern_id, pattern_info);
+    pattern_id
+}
+
+fn is_irrefutable_pattern(pattern_id: PatternId, program: &Program, module: &Module) -> bool {
+    match &program.patterns.get(&pattern_id).item {
+        Pattern::Binding(_) | Pattern::Wildcard => true,
+        Pattern::Tuple(items) => items
+            .iter()
+            .all(|item| is_irrefutable_pattern(*item, program, module)),
+        Pattern::Typed(inner, _) | Pattern::As(inner, _) => {
+            is_irrefutable_pattern(*inner, program, module)
+        }
+        Pattern::Record(_, fields) => fields
+            .iter()
+            .all(|field| is_irrefutable_pattern(field.value, program, module)),
+        Pattern::Constructor(name, items) => {
+            let is_record = match module.imported_items.get(name) {
+                Some(imported_items) => {
+                    match ImportedItemInfo::resolve_ambiguity(imported_items, Namespace::Value) {
+                        Some(item) => match item.item {
+                            Item::Record(..) | Item::ProtocolConstructor(..) => true,
+                            Item::Variant(adt_id, ..) => program.adts.get(&adt_id).newtype,
+                            _ => false,
+                        },
+                        None => false,
+                    }
+                }
+                None => false,
+            };
+            is_record
+                && items
+                    .iter()
+                    .all(|item| is_irrefutable_pattern(*item, program, module))
+        }
+        _ => false,
+    }
+}
+
+// The name of each argument, if every clause binds it to the same name
+pub fn get_arg_names(clauses: &[&AstFunction], program: &Program) -> Vec<Option<String>> {
+    fn get_name(pattern_id: PatternId, program: &Program) -> Option<&String> {
+        match &program.patterns.get(&pattern_id).item {
+            Pattern::Binding(name) | Pattern::As(_, name) => Some(name),
+            Pattern::Typed(inner, _) => get_name(*inner, program),
+            _ => None,
+        }
+    }
+    (0..clauses[0].args.len())
+        .map(|index| {
+            let name = get_name(clauses[0].args[index], program)?;
+            if clauses
+                .iter()
+                .all(|clause| get_name(clause.args[index], program) == Some(name))
+            {
+                Some(name.clone())
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn process_function_arg_bind(
+    function: &AstFunction,
+    body: ExprId,
+    ir_function_id: IrFunctionId,
+    program: &Program,
+    module: &Module,
+    environment: &mut Environment,
+    ir_program: &mut IrProgram,
+    errors: &mut Vec<ResolverError>,
+    lambda_helper: LambdaHelper,
+    type_arg_resolver: &mut TypeArgResolver,
+) -> IrExprId {
+    let location_id = function.location_id;
+    let arg_exprs: Vec<_> = (0..function.args.len())
+        .map(|index| {
+            let arg_ref = FunctionArgumentRef::new(false, ir_function_id, index);
+            add_expr_with_location(IrExpr::ArgRef(arg_ref), location_id, ir_program)
+        })
+        .collect();
+    let tuple_expr_id = add_expr_with_location(IrExpr::Tuple(arg_exprs), location_id, ir_program);
+    let mut bindings = BTreeMap::new();
+    let pattern_ids: Vec<_> = function
+        .args
+        .iter()
+        .map(|arg| {
+            process_pattern(
+                tuple_expr_id,
+                *arg,
+                program,
+                ir_program,
+                module,
+                environment,
+                &mut bindings,
+                errors,
+                lambda_helper.clone(),
+                true,
+                type_arg_resolver,
+            )
+        })
+        .collect();
+    for binding in bindings {
+        if binding.1.len() > 1 {
+            let err = ResolverError::PatternBindConflict(binding.0.clone(), binding.1.clone());
+            errors.push(err);
+        }
+    }
+    let tuple_pattern_id =
+        add_pattern_with_location(IrPattern::Tuple(pattern_ids), location_id, ir_program);
+    let bind_expr_id = add_expr_with_location(
+        IrExpr::Bind(tuple_pattern_id, tuple_expr_id),
+        location_id,
+        ir_program,
+    );
+    let ir_body_id = process_expr(
+        body,
+        program,
+        module,
+        environment,
+        ir_program,
+        errors,
+        lambda_helper,
+        type_arg_resolver,
+    );
+    add_expr_with_location(
+        IrExpr::Do(vec![bind_expr_id, ir_body_id]),
+        location_id,
+        ir_program,
+    )
+}
+
+pub fn process_function_body(
+    clauses: &[&AstFunction],
+    ir_function_id: IrFunctionId,
+    program: &Program,
+    module: &Module,
+    environment: &mut Environment,
+    ir_program: &mut IrProgram,
+    errors: &mut Vec<ResolverError>,
+    lambda_helper: LambdaHelper,
+    type_arg_resolver: &mut TypeArgResolver,
+) -> IrExprId {
+    let function = clauses[0];
+    if clauses.len() == 1 && function.guard.is_none() {
+        if let FunctionBody::Expr(body) = function.body {
+            if function.args.is_empty() {
+                return process_expr(
+                    body,
+                    program,
+                    module,
+                    environment,
+                    ir_program,
+                    errors,
+                    lambda_helper,
+                    type_arg_resolver,
+                );
+            }
+            if function
+                .args
+                .iter()
+                .all(|arg| is_irrefutable_pattern(*arg, program, module))
+            {
+                return process_function_arg_bind(
+                    function,
+                    body,
+                    ir_function_id,
+                    program,
+                    module,
+                    environment,
+                    ir_program,
+                    errors,
+                    lambda_helper,
+                    type_arg_resolver,
+                );
+            }
+        }
+    }
+    process_function_clauses(
+        clauses,
+        ir_function_id,
+        program,
+        module,
+        environment,
+        ir_program,
+        errors,
+        lambda_helper,
+        type_arg_resolver,
+    )
+}
+
+fn process_function_clauses(
+    clauses: &[&AstFunction],
+    ir_function_id: IrFunctionId,
+    program: &Program,
+    module: &Module,
+    environment: &mut Environment,
+    ir_program: &mut IrProgram,
+    errors: &mut Vec<ResolverError>,
+    lambda_helper: LambdaHelper,
+    type_arg_resolver: &mut TypeArgResolver,
+) -> IrExprId {
+    let location_id = clauses[0].location_id;
+    let arg_count = clauses[0].args.len();
+    let mut arg_exprs: Vec<_> = (0..arg_count)
+        .map(|index| {
+            let arg_ref = FunctionArgumentRef::new(false, ir_function_id, index);
+            add_expr_with_location(IrExpr::ArgRef(arg_ref), location_id, ir_program)
+        })
+        .collect();
+    let ir_body_id = if arg_count == 1 {
+        arg_exprs.remove(0)
+    } else {
+        add_expr_with_location(IrExpr::Tuple(arg_exprs), location_id, ir_program)
+    };
+    let mut ir_cases = Vec::new();
+    for clause in clauses {
+        let mut case_environment = Environment::child(environment);
+        let mut bindings = BTreeMap::new();
+        let mut pattern_ids: Vec<_> = clause
+            .args
+            .iter()
+            .map(|arg| {
+                process_pattern(
+                    ir_body_id,
+                    *arg,
+                    program,
+                    ir_program,
+                    module,
+                    &mut case_environment,
+                    &mut bindings,
+                    errors,
+                    lambda_helper.clone(),
+                    false,
+                    type_arg_resolver,
+                )
+            })
+            .collect();
+        let mut pattern_id = if arg_count == 1 {
+            pattern_ids.remove(0)
+        } else {
+            add_pattern_with_location(
+                IrPattern::Tuple(pattern_ids),
+                clause.location_id,
+                ir_program,
+            )
+        };
+        for binding in bindings {
+            if binding.1.len() > 1 {
+                let err = ResolverError::PatternBindConflict(binding.0.clone(), binding.1.clone());
+                errors.push(err);
+            }
+        }
+        if let Some(guard) = clause.guard {
+            let ir_guard_expr_id = process_expr(
+                guard,
+                program,
+                module,
+                &mut case_environment,
+                ir_program,
+                errors,
+                lambda_helper.clone(),
+                type_arg_resolver,
+            );
+            pattern_id = add_pattern_with_location(
+                IrPattern::Guarded(pattern_id, ir_guard_expr_id),
+                clause.location_id,
+                ir_program,
+            );
+        }
+        let body = match clause.body {
+            FunctionBody::Expr(body) => body,
+            FunctionBody::Extern => unreachable!(),
+        };
+        let ir_case_body_id = process_expr(
+            body,
+            program,
+            module,
+            &mut case_environment,
+            ir_program,
+            errors,
+            lambda_helper.clone(),
+            type_arg_resolver,
+        );
+        let ir_case = IrCase {
+            pattern_id: pattern_id,
+            body: ir_case_body_id,
+        };
+        ir_cases.push(ir_case);
+    }
+    let ir_expr = IrExpr::CaseOf(ir_body_id, ir_cases, Vec::new());
+    add_expr_with_location(ir_expr, location_id, ir_program)
+}
+
+fn collect_pattern_names(pattern_id: PatternId, program: &Program, names: &mut BTreeSet<String>) {
+    match &program.patterns.get(&pattern_id).item {
+        Pattern::Tuple(items) | Pattern::Constructor(_, items) | Pattern::Or(items) => {
+            for item in items {
+                collect_pattern_names(*item, program, names);
+            }
+        }
+        Pattern::Guarded(inner, guard) => {
+            collect_pattern_names(*inner, program, names);
+            collect_expr_names(*guard, program, names);
+        }
+        Pattern::Typed(inner, _) | Pattern::As(inner, _) => {
+            collect_pattern_names(*inner, program, names)
+        }
+        Pattern::List(items, rest) => {
+            for item in items.iter().chain(rest.iter()) {
+                collect_pattern_names(*item, program, names);
+            }
+        }
+        Pattern::Record(_, fields) => {
+            for field in fields {
+                collect_pattern_names(field.value, program, names);
+            }
+        }
+        Pattern::Binding(_)
+        | Pattern::Wildcard
+        | Pattern::IntegerLiteral(_)
+        | Pattern::StringLiteral(_)
+        | Pattern::CharLiteral(_)
+        | Pattern::CharRange(_, _)
+        | Pattern::IntegerRange(_, _)
+        | Pattern::FloatLiteral(_) => {}
+    }
+}
+
+fn collect_function_names(function: &AstFunction, program: &Program, names: &mut BTreeSet<String>) {
+    for arg in &function.args {
+        collect_pattern_names(*arg, program, names);
+    }
+    if let Some(guard) = function.guard {
+        collect_expr_names(guard, program, names);
+    }
+    if let FunctionBody::Expr(body) = function.body {
+        collect_expr_names(body, program, names);
+    }
+}
+
+// Collects every name a local function may refer to, this is an overestimation of its captures
+fn collect_expr_names(expr_id: ExprId, program: &Program, names: &mut BTreeSet<String>) {
+    match &program.exprs.get(&expr_id).item {
+        Expr::Path(name) => {
+            names.insert(name.clone());
+        }
+        Expr::Lambda(_, body) | Expr::FieldAccess(_, body) | Expr::TupleFieldAccess(_, body) => {
+            collect_expr_names(*body, program, names);
+        }
+        Expr::FunctionCall(function, args) => {
+            collect_expr_names(*function, program, names);
+            for arg in args {
+                collect_expr_names(*arg, program, names);
+            }
+        }
+        Expr::If(cond, true_branch, false_branch) => {
+            collect_expr_names(*cond, program, names);
+            collect_expr_names(*true_branch, program, names);
+            collect_expr_names(*false_branch, program, names);
+        }
+        Expr::Tuple(items) | Expr::List(items) | Expr::Do(items) | Expr::Formatter(_, items) => {
+            for item in items {
+                collect_expr_names(*item, program, names);
+            }
+        }
+        Expr::Bind(pattern, body) => {
+            collect_pattern_names(*pattern, program, names);
+            collect_expr_names(*body, program, names);
+        }
+        Expr::CaseOf(body, cases) => {
+            collect_expr_names(*body, program, names);
+            for case in cases {
+                collect_pattern_names(case.pattern_id, program, names);
+                collect_expr_names(case.body, program, names);
+            }
+        }
+        Expr::RecordInitialization(_, items) => {
+            for item in items {
+                collect_expr_names(item.body, program, names);
+            }
+        }
+        Expr::RecordUpdate(name, items) => {
+            names.insert(name.clone());
+            for item in items {
+                collect_expr_names(item.body, program, names);
+            }
+        }
+        Expr::Let(local_functions, body) => {
+            for function_id in local_functions {
+                let function = program.functions.get(function_id);
+                collect_function_names(function, program, names);
+            }
+            collect_expr_names(*body, program, names);
+        }
+        Expr::Builtin(_)
+        | Expr::IntegerLiteral(_)
+        | Expr::FloatLiteral(_)
+        | Expr::StringLiteral(_)
+        | Expr::CharLiteral(_) => {}
+    }
+}
+
+fn process_let(
+    local_functions: &[FunctionId],
+    body: ExprId,
+    program: &Program,
+    module: &Module,
+    environment: &mut Environment,
+    ir_program: &mut IrProgram,
+    errors: &mut Vec<ResolverError>,
+    lambda_helper: LambdaHelper,
+    type_arg_resolver: &mut TypeArgResolver,
+) -> IrExprId {
+    let mut groups: Vec<(String, Vec<&AstFunction>)> = Vec::new();
+    let mut names = BTreeSet::new();
+    for function_id in local_functions {
+        let function = program.functions.get(function_id);
+        collect_function_names(function, program, &mut names);
+        // only the clauses following each other belong to the same function
+        match groups.last_mut() {
+            Some((name, clauses)) if *name == function.name => clauses.push(function),
+            _ => {
+                if let Some((_, clauses)) = groups.iter().find(|(name, _)| *name == function.name) {
+                    let locations = vec![clauses[0].location_id, function.location_id];
+                    let err =
+                        ResolverError::ConflictingLocalFunctions(function.name.clone(), locations);
+                    errors.push(err);
+                    continue;
+                }
+                groups.push((function.name.clone(), vec![function]));
+            }
+        }
+    }
+    // every function of the let captures the same variables, so they can call each other freely
+    let mut captures = Vec::new();
+    for name in &names {
+        let refs = match environment.get_ref(name) {
+            Some((NamedRef::LocalFunction(function_id), _)) => {
+                environment.get_local_function_captures(&function_id)
+            }
+            Some(named_ref) => vec![named_ref],
+            None => Vec::new(),
+        };
+        for named_ref in refs {
+            if !captures.contains(&named_ref) {
+                captures.push(named_ref);
+            }
+        }
+    }
+    let mut let_environment = Environment::child(environment);
+    let mut ir_function_ids = Vec::new();
+    for (name, _) in &groups {
+        let ir_function_id = ir_program.functions.get_id();
+        let_environment.add_local_function(name.clone(), ir_function_id, captures.clone());
+        ir_function_ids.push(ir_function_id);
+    }
+    let host_info = lambda_helper.host_function_name();
+    for ((name, clauses), ir_function_id) in groups.iter().zip(ir_function_ids) {
+        let function = clauses[0];
+        let body_id = if clauses.iter().any(|c| c.args.len() != function.args.len()) {
+            let clause_locations: Vec<_> = clauses.iter().map(|c| c.location_id).collect();
+            let err = ResolverError::FunctionClauseArgCountMismatch(name.clone(), clause_locations);
+            errors.push(err);
+            add_expr_with_location(IrExpr::Tuple(vec![]), function.location_id, ir_program)
+        } else {
+            let mut function_environment = Environment::child(&let_environment);
+            let local_lambda_helper = LambdaHelper::new_local_function(
+                function_environment.level(),
+                format!("{}/{}", host_info, name),
+                lambda_helper.clone_counter(),
+                ir_function_id,
+                captures.clone(),
+            );
+            process_function_body(
+                clauses,
+                ir_function_id,
+                program,
+                module,
+                &mut function_environment,
+                ir_program,
+                errors,
+                local_lambda_helper,
+                type_arg_resolver,
+            )
+        };
+        let local_function_info = LocalFunctionInfo {
+            body: body_id,
+            module: module.name.clone(),
+            name: name.clone(),
+            host_info: host_info.clone(),
+            location_id: function.location_id,
+        };
+        let arg_locations: Vec<_> = function
+            .args
+            .iter()
+            .map(|arg| program.patterns.get(arg).location_id)
+            .collect();
+        let arg_names = if clauses.iter().any(|c| c.args.len() != function.args.len()) {
+            vec![None; function.args.len()]
+        } else {
+            get_arg_names(clauses, program)
+        };
+        let ir_function = IrFunction {
+            id: ir_function_id,
+            arg_count: captures.len() + arg_locations.len(),
+            arg_locations: arg_locations,
+            arg_names: arg_names,
+            info: FunctionInfo::LocalFunction(local_function_info),
+        };
+        ir_program.functions.add_item(ir_function_id, ir_function);
+    }
+    process_expr(
+        body,
+        program,
+        module,
+        &mut let_environment,
+        ir_program,
+        errors,
+        lambda_helper,
+        type_arg_resolver,
+    )
+}
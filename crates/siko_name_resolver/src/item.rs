@@ -1,11 +1,15 @@
 use siko_ir::class::ClassId as IrClassId;
 use siko_ir::class::ClassMemberId as IrClassMemberId;
+use siko_ir::data::TypeAliasId as IrTypeAliasId;
 use siko_ir::data::TypeDefId;
 use siko_ir::function::FunctionId as IrFunctionId;
+use siko_syntax::actor::ActorId;
+use siko_syntax::actor::ProtocolId;
 use siko_syntax::class::ClassId;
 use siko_syntax::data::AdtId;
 use siko_syntax::data::RecordFieldId;
 use siko_syntax::data::RecordId;
+use siko_syntax::data::TypeAliasId;
 use siko_syntax::data::VariantId;
 use siko_syntax::function::FunctionId;
 use siko_syntax::function::FunctionTypeId;
@@ -18,6 +22,11 @@ pub enum Item {
     Variant(AdtId, VariantId, TypeDefId, usize),
     Class(ClassId, IrClassId),
     ClassMember(ClassId, FunctionTypeId, IrClassMemberId),
+    Protocol(ProtocolId, TypeDefId),
+    ProtocolConstructor(ProtocolId, TypeDefId),
+    Actor(ActorId, TypeDefId),
+    ActorConstructor(ActorId, IrFunctionId),
+    TypeAlias(TypeAliasId, IrTypeAliasId),
 }
 
 impl Item {
@@ -29,6 +38,11 @@ impl Item {
             Item::Variant(..) => false,
             Item::Class(..) => true,
             Item::ClassMember(..) => false,
+            Item::Protocol(..) => true,
+            Item::ProtocolConstructor(..) => false,
+            Item::Actor(..) => true,
+            Item::ActorConstructor(..) => false,
+            Item::TypeAlias(..) => true,
         }
     }
 
@@ -15,16 +15,28 @@ struct LambdaHelperInner {
     function_id: FunctionId,
     host_function_id: FunctionId,
     parent: Option<LambdaHelper>,
+    fixed_captures: Option<Vec<(NamedRef, usize)>>,
 }
 
 impl LambdaHelperInner {
-    fn process_named_ref(&mut self, r: NamedRef, level: usize) -> Expr {
+    fn process_named_ref(&mut self, named_ref: NamedRef, level: usize) -> Expr {
+        if let Some(fixed_captures) = &self.fixed_captures {
+            if level < self.level {
+                let arg_index = fixed_captures
+                    .iter()
+                    .position(|c| *c == (named_ref, level))
+                    .expect("Captured variable not found in local function captures");
+                let arg_ref = FunctionArgumentRef::new(true, self.function_id, arg_index);
+                return Expr::ArgRef(arg_ref);
+            }
+        }
         let r = if let Some(parent) = &self.parent {
-            parent.process_named_ref(r, level)
+            parent.process_named_ref(named_ref, level)
         } else {
-            match r {
+            match named_ref {
                 NamedRef::ExprValue(expr_ref, pattern_id) => Expr::ExprValue(expr_ref, pattern_id),
                 NamedRef::FunctionArg(arg_ref) => Expr::ArgRef(arg_ref),
+                NamedRef::LocalFunction(_) => unreachable!(),
             }
         };
         if level < self.level {
@@ -88,6 +100,29 @@ impl LambdaHelper {
             function_id: function_id,
             host_function_id: host_function_id,
             parent: parent,
+            fixed_captures: None,
+        };
+        LambdaHelper {
+            inner: Rc::new(RefCell::new(inner)),
+        }
+    }
+
+    pub fn new_local_function(
+        level: usize,
+        host_function_name: String,
+        counter: Rc<RefCell<Counter>>,
+        function_id: FunctionId,
+        captures: Vec<(NamedRef, usize)>,
+    ) -> LambdaHelper {
+        let inner = LambdaHelperInner {
+            captures: Vec::new(),
+            level: level,
+            host_function_name: host_function_name,
+            counter: counter,
+            function_id: function_id,
+            host_function_id: function_id,
+            parent: None,
+            fixed_captures: Some(captures),
         };
         LambdaHelper {
             inner: Rc::new(RefCell::new(inner)),
@@ -2,7 +2,8 @@ use crate::environment::Environment;
 use crate::error::Error;
 use crate::error::ResolverError;
 use crate::export_processor::process_exports;
-use crate::expr_processor::process_expr;
+use crate::expr_processor::get_arg_names;
+use crate::expr_processor::process_function_body;
 use crate::import::ImportedItemInfo;
 use crate::import::Namespace;
 use crate::import_processor::process_imports;
@@ -14,13 +15,21 @@ use crate::lambda_arg_shifter::LambdaArgShifter;
 use crate::lambda_helper::LambdaHelper;
 use crate::module::Module;
 use crate::type_arg_resolver::TypeArgResolver;
+use crate::type_processor::check_type_arg_kinds;
+use crate::type_processor::collect_type_arg_kinds;
 use crate::type_processor::collect_type_args;
+use crate::type_processor::collect_type_names;
 use crate::type_processor::process_class_type_signature;
+use crate::type_processor::process_instance_type_signature;
 use crate::type_processor::process_type_signature;
 use crate::type_processor::subtitute_type_signature;
 use siko_constants::get_auto_derivable_classes;
 use siko_constants::STD_OPS_MODULE_NAME;
+use siko_ir::actor::Actor as IrActor;
+use siko_ir::actor::ActorHandler as IrActorHandler;
+use siko_ir::actor::Protocol as IrProtocol;
 use siko_ir::class::Class as IrClass;
+use siko_ir::class::ClassConstraint as IrClassConstraint;
 use siko_ir::class::ClassId as IrClassId;
 use siko_ir::class::ClassMember as IrClassMember;
 use siko_ir::class::Instance as IrInstance;
@@ -29,28 +38,39 @@ use siko_ir::data::Adt;
 use siko_ir::data::DerivedClass as IrDerivedClass;
 use siko_ir::data::Record;
 use siko_ir::data::RecordField as IrRecordField;
+use siko_ir::data::TypeAlias as IrTypeAlias;
+use siko_ir::data::TypeAliasId as IrTypeAliasId;
 use siko_ir::data::TypeDef;
 use siko_ir::data::TypeDefId;
 use siko_ir::data::Variant as IrVariant;
 use siko_ir::data::VariantItem;
+use siko_ir::function::ActorConstructorInfo;
 use siko_ir::function::Function as IrFunction;
 use siko_ir::function::FunctionId as IrFunctionId;
 use siko_ir::function::FunctionInfo;
+use siko_ir::function::LambdaInfo;
+use siko_ir::function::LocalFunctionInfo;
 use siko_ir::function::NamedFunctionInfo;
 use siko_ir::function::NamedFunctionKind;
 use siko_ir::function::RecordConstructorInfo;
 use siko_ir::function::VariantConstructorInfo;
+use siko_ir::kind::Kind;
 use siko_ir::program::Program as IrProgram;
 use siko_ir::type_signature::TypeSignature;
 use siko_ir::type_signature::TypeSignatureId;
 use siko_ir::type_var_generator::TypeVarGenerator;
 use siko_ir::walker::walk_expr;
+use siko_location_info::item::ItemInfo;
 use siko_location_info::location_id::LocationId;
+use siko_syntax::actor::ActorId as AstActorId;
+use siko_syntax::actor::ProtocolId as AstProtocolId;
 use siko_syntax::class::ClassId as AstClassId;
+use siko_syntax::class::Constraint as AstConstraint;
 use siko_syntax::class::Instance as AstInstance;
 use siko_syntax::data::AdtId;
 use siko_syntax::data::DerivedClass as AstDerivedClass;
 use siko_syntax::data::RecordId;
+use siko_syntax::data::TypeAliasId as AstTypeAliasId;
 use siko_syntax::function::Function as AstFunction;
 use siko_syntax::function::FunctionBody as AstFunctionBody;
 use siko_syntax::function::FunctionId as AstFunctionId;
@@ -58,17 +78,21 @@ use siko_syntax::function::FunctionType as AstFunctionType;
 use siko_syntax::function::FunctionTypeId as AstFunctionTypeId;
 use siko_syntax::module::Module as AstModule;
 use siko_syntax::program::Program;
+use siko_syntax::types::TypeSignature as AstTypeSignature;
+use siko_syntax::types::TypeSignatureId as AstTypeSignatureId;
+use siko_util::dependency_processor::DependencyCollector;
+use siko_util::dependency_processor::DependencyProcessor;
 use siko_util::RcCounter;
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 
 fn check_function_and_function_type_consistency(
-    functions: &BTreeMap<String, Vec<AstFunctionId>>,
+    functions: &BTreeMap<String, Vec<Vec<AstFunctionId>>>,
     function_types: &BTreeMap<String, Vec<AstFunctionTypeId>>,
 ) -> (
     Vec<AstFunctionId>,
     Vec<AstFunctionTypeId>,
-    BTreeMap<String, Vec<AstFunctionId>>,
+    BTreeMap<String, Vec<Vec<AstFunctionId>>>,
     BTreeMap<String, Vec<AstFunctionTypeId>>,
 ) {
     let function_name_set: BTreeSet<_> = functions.keys().collect();
@@ -76,16 +100,16 @@ fn check_function_and_function_type_consistency(
     let mut functions_without_types: Vec<AstFunctionId> = Vec::new();
     let mut function_types_without_functions: Vec<AstFunctionTypeId> = Vec::new();
     for n in function_name_set.difference(&function_type_name_set) {
-        let ids = functions.get(*n).unwrap();
-        functions_without_types.extend(ids);
+        let definitions = functions.get(*n).unwrap();
+        functions_without_types.push(definitions[0][0]);
     }
     for n in function_type_name_set.difference(&function_name_set) {
         let ids = function_types.get(*n).unwrap();
         function_types_without_functions.extend(ids);
     }
     let mut conflicting_functions = functions.clone();
-    for (name, ids) in functions {
-        if ids.len() == 1 {
+    for (name, definitions) in functions {
+        if definitions.len() == 1 {
             conflicting_functions.remove(name);
         }
     }
@@ -103,6 +127,34 @@ fn check_function_and_function_type_consistency(
     )
 }
 
+struct TypeAliasDependencyCollector<'a> {
+    program: &'a Program,
+    type_aliases: &'a BTreeMap<IrTypeAliasId, (AstTypeAliasId, &'a Module)>,
+}
+
+impl<'a> DependencyCollector<IrTypeAliasId> for TypeAliasDependencyCollector<'a> {
+    fn collect(&self, ir_type_alias_id: IrTypeAliasId) -> Vec<IrTypeAliasId> {
+        let (type_alias_id, module) = self
+            .type_aliases
+            .get(&ir_type_alias_id)
+            .expect("Type alias not found");
+        let type_alias = self.program.type_aliases.get(type_alias_id);
+        let mut names = BTreeSet::new();
+        collect_type_names(&type_alias.type_signature_id, self.program, &mut names);
+        let mut deps = Vec::new();
+        for name in names {
+            if let Some(items) = module.imported_items.get(&name) {
+                if let Some(item) = ImportedItemInfo::resolve_ambiguity(items, Namespace::Type) {
+                    if let Item::TypeAlias(_, dep) = item.item {
+                        deps.push(dep);
+                    }
+                }
+            }
+        }
+        deps
+    }
+}
+
 #[derive(Debug)]
 pub struct Resolver {
     modules: BTreeMap<String, Module>,
@@ -185,10 +237,16 @@ impl Resolver {
                     .iter()
                     .map(|field_id| program.record_fields.get(field_id).location_id)
                     .collect();
+                let arg_names: Vec<_> = record
+                    .fields
+                    .iter()
+                    .map(|field_id| Some(program.record_fields.get(field_id).name.clone()))
+                    .collect();
                 let ir_ctor_function = IrFunction {
                     id: ir_ctor_id,
                     arg_count: arg_locations.len(),
                     arg_locations: arg_locations,
+                    arg_names: arg_names,
                     info: FunctionInfo::RecordConstructor(record_ctor_info),
                 };
                 ir_program.functions.add_item(ir_ctor_id, ir_ctor_function);
@@ -198,11 +256,13 @@ impl Resolver {
                     module: ast_module.name.clone(),
                     id: ir_typedef_id,
                     type_args: (0..record.type_args.len()).collect(),
+                    type_arg_kinds: vec![Kind::star(); record.type_args.len()],
                     fields: Vec::new(),
                     constructor: ir_ctor_id,
                     location_id: record.location_id,
                     external: record.external,
                     derived_classes: Vec::new(),
+                    doc: record.doc.clone(),
                 };
 
                 let typedef = TypeDef::Record(ir_record);
@@ -229,8 +289,11 @@ impl Resolver {
                     module: ast_module.name.clone(),
                     id: ir_typedef_id,
                     type_args: (0..adt.type_args.len()).collect(),
+                    type_arg_kinds: vec![Kind::star(); adt.type_args.len()],
                     variants: Vec::new(),
                     derived_classes: Vec::new(),
+                    doc: adt.doc.clone(),
+                    newtype: adt.newtype,
                 };
                 let typedef = TypeDef::Adt(ir_adt);
                 ir_program.typedefs.add_item(ir_typedef_id, typedef);
@@ -248,9 +311,83 @@ impl Resolver {
                     module.add_member(ast_variant.name.clone(), member);
                 }
             }
-            for (_, function_ids) in &ast_module.functions {
-                for function_id in function_ids {
-                    let function = program.functions.get(function_id);
+            for type_alias_id in &ast_module.type_aliases {
+                let type_alias = program.type_aliases.get(type_alias_id);
+                let ir_type_alias_id = ir_program.type_aliases.get_id();
+                let ir_type_alias = IrTypeAlias {
+                    name: type_alias.name.clone(),
+                    module: ast_module.name.clone(),
+                    id: ir_type_alias_id,
+                    type_args: Vec::new(),
+                    type_signature: None,
+                    location_id: type_alias.location_id,
+                    doc: type_alias.doc.clone(),
+                };
+                ir_program
+                    .type_aliases
+                    .add_item(ir_type_alias_id, ir_type_alias);
+                let item = Item::TypeAlias(*type_alias_id, ir_type_alias_id);
+                module.add_item(type_alias.name.clone(), item);
+            }
+            for protocol_id in &ast_module.protocols {
+                let protocol = program.protocols.get(protocol_id);
+                let ir_typedef_id = ir_program.typedefs.get_id();
+                let ir_adt = Adt {
+                    name: protocol.name.clone(),
+                    module: ast_module.name.clone(),
+                    id: ir_typedef_id,
+                    type_args: Vec::new(),
+                    type_arg_kinds: Vec::new(),
+                    variants: Vec::new(),
+                    derived_classes: Vec::new(),
+                    doc: None,
+                    newtype: false,
+                };
+                let typedef = TypeDef::Adt(ir_adt);
+                ir_program.typedefs.add_item(ir_typedef_id, typedef);
+                let item = Item::Protocol(*protocol_id, ir_typedef_id);
+                module.add_item(protocol.name.clone(), item);
+                let item = Item::ProtocolConstructor(*protocol_id, ir_typedef_id);
+                module.add_item(protocol.name.clone(), item);
+            }
+            for actor_id in &ast_module.actors {
+                let actor = program.actors.get(actor_id);
+                let ir_typedef_id = ir_program.typedefs.get_id();
+                let ir_actor_id = ir_program.actors.get_id();
+                let ir_ctor_id = ir_program.functions.get_id();
+                let ir_adt = Adt {
+                    name: actor.name.clone(),
+                    module: ast_module.name.clone(),
+                    id: ir_typedef_id,
+                    type_args: Vec::new(),
+                    type_arg_kinds: Vec::new(),
+                    variants: Vec::new(),
+                    derived_classes: Vec::new(),
+                    doc: None,
+                    newtype: false,
+                };
+                let typedef = TypeDef::Adt(ir_adt);
+                ir_program.typedefs.add_item(ir_typedef_id, typedef);
+                let actor_ctor_info = ActorConstructorInfo {
+                    actor_id: ir_actor_id,
+                    type_id: ir_typedef_id,
+                };
+                let ir_ctor_function = IrFunction {
+                    id: ir_ctor_id,
+                    arg_count: 1,
+                    arg_locations: vec![actor.location_id],
+                    arg_names: vec![None],
+                    info: FunctionInfo::ActorConstructor(actor_ctor_info),
+                };
+                ir_program.functions.add_item(ir_ctor_id, ir_ctor_function);
+                let item = Item::Actor(*actor_id, ir_typedef_id);
+                module.add_item(actor.name.clone(), item);
+                let item = Item::ActorConstructor(*actor_id, ir_ctor_id);
+                module.add_item(actor.name.clone(), item);
+            }
+            for (_, definitions) in &ast_module.functions {
+                for function_ids in definitions {
+                    let function = program.functions.get(&function_ids[0]);
                     let ir_function_id = ir_program.functions.get_id();
                     let item = Item::Function(function.id, ir_function_id);
                     module.add_item(function.name.clone(), item);
@@ -275,6 +412,32 @@ impl Resolver {
                         members.insert(class_member.name.clone(), ir_class_member_id);
                     }
                 }
+                let mut kinds = BTreeMap::new();
+                for (_, member_function_type_ids) in &class.member_function_types {
+                    for member_function_type_id in member_function_type_ids {
+                        let class_member = program.function_types.get(member_function_type_id);
+                        collect_type_arg_kinds(
+                            &class_member.type_signature_id,
+                            program,
+                            module,
+                            ir_program,
+                            &mut kinds,
+                        );
+                    }
+                }
+                // the kind of the class argument is inferred from its uses in the
+                // member signatures, mismatching uses are reported with the class
+                let arg_kinds = class
+                    .args
+                    .iter()
+                    .map(|arg| match &program.type_signatures.get(arg).item {
+                        AstTypeSignature::TypeArg(name) => kinds
+                            .get(name)
+                            .map(|uses| uses[0].0)
+                            .unwrap_or_else(|| Kind::star()),
+                        _ => Kind::star(),
+                    })
+                    .collect();
                 let auto_derivable_classes: Vec<_> = get_auto_derivable_classes();
                 let auto_derivable = module.name == STD_OPS_MODULE_NAME
                     && auto_derivable_classes.contains(&class.name.as_ref());
@@ -283,10 +446,12 @@ impl Resolver {
                     name: class.name.clone(),
                     module: module.name.clone(),
                     type_signature: None,
+                    arg_kinds: arg_kinds,
                     constraints: Vec::new(),
                     members: members,
                     location_id: class.location_id,
                     auto_derivable: auto_derivable,
+                    doc: class.doc.clone(),
                 };
                 ir_program.classes.add_item(ir_class_id, ir_class);
             }
@@ -298,6 +463,10 @@ impl Resolver {
                     let mut locations = Vec::new();
                     let mut adt_found = false;
                     let mut variant_found = false;
+                    let mut protocol_found = false;
+                    let mut protocol_ctor_found = false;
+                    let mut actor_found = false;
+                    let mut actor_ctor_found = false;
                     for item in items {
                         match item {
                             Item::Function(id, _) => {
@@ -326,11 +495,41 @@ impl Resolver {
                                 let class_member = program.function_types.get(id);
                                 locations.push(class_member.location_id);
                             }
+                            Item::Protocol(id, _) => {
+                                let protocol = program.protocols.get(id);
+                                locations.push(protocol.location_id);
+                                protocol_found = true;
+                            }
+                            Item::ProtocolConstructor(id, _) => {
+                                let protocol = program.protocols.get(id);
+                                locations.push(protocol.location_id);
+                                protocol_ctor_found = true;
+                            }
+                            Item::Actor(id, _) => {
+                                let actor = program.actors.get(id);
+                                locations.push(actor.location_id);
+                                actor_found = true;
+                            }
+                            Item::ActorConstructor(id, _) => {
+                                let actor = program.actors.get(id);
+                                locations.push(actor.location_id);
+                                actor_ctor_found = true;
+                            }
+                            Item::TypeAlias(id, _) => {
+                                let type_alias = program.type_aliases.get(id);
+                                locations.push(type_alias.location_id);
+                            }
                         }
                     }
                     if items.len() == 2 && adt_found && variant_found {
                         continue;
                     }
+                    if items.len() == 2 && protocol_found && protocol_ctor_found {
+                        continue;
+                    }
+                    if items.len() == 2 && actor_found && actor_ctor_found {
+                        continue;
+                    }
                     let err = ResolverError::InternalModuleConflicts(
                         module.name.clone(),
                         name.clone(),
@@ -372,6 +571,84 @@ impl Resolver {
         }
     }
 
+    fn process_type_alias(
+        &self,
+        program: &Program,
+        ir_program: &mut IrProgram,
+        type_alias_id: &AstTypeAliasId,
+        ir_type_alias_id: IrTypeAliasId,
+        module: &Module,
+        errors: &mut Vec<ResolverError>,
+    ) {
+        let type_alias = program.type_aliases.get(type_alias_id);
+        let mut type_arg_resolver = TypeArgResolver::new(self.type_var_generator.clone());
+        let mut type_args = Vec::new();
+        for (type_arg, location_id) in type_alias.type_args.iter() {
+            let index = type_arg_resolver.add_explicit(type_arg.clone(), Vec::new(), *location_id);
+            type_args.push(index);
+        }
+        let result = process_type_signature(
+            &type_alias.type_signature_id,
+            program,
+            ir_program,
+            module,
+            &mut type_arg_resolver,
+            errors,
+        );
+        let unused_args = type_arg_resolver.collect_unused_args();
+        for (unused_arg, location_id) in unused_args {
+            let err = ResolverError::UnusedTypeArgument(unused_arg, location_id);
+            errors.push(err);
+        }
+        let ir_type_alias = ir_program.type_aliases.get_mut(&ir_type_alias_id);
+        ir_type_alias.type_args = type_args;
+        ir_type_alias.type_signature = result;
+    }
+
+    // Aliases are expanded at their use sites so they are resolved in dependency order,
+    // an alias referring to itself directly or indirectly is an error
+    fn process_type_aliases(
+        &self,
+        program: &Program,
+        ir_program: &mut IrProgram,
+        errors: &mut Vec<ResolverError>,
+    ) {
+        let mut type_aliases = BTreeMap::new();
+        for (_, module) in &self.modules {
+            for (_, items) in &module.items {
+                for item in items {
+                    if let Item::TypeAlias(type_alias_id, ir_type_alias_id) = item {
+                        type_aliases.insert(*ir_type_alias_id, (*type_alias_id, module));
+                    }
+                }
+            }
+        }
+        let collector = TypeAliasDependencyCollector {
+            program: program,
+            type_aliases: &type_aliases,
+        };
+        let dep_processor = DependencyProcessor::new(type_aliases.keys().cloned().collect());
+        let groups = dep_processor.process_items(&collector);
+        for group in groups {
+            let first = group.items.iter().next().expect("Empty dependency group");
+            if group.items.len() > 1 || collector.collect(*first).contains(first) {
+                let mut names = Vec::new();
+                let mut locations = Vec::new();
+                for ir_type_alias_id in &group.items {
+                    let (type_alias_id, _) = type_aliases[ir_type_alias_id];
+                    let type_alias = program.type_aliases.get(&type_alias_id);
+                    names.push(type_alias.name.clone());
+                    locations.push(type_alias.location_id);
+                }
+                let err = ResolverError::CyclicTypeAlias(names, locations);
+                errors.push(err);
+                continue;
+            }
+            let (type_alias_id, module) = type_aliases[first];
+            self.process_type_alias(program, ir_program, &type_alias_id, *first, module, errors);
+        }
+    }
+
     fn process_function_type(
         &self,
         function_type: &AstFunctionType,
@@ -386,6 +663,16 @@ impl Resolver {
             type_arg_resolver.add_explicit(type_arg.clone(), Vec::new(), *location_id);
         }
 
+        let mut kinds = BTreeMap::new();
+        collect_type_arg_kinds(
+            &function_type.type_signature_id,
+            program,
+            module,
+            ir_program,
+            &mut kinds,
+        );
+        let kinds = check_type_arg_kinds(&kinds, errors);
+
         for constraint in &function_type.constraints {
             if let Some(ir_class_id) = self.lookup_class(
                 &constraint.class_name,
@@ -393,7 +680,14 @@ impl Resolver {
                 module,
                 errors,
             ) {
-                type_arg_resolver.add_constraint(&constraint.arg, ir_class_id);
+                self.add_constraint(
+                    &kinds,
+                    constraint,
+                    ir_class_id,
+                    &mut type_arg_resolver,
+                    ir_program,
+                    errors,
+                );
             }
         }
 
@@ -420,33 +714,39 @@ impl Resolver {
         &self,
         program: &Program,
         ir_program: &mut IrProgram,
-        function: &AstFunction,
+        clauses: &[&AstFunction],
         ir_function_id: IrFunctionId,
         module: &Module,
         errors: &mut Vec<ResolverError>,
         type_signature_id: Option<TypeSignatureId>,
         type_arg_resolver: &mut TypeArgResolver,
         kind: NamedFunctionKind,
+        doc: Option<String>,
     ) {
+        let function = clauses[0];
         let mut body = None;
 
-        if let AstFunctionBody::Expr(id) = function.body {
+        let clause_locations: Vec<_> = clauses.iter().map(|c| c.location_id).collect();
+        let is_extern = clauses.iter().any(|c| match c.body {
+            AstFunctionBody::Extern => true,
+            AstFunctionBody::Expr(_) => false,
+        });
+        let arg_count_mismatch = clauses.iter().any(|c| c.args.len() != function.args.len());
+
+        if is_extern && clauses.len() > 1 {
+            let err = ResolverError::ExternFunctionWithMultipleClauses(
+                function.name.clone(),
+                clause_locations,
+            );
+            errors.push(err);
+        } else if arg_count_mismatch {
+            let err = ResolverError::FunctionClauseArgCountMismatch(
+                function.name.clone(),
+                clause_locations,
+            );
+            errors.push(err);
+        } else if let AstFunctionBody::Expr(_) = function.body {
             let mut environment = Environment::new();
-            let mut arg_names = BTreeSet::new();
-            let mut conflicting_names = BTreeSet::new();
-            for (index, arg) in function.args.iter().enumerate() {
-                if !arg_names.insert(arg.0.clone()) {
-                    conflicting_names.insert(arg.0.clone());
-                }
-                environment.add_arg(arg.0.clone(), ir_function_id, index);
-            }
-            if !conflicting_names.is_empty() {
-                let err = ResolverError::ArgumentConflict(
-                    conflicting_names.into_iter().collect(),
-                    function.location_id.clone(),
-                );
-                errors.push(err);
-            }
             let host_function = format!("{}/{}", module.name, function.name);
             let lambda_helper = LambdaHelper::new(
                 0,
@@ -456,8 +756,9 @@ impl Resolver {
                 ir_function_id,
                 None,
             );
-            let body_id = process_expr(
-                id,
+            let body_id = process_function_body(
+                clauses,
+                ir_function_id,
                 program,
                 module,
                 &mut environment,
@@ -469,6 +770,9 @@ impl Resolver {
             body = Some(body_id);
         }
 
+        let constraints =
+            self.process_class_constraints(type_arg_resolver, ir_program, function.location_id);
+
         let named_info = NamedFunctionInfo {
             body: body,
             name: function.name.clone(),
@@ -476,19 +780,130 @@ impl Resolver {
             type_signature: type_signature_id,
             location_id: function.location_id,
             kind: kind,
+            constraints: constraints,
+            doc: doc,
         };
 
-        let arg_locations: Vec<_> = function.args.iter().map(|arg| arg.1).collect();
+        let arg_locations: Vec<_> = function
+            .args
+            .iter()
+            .map(|arg| program.patterns.get(arg).location_id)
+            .collect();
+
+        let arg_names = if arg_count_mismatch {
+            vec![None; function.args.len()]
+        } else {
+            get_arg_names(clauses, program)
+        };
 
         let ir_function = IrFunction {
             id: ir_function_id,
             arg_count: arg_locations.len(),
             arg_locations: arg_locations,
+            arg_names: arg_names,
             info: FunctionInfo::NamedFunction(named_info),
         };
         ir_program.functions.add_item(ir_function_id, ir_function);
     }
 
+    // Infers the kind of the type arguments of every data type from their uses
+    // in the data definitions, `data Wrap f a = Wrap (f a)` gives f the kind
+    // `* -> *`. Giving a type argument to another data type takes the kind of
+    // that data type's argument, so the inference runs until nothing changes.
+    fn infer_data_type_arg_kinds(&self, program: &Program, ir_program: &mut IrProgram) {
+        let mut data_types = Vec::new();
+        for (_, module) in &self.modules {
+            for (_, items) in &module.items {
+                for item in items {
+                    match item {
+                        Item::Adt(ast_adt_id, ir_typedef_id) => {
+                            let adt = program.adts.get(ast_adt_id);
+                            let type_signature_ids: Vec<_> = adt
+                                .variants
+                                .iter()
+                                .map(|id| program.variants.get(id).type_signature_id)
+                                .collect();
+                            data_types.push((
+                                module,
+                                &adt.type_args,
+                                type_signature_ids,
+                                *ir_typedef_id,
+                            ));
+                        }
+                        Item::Record(ast_record_id, ir_typedef_id) => {
+                            let record = program.records.get(ast_record_id);
+                            let type_signature_ids: Vec<_> = record
+                                .fields
+                                .iter()
+                                .map(|id| program.record_fields.get(id).type_signature_id)
+                                .collect();
+                            data_types.push((
+                                module,
+                                &record.type_args,
+                                type_signature_ids,
+                                *ir_typedef_id,
+                            ));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        // kinds only grow between rounds, the bound stops the inference for
+        // ill-kinded definitions whose kinds would grow forever
+        for _ in 0..=data_types.len() {
+            let mut changed = false;
+            for (module, type_args, type_signature_ids, ir_typedef_id) in &data_types {
+                let mut kinds = BTreeMap::new();
+                for type_signature_id in type_signature_ids {
+                    collect_type_arg_kinds(
+                        type_signature_id,
+                        program,
+                        module,
+                        ir_program,
+                        &mut kinds,
+                    );
+                }
+                let arg_kinds: Vec<_> = type_args
+                    .iter()
+                    .map(|(name, _)| {
+                        kinds
+                            .get(name)
+                            .and_then(|uses| uses.iter().map(|(kind, _)| *kind).max())
+                            .unwrap_or_else(|| Kind::star())
+                    })
+                    .collect();
+                let type_arg_kinds = match ir_program.typedefs.get_mut(ir_typedef_id) {
+                    TypeDef::Adt(adt) => &mut adt.type_arg_kinds,
+                    TypeDef::Record(record) => &mut record.type_arg_kinds,
+                };
+                if *type_arg_kinds != arg_kinds {
+                    *type_arg_kinds = arg_kinds;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    // every type argument of a data type is used with the same kind
+    fn check_data_type_arg_kinds(
+        &self,
+        type_signature_ids: &Vec<AstTypeSignatureId>,
+        program: &Program,
+        module: &Module,
+        ir_program: &IrProgram,
+        errors: &mut Vec<ResolverError>,
+    ) {
+        let mut kinds = BTreeMap::new();
+        for type_signature_id in type_signature_ids {
+            collect_type_arg_kinds(type_signature_id, program, module, ir_program, &mut kinds);
+        }
+        check_type_arg_kinds(&kinds, errors);
+    }
+
     fn process_adt(
         &self,
         program: &Program,
@@ -505,6 +920,8 @@ impl Resolver {
             type_signature_ids.push(variant.type_signature_id);
         }
 
+        self.check_data_type_arg_kinds(&type_signature_ids, program, module, ir_program, errors);
+
         let mut type_arg_resolver = TypeArgResolver::new(self.type_var_generator.clone());
         {
             let ir_adt = ir_program.typedefs.get_mut(&ir_typedef_id).get_mut_adt();
@@ -570,6 +987,7 @@ impl Resolver {
                     let ir_ctor_function = IrFunction {
                         id: ir_ctor_id,
                         arg_count: arg_locations.len(),
+                        arg_names: vec![None; arg_locations.len()],
                         arg_locations: arg_locations,
                         info: FunctionInfo::VariantConstructor(variant_ctor_info),
                     };
@@ -580,6 +998,7 @@ impl Resolver {
                         items: items,
                         type_signature_id: ir_typesignature_id,
                         constructor: ir_ctor_id,
+                        doc: program.variants.get(&adt.variants[index]).doc.clone(),
                     };
 
                     ir_variants.push(ir_variant);
@@ -611,6 +1030,8 @@ impl Resolver {
             type_signature_ids.push(field.type_signature_id);
         }
 
+        self.check_data_type_arg_kinds(&type_signature_ids, program, module, ir_program, errors);
+
         let mut type_arg_resolver = TypeArgResolver::new(self.type_var_generator.clone());
         {
             let ir_record = ir_program.typedefs.get_mut(&ir_typedef_id).get_mut_record();
@@ -652,6 +1073,7 @@ impl Resolver {
                 let ir_field = IrRecordField {
                     name: field.name.clone(),
                     type_signature_id: ir_typesignature_id,
+                    doc: field.doc.clone(),
                 };
                 ir_fields.push(ir_field);
             }
@@ -663,6 +1085,204 @@ impl Resolver {
         }
     }
 
+    fn process_protocol(
+        &self,
+        program: &Program,
+        ir_program: &mut IrProgram,
+        protocol_id: &AstProtocolId,
+        ir_typedef_id: TypeDefId,
+        module: &Module,
+        errors: &mut Vec<ResolverError>,
+    ) {
+        let protocol = program.protocols.get(protocol_id);
+        let mut type_arg_resolver = TypeArgResolver::new(self.type_var_generator.clone());
+
+        let result = process_type_signature(
+            &protocol.type_signature,
+            program,
+            ir_program,
+            module,
+            &mut type_arg_resolver,
+            errors,
+        );
+
+        if let Some(ir_typesignature_id) = result {
+            let item_location_id = ir_program
+                .type_signatures
+                .get(&ir_typesignature_id)
+                .location_id;
+            let variant_signature =
+                TypeSignature::Variant(protocol.name.clone(), vec![ir_typesignature_id]);
+            let variant_signature_id = ir_program.type_signatures.get_id();
+            ir_program.type_signatures.add_item(
+                variant_signature_id,
+                ItemInfo::new(variant_signature, protocol.location_id),
+            );
+            let ir_ctor_id = ir_program.functions.get_id();
+            let variant_ctor_info = VariantConstructorInfo {
+                type_id: ir_typedef_id,
+                index: 0,
+            };
+            let ir_ctor_function = IrFunction {
+                id: ir_ctor_id,
+                arg_count: 1,
+                arg_locations: vec![item_location_id],
+                arg_names: vec![None],
+                info: FunctionInfo::VariantConstructor(variant_ctor_info),
+            };
+            ir_program.functions.add_item(ir_ctor_id, ir_ctor_function);
+            let ir_variant = IrVariant {
+                name: protocol.name.clone(),
+                items: vec![VariantItem {
+                    type_signature_id: ir_typesignature_id,
+                }],
+                type_signature_id: variant_signature_id,
+                constructor: ir_ctor_id,
+                doc: None,
+            };
+            let ir_adt = ir_program.typedefs.get_mut(&ir_typedef_id).get_mut_adt();
+            ir_adt.variants = vec![ir_variant];
+            let ir_protocol_id = ir_program.protocols.get_id();
+            let ir_protocol = IrProtocol {
+                id: ir_protocol_id,
+                name: protocol.name.clone(),
+                module: module.name.clone(),
+                typedef_id: ir_typedef_id,
+                type_signature: ir_typesignature_id,
+                location_id: protocol.location_id,
+            };
+            ir_program.protocols.add_item(ir_protocol_id, ir_protocol);
+        }
+    }
+
+    fn process_actor(
+        &self,
+        program: &Program,
+        ir_program: &mut IrProgram,
+        actor_id: &AstActorId,
+        ir_ctor_id: IrFunctionId,
+        module: &Module,
+        errors: &mut Vec<ResolverError>,
+    ) {
+        let actor = program.actors.get(actor_id);
+        let actor_ctor_info = match &ir_program.functions.get(&ir_ctor_id).info {
+            FunctionInfo::ActorConstructor(info) => info.clone(),
+            _ => unreachable!(),
+        };
+        let mut type_arg_resolver = TypeArgResolver::new(self.type_var_generator.clone());
+
+        let result = process_type_signature(
+            &actor.type_signature,
+            program,
+            ir_program,
+            module,
+            &mut type_arg_resolver,
+            errors,
+        );
+
+        let mut handlers = Vec::new();
+        let mut handled_protocols = BTreeSet::new();
+        for handler in &actor.handlers {
+            let protocol_id = match module.imported_items.get(&handler.protocol) {
+                Some(items) => match ImportedItemInfo::resolve_ambiguity(items, Namespace::Type) {
+                    None => {
+                        let err = ResolverError::AmbiguousName(
+                            handler.protocol.clone(),
+                            handler.protocol_location_id,
+                        );
+                        errors.push(err);
+                        None
+                    }
+                    Some(item) => match item.item {
+                        Item::Protocol(_, ir_typedef_id) => ir_program
+                            .get_protocol_by_typedef(ir_typedef_id)
+                            .map(|protocol| protocol.id),
+                        _ => {
+                            let err = ResolverError::NotAProtocolName(
+                                handler.protocol.clone(),
+                                handler.protocol_location_id,
+                            );
+                            errors.push(err);
+                            None
+                        }
+                    },
+                },
+                None => {
+                    let err = ResolverError::NotAProtocolName(
+                        handler.protocol.clone(),
+                        handler.protocol_location_id,
+                    );
+                    errors.push(err);
+                    None
+                }
+            };
+            let function_id = match module.imported_items.get(&handler.handler_func) {
+                Some(items) => match ImportedItemInfo::resolve_ambiguity(items, Namespace::Value) {
+                    None => {
+                        let err = ResolverError::AmbiguousName(
+                            handler.handler_func.clone(),
+                            handler.handler_func_location_id,
+                        );
+                        errors.push(err);
+                        None
+                    }
+                    Some(item) => match item.item {
+                        Item::Function(_, ir_function_id) => Some(ir_function_id),
+                        _ => {
+                            let err = ResolverError::UnknownFunction(
+                                handler.handler_func.clone(),
+                                handler.handler_func_location_id,
+                            );
+                            errors.push(err);
+                            None
+                        }
+                    },
+                },
+                None => {
+                    let err = ResolverError::UnknownFunction(
+                        handler.handler_func.clone(),
+                        handler.handler_func_location_id,
+                    );
+                    errors.push(err);
+                    None
+                }
+            };
+            if let (Some(protocol_id), Some(function_id)) = (protocol_id, function_id) {
+                if !handled_protocols.insert(protocol_id) {
+                    let err = ResolverError::ConflictingProtocolHandlers(
+                        actor.name.clone(),
+                        handler.protocol.clone(),
+                        handler.protocol_location_id,
+                    );
+                    errors.push(err);
+                    continue;
+                }
+                let ir_handler = IrActorHandler {
+                    protocol_id: protocol_id,
+                    function_id: function_id,
+                    location_id: handler.protocol_location_id,
+                };
+                handlers.push(ir_handler);
+            }
+        }
+
+        if let Some(state_type_signature) = result {
+            let ir_actor = IrActor {
+                id: actor_ctor_info.actor_id,
+                name: actor.name.clone(),
+                module: module.name.clone(),
+                typedef_id: actor_ctor_info.type_id,
+                state_type_signature: state_type_signature,
+                constructor: ir_ctor_id,
+                handlers: handlers,
+                location_id: actor.location_id,
+            };
+            ir_program
+                .actors
+                .add_item(actor_ctor_info.actor_id, ir_actor);
+        }
+    }
+
     fn process_derived_classes(
         &self,
         derived_classes: &Vec<AstDerivedClass>,
@@ -718,6 +1338,96 @@ impl Resolver {
         None
     }
 
+    fn check_constraint_kind(
+        &self,
+        kinds: &BTreeMap<String, Kind>,
+        constraint: &AstConstraint,
+        ir_class_id: IrClassId,
+        ir_program: &IrProgram,
+        errors: &mut Vec<ResolverError>,
+    ) {
+        let class_kinds = &ir_program.classes.get(&ir_class_id).arg_kinds;
+        for (arg, class_kind) in constraint.args.iter().zip(class_kinds.iter()) {
+            let arg_kind = kinds.get(arg).cloned().unwrap_or_else(|| Kind::star());
+            if *class_kind != arg_kind {
+                let err = ResolverError::KindMismatch(
+                    arg.clone(),
+                    format!("{}", class_kind),
+                    format!("{}", arg_kind),
+                    constraint.location_id,
+                );
+                errors.push(err);
+            }
+        }
+    }
+
+    // Returns the first argument of the constraint that is not a known type
+    // argument, the caller decides whether that is an error.
+    fn add_constraint(
+        &self,
+        kinds: &BTreeMap<String, Kind>,
+        constraint: &AstConstraint,
+        ir_class_id: IrClassId,
+        type_arg_resolver: &mut TypeArgResolver,
+        ir_program: &IrProgram,
+        errors: &mut Vec<ResolverError>,
+    ) -> Option<String> {
+        let class = ir_program.classes.get(&ir_class_id);
+        if class.arg_kinds.len() != constraint.args.len() {
+            let err = ResolverError::IncorrectClassArgumentCount(
+                class.name.clone(),
+                class.arg_kinds.len(),
+                constraint.args.len(),
+                constraint.location_id,
+            );
+            errors.push(err);
+            return None;
+        }
+        self.check_constraint_kind(kinds, constraint, ir_class_id, ir_program, errors);
+        if let Some(arg) = constraint
+            .args
+            .iter()
+            .find(|arg| !type_arg_resolver.contains(arg))
+        {
+            return Some(arg.clone());
+        }
+        if class.is_multi_param() {
+            type_arg_resolver.add_class_constraint(&constraint.args, ir_class_id);
+        } else {
+            type_arg_resolver.add_constraint(&constraint.args[0], ir_class_id);
+        }
+        None
+    }
+
+    fn process_class_constraints(
+        &self,
+        type_arg_resolver: &TypeArgResolver,
+        ir_program: &mut IrProgram,
+        location_id: LocationId,
+    ) -> Vec<IrClassConstraint> {
+        let mut constraints = Vec::new();
+        for (class_id, args) in type_arg_resolver.get_class_constraints() {
+            let mut items = Vec::new();
+            for (name, info) in args {
+                let item = TypeSignature::TypeArgument(info.index, name, info.constraints);
+                let id = ir_program.type_signatures.get_id();
+                ir_program
+                    .type_signatures
+                    .add_item(id, ItemInfo::new(item, location_id));
+                items.push(id);
+            }
+            let id = ir_program.type_signatures.get_id();
+            ir_program
+                .type_signatures
+                .add_item(id, ItemInfo::new(TypeSignature::Tuple(items), location_id));
+            constraints.push(IrClassConstraint {
+                class_id: class_id,
+                type_signature: id,
+            });
+        }
+        constraints
+    }
+
     fn process_class(
         &self,
         program: &Program,
@@ -731,9 +1441,9 @@ impl Resolver {
 
         let mut type_arg_resolver = TypeArgResolver::new(self.type_var_generator.clone());
 
-        let (class_type_signature_id, class_arg) = if let Some(class_type_signature_id) =
+        let (class_type_signature_id, class_args) = if let Some(class_type_signature_id) =
             process_class_type_signature(
-                &class.arg,
+                &class.args,
                 program,
                 ir_program,
                 &mut type_arg_resolver,
@@ -745,22 +1455,67 @@ impl Resolver {
             return;
         };
 
+        let mut class_arg_kinds = BTreeMap::new();
+        for (_, function_type_ids) in &class.member_function_types {
+            for function_type_id in function_type_ids {
+                let class_member = program.function_types.get(function_type_id);
+                let mut kinds = BTreeMap::new();
+                collect_type_arg_kinds(
+                    &class_member.type_signature_id,
+                    program,
+                    module,
+                    ir_program,
+                    &mut kinds,
+                );
+                // uses of the class arguments are checked across all members
+                for class_arg in &class_args {
+                    if let Some(mut uses) = kinds.remove(class_arg) {
+                        let class_arg_uses = class_arg_kinds
+                            .entry(class_arg.clone())
+                            .or_insert_with(|| Vec::new());
+                        class_arg_uses.append(&mut uses);
+                    }
+                }
+                check_type_arg_kinds(&kinds, errors);
+            }
+        }
+        let class_kinds = check_type_arg_kinds(&class_arg_kinds, errors);
+
         let mut ir_constraints = Vec::new();
 
-        for constraint in &class.constraints {
-            if let Some(ir_class_id) = self.lookup_class(
-                &constraint.class_name,
-                constraint.location_id,
-                module,
-                errors,
-            ) {
-                ir_constraints.push(ir_class_id);
-                if !type_arg_resolver.add_constraint(&constraint.arg, ir_class_id) {
-                    let err = ResolverError::InvalidArgumentInTypeClassConstraint(
-                        constraint.arg.clone(),
-                        constraint.location_id,
-                    );
-                    errors.push(err);
+        if class_args.len() > 1 {
+            if let Some(constraint) = class.constraints.first() {
+                let err = ResolverError::SuperclassOfMultiParamClass(
+                    class.name.clone(),
+                    constraint.location_id,
+                );
+                errors.push(err);
+            }
+            // default members may rely on the constraint of the class itself
+            type_arg_resolver.add_class_constraint(&class_args, *ir_class_id);
+        } else {
+            for constraint in &class.constraints {
+                if let Some(ir_class_id) = self.lookup_class(
+                    &constraint.class_name,
+                    constraint.location_id,
+                    module,
+                    errors,
+                ) {
+                    ir_constraints.push(ir_class_id);
+                    if let Some(arg) = self.add_constraint(
+                        &class_kinds,
+                        constraint,
+                        ir_class_id,
+                        &mut type_arg_resolver,
+                        ir_program,
+                        errors,
+                    ) {
+                        let err = ResolverError::InvalidArgumentInTypeClassConstraint(
+                            arg,
+                            constraint.location_id,
+                        );
+                        errors.push(err);
+                    }
                 }
             }
         }
@@ -785,10 +1540,10 @@ impl Resolver {
             errors.push(err);
         }
 
-        for (name, ids) in conflicting_functions {
-            let locations: Vec<_> = ids
+        for (name, definitions) in conflicting_functions {
+            let locations: Vec<_> = definitions
                 .iter()
-                .map(|id| program.functions.get(id).location_id)
+                .map(|ids| program.functions.get(&ids[0]).location_id)
                 .collect();
             let err = ResolverError::ConflictingDefaultClassMember(
                 class.name.clone(),
@@ -806,7 +1561,10 @@ impl Resolver {
             let ir_class_member_id = *ir_class.members.get(name).expect("Class member not found");
             let signature_type_args: BTreeSet<_> =
                 class_member.type_args.iter().map(|i| i.0.clone()).collect();
-            if !signature_type_args.contains(&class_arg) {
+            if let Some(class_arg) = class_args
+                .iter()
+                .find(|class_arg| !signature_type_args.contains(*class_arg))
+            {
                 let err = ResolverError::ClassMemberTypeArgMissing(
                     class_member.name.clone(),
                     class_arg.clone(),
@@ -819,12 +1577,25 @@ impl Resolver {
             let mut type_arg_resolver = type_arg_resolver.clone();
 
             for (type_arg, location) in &class_member.type_args {
-                if *type_arg == class_arg {
+                if class_args.contains(type_arg) {
                     continue;
                 }
                 type_arg_resolver.add_explicit(type_arg.clone(), vec![], *location);
             }
 
+            let mut kinds = BTreeMap::new();
+            collect_type_arg_kinds(
+                &class_member.type_signature_id,
+                program,
+                module,
+                ir_program,
+                &mut kinds,
+            );
+            let kinds: BTreeMap<_, _> = kinds
+                .into_iter()
+                .map(|(name, uses)| (name, uses[0].0))
+                .collect();
+
             for constraint in &class_member.constraints {
                 if let Some(ir_class_id) = self.lookup_class(
                     &constraint.class_name,
@@ -832,7 +1603,14 @@ impl Resolver {
                     module,
                     errors,
                 ) {
-                    type_arg_resolver.add_constraint(&constraint.arg, ir_class_id);
+                    self.add_constraint(
+                        &kinds,
+                        constraint,
+                        ir_class_id,
+                        &mut type_arg_resolver,
+                        ir_program,
+                        errors,
+                    );
                 }
             }
 
@@ -847,21 +1625,23 @@ impl Resolver {
 
             if errors.is_empty() {
                 let default_implementation =
-                    if let Some(impls) = class.member_functions.get(&class_member.name) {
-                        assert_eq!(impls.len(), 1);
-                        let impl_id = impls[0];
+                    if let Some(definitions) = class.member_functions.get(&class_member.name) {
                         let ir_function_id = ir_program.functions.get_id();
-                        let function = &program.functions.get(&impl_id);
+                        let clauses: Vec<_> = definitions[0]
+                            .iter()
+                            .map(|id| program.functions.get(id))
+                            .collect();
                         self.process_function(
                             program,
                             ir_program,
-                            &function,
+                            &clauses,
                             ir_function_id,
                             module,
                             errors,
                             result,
                             &mut type_arg_resolver,
                             NamedFunctionKind::DefaultClassMember(ir_class_member_id),
+                            None,
                         );
                         Some(ir_function_id)
                     } else {
@@ -875,6 +1655,7 @@ impl Resolver {
                     type_signature: result.expect("Type signature not found"),
                     default_implementation: default_implementation,
                     location_id: class_member.location_id,
+                    doc: class_member.doc.clone(),
                 };
                 ir_program
                     .class_members
@@ -893,13 +1674,33 @@ impl Resolver {
             .type_signatures
             .get(&ir_class_member.class_type_signature)
             .item;
-        match item {
+        match item.clone() {
             TypeSignature::TypeArgument(index, _, _) => subtitute_type_signature(
                 &ir_class_member.type_signature,
-                *index,
+                index,
                 &instance_type_signature,
                 ir_program,
             ),
+            TypeSignature::Tuple(class_args) => {
+                let instance_args = match &ir_program
+                    .type_signatures
+                    .get(&instance_type_signature)
+                    .item
+                {
+                    TypeSignature::Tuple(instance_args) => instance_args.clone(),
+                    _ => panic!("Invalid instance type signature"),
+                };
+                let mut type_signature = ir_class_member.type_signature;
+                for (class_arg, instance_arg) in class_args.iter().zip(instance_args.iter()) {
+                    let index = match &ir_program.type_signatures.get(class_arg).item {
+                        TypeSignature::TypeArgument(index, _, _) => *index,
+                        _ => panic!("Invalid class type signature"),
+                    };
+                    type_signature =
+                        subtitute_type_signature(&type_signature, index, instance_arg, ir_program);
+                }
+                type_signature
+            }
             _ => panic!("Invalid class type signature"),
         }
     }
@@ -912,16 +1713,47 @@ impl Resolver {
         module: &Module,
         errors: &mut Vec<ResolverError>,
     ) {
+        let ir_class_id =
+            match self.lookup_class(&instance.class_name, instance.location_id, module, errors) {
+                Some(ir_class_id) => ir_class_id,
+                None => {
+                    return;
+                }
+            };
+        let ir_class = ir_program.classes.get(&ir_class_id).clone();
+
+        let head_ids = match instance.type_signature_id {
+            Some(type_signature_id) if !ir_class.is_multi_param() => vec![type_signature_id],
+            _ => instance.args.clone(),
+        };
+
+        if head_ids.len() != ir_class.arg_kinds.len() {
+            let err = ResolverError::IncorrectClassArgumentCount(
+                ir_class.name.clone(),
+                ir_class.arg_kinds.len(),
+                head_ids.len(),
+                instance.location_id,
+            );
+            errors.push(err);
+            return;
+        }
+
         let mut type_arg_resolver = TypeArgResolver::new(self.type_var_generator.clone());
 
         let mut type_args = BTreeMap::new();
+        let mut kinds = BTreeMap::new();
 
-        collect_type_args(&instance.type_signature_id, program, &mut type_args);
+        for head_id in &head_ids {
+            collect_type_args(head_id, program, &mut type_args);
+            collect_type_arg_kinds(head_id, program, module, ir_program, &mut kinds);
+        }
 
         for (type_arg, location_id) in type_args {
             type_arg_resolver.add_explicit(type_arg, Vec::new(), location_id);
         }
 
+        let kinds = check_type_arg_kinds(&kinds, errors);
+
         for constraint in &instance.constraints {
             if let Some(ir_class_id) = self.lookup_class(
                 &constraint.class_name,
@@ -929,9 +1761,16 @@ impl Resolver {
                 module,
                 errors,
             ) {
-                if !type_arg_resolver.add_constraint(&constraint.arg, ir_class_id) {
+                if let Some(arg) = self.add_constraint(
+                    &kinds,
+                    constraint,
+                    ir_class_id,
+                    &mut type_arg_resolver,
+                    ir_program,
+                    errors,
+                ) {
                     let err = ResolverError::InvalidTypeArgInInstanceConstraint(
-                        constraint.arg.clone(),
+                        arg,
                         constraint.location_id,
                     );
                     errors.push(err);
@@ -939,22 +1778,43 @@ impl Resolver {
             }
         }
 
-        let ir_class_id =
-            match self.lookup_class(&instance.class_name, instance.location_id, module, errors) {
-                Some(ir_class_id) => ir_class_id,
-                None => {
-                    return;
+        let mut ids = Vec::new();
+        for (head_id, class_kind) in head_ids.iter().zip(ir_class.arg_kinds.iter()) {
+            let result = process_instance_type_signature(
+                head_id,
+                program,
+                ir_program,
+                module,
+                &mut type_arg_resolver,
+                errors,
+            );
+            if let Some((id, kind)) = result {
+                if kind != *class_kind {
+                    let name = match &program.type_signatures.get(head_id).item {
+                        AstTypeSignature::Named(name, _) => name.clone(),
+                        _ => instance.class_name.clone(),
+                    };
+                    let err = ResolverError::KindMismatch(
+                        name,
+                        format!("{}", class_kind),
+                        format!("{}", kind),
+                        instance.location_id,
+                    );
+                    errors.push(err);
                 }
-            };
-        let result = process_type_signature(
-            &instance.type_signature_id,
-            program,
-            ir_program,
-            module,
-            &mut type_arg_resolver,
-            errors,
-        );
-        let ir_class = ir_program.classes.get(&ir_class_id).clone();
+                ids.push(id);
+            }
+        }
+        let result = if ids.len() != head_ids.len() {
+            None
+        } else if ir_class.is_multi_param() {
+            let id = ir_program.type_signatures.get_id();
+            let item = ItemInfo::new(TypeSignature::Tuple(ids), instance.location_id);
+            ir_program.type_signatures.add_item(id, item);
+            Some(id)
+        } else {
+            Some(ids[0])
+        };
 
         let (
             _,
@@ -975,10 +1835,10 @@ impl Resolver {
             errors.push(err);
         }
 
-        for (name, ids) in conflicting_functions {
-            let locations: Vec<_> = ids
+        for (name, definitions) in conflicting_functions {
+            let locations: Vec<_> = definitions
                 .iter()
-                .map(|id| program.functions.get(id).location_id)
+                .map(|ids| program.functions.get(&ids[0]).location_id)
                 .collect();
             let err = ResolverError::ConflictingInstanceMemberFunction(name.clone(), locations);
             errors.push(err);
@@ -1003,11 +1863,12 @@ impl Resolver {
             let mut members = BTreeMap::new();
             let mut implemented_members = BTreeSet::new();
 
-            for (_, member_function_ids) in &instance.member_functions {
-                assert_eq!(member_function_ids.len(), 1);
-                let member_function_id = &member_function_ids[0];
-                let function = program.functions.get(member_function_id);
-                let member_name = &function.name;
+            for (_, definitions) in &instance.member_functions {
+                let clauses: Vec<_> = definitions[0]
+                    .iter()
+                    .map(|id| program.functions.get(id))
+                    .collect();
+                let member_name = &clauses[0].name;
                 if let Some(class_member_id) = ir_class.members.get(member_name) {
                     let ir_function_id = ir_program.functions.get_id();
                     let ir_class_member = ir_program.class_members.get(class_member_id).clone();
@@ -1031,19 +1892,20 @@ impl Resolver {
                     self.process_function(
                         program,
                         ir_program,
-                        &function,
+                        &clauses,
                         ir_function_id,
                         module,
                         errors,
                         Some(member_function_type_signature_id),
                         &mut type_arg_resolver,
                         NamedFunctionKind::InstanceMember(instance.name.clone()),
+                        None,
                     );
                 } else {
                     let err = ResolverError::NotAClassMember(
                         member_name.clone(),
                         instance.class_name.clone(),
-                        function.location_id,
+                        clauses[0].location_id,
                     );
                     errors.push(err);
                 }
@@ -1064,19 +1926,32 @@ impl Resolver {
                 }
             }
 
+            let constraints = self.process_class_constraints(
+                &type_arg_resolver,
+                ir_program,
+                instance.location_id,
+            );
+
             let ir_instance = IrInstance {
                 id: id,
                 name: instance.name.clone(),
+                module: module.name.clone(),
                 class_id: ir_class_id,
                 type_signature: instance_type_signature,
+                constraints: constraints,
                 members: members,
                 location_id: instance.location_id,
+                doc: instance.doc.clone(),
             };
 
             ir_program.instances.add_item(id, ir_instance);
         }
     }
 
+    pub fn get_modules(&self) -> &BTreeMap<String, Module> {
+        &self.modules
+    }
+
     pub fn resolve(&mut self, program: &Program) -> Result<IrProgram, Error> {
         let mut errors = Vec::new();
 
@@ -1108,6 +1983,14 @@ impl Resolver {
             return Err(Error::resolve_err(errors));
         }
 
+        self.process_type_aliases(program, &mut ir_program, &mut errors);
+
+        if !errors.is_empty() {
+            return Err(Error::resolve_err(errors));
+        }
+
+        self.infer_data_type_arg_kinds(program, &mut ir_program);
+
         for (_, module) in &self.modules {
             for (_, items) in &module.items {
                 for item in items {
@@ -1136,6 +2019,36 @@ impl Resolver {
                             module,
                             &mut errors,
                         ),
+                        Item::Protocol(ast_protocol_id, ir_typedef_id) => self.process_protocol(
+                            program,
+                            &mut ir_program,
+                            ast_protocol_id,
+                            *ir_typedef_id,
+                            module,
+                            &mut errors,
+                        ),
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(Error::resolve_err(errors));
+        }
+
+        for (_, module) in &self.modules {
+            for (_, items) in &module.items {
+                for item in items {
+                    match item {
+                        Item::ActorConstructor(ast_actor_id, ir_ctor_id) => self.process_actor(
+                            program,
+                            &mut ir_program,
+                            ast_actor_id,
+                            *ir_ctor_id,
+                            module,
+                            &mut errors,
+                        ),
                         _ => {}
                     }
                 }
@@ -1230,16 +2143,34 @@ impl Resolver {
                                 } else {
                                     (None, TypeArgResolver::new(self.type_var_generator.clone()))
                                 };
+                            // the definitions sharing a name are reported as conflicts
+                            let clauses: Vec<_> = ast_module
+                                .functions
+                                .get(&function.name)
+                                .and_then(|definitions| {
+                                    definitions.iter().find(|ids| ids[0] == function.id)
+                                })
+                                .expect("Function clauses not found")
+                                .iter()
+                                .map(|id| program.functions.get(id))
+                                .collect();
+                            // the doc comment can be attached to the signature or to a clause
+                            let doc = ast_module
+                                .function_types
+                                .get(&function.name)
+                                .and_then(|ids| program.function_types.get(&ids[0]).doc.clone())
+                                .or_else(|| clauses.iter().find_map(|c| c.doc.clone()));
                             self.process_function(
                                 program,
                                 &mut ir_program,
-                                &function,
+                                &clauses,
                                 *ir_function_id,
                                 module,
                                 &mut errors,
                                 type_signature_id,
                                 &mut type_arg_resolver,
                                 NamedFunctionKind::Free,
+                                doc,
                             );
                         }
                         _ => {}
@@ -1280,9 +2211,10 @@ impl Resolver {
 
         for (_, function) in ir_program.functions.items.iter() {
             match &function.info {
-                FunctionInfo::Lambda(info) => {
+                FunctionInfo::Lambda(LambdaInfo { body, .. })
+                | FunctionInfo::LocalFunction(LocalFunctionInfo { body, .. }) => {
                     let capture_count = function.arg_count - function.arg_locations.len();
-                    lambdas.push((info.body, capture_count));
+                    lambdas.push((*body, capture_count));
                 }
                 _ => {}
             }
@@ -14,6 +14,7 @@ pub struct TypeArgInfo {
 #[derive(Clone)]
 pub struct TypeArgResolver {
     args: BTreeMap<String, TypeArgInfo>,
+    class_constraints: Vec<(ClassId, Vec<String>)>,
     type_var_generator: TypeVarGenerator,
 }
 
@@ -21,6 +22,7 @@ impl TypeArgResolver {
     pub fn new(type_var_generator: TypeVarGenerator) -> TypeArgResolver {
         TypeArgResolver {
             args: BTreeMap::new(),
+            class_constraints: Vec::new(),
             type_var_generator: type_var_generator,
         }
     }
@@ -53,6 +55,29 @@ impl TypeArgResolver {
         }
     }
 
+    // constraints of multi parameter classes are not attached to a single
+    // argument, they are resolved when the function or instance is built
+    pub fn add_class_constraint(&mut self, args: &Vec<String>, constraint: ClassId) -> bool {
+        if !args.iter().all(|arg| self.args.contains_key(arg)) {
+            return false;
+        }
+        self.class_constraints.push((constraint, args.clone()));
+        true
+    }
+
+    pub fn get_class_constraints(&self) -> Vec<(ClassId, Vec<(String, TypeArgInfo)>)> {
+        self.class_constraints
+            .iter()
+            .map(|(class_id, args)| {
+                let args = args
+                    .iter()
+                    .map(|arg| (arg.clone(), self.args[arg].clone()))
+                    .collect();
+                (*class_id, args)
+            })
+            .collect()
+    }
+
     pub fn resolve_arg(&mut self, arg: &String) -> Option<TypeArgInfo> {
         if let Some(info) = self.args.get_mut(arg) {
             info.used = true;
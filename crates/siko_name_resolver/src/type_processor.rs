@@ -5,6 +5,8 @@ use crate::item::Item;
 use crate::module::Module;
 use crate::type_arg_resolver::TypeArgResolver;
 use siko_ir::class::ClassId;
+use siko_ir::data::TypeDef;
+use siko_ir::kind::Kind;
 use siko_ir::program::Program as IrProgram;
 use siko_ir::type_signature::TypeSignature as IrTypeSignature;
 use siko_ir::type_signature::TypeSignatureId as IrTypeSignatureId;
@@ -14,6 +16,7 @@ use siko_syntax::program::Program;
 use siko_syntax::types::TypeSignature as AstTypeSignature;
 use siko_syntax::types::TypeSignatureId;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 
 fn process_named_type(
     name: &str,
@@ -24,6 +27,7 @@ fn process_named_type(
     module: &Module,
     type_arg_resolver: &mut TypeArgResolver,
     errors: &mut Vec<ResolverError>,
+    allow_partial: bool,
 ) -> Option<IrTypeSignatureId> {
     let ir_type_signature = match module.imported_items.get(name) {
         Some(items) => match ImportedItemInfo::resolve_ambiguity(items, Namespace::Type) {
@@ -34,10 +38,16 @@ fn process_named_type(
                 return None;
             }
             Some(item) => {
+                let arg_kinds = get_named_type_arg_kinds(name, module, ir_program);
                 let mut named_arg_ids = Vec::new();
-                for named_arg in named_args {
-                    match process_type_signature(
+                for (index, named_arg) in named_args.iter().enumerate() {
+                    let arg_kind = arg_kinds
+                        .get(index)
+                        .cloned()
+                        .unwrap_or_else(|| Kind::star());
+                    match process_named_type_arg(
                         named_arg,
+                        arg_kind,
                         program,
                         ir_program,
                         module,
@@ -53,9 +63,15 @@ fn process_named_type(
                     }
                 }
                 match item.item {
-                    Item::Adt(_, ir_typedef_id) => {
+                    Item::Adt(_, ir_typedef_id)
+                    | Item::Protocol(_, ir_typedef_id)
+                    | Item::Actor(_, ir_typedef_id) => {
                         let ir_adt = ir_program.typedefs.get(&ir_typedef_id).get_adt();
-                        if ir_adt.type_args.len() != named_arg_ids.len() {
+                        if !type_arg_count_matches(
+                            ir_adt.type_args.len(),
+                            named_arg_ids.len(),
+                            allow_partial,
+                        ) {
                             let err = ResolverError::IncorrectTypeArgumentCount(
                                 name.to_string(),
                                 ir_adt.type_args.len(),
@@ -70,7 +86,11 @@ fn process_named_type(
                     Item::Record(_, ir_typedef_id) => {
                         let ir_record = ir_program.typedefs.get(&ir_typedef_id).get_record();
 
-                        if ir_record.type_args.len() != named_arg_ids.len() {
+                        if !type_arg_count_matches(
+                            ir_record.type_args.len(),
+                            named_arg_ids.len(),
+                            allow_partial,
+                        ) {
                             let err = ResolverError::IncorrectTypeArgumentCount(
                                 name.to_string(),
                                 ir_record.type_args.len(),
@@ -82,10 +102,38 @@ fn process_named_type(
                         }
                         IrTypeSignature::Named(ir_record.name.clone(), ir_typedef_id, named_arg_ids)
                     }
+                    Item::TypeAlias(_, ir_type_alias_id) => {
+                        let ir_type_alias = ir_program.type_aliases.get(&ir_type_alias_id);
+                        if ir_type_alias.type_args.len() != named_arg_ids.len() {
+                            let err = ResolverError::IncorrectTypeArgumentCount(
+                                name.to_string(),
+                                ir_type_alias.type_args.len(),
+                                named_arg_ids.len(),
+                                location_id,
+                            );
+                            errors.push(err);
+                            return None;
+                        }
+                        // the alias was resolved earlier, an error was already reported if it failed
+                        let mut result = ir_type_alias.type_signature?;
+                        let type_args = ir_type_alias.type_args.clone();
+                        for (type_arg, named_arg_id) in type_args.iter().zip(named_arg_ids.iter()) {
+                            result = subtitute_type_signature(
+                                &result,
+                                *type_arg,
+                                named_arg_id,
+                                ir_program,
+                            );
+                        }
+                        // the expanded signature is located at the use site of the alias
+                        ir_program.type_signatures.get(&result).item.clone()
+                    }
                     Item::Function(..)
                     | Item::Variant(..)
                     | Item::ClassMember(..)
-                    | Item::Class(..) => {
+                    | Item::Class(..)
+                    | Item::ProtocolConstructor(..)
+                    | Item::ActorConstructor(..) => {
                         let err = ResolverError::NameNotType(name.to_string(), location_id);
                         errors.push(err);
                         return None;
@@ -105,6 +153,77 @@ fn process_named_type(
     return Some(id);
 }
 
+// An argument of a data type expecting a type constructor may be a partially
+// applied named type, `Wrap Option Int` gives Option to Wrap.
+fn process_named_type_arg(
+    type_signature_id: &TypeSignatureId,
+    expected: Kind,
+    program: &Program,
+    ir_program: &mut IrProgram,
+    module: &Module,
+    type_arg_resolver: &mut TypeArgResolver,
+    errors: &mut Vec<ResolverError>,
+) -> Option<IrTypeSignatureId> {
+    let info = program.type_signatures.get(type_signature_id);
+    match &info.item {
+        AstTypeSignature::Named(name, named_args) if expected != Kind::star() => {
+            let id = process_named_type(
+                name,
+                named_args,
+                info.location_id,
+                program,
+                ir_program,
+                module,
+                type_arg_resolver,
+                errors,
+                true,
+            )?;
+            let kind = get_named_type_kind(&id, ir_program);
+            if kind != expected {
+                let err = ResolverError::KindMismatch(
+                    name.clone(),
+                    format!("{}", expected),
+                    format!("{}", kind),
+                    info.location_id,
+                );
+                errors.push(err);
+                return None;
+            }
+            Some(id)
+        }
+        _ => process_type_signature(
+            type_signature_id,
+            program,
+            ir_program,
+            module,
+            type_arg_resolver,
+            errors,
+        ),
+    }
+}
+
+// The number of type arguments a possibly partially applied named type still needs
+fn get_named_type_kind(id: &IrTypeSignatureId, ir_program: &IrProgram) -> Kind {
+    match &ir_program.type_signatures.get(id).item {
+        IrTypeSignature::Named(_, typedef_id, items) => {
+            let type_arg_count = match ir_program.typedefs.get(typedef_id) {
+                TypeDef::Adt(adt) => adt.type_args.len(),
+                TypeDef::Record(record) => record.type_args.len(),
+            };
+            Kind::new(type_arg_count - items.len())
+        }
+        _ => Kind::star(),
+    }
+}
+
+fn type_arg_count_matches(expected: usize, found: usize, allow_partial: bool) -> bool {
+    if allow_partial {
+        found <= expected
+    } else {
+        found == expected
+    }
+}
+
 pub fn subtitute_type_signature(
     source: &IrTypeSignatureId,
     from: usize,
@@ -142,6 +261,23 @@ pub fn subtitute_type_signature(
                 .collect();
             IrTypeSignature::Tuple(new_items)
         }
+        IrTypeSignature::Application(head, args) => {
+            let new_head = subtitute_type_signature(head, from, to, ir_program);
+            let mut new_args: Vec<_> = args
+                .iter()
+                .map(|arg| subtitute_type_signature(arg, from, to, ir_program))
+                .collect();
+            // a type constructor variable replaced by a partially applied
+            // named type takes the remaining arguments
+            match &ir_program.type_signatures.get(&new_head).item {
+                IrTypeSignature::Named(name, type_id, items) => {
+                    let mut items = items.clone();
+                    items.append(&mut new_args);
+                    IrTypeSignature::Named(name.clone(), *type_id, items)
+                }
+                _ => IrTypeSignature::Application(new_head, new_args),
+            }
+        }
         IrTypeSignature::Variant(_, _) => unreachable!(),
     };
     let id = ir_program.type_signatures.get_id();
@@ -174,6 +310,12 @@ pub fn collect_type_args(
         AstTypeSignature::TypeArg(name) => {
             type_args.insert(name.clone(), info.location_id);
         }
+        AstTypeSignature::TypeArgApplication(name, items) => {
+            type_args.insert(name.clone(), info.location_id);
+            for item in items {
+                collect_type_args(item, program, type_args);
+            }
+        }
         AstTypeSignature::Variant(_, items) => {
             for item in items {
                 collect_type_args(item, program, type_args);
@@ -183,32 +325,197 @@ pub fn collect_type_args(
     }
 }
 
-pub fn process_class_type_signature(
+pub fn collect_type_names(
     type_signature_id: &TypeSignatureId,
     program: &Program,
-    ir_program: &mut IrProgram,
-    type_arg_resolver: &mut TypeArgResolver,
-    errors: &mut Vec<ResolverError>,
-    class_id: ClassId,
-) -> Option<(IrTypeSignatureId, String)> {
+    names: &mut BTreeSet<String>,
+) {
+    let info = program.type_signatures.get(type_signature_id);
+    match &info.item {
+        AstTypeSignature::Function(from, to) => {
+            collect_type_names(from, program, names);
+            collect_type_names(to, program, names);
+        }
+        AstTypeSignature::Named(name, items) => {
+            names.insert(name.clone());
+            for item in items {
+                collect_type_names(item, program, names);
+            }
+        }
+        AstTypeSignature::Tuple(items)
+        | AstTypeSignature::Variant(_, items)
+        | AstTypeSignature::TypeArgApplication(_, items) => {
+            for item in items {
+                collect_type_names(item, program, names);
+            }
+        }
+        AstTypeSignature::TypeArg(_) | AstTypeSignature::Wildcard => {}
+    }
+}
+
+// The kinds of the type arguments of a data type visible in the module, unknown
+// names have no kinds and their arguments are treated as proper types.
+pub fn get_named_type_arg_kinds(name: &str, module: &Module, ir_program: &IrProgram) -> Vec<Kind> {
+    if let Some(items) = module.imported_items.get(name) {
+        if let Some(item) = ImportedItemInfo::resolve_ambiguity(items, Namespace::Type) {
+            match item.item {
+                Item::Adt(_, ir_typedef_id) | Item::Record(_, ir_typedef_id) => {
+                    return ir_program
+                        .typedefs
+                        .get(&ir_typedef_id)
+                        .get_type_arg_kinds()
+                        .clone();
+                }
+                _ => {}
+            }
+        }
+    }
+    Vec::new()
+}
+
+// Records the kind of every use of the type arguments, a type argument applied
+// to n arguments is used as a type constructor of arity n. A type argument
+// given to a data type takes the kind of the data type's argument.
+pub fn collect_type_arg_kinds(
+    type_signature_id: &TypeSignatureId,
+    program: &Program,
+    module: &Module,
+    ir_program: &IrProgram,
+    kinds: &mut BTreeMap<String, Vec<(Kind, LocationId)>>,
+) {
+    collect_type_arg_kinds_with_arity(
+        type_signature_id,
+        program,
+        module,
+        ir_program,
+        Kind::star(),
+        kinds,
+    );
+}
+
+fn collect_type_arg_kinds_with_arity(
+    type_signature_id: &TypeSignatureId,
+    program: &Program,
+    module: &Module,
+    ir_program: &IrProgram,
+    expected: Kind,
+    kinds: &mut BTreeMap<String, Vec<(Kind, LocationId)>>,
+) {
     let info = program.type_signatures.get(type_signature_id);
     match &info.item {
+        AstTypeSignature::Function(from, to) => {
+            collect_type_arg_kinds(from, program, module, ir_program, kinds);
+            collect_type_arg_kinds(to, program, module, ir_program, kinds);
+        }
+        AstTypeSignature::Named(name, items) => {
+            let item_kinds = get_named_type_arg_kinds(name, module, ir_program);
+            for (index, item) in items.iter().enumerate() {
+                let item_kind = item_kinds
+                    .get(index)
+                    .cloned()
+                    .unwrap_or_else(|| Kind::star());
+                collect_type_arg_kinds_with_arity(
+                    item, program, module, ir_program, item_kind, kinds,
+                );
+            }
+        }
+        AstTypeSignature::Tuple(items) | AstTypeSignature::Variant(_, items) => {
+            for item in items {
+                collect_type_arg_kinds(item, program, module, ir_program, kinds);
+            }
+        }
         AstTypeSignature::TypeArg(name) => {
-            let constraints = vec![class_id];
-            let index =
-                type_arg_resolver.add_explicit(name.clone(), constraints.clone(), info.location_id);
-            let ir_type_signature = IrTypeSignature::TypeArgument(index, name.clone(), constraints);
-            let id = ir_program.type_signatures.get_id();
-            let type_info = ItemInfo::new(ir_type_signature, info.location_id);
-            ir_program.type_signatures.add_item(id, type_info);
-            Some((id, name.clone()))
+            let uses = kinds.entry(name.clone()).or_insert_with(|| Vec::new());
+            uses.push((expected, info.location_id));
         }
-        _ => {
-            let err = ResolverError::InvalidClassArgument(info.location_id);
-            errors.push(err);
-            None
+        AstTypeSignature::TypeArgApplication(name, items) => {
+            let uses = kinds.entry(name.clone()).or_insert_with(|| Vec::new());
+            uses.push((Kind::new(items.len() + expected.arity), info.location_id));
+            for item in items {
+                collect_type_arg_kinds(item, program, module, ir_program, kinds);
+            }
+        }
+        AstTypeSignature::Wildcard => {}
+    }
+}
+
+// Checks that every type argument is used with the same kind everywhere and
+// returns the kind of each argument, the first use decides.
+pub fn check_type_arg_kinds(
+    kinds: &BTreeMap<String, Vec<(Kind, LocationId)>>,
+    errors: &mut Vec<ResolverError>,
+) -> BTreeMap<String, Kind> {
+    let mut result = BTreeMap::new();
+    for (name, uses) in kinds {
+        let expected = uses[0].0;
+        for (kind, location_id) in uses {
+            if *kind != expected {
+                let err = ResolverError::KindMismatch(
+                    name.clone(),
+                    format!("{}", expected),
+                    format!("{}", kind),
+                    *location_id,
+                );
+                errors.push(err);
+            }
         }
+        result.insert(name.clone(), expected);
     }
+    result
+}
+
+// The class argument of a multi parameter class is a tuple of its type
+// arguments, only single parameter classes constrain their argument.
+pub fn process_class_type_signature(
+    type_signature_ids: &Vec<TypeSignatureId>,
+    program: &Program,
+    ir_program: &mut IrProgram,
+    type_arg_resolver: &mut TypeArgResolver,
+    errors: &mut Vec<ResolverError>,
+    class_id: ClassId,
+) -> Option<(IrTypeSignatureId, Vec<String>)> {
+    let constraints = if type_signature_ids.len() == 1 {
+        vec![class_id]
+    } else {
+        Vec::new()
+    };
+    let mut ids = Vec::new();
+    let mut names = Vec::new();
+    for type_signature_id in type_signature_ids {
+        let info = program.type_signatures.get(type_signature_id);
+        match &info.item {
+            AstTypeSignature::TypeArg(name) => {
+                let index = type_arg_resolver.add_explicit(
+                    name.clone(),
+                    constraints.clone(),
+                    info.location_id,
+                );
+                let ir_type_signature =
+                    IrTypeSignature::TypeArgument(index, name.clone(), constraints.clone());
+                let id = ir_program.type_signatures.get_id();
+                let type_info = ItemInfo::new(ir_type_signature, info.location_id);
+                ir_program.type_signatures.add_item(id, type_info);
+                ids.push(id);
+                names.push(name.clone());
+            }
+            _ => {
+                let err = ResolverError::InvalidClassArgument(info.location_id);
+                errors.push(err);
+                return None;
+            }
+        }
+    }
+    if ids.len() == 1 {
+        return Some((ids[0], names));
+    }
+    let location_id = program
+        .type_signatures
+        .get(&type_signature_ids[0])
+        .location_id;
+    let id = ir_program.type_signatures.get_id();
+    let type_info = ItemInfo::new(IrTypeSignature::Tuple(ids), location_id);
+    ir_program.type_signatures.add_item(id, type_info);
+    Some((id, names))
 }
 
 pub fn process_type_signature(
@@ -232,6 +539,38 @@ pub fn process_type_signature(
                 return None;
             }
         }
+        AstTypeSignature::TypeArgApplication(name, items) => {
+            let head = if let Some(info) = type_arg_resolver.resolve_arg(name) {
+                IrTypeSignature::TypeArgument(info.index, name.clone(), info.constraints)
+            } else {
+                let error = ResolverError::UnknownTypeArg(name.clone(), location_id);
+                errors.push(error);
+                return None;
+            };
+            let head_id = ir_program.type_signatures.get_id();
+            ir_program
+                .type_signatures
+                .add_item(head_id, ItemInfo::new(head, location_id));
+            let mut item_ids = Vec::new();
+            for item in items {
+                match process_type_signature(
+                    item,
+                    program,
+                    ir_program,
+                    module,
+                    type_arg_resolver,
+                    errors,
+                ) {
+                    Some(id) => {
+                        item_ids.push(id);
+                    }
+                    None => {
+                        return None;
+                    }
+                }
+            }
+            IrTypeSignature::Application(head_id, item_ids)
+        }
         AstTypeSignature::Variant(name, items) => {
             let mut item_ids = Vec::new();
             for item in items {
@@ -263,6 +602,7 @@ pub fn process_type_signature(
                 module,
                 type_arg_resolver,
                 errors,
+                false,
             );
         }
         AstTypeSignature::Tuple(items) => {
@@ -316,3 +656,44 @@ pub fn process_type_signature(
     ir_program.type_signatures.add_item(id, type_info);
     return Some(id);
 }
+
+// Instance heads may be partially applied named types, `instance Functor Option`
+// is an instance for the type constructor Option. Returns the kind of the head.
+pub fn process_instance_type_signature(
+    type_signature_id: &TypeSignatureId,
+    program: &Program,
+    ir_program: &mut IrProgram,
+    module: &Module,
+    type_arg_resolver: &mut TypeArgResolver,
+    errors: &mut Vec<ResolverError>,
+) -> Option<(IrTypeSignatureId, Kind)> {
+    let info = program.type_signatures.get(type_signature_id);
+    match &info.item {
+        AstTypeSignature::Named(name, named_args) => {
+            let id = process_named_type(
+                name,
+                named_args,
+                info.location_id,
+                program,
+                ir_program,
+                module,
+                type_arg_resolver,
+                errors,
+                true,
+            )?;
+            let kind = get_named_type_kind(&id, ir_program);
+            Some((id, kind))
+        }
+        _ => {
+            let id = process_type_signature(
+                type_signature_id,
+                program,
+                ir_program,
+                module,
+                type_arg_resolver,
+                errors,
+            )?;
+            Some((id, Kind::star()))
+        }
+    }
+}
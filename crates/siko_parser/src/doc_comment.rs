@@ -0,0 +1,37 @@
+use std::collections::BTreeMap;
+
+pub const DOC_COMMENT_PREFIX: &str = "--|";
+
+// Doc comments are not part of the token stream, the layout rules would treat them as items.
+// They are stored per line and attached to the item that starts on the line right after them.
+#[derive(Debug, Clone, Default)]
+pub struct DocComments {
+    lines: BTreeMap<usize, String>,
+}
+
+impl DocComments {
+    pub fn new() -> DocComments {
+        DocComments {
+            lines: BTreeMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, line: usize, text: &str) {
+        let text = &text[DOC_COMMENT_PREFIX.len()..];
+        let text = text.strip_prefix(' ').unwrap_or(text);
+        self.lines.insert(line, text.trim_end().to_string());
+    }
+
+    // Returns the doc comment lines directly preceding the given line, joined
+    pub fn get(&self, line: usize) -> Option<String> {
+        let mut first = line;
+        while first > 0 && self.lines.contains_key(&(first - 1)) {
+            first -= 1;
+        }
+        if first == line {
+            return None;
+        }
+        let lines: Vec<_> = (first..line).map(|l| self.lines[&l].clone()).collect();
+        Some(lines.join("\n"))
+    }
+}
@@ -1,22 +1,112 @@
 use super::util::parse_parens;
+use super::util::report_parser_error;
 use super::util::report_unexpected_token;
 use super::util::ParenParseResult;
+use super::util::ParserErrorReason;
 use crate::error::ParseError;
 use crate::parser::Parser;
 use crate::token::Token;
 use crate::token::TokenKind;
 use siko_constants::BuiltinOperator;
+use siko_constants::ITERATOR_FLAT_MAP_NAME;
+use siko_constants::ITERATOR_MODULE_NAME;
+use siko_constants::ITERATOR_RANGE_FROM_NAME;
+use siko_constants::ITERATOR_RANGE_NAME;
+use siko_constants::LIST_ITER_NAME;
+use siko_constants::LIST_MODULE_NAME;
+use siko_constants::LIST_TO_LIST_NAME;
+use siko_location_info::location_id::LocationId;
 use siko_syntax::expr::Case;
 use siko_syntax::expr::Expr;
 use siko_syntax::expr::ExprId;
 use siko_syntax::expr::RecordConstructionItem;
+use siko_syntax::fixity::Associativity;
 use siko_syntax::pattern::Pattern;
 use siko_syntax::pattern::PatternId;
 use siko_syntax::pattern::RecordFieldPattern;
 
+fn add_temp_arg(
+    parser: &mut Parser,
+    args: &mut Vec<(String, LocationId)>,
+    start_index: usize,
+) -> ExprId {
+    let name = parser.get_temp_var_name();
+    let id = parser.add_expr(Expr::Path(name.clone()), start_index);
+    let location_id = parser.get_program().exprs.get(&id).location_id;
+    args.push((name, location_id));
+    id
+}
+
+// Sections and operators used as functions are turned into lambdas, the missing operands become
+// the arguments of the lambda
+fn operator_lambda(
+    parser: &mut Parser,
+    function_id_expr_id: ExprId,
+    left: Option<ExprId>,
+    right: Option<ExprId>,
+    start_index: usize,
+) -> ExprId {
+    let mut args = Vec::new();
+    let left = match left {
+        Some(left) => left,
+        None => add_temp_arg(parser, &mut args, start_index),
+    };
+    let right = match right {
+        Some(right) => right,
+        None => add_temp_arg(parser, &mut args, start_index),
+    };
+    let expr = Expr::FunctionCall(function_id_expr_id, vec![left, right]);
+    let body = parser.add_expr(expr, start_index);
+    let expr = Expr::Lambda(args, body);
+    parser.add_expr(expr, start_index)
+}
+
+fn parse_operator_function_or_right_section(
+    parser: &mut Parser,
+) -> Result<Option<ExprId>, ParseError> {
+    let start_index = parser.get_index();
+    let operator = match peek_binary_operator(parser, 1) {
+        Some(operator) => operator,
+        None => return Ok(None),
+    };
+    if closing_paren_follows(parser, 1, &operator) {
+        parser.expect(TokenKind::LParen)?;
+        let function_id_expr_id = consume_binary_operator(parser, operator)?;
+        parser.expect(TokenKind::RParen)?;
+        let id = operator_lambda(parser, function_id_expr_id, None, None, start_index);
+        return Ok(Some(id));
+    }
+    if let Expr::Builtin(BuiltinOperator::Sub) = operator.expr {
+        // (- x) is a negation, not a section
+        return Ok(None);
+    }
+    parser.expect(TokenKind::LParen)?;
+    let function_id_expr_id = consume_binary_operator(parser, operator)?;
+    let right = parser.parse_expr()?;
+    parser.expect(TokenKind::RParen)?;
+    let id = operator_lambda(parser, function_id_expr_id, None, Some(right), start_index);
+    Ok(Some(id))
+}
+
+fn parse_paren_item(parser: &mut Parser) -> Result<ExprId, ParseError> {
+    let start_index = parser.get_index();
+    let left = parser.parse_expr()?;
+    if let Some(operator) = peek_binary_operator(parser, 0) {
+        if closing_paren_follows(parser, 0, &operator) {
+            let function_id_expr_id = consume_binary_operator(parser, operator)?;
+            let id = operator_lambda(parser, function_id_expr_id, Some(left), None, start_index);
+            return Ok(id);
+        }
+    }
+    Ok(left)
+}
+
 fn parse_paren_expr(parser: &mut Parser) -> Result<ExprId, ParseError> {
     let start_index = parser.get_index();
-    let res = parse_parens(parser, |p| p.parse_expr(), " expression")?;
+    if let Some(id) = parse_operator_function_or_right_section(parser)? {
+        return Ok(id);
+    }
+    let res = parse_parens(parser, parse_paren_item, " expression")?;
     match res {
         ParenParseResult::Single(e) => {
             return Ok(e);
@@ -29,10 +119,178 @@ fn parse_paren_expr(parser: &mut Parser) -> Result<ExprId, ParseError> {
     }
 }
 
+enum Qualifier {
+    Generator(PatternId, ExprId),
+    Guard(ExprId),
+}
+
+fn add_std_call(
+    parser: &mut Parser,
+    module: &str,
+    name: &str,
+    args: Vec<ExprId>,
+    start_index: usize,
+) -> ExprId {
+    let path = Expr::Path(format!("{}.{}", module, name));
+    let path_id = parser.add_expr(path, start_index);
+    let expr = Expr::FunctionCall(path_id, args);
+    parser.add_expr(expr, start_index)
+}
+
+// [from..to] and [from, then..to] are lists, the unbounded forms are iterators
+fn parse_range(
+    parser: &mut Parser,
+    from: ExprId,
+    then: Option<ExprId>,
+    start_index: usize,
+) -> Result<ExprId, ParseError> {
+    parser.expect(TokenKind::DoubleDot)?;
+    let step = match then {
+        Some(then) => {
+            let sub = parser.add_expr(Expr::Builtin(BuiltinOperator::Sub), start_index);
+            let expr = Expr::FunctionCall(sub, vec![then, from]);
+            parser.add_expr(expr, start_index)
+        }
+        None => parser.add_expr(Expr::IntegerLiteral(1), start_index),
+    };
+    if parser.current(TokenKind::RBracket) {
+        parser.expect(TokenKind::RBracket)?;
+        let id = add_std_call(
+            parser,
+            ITERATOR_MODULE_NAME,
+            ITERATOR_RANGE_FROM_NAME,
+            vec![from, step],
+            start_index,
+        );
+        return Ok(id);
+    }
+    let to = parser.parse_expr()?;
+    parser.expect(TokenKind::RBracket)?;
+    let iter = add_std_call(
+        parser,
+        ITERATOR_MODULE_NAME,
+        ITERATOR_RANGE_NAME,
+        vec![from, step, to],
+        start_index,
+    );
+    let id = add_std_call(
+        parser,
+        LIST_MODULE_NAME,
+        LIST_TO_LIST_NAME,
+        vec![iter],
+        start_index,
+    );
+    Ok(id)
+}
+
+// [body | x <- xs, cond] becomes toList (flatMap (\x -> if cond then iter [body] else iter []) (iter xs))
+fn parse_comprehension(
+    parser: &mut Parser,
+    body: ExprId,
+    start_index: usize,
+) -> Result<ExprId, ParseError> {
+    parser.expect(TokenKind::Pipe)?;
+    let mut qualifiers = Vec::new();
+    loop {
+        if parser.generator_follows() {
+            let pattern_id = parse_pattern(parser)?;
+            parser.expect(TokenKind::Op(BuiltinOperator::Bind))?;
+            let source = parser.parse_expr()?;
+            qualifiers.push(Qualifier::Generator(pattern_id, source));
+        } else {
+            let guard = parser.parse_expr()?;
+            qualifiers.push(Qualifier::Guard(guard));
+        }
+        if parser.current(TokenKind::Comma) {
+            parser.expect(TokenKind::Comma)?;
+        } else {
+            break;
+        }
+    }
+    parser.expect(TokenKind::RBracket)?;
+    let single = parser.add_expr(Expr::List(vec![body]), start_index);
+    let mut iter = add_std_call(
+        parser,
+        LIST_MODULE_NAME,
+        LIST_ITER_NAME,
+        vec![single],
+        start_index,
+    );
+    for qualifier in qualifiers.into_iter().rev() {
+        iter = match qualifier {
+            Qualifier::Guard(guard) => {
+                let empty = parser.add_expr(Expr::List(vec![]), start_index);
+                let empty = add_std_call(
+                    parser,
+                    LIST_MODULE_NAME,
+                    LIST_ITER_NAME,
+                    vec![empty],
+                    start_index,
+                );
+                parser.add_expr(Expr::If(guard, iter, empty), start_index)
+            }
+            Qualifier::Generator(pattern_id, source) => {
+                let location = parser.get_program().patterns.get(&pattern_id).location_id;
+                let arg_name = parser.get_temp_var_name();
+                let arg = parser.add_expr(Expr::Path(arg_name.clone()), start_index);
+                let bind = parser.add_expr(Expr::Bind(pattern_id, arg), start_index);
+                let lambda_body = parser.add_expr(Expr::Do(vec![bind, iter]), start_index);
+                let lambda = Expr::Lambda(vec![(arg_name, location)], lambda_body);
+                let lambda = parser.add_expr(lambda, start_index);
+                let source = add_std_call(
+                    parser,
+                    LIST_MODULE_NAME,
+                    LIST_ITER_NAME,
+                    vec![source],
+                    start_index,
+                );
+                add_std_call(
+                    parser,
+                    ITERATOR_MODULE_NAME,
+                    ITERATOR_FLAT_MAP_NAME,
+                    vec![lambda, source],
+                    start_index,
+                )
+            }
+        };
+    }
+    let id = add_std_call(
+        parser,
+        LIST_MODULE_NAME,
+        LIST_TO_LIST_NAME,
+        vec![iter],
+        start_index,
+    );
+    Ok(id)
+}
+
 fn parse_list_expr(parser: &mut Parser) -> Result<ExprId, ParseError> {
     let start_index = parser.get_index();
     parser.expect(TokenKind::LBracket)?;
     let mut items = Vec::new();
+    if !parser.current(TokenKind::RBracket) {
+        let first = parser.parse_expr()?;
+        if parser.current(TokenKind::DoubleDot) {
+            return parse_range(parser, first, None, start_index);
+        }
+        if parser.current(TokenKind::Pipe) {
+            return parse_comprehension(parser, first, start_index);
+        }
+        items.push(first);
+        if parser.current(TokenKind::Comma) {
+            parser.expect(TokenKind::Comma)?;
+            if !parser.current(TokenKind::RBracket) {
+                let second = parser.parse_expr()?;
+                if parser.current(TokenKind::DoubleDot) {
+                    return parse_range(parser, first, Some(second), start_index);
+                }
+                items.push(second);
+                if parser.current(TokenKind::Comma) {
+                    parser.expect(TokenKind::Comma)?;
+                }
+            }
+        }
+    }
     loop {
         if parser.current(TokenKind::RBracket) {
             break;
@@ -79,32 +337,45 @@ fn parse_lambda(parser: &mut Parser) -> Result<ExprId, ParseError> {
     Ok(lambda_expr_id)
 }
 
+fn parse_do_statement(parser: &mut Parser) -> Result<ExprId, ParseError> {
+    let bind_start_index = parser.get_index();
+    let mut pattern_id = None;
+    if parser.irrefutable_pattern_follows() {
+        let mut inner_pattern_id = parse_pattern(parser)?;
+        if parser.current(TokenKind::KeywordDoubleColon) {
+            parser.expect(TokenKind::KeywordDoubleColon)?;
+            let type_signature_id = parser.parse_function_type(false, true)?;
+            let pattern = Pattern::Typed(inner_pattern_id, type_signature_id);
+            inner_pattern_id = parser.add_pattern(pattern, bind_start_index);
+        }
+        parser.expect(TokenKind::Op(BuiltinOperator::Bind))?;
+        pattern_id = Some(inner_pattern_id);
+    }
+    let expr = parser.parse_expr()?;
+    parser.expect(TokenKind::EndOfItem)?;
+    if let Some(pattern_id) = pattern_id {
+        let expr = Expr::Bind(pattern_id, expr);
+        let id = parser.add_expr(expr, bind_start_index);
+        Ok(id)
+    } else {
+        Ok(expr)
+    }
+}
+
 fn parse_do(parser: &mut Parser) -> Result<ExprId, ParseError> {
     let start_index = parser.get_index();
     parser.expect(TokenKind::KeywordDo)?;
     let mut exprs = Vec::new();
     loop {
-        let bind_start_index = parser.get_index();
-        let mut pattern_id = None;
-        if parser.irrefutable_pattern_follows() {
-            let mut inner_pattern_id = parse_pattern(parser)?;
-            if parser.current(TokenKind::KeywordDoubleColon) {
-                parser.expect(TokenKind::KeywordDoubleColon)?;
-                let type_signature_id = parser.parse_function_type(false, true)?;
-                let pattern = Pattern::Typed(inner_pattern_id, type_signature_id);
-                inner_pattern_id = parser.add_pattern(pattern, bind_start_index);
+        let statement_start_index = parser.get_index();
+        match parse_do_statement(parser) {
+            Ok(id) => exprs.push(id),
+            Err(err) => {
+                parser.recover(err, statement_start_index);
+                if parser.get_index() == statement_start_index {
+                    break;
+                }
             }
-            parser.expect(TokenKind::Op(BuiltinOperator::Bind))?;
-            pattern_id = Some(inner_pattern_id);
-        }
-        let expr = parser.parse_expr()?;
-        parser.expect(TokenKind::EndOfItem)?;
-        if let Some(pattern_id) = pattern_id {
-            let expr = Expr::Bind(pattern_id, expr);
-            let id = parser.add_expr(expr, bind_start_index);
-            exprs.push(id);
-        } else {
-            exprs.push(expr);
         }
         if parser.current(TokenKind::EndOfBlock) {
             break;
@@ -116,6 +387,17 @@ fn parse_do(parser: &mut Parser) -> Result<ExprId, ParseError> {
     Ok(id)
 }
 
+fn parse_let(parser: &mut Parser) -> Result<ExprId, ParseError> {
+    let start_index = parser.get_index();
+    parser.expect(TokenKind::KeywordLet)?;
+    let local_functions = parser.parse_local_functions()?;
+    parser.expect(TokenKind::KeywordIn)?;
+    let body = parser.parse_expr()?;
+    let expr = Expr::Let(local_functions, body);
+    let id = parser.add_expr(expr, start_index);
+    Ok(id)
+}
+
 fn parse_tuple_pattern(parser: &mut Parser) -> Result<PatternId, ParseError> {
     let start_index = parser.get_index();
     let res = parse_parens(parser, |p| parse_pattern(p), "<pattern>")?;
@@ -150,23 +432,113 @@ fn parse_record_field_pattern(parser: &mut Parser) -> Result<RecordFieldPattern,
     }
 }
 
-fn parse_sub_pattern(parser: &mut Parser, inner: bool) -> Result<Option<PatternId>, ParseError> {
-    let id = match parser.current_kind() {
-        TokenKind::LParen => {
-            let id = parse_tuple_pattern(parser)?;
-            id
-        }
+fn parse_integer_bound(parser: &mut Parser) -> Result<i64, ParseError> {
+    let negative = if parser.current(TokenKind::Op(BuiltinOperator::Sub)) {
+        parser.expect(TokenKind::Op(BuiltinOperator::Sub))?;
+        true
+    } else {
+        false
+    };
+    if parser.current_kind() != TokenKind::IntegerLiteral {
+        return report_unexpected_token(parser, format!("integer literal"));
+    }
+    let literal = parser.advance()?;
+    if let Token::IntegerLiteral(i) = literal.token {
+        Ok(if negative { -i } else { i })
+    } else {
+        unreachable!()
+    }
+}
+
+// integer literals, integer ranges (1..9, end exclusive like char ranges) and float literals, all optionally negative
+fn parse_number_pattern(parser: &mut Parser) -> Result<PatternId, ParseError> {
+    let start_index = parser.get_index();
+    let negative = if parser.current(TokenKind::Op(BuiltinOperator::Sub)) {
+        parser.expect(TokenKind::Op(BuiltinOperator::Sub))?;
+        true
+    } else {
+        false
+    };
+    let pattern = match parser.current_kind() {
         TokenKind::IntegerLiteral => {
-            let start_index = parser.get_index();
             let literal = parser.advance()?;
             if let Token::IntegerLiteral(i) = literal.token {
-                let pattern = Pattern::IntegerLiteral(i);
-                let id = parser.add_pattern(pattern, start_index);
-                id
+                let i = if negative { -i } else { i };
+                if parser.current(TokenKind::DoubleDot) {
+                    parser.expect(TokenKind::DoubleDot)?;
+                    let end = parse_integer_bound(parser)?;
+                    Pattern::IntegerRange(i, end)
+                } else {
+                    Pattern::IntegerLiteral(i)
+                }
             } else {
                 unreachable!()
             }
         }
+        TokenKind::FloatLiteral => {
+            let literal = parser.advance()?;
+            if let Token::FloatLiteral(f) = literal.token {
+                Pattern::FloatLiteral(if negative { -f } else { f })
+            } else {
+                unreachable!()
+            }
+        }
+        _ => {
+            return report_unexpected_token(parser, format!("number literal"));
+        }
+    };
+    let id = parser.add_pattern(pattern, start_index);
+    Ok(id)
+}
+
+// [a, b], [] and [head, ..tail], the rest pattern must be the last one
+fn parse_list_pattern(parser: &mut Parser) -> Result<PatternId, ParseError> {
+    let start_index = parser.get_index();
+    parser.expect(TokenKind::LBracket)?;
+    let mut items = Vec::new();
+    let mut rest = None;
+    while !parser.current(TokenKind::RBracket) {
+        if parser.current(TokenKind::DoubleDot) {
+            let rest_start_index = parser.get_index();
+            parser.expect(TokenKind::DoubleDot)?;
+            let rest_id = if parser.current(TokenKind::RBracket) {
+                parser.add_pattern(Pattern::Wildcard, rest_start_index)
+            } else {
+                parse_pattern(parser)?
+            };
+            rest = Some(rest_id);
+            break;
+        }
+        let item = parse_pattern(parser)?;
+        items.push(item);
+        if parser.current(TokenKind::Comma) {
+            parser.expect(TokenKind::Comma)?;
+        } else {
+            break;
+        }
+    }
+    parser.expect(TokenKind::RBracket)?;
+    let pattern = Pattern::List(items, rest);
+    let id = parser.add_pattern(pattern, start_index);
+    Ok(id)
+}
+
+fn parse_sub_pattern(parser: &mut Parser, inner: bool) -> Result<Option<PatternId>, ParseError> {
+    let id = match parser.current_kind() {
+        TokenKind::LParen => {
+            let id = parse_tuple_pattern(parser)?;
+            id
+        }
+        TokenKind::LBracket => {
+            let id = parse_list_pattern(parser)?;
+            id
+        }
+        TokenKind::IntegerLiteral
+        | TokenKind::FloatLiteral
+        | TokenKind::Op(BuiltinOperator::Sub) => {
+            let id = parse_number_pattern(parser)?;
+            id
+        }
         TokenKind::CharLiteral => {
             let start_index = parser.get_index();
             let literal = parser.advance()?;
@@ -208,7 +580,15 @@ fn parse_sub_pattern(parser: &mut Parser, inner: bool) -> Result<Option<PatternI
         TokenKind::VarIdentifier => {
             let start_index = parser.get_index();
             let name = parser.var_identifier("pattern binding")?;
-            let pattern = Pattern::Binding(name);
+            let pattern = if parser.current(TokenKind::At) {
+                parser.expect(TokenKind::At)?;
+                match parse_sub_pattern(parser, true)? {
+                    Some(inner) => Pattern::As(inner, name),
+                    None => return report_unexpected_token(parser, format!("<pattern>")),
+                }
+            } else {
+                Pattern::Binding(name)
+            };
             let id = parser.add_pattern(pattern, start_index);
             id
         }
@@ -383,6 +763,12 @@ fn parse_arg(parser: &mut Parser) -> Result<ExprId, ParseError> {
                 id
             }
         }
+        Token::Wildcard => {
+            parser.advance()?;
+            let expr = Expr::Path(format!("_"));
+            let id = parser.add_expr(expr, start_index);
+            id
+        }
         Token::IntegerLiteral(n) => {
             parser.advance()?;
             let expr = Expr::IntegerLiteral(n);
@@ -438,6 +824,9 @@ fn parse_arg(parser: &mut Parser) -> Result<ExprId, ParseError> {
         Token::KeywordCase => {
             return parse_case(parser);
         }
+        Token::KeywordLet => {
+            return parse_let(parser);
+        }
         _ => {
             return report_unexpected_token(parser, format!("expression"));
         }
@@ -454,6 +843,7 @@ fn parse_primary(parser: &mut Parser) -> Result<ExprId, ParseError> {
             TokenKind::Op(BuiltinOperator::Not)
             | TokenKind::TypeIdentifier
             | TokenKind::VarIdentifier
+            | TokenKind::Wildcard
             | TokenKind::IntegerLiteral
             | TokenKind::FloatLiteral
             | TokenKind::StringLiteral
@@ -513,80 +903,111 @@ fn parse_unary(parser: &mut Parser, is_arg: bool) -> Result<ExprId, ParseError>
     }
 }
 
-fn parse_binary_op(
+struct BinaryOperator {
+    expr: Expr,
+    name: String,
+    token_count: usize,
+}
+
+fn peek_binary_operator(parser: &Parser, offset: usize) -> Option<BinaryOperator> {
+    let token_info = parser.peek_at(offset)?;
+    match token_info.token {
+        Token::Op(op) if op.is_binary() => Some(BinaryOperator {
+            expr: Expr::Builtin(op),
+            name: op.get_symbol().to_string(),
+            token_count: 1,
+        }),
+        Token::UserOperator(name) => Some(BinaryOperator {
+            expr: Expr::Path(name.clone()),
+            name: name,
+            token_count: 1,
+        }),
+        Token::Backtick => {
+            let name = match parser.peek_at(offset + 1)?.token {
+                Token::VarIdentifier(name) => name,
+                _ => return None,
+            };
+            if parser.peek_at(offset + 2)?.token.kind() != TokenKind::Backtick {
+                return None;
+            }
+            Some(BinaryOperator {
+                expr: Expr::Path(name.clone()),
+                name: name,
+                token_count: 3,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn consume_binary_operator(
+    parser: &mut Parser,
+    operator: BinaryOperator,
+) -> Result<ExprId, ParseError> {
+    let start_index = parser.get_index();
+    for _ in 0..operator.token_count {
+        parser.advance()?;
+    }
+    let id = parser.add_expr(operator.expr, start_index);
+    Ok(id)
+}
+
+fn closing_paren_follows(parser: &Parser, offset: usize, operator: &BinaryOperator) -> bool {
+    match parser.peek_at(offset + operator.token_count) {
+        Some(token_info) => token_info.token.kind() == TokenKind::RParen,
+        None => false,
+    }
+}
+
+fn parse_binary_operators(
     parser: &mut Parser,
-    ops: &[BuiltinOperator],
-    next: fn(&mut Parser) -> Result<ExprId, ParseError>,
+    min_precedence: usize,
 ) -> Result<ExprId, ParseError> {
     let start_index = parser.get_index();
-    let mut left = next(parser)?;
+    let mut left = parse_primary(parser)?;
+    let mut non_associative: Option<(String, usize)> = None;
     loop {
-        if let Some((op, _)) = parser.consume_op(ops) {
-            let function_id_expr = Expr::Builtin(op);
-            let function_id_expr_id = parser.add_expr(function_id_expr, start_index);
-            let right = next(parser)?;
-            let expr = Expr::FunctionCall(function_id_expr_id, vec![left, right]);
-            let id = parser.add_expr(expr, start_index);
-            left = id;
-            continue;
-        } else {
+        let operator = match peek_binary_operator(parser, 0) {
+            Some(operator) => operator,
+            None => break,
+        };
+        if closing_paren_follows(parser, 0, &operator) {
+            // left section, handled by the enclosing parens
+            break;
+        }
+        let fixity = parser.get_fixity(&operator.name);
+        if fixity.precedence < min_precedence {
             break;
         }
+        if let Some((prev, precedence)) = &non_associative {
+            if *precedence == fixity.precedence {
+                let reason = ParserErrorReason::Custom {
+                    msg: format!(
+                        "Non-associative operator {} cannot be chained with {}",
+                        prev, operator.name
+                    ),
+                };
+                return report_parser_error(parser, reason);
+            }
+        }
+        non_associative = match fixity.associativity {
+            Associativity::None => Some((operator.name.clone(), fixity.precedence)),
+            _ => None,
+        };
+        let next_min_precedence = match fixity.associativity {
+            Associativity::Right => fixity.precedence,
+            Associativity::Left | Associativity::None => fixity.precedence + 1,
+        };
+        let function_id_expr_id = consume_binary_operator(parser, operator)?;
+        let right = parse_binary_operators(parser, next_min_precedence)?;
+        let expr = Expr::FunctionCall(function_id_expr_id, vec![left, right]);
+        left = parser.add_expr(expr, start_index);
     }
     Ok(left)
 }
 
 pub fn parse_ops(parser: &mut Parser) -> Result<ExprId, ParseError> {
-    return parse_andor(parser);
-}
-
-fn parse_andor(parser: &mut Parser) -> Result<ExprId, ParseError> {
-    return parse_binary_op(
-        parser,
-        &[BuiltinOperator::And, BuiltinOperator::Or],
-        parse_equal,
-    );
-}
-
-fn parse_equal(parser: &mut Parser) -> Result<ExprId, ParseError> {
-    return parse_binary_op(
-        parser,
-        &[BuiltinOperator::Equals, BuiltinOperator::NotEquals],
-        parse_ord_ops,
-    );
-}
-
-fn parse_ord_ops(parser: &mut Parser) -> Result<ExprId, ParseError> {
-    return parse_binary_op(
-        parser,
-        &[
-            BuiltinOperator::LessThan,
-            BuiltinOperator::LessOrEqualThan,
-            BuiltinOperator::GreaterThan,
-            BuiltinOperator::GreaterOrEqualThan,
-        ],
-        parse_addsub,
-    );
-}
-
-fn parse_addsub(parser: &mut Parser) -> Result<ExprId, ParseError> {
-    return parse_binary_op(
-        parser,
-        &[BuiltinOperator::Add, BuiltinOperator::Sub],
-        parse_muldiv,
-    );
-}
-
-fn parse_muldiv(parser: &mut Parser) -> Result<ExprId, ParseError> {
-    return parse_binary_op(
-        parser,
-        &[BuiltinOperator::Mul, BuiltinOperator::Div],
-        parse_pipe_forward,
-    );
-}
-
-fn parse_pipe_forward(parser: &mut Parser) -> Result<ExprId, ParseError> {
-    return parse_binary_op(parser, &[BuiltinOperator::PipeForward], parse_primary);
+    return parse_binary_operators(parser, 0);
 }
 
 fn parse_field_access(parser: &mut Parser) -> Result<ExprId, ParseError> {
@@ -0,0 +1,110 @@
+use crate::error::ParseError;
+use crate::token::Token;
+use crate::token::TokenInfo;
+use siko_location_info::filepath::FilePath;
+use siko_location_info::location::Location;
+use siko_syntax::fixity::Associativity;
+use siko_syntax::fixity::Fixity;
+use std::collections::BTreeMap;
+
+pub const MAX_PRECEDENCE: usize = 9;
+
+#[derive(Default)]
+pub struct FixityTable {
+    fixities: BTreeMap<String, Fixity>,
+}
+
+impl FixityTable {
+    pub fn new() -> FixityTable {
+        FixityTable {
+            fixities: BTreeMap::new(),
+        }
+    }
+
+    pub fn get(&self, operator: &str) -> Fixity {
+        match self.fixities.get(operator) {
+            Some(fixity) => *fixity,
+            None => Fixity::default(),
+        }
+    }
+
+    fn add(
+        &mut self,
+        operator: String,
+        fixity: Fixity,
+        file_path: &FilePath,
+        location: Location,
+    ) -> Result<(), ParseError> {
+        if let Some(prev) = self.fixities.get(&operator) {
+            if *prev != fixity {
+                let err = ParseError::new(
+                    format!(
+                        "Conflicting fixity declarations for operator {}, previously declared as {}",
+                        operator, prev
+                    ),
+                    file_path.clone(),
+                    location,
+                );
+                return Err(err);
+            }
+        }
+        self.fixities.insert(operator, fixity);
+        Ok(())
+    }
+
+    // Fixity declarations must be known before any expression is parsed, so they are collected
+    // from the token streams of all files before the parser runs. The table is shared by all
+    // modules, a declared fixity applies to the operator wherever it is used.
+    pub fn collect(
+        &mut self,
+        tokens: &[TokenInfo],
+        file_path: &FilePath,
+    ) -> Result<(), ParseError> {
+        let mut index = 0;
+        while index < tokens.len() {
+            let associativity = match tokens[index].token {
+                Token::KeywordInfixl => Associativity::Left,
+                Token::KeywordInfixr => Associativity::Right,
+                Token::KeywordInfix => Associativity::None,
+                _ => {
+                    index += 1;
+                    continue;
+                }
+            };
+            index += 1;
+            let precedence = match tokens.get(index).map(|t| &t.token) {
+                Some(Token::IntegerLiteral(n)) if *n >= 0 && *n as usize <= MAX_PRECEDENCE => {
+                    *n as usize
+                }
+                // the parser reports the malformed declaration
+                _ => continue,
+            };
+            let fixity = Fixity::new(associativity, precedence);
+            index += 1;
+            while index < tokens.len() {
+                let location = tokens[index].location.clone();
+                let operator = match &tokens[index].token {
+                    Token::Op(op) if op.is_binary() => op.get_symbol().to_string(),
+                    Token::UserOperator(op) => op.clone(),
+                    Token::Backtick => match tokens.get(index + 1).map(|t| &t.token) {
+                        Some(Token::VarIdentifier(name)) => {
+                            index += 2;
+                            name.clone()
+                        }
+                        _ => break,
+                    },
+                    _ => break,
+                };
+                self.add(operator, fixity, file_path, location)?;
+                index += 1;
+                match tokens.get(index).map(|t| &t.token) {
+                    Some(Token::Comma) => {
+                        index += 1;
+                    }
+                    _ => break,
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,3 +1,5 @@
+use crate::doc_comment::DocComments;
+use crate::doc_comment::DOC_COMMENT_PREFIX;
 use crate::error::LexerError;
 use crate::error::LocationInfo;
 use crate::token::Token;
@@ -16,6 +18,19 @@ pub struct Lexer {
     input: Vec<char>,
     line_offset: usize,
     tokens: Vec<TokenInfo>,
+    // the line where each token ends, differs from its start for multi-line literals
+    end_lines: Vec<usize>,
+    keep_trivia: bool,
+    source_texts: Vec<(String, usize, usize)>,
+    doc_comments: DocComments,
+}
+
+#[derive(Debug, Clone)]
+pub struct SourceToken {
+    pub info: TokenInfo,
+    pub text: String,
+    pub end_line: usize,
+    pub end_offset: usize,
 }
 
 impl Lexer {
@@ -27,6 +42,10 @@ impl Lexer {
             input: input.chars().collect(),
             line_offset: 0,
             tokens: Vec::new(),
+            end_lines: Vec::new(),
+            keep_trivia: false,
+            source_texts: Vec::new(),
+            doc_comments: DocComments::new(),
         }
     }
 
@@ -85,7 +104,8 @@ impl Lexer {
 
     fn is_operator(c: char) -> bool {
         match c {
-            '|' | '>' | '<' | '&' | '*' | '+' | '-' | '/' | '=' | '!' | '\\' | ':' | '.' => true,
+            '|' | '>' | '<' | '&' | '*' | '+' | '-' | '/' | '=' | '!' | '\\' | ':' | '.' | '^'
+            | '$' | '~' | '?' | '@' => true,
             _ => false,
         }
     }
@@ -97,6 +117,13 @@ impl Lexer {
         });
     }
 
+    fn add_token_at(&mut self, token: Token, line_index: usize, span: Span) {
+        self.tokens.push(TokenInfo {
+            token: token,
+            location: Location::new(line_index, span),
+        });
+    }
+
     fn collect(&mut self, filter_fn: fn(char) -> bool) -> Result<(String, Span), LexerError> {
         let start = self.line_offset;
         let mut token = String::new();
@@ -122,6 +149,8 @@ impl Lexer {
         let t = match identifier.as_ref() {
             "where" => Token::KeywordWhere,
             "data" => Token::KeywordData,
+            "type" => Token::KeywordType,
+            "newtype" => Token::KeywordNewtype,
             "module" => Token::KeywordModule,
             "import" => Token::KeywordImport,
             "if" => Token::KeywordIf,
@@ -138,6 +167,11 @@ impl Lexer {
             "protocol" => Token::KeywordProtocol,
             "actor" => Token::KeywordActor,
             "deriving" => Token::KeywordDeriving,
+            "let" => Token::KeywordLet,
+            "in" => Token::KeywordIn,
+            "infixl" => Token::KeywordInfixl,
+            "infixr" => Token::KeywordInfixr,
+            "infix" => Token::KeywordInfix,
             "_" => Token::Wildcard,
             _ => {
                 let first = identifier.chars().next().expect("empty identifer");
@@ -177,141 +211,192 @@ impl Lexer {
             "::" => Token::KeywordDoubleColon,
             "." => Token::Dot,
             ".." => Token::DoubleDot,
+            "@" => Token::At,
             ":" => Token::Colon,
             "=>" => Token::KeywordConstraint,
-            _ => {
-                return Err(LexerError::General(
-                    format!("Unsupported operator {}", operator),
-                    self.file_path.clone(),
-                    Location::new(self.line_index, span),
-                ));
-            }
+            _ => Token::UserOperator(operator),
         };
         self.add_token(t, span);
         Ok(())
     }
 
-    fn collect_string_literal(&mut self) -> Result<(), LexerError> {
-        let start = self.line_offset;
-        let mut prev_backslash = false;
-        let mut literal = String::new();
-        let mut inside = true;
+    fn follows(&self, text: &str) -> bool {
+        text.chars()
+            .enumerate()
+            .all(|(offset, c)| self.input.get(self.index + offset) == Some(&c))
+    }
+
+    fn raw_string_follows(&self) -> bool {
+        if !self.follows("r") {
+            return false;
+        }
+        let mut index = self.index + 1;
+        while self.input.get(index) == Some(&'#') {
+            index += 1;
+        }
+        self.input.get(index) == Some(&'"')
+    }
+
+    fn collect_unicode_escape(&mut self, start: usize) -> Result<char, LexerError> {
+        let invalid = |lexer: &Lexer| {
+            LexerError::General(
+                format!("Invalid unicode escape sequence"),
+                lexer.file_path.clone(),
+                Location::new(lexer.line_index, Span::new(start, lexer.line_offset)),
+            )
+        };
+        if self.is_done() || self.peek()? != '{' {
+            return Err(invalid(self));
+        }
         self.advance()?;
-        while !self.is_done() {
+        let mut digits = String::new();
+        loop {
+            if self.is_done() {
+                return Err(invalid(self));
+            }
             let c = self.peek()?;
-            if c == '\\' {
-                if prev_backslash {
-                    prev_backslash = false;
-                    literal.push(c);
-                    self.advance()?;
-                } else {
-                    prev_backslash = true;
-                    self.advance()?;
-                }
-            } else {
-                if prev_backslash {
-                    prev_backslash = false;
-                    let special = match c {
-                        'n' => '\n',
-                        't' => '\t',
-                        '"' => '"',
-                        _ => {
-                            return Err(LexerError::General(
-                                format!("Invalid escape sequence \\{}", c),
-                                self.file_path.clone(),
-                                Location::new(self.line_index, Span::single(self.line_offset)),
-                            ));
-                        }
-                    };
-                    literal.push(special);
-                    self.advance()?;
-                } else {
-                    if c == '"' {
-                        inside = false;
-                        self.advance()?;
-                        break;
-                    }
-                    if c == '\n' {
-                        break;
-                    }
-                    literal.push(c);
-                    self.advance()?;
-                }
+            if c == '}' {
+                self.advance()?;
+                break;
             }
+            if !c.is_ascii_hexdigit() || digits.len() == 6 {
+                return Err(invalid(self));
+            }
+            digits.push(c);
+            self.advance()?;
         }
-        if inside {
-            return Err(LexerError::General(
-                format!("Unexpected end of string literal",),
+        let code = u32::from_str_radix(&digits, 16).map_err(|_| invalid(self))?;
+        match std::char::from_u32(code) {
+            Some(c) => Ok(c),
+            None => Err(LexerError::General(
+                format!("Invalid unicode code point {:x}", code),
                 self.file_path.clone(),
                 Location::new(self.line_index, Span::new(start, self.line_offset)),
-            ));
+            )),
         }
-        let span = Span {
-            start: start,
-            end: self.line_offset,
-        };
-        self.add_token(Token::StringLiteral(literal), span);
-        Ok(())
     }
 
-    fn collect_char_literal(&mut self) -> Result<(), LexerError> {
+    fn collect_escape_sequence(&mut self) -> Result<char, LexerError> {
         let start = self.line_offset;
-        let mut prev_backslash = false;
-        let mut literal = String::new();
-        let mut inside = true;
         self.advance()?;
-        while !self.is_done() {
-            let c = self.peek()?;
-            if c == '\\' {
-                if prev_backslash {
-                    prev_backslash = false;
-                    literal.push(c);
-                    self.advance()?;
-                } else {
-                    prev_backslash = true;
+        let c = self.advance()?;
+        let special = match c {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            '\\' => '\\',
+            '"' => '"',
+            '\'' => '\'',
+            'u' => self.collect_unicode_escape(start)?,
+            _ => {
+                return Err(LexerError::General(
+                    format!("Invalid escape sequence \\{}", c),
+                    self.file_path.clone(),
+                    Location::new(self.line_index, Span::new(start, self.line_offset)),
+                ));
+            }
+        };
+        Ok(special)
+    }
+
+    // Literals spanning multiple lines are located at their first line, the span ends at the end
+    // of that line so error reports can still highlight the right columns.
+    fn collect_literal_body(
+        &mut self,
+        start: usize,
+        start_line: usize,
+        terminator: &str,
+        escapes: bool,
+        multiline: bool,
+        kind: &str,
+    ) -> Result<(String, Span), LexerError> {
+        let mut literal = String::new();
+        let mut first_line_end = None;
+        loop {
+            if self.follows(terminator) {
+                for _ in terminator.chars() {
                     self.advance()?;
                 }
-            } else {
-                if prev_backslash {
-                    prev_backslash = false;
-                    let special = match c {
-                        '\'' => '\'',
-                        _ => {
-                            return Err(LexerError::General(
-                                format!("Invalid escape sequence \\{}", c),
-                                self.file_path.clone(),
-                                Location::new(self.line_index, Span::single(self.line_offset)),
-                            ));
-                        }
-                    };
-                    literal.push(special);
-                    self.advance()?;
-                } else {
-                    if c == '\'' {
-                        inside = false;
-                        self.advance()?;
-                        break;
-                    }
-                    if c == '\n' {
-                        break;
-                    }
-                    literal.push(c);
-                    self.advance()?;
+                break;
+            }
+            let c = if self.is_done() { '\n' } else { self.peek()? };
+            if c == '\n' {
+                if first_line_end.is_none() {
+                    first_line_end = Some(self.line_offset);
+                }
+                if !multiline || self.is_done() {
+                    return Err(LexerError::General(
+                        format!("Unexpected end of {} literal", kind),
+                        self.file_path.clone(),
+                        Location::new(start_line, Span::new(start, first_line_end.unwrap())),
+                    ));
                 }
             }
+            if escapes && c == '\\' {
+                let special = self.collect_escape_sequence()?;
+                literal.push(special);
+            } else {
+                literal.push(c);
+                self.advance()?;
+            }
         }
-        if inside {
-            return Err(LexerError::General(
-                format!("Unexpected end of char literal",),
-                self.file_path.clone(),
-                Location::new(self.line_index, Span::new(start, self.line_offset)),
-            ));
-        }
-        let span = Span {
-            start: start,
-            end: self.line_offset,
+        let end = match first_line_end {
+            Some(end) => end,
+            None => self.line_offset,
         };
-        if literal.len() != 1 {
+        Ok((literal, Span::new(start, end)))
+    }
+
+    fn collect_string_literal(&mut self) -> Result<(), LexerError> {
+        let start = self.line_offset;
+        let start_line = self.line_index;
+        let (literal, span) = if self.follows("\"\"\"") {
+            for _ in 0..3 {
+                self.advance()?;
+            }
+            // the line break right after the opening quotes is not part of the string
+            let leading_line_break = if self.follows("\r\n") {
+                2
+            } else if self.follows("\n") {
+                1
+            } else {
+                0
+            };
+            let (literal, span) =
+                self.collect_literal_body(start, start_line, "\"\"\"", true, true, "string")?;
+            (literal.chars().skip(leading_line_break).collect(), span)
+        } else {
+            self.advance()?;
+            self.collect_literal_body(start, start_line, "\"", true, false, "string")?
+        };
+        self.add_token_at(Token::StringLiteral(literal), start_line, span);
+        Ok(())
+    }
+
+    fn collect_raw_string_literal(&mut self) -> Result<(), LexerError> {
+        let start = self.line_offset;
+        let start_line = self.line_index;
+        self.advance()?;
+        let mut terminator = "\"".to_string();
+        while self.peek()? == '#' {
+            self.advance()?;
+            terminator.push('#');
+        }
+        self.advance()?;
+        let (literal, span) =
+            self.collect_literal_body(start, start_line, &terminator, false, true, "raw string")?;
+        self.add_token_at(Token::StringLiteral(literal), start_line, span);
+        Ok(())
+    }
+
+    fn collect_char_literal(&mut self) -> Result<(), LexerError> {
+        let start = self.line_offset;
+        let start_line = self.line_index;
+        self.advance()?;
+        let (literal, span) =
+            self.collect_literal_body(start, start_line, "'", true, false, "char")?;
+        if literal.chars().count() != 1 {
             return Err(LexerError::General(
                 format!("Invalid char literal"),
                 self.file_path.clone(),
@@ -323,6 +408,171 @@ impl Lexer {
         Ok(())
     }
 
+    fn number_error(&self, msg: String, span: Span) -> LexerError {
+        LexerError::General(
+            msg,
+            self.file_path.clone(),
+            Location::new(self.line_index, span),
+        )
+    }
+
+    // Digit separators are only allowed between two digits
+    fn check_digits(
+        &self,
+        digits: &str,
+        start: usize,
+        offset: usize,
+        radix: u32,
+        kind: &str,
+    ) -> Result<String, LexerError> {
+        let chars: Vec<_> = digits.chars().collect();
+        if chars.is_empty() {
+            return Err(self.number_error(
+                format!("Missing digits in {} literal", kind),
+                Span::new(start, offset),
+            ));
+        }
+        for (index, c) in chars.iter().enumerate() {
+            if *c == '_' {
+                let misplaced = index == 0 || index == chars.len() - 1 || chars[index + 1] == '_';
+                if misplaced {
+                    return Err(self.number_error(
+                        format!("Misplaced digit separator in {} literal", kind),
+                        Span::single(offset + index),
+                    ));
+                }
+            } else if !c.is_digit(radix) {
+                return Err(self.number_error(
+                    format!("Invalid digit '{}' in {} literal", c, kind),
+                    Span::single(offset + index),
+                ));
+            }
+        }
+        Ok(chars.into_iter().filter(|c| *c != '_').collect())
+    }
+
+    fn collect_digits(
+        &mut self,
+        start: usize,
+        radix: u32,
+        kind: &str,
+    ) -> Result<String, LexerError> {
+        let offset = self.line_offset;
+        let (digits, _) = self.collect(|c| c.is_ascii_alphanumeric() || c == '_')?;
+        self.check_digits(&digits, start, offset, radix, kind)
+    }
+
+    fn collect_decimal_digits(&mut self, start: usize, kind: &str) -> Result<String, LexerError> {
+        let offset = self.line_offset;
+        let (digits, _) = self.collect(|c| c.is_ascii_digit() || c == '_')?;
+        self.check_digits(&digits, start, offset, 10, kind)
+    }
+
+    fn check_number_suffix(&mut self) -> Result<(), LexerError> {
+        let (suffix, span) = self.collect(|c| Lexer::is_identifier(c))?;
+        if !suffix.is_empty() {
+            return Err(self.number_error(
+                format!("Invalid suffix {} for numeric literal", suffix),
+                span,
+            ));
+        }
+        Ok(())
+    }
+
+    fn collect_radix_integer(&mut self, start: usize) -> Result<Token, LexerError> {
+        self.advance()?;
+        let (radix, kind) = match self.advance()? {
+            'x' | 'X' => (16, "hexadecimal"),
+            'o' | 'O' => (8, "octal"),
+            _ => (2, "binary"),
+        };
+        let digits = self.collect_digits(start, radix, kind)?;
+        match i64::from_str_radix(&digits, radix) {
+            Ok(v) => Ok(Token::IntegerLiteral(v)),
+            Err(_) => Err(self.number_error(
+                format!("Integer literal out of range"),
+                Span::new(start, self.line_offset),
+            )),
+        }
+    }
+
+    fn collect_decimal_number(&mut self, start: usize) -> Result<Token, LexerError> {
+        // after a dot the number is a tuple member index, t.0.1 is not a float
+        let after_dot = match self.tokens.last() {
+            Some(token) => token.token.kind() == TokenKind::Dot,
+            None => false,
+        };
+        let mut literal = self.collect_decimal_digits(start, "decimal")?;
+        let mut is_float = false;
+        if !after_dot && !self.is_done() && self.peek()? == '.' {
+            if let Some(next) = self.peek_next() {
+                if next.is_ascii_digit() {
+                    self.advance()?;
+                    let fraction = self.collect_decimal_digits(start, "float")?;
+                    literal = format!("{}.{}", literal, fraction);
+                    is_float = true;
+                }
+            }
+        }
+        if !after_dot && !self.is_done() {
+            let c = self.peek()?;
+            let exponent_follows = match self.peek_next() {
+                Some(next) => next.is_ascii_digit() || next == '+' || next == '-',
+                None => false,
+            };
+            if (c == 'e' || c == 'E') && exponent_follows {
+                self.advance()?;
+                let mut sign = String::new();
+                let c = self.peek()?;
+                if c == '+' || c == '-' {
+                    self.advance()?;
+                    sign.push(c);
+                }
+                let exponent = self.collect_decimal_digits(start, "float exponent")?;
+                literal = format!("{}e{}{}", literal, sign, exponent);
+                is_float = true;
+            }
+        }
+        self.check_number_suffix()?;
+        let span = Span::new(start, self.line_offset);
+        if is_float {
+            match literal.parse::<f64>() {
+                Ok(v) if v.is_finite() => Ok(Token::FloatLiteral(v)),
+                _ => Err(self.number_error(format!("Float literal out of range"), span)),
+            }
+        } else {
+            match literal.parse::<i64>() {
+                Ok(v) => Ok(Token::IntegerLiteral(v)),
+                Err(_) => Err(self.number_error(format!("Integer literal out of range"), span)),
+            }
+        }
+    }
+
+    fn collect_number(&mut self, errors: &mut Vec<LexerError>) -> Result<(), LexerError> {
+        let start = self.line_offset;
+        let is_radix_literal = self.peek()? == '0'
+            && match self.peek_next() {
+                Some('x') | Some('X') | Some('o') | Some('O') | Some('b') | Some('B') => true,
+                _ => false,
+            };
+        let result = if is_radix_literal {
+            self.collect_radix_integer(start)
+        } else {
+            self.collect_decimal_number(start)
+        };
+        let token = match result {
+            Ok(token) => token,
+            Err(err) => {
+                errors.push(err);
+                // skip the rest of the malformed literal and keep lexing
+                self.collect(|c| Lexer::is_identifier(c))?;
+                Token::IntegerLiteral(0)
+            }
+        };
+        self.add_token(token, Span::new(start, self.line_offset));
+        Ok(())
+    }
+
     fn process_line_comment(&mut self) -> Result<(), LexerError> {
         while !self.is_done() {
             let c = self.peek()?;
@@ -373,158 +623,173 @@ impl Lexer {
         Ok(())
     }
 
-    pub fn process(&mut self, errors: &mut Vec<LexerError>) -> Result<Vec<TokenInfo>, LexerError> {
-        loop {
-            if self.is_done() {
-                break;
+    fn add_comment(&mut self, start_index: usize, start_line: usize, start_offset: usize) {
+        let text: String = self.input[start_index..self.index].iter().collect();
+        if self.keep_trivia {
+            let span = Span::new(start_offset, start_offset + 1);
+            self.add_token_at(Token::Comment(text), start_line, span);
+        } else if text.starts_with(DOC_COMMENT_PREFIX) {
+            // only comments on their own line document the next item
+            let alone = match self.tokens.last() {
+                Some(token) => token.location.line != start_line,
+                None => true,
+            };
+            if alone {
+                self.doc_comments.add(start_line, &text);
             }
-            let c = self.peek()?;
-            if Lexer::is_identifier(c) {
-                self.collect_identifier()?;
-            } else if Lexer::is_operator(c) {
-                match self.peek_next() {
-                    Some(next_char) => match (c, next_char) {
-                        ('/', '/') | ('-', '-') => {
-                            self.process_line_comment()?;
-                            continue;
-                        }
-                        ('/', '*') => {
-                            self.advance()?;
-                            self.advance()?;
-                            self.process_block_comment(('*', '/'))?;
-                            continue;
-                        }
-                        ('{', '-') => {
-                            self.advance()?;
-                            self.advance()?;
-                            self.process_block_comment(('-', '}'))?;
-                            continue;
-                        }
-                        _ => {}
-                    },
-                    None => {}
-                }
-                self.collect_operator()?;
-            } else if c == '"' {
-                self.collect_string_literal()?;
-            } else if c == '\'' {
-                self.collect_char_literal()?;
-            } else {
-                let span = Span::single(self.line_offset);
-                let t = match c {
-                    ' ' | '\t' => {
-                        self.advance()?;
-                        continue;
-                    }
-                    '\n' => {
-                        self.advance()?;
-                        continue;
+        }
+    }
+
+    fn scan_next(&mut self, errors: &mut Vec<LexerError>) -> Result<(), LexerError> {
+        let start_index = self.index;
+        let start_line = self.line_index;
+        let start_offset = self.line_offset;
+        let c = self.peek()?;
+        if c.is_ascii_digit() {
+            self.collect_number(errors)?;
+        } else if self.raw_string_follows() {
+            self.collect_raw_string_literal()?;
+        } else if Lexer::is_identifier(c) {
+            self.collect_identifier()?;
+        } else if Lexer::is_operator(c) {
+            match self.peek_next() {
+                Some(next_char) => match (c, next_char) {
+                    ('/', '/') | ('-', '-') => {
+                        self.process_line_comment()?;
+                        self.add_comment(start_index, start_line, start_offset);
+                        return Ok(());
                     }
-                    '\x0d' => {
+                    ('/', '*') => {
                         self.advance()?;
-                        continue;
-                    }
-                    '=' => Token::Equal,
-                    ',' => Token::Comma,
-                    '{' => Token::LCurly,
-                    '}' => Token::RCurly,
-                    '(' => Token::LParen,
-                    ')' => Token::RParen,
-                    '[' => Token::LBracket,
-                    ']' => Token::RBracket,
-                    ';' => Token::Semicolon,
-                    '%' => Token::Formatter,
-                    _ => {
-                        let err = LexerError::UnsupportedCharacter(
-                            c,
-                            LocationInfo {
-                                file_path: self.file_path.clone(),
-                                location: Location::new(self.line_index, span),
-                            },
-                        );
-                        errors.push(err);
                         self.advance()?;
-                        continue;
+                        self.process_block_comment(('*', '/'))?;
+                        self.add_comment(start_index, start_line, start_offset);
+                        return Ok(());
                     }
-                };
-                self.add_token(t, span);
-                self.advance()?;
+                    _ => {}
+                },
+                None => {}
             }
+            self.collect_operator()?;
+        } else if c == '{' && self.peek_next() == Some('-') {
+            self.advance()?;
+            self.advance()?;
+            self.process_block_comment(('-', '}'))?;
+            self.add_comment(start_index, start_line, start_offset);
+        } else if c == '"' {
+            self.collect_string_literal()?;
+        } else if c == '\'' {
+            self.collect_char_literal()?;
+        } else {
+            let span = Span::single(self.line_offset);
+            let t = match c {
+                ' ' | '\t' => {
+                    self.advance()?;
+                    return Ok(());
+                }
+                '\n' => {
+                    self.advance()?;
+                    return Ok(());
+                }
+                '\x0d' => {
+                    self.advance()?;
+                    return Ok(());
+                }
+                '=' => Token::Equal,
+                ',' => Token::Comma,
+                '{' => Token::LCurly,
+                '}' => Token::RCurly,
+                '(' => Token::LParen,
+                ')' => Token::RParen,
+                '[' => Token::LBracket,
+                ']' => Token::RBracket,
+                ';' => Token::Semicolon,
+                '%' => Token::Formatter,
+                '`' => Token::Backtick,
+                _ => {
+                    let err = LexerError::UnsupportedCharacter(
+                        c,
+                        LocationInfo {
+                            file_path: self.file_path.clone(),
+                            location: Location::new(self.line_index, span),
+                        },
+                    );
+                    errors.push(err);
+                    self.advance()?;
+                    return Ok(());
+                }
+            };
+            self.add_token(t, span);
+            self.advance()?;
         }
-        let mut token_iterator = TokenIterator::new(self.tokens.clone());
-        process_program(&mut token_iterator, &self.file_path)?;
-        let mut result = token_iterator.result;
-        result.pop();
+        Ok(())
+    }
 
-        let mut index = 0;
-
-        let mut new_result = Vec::new();
-
-        while index < result.len() {
-            if let Token::VarIdentifier(v1) = &result[index].token {
-                if index + 2 < result.len() {
-                    let prev_dot = if index > 0 {
-                        result[index - 1].token.kind() == TokenKind::Dot
-                    } else {
-                        false
-                    };
-                    if let Token::Dot = result[index + 1].token {
-                        if !prev_dot {
-                            let t2 = &result[index + 2];
-                            if let Token::VarIdentifier(v2) = &t2.token {
-                                let v = format!("{}.{}", v1, v2);
-                                match v.parse::<f64>() {
-                                    Ok(v) => {
-                                        let mut new_token = result[index].clone();
-                                        new_token.token = Token::FloatLiteral(v);
-                                        new_token.location.span.end = t2.location.span.end;
-                                        new_result.push(new_token);
-                                        index += 3;
-                                        continue;
-                                    }
-                                    _ => {}
-                                }
-                            }
-                        }
-                    }
-                }
+    fn scan(&mut self, errors: &mut Vec<LexerError>) -> Result<(), LexerError> {
+        while !self.is_done() {
+            let start_index = self.index;
+            let token_count = self.tokens.len();
+            self.scan_next(errors)?;
+            if self.tokens.len() > token_count {
+                self.end_lines.push(self.line_index);
             }
-            new_result.push(result[index].clone());
-            index += 1;
-            continue;
-        }
-
-        let new_result = new_result
-            .into_iter()
-            .map(|token| {
-                if let Token::VarIdentifier(v) = &token.token {
-                    match v.parse() {
-                        Ok(v) => {
-                            let mut new_token = token.clone();
-                            new_token.token = Token::IntegerLiteral(v);
-                            return new_token;
-                        }
-                        _ => {}
-                    }
-                }
-                return token;
+            if self.keep_trivia && self.tokens.len() > token_count {
+                let text = self.input[start_index..self.index].iter().collect();
+                self.source_texts
+                    .push((text, self.line_index, self.line_offset));
+            }
+        }
+        Ok(())
+    }
+
+    // Returns every token with its original text, comments included, without layout processing.
+    // Used by tools that have to reproduce the source, like the formatter.
+    pub fn process_source(
+        &mut self,
+        errors: &mut Vec<LexerError>,
+    ) -> Result<Vec<SourceToken>, LexerError> {
+        self.keep_trivia = true;
+        self.scan(errors)?;
+        let tokens = self
+            .tokens
+            .iter()
+            .zip(self.source_texts.iter())
+            .map(|(info, (text, end_line, end_offset))| SourceToken {
+                info: info.clone(),
+                text: text.clone(),
+                end_line: *end_line,
+                end_offset: *end_offset,
             })
             .collect();
+        Ok(tokens)
+    }
 
-        Ok(new_result)
+    pub fn process(&mut self, errors: &mut Vec<LexerError>) -> Result<Vec<TokenInfo>, LexerError> {
+        self.scan(errors)?;
+        let mut token_iterator = TokenIterator::new(self.tokens.clone(), self.end_lines.clone());
+        process_program(&mut token_iterator, &self.file_path)?;
+        let mut result = token_iterator.result;
+        result.pop();
+        Ok(result)
+    }
+
+    pub fn take_doc_comments(&mut self) -> DocComments {
+        std::mem::take(&mut self.doc_comments)
     }
 }
 
 struct TokenIterator {
-    tokens: Vec<TokenInfo>,
+    tokens: Vec<(TokenInfo, usize)>,
     result: Vec<TokenInfo>,
+    last_end_line: Option<usize>,
 }
 
 impl TokenIterator {
-    fn new(tokens: Vec<TokenInfo>) -> TokenIterator {
+    fn new(tokens: Vec<TokenInfo>, end_lines: Vec<usize>) -> TokenIterator {
         TokenIterator {
-            tokens: tokens,
+            tokens: tokens.into_iter().zip(end_lines).collect(),
             result: Vec::new(),
+            last_end_line: None,
         }
     }
 
@@ -533,11 +798,19 @@ impl TokenIterator {
     }
 
     fn peek(&self) -> TokenInfo {
-        self.tokens.first().expect("ran out of tokeninfo").clone()
+        self.tokens.first().expect("ran out of tokeninfo").0.clone()
     }
 
     fn advance(&mut self) -> TokenInfo {
-        self.tokens.remove(0)
+        let (info, end_line) = self.tokens.remove(0);
+        self.last_end_line = Some(end_line);
+        info
+    }
+
+    // Whether the next token is the first one on its line, a token following a
+    // multi-line literal on the line where the literal ends continues that line
+    fn starts_line(&self) -> bool {
+        self.last_end_line != Some(self.peek().location.line)
     }
 
     fn add_end(&mut self, token: Token) {
@@ -563,8 +836,15 @@ fn process_block(
         ));
     }
     let first = iterator.peek();
+    let let_block = block_token.token.kind() == TokenKind::KeywordLet;
     while !iterator.is_done() {
-        let end_of_block = process_item(iterator, first.location.clone(), module, file_path)?;
+        let end_of_block = process_item(
+            iterator,
+            first.location.clone(),
+            module,
+            let_block,
+            file_path,
+        )?;
         if end_of_block {
             break;
         }
@@ -600,16 +880,18 @@ fn process_item(
     iterator: &mut TokenIterator,
     start: Location,
     module: bool,
+    let_block: bool,
     file_path: &FilePath,
 ) -> Result<bool, LexerError> {
     let mut first = true;
     let mut paren_level = 0;
     while !iterator.is_done() {
         let info = iterator.peek();
+        let first_token = first;
         if first {
             first = false;
         } else {
-            if info.location.span.start <= start.span.start {
+            if iterator.starts_line() && info.location.span.start <= start.span.start {
                 if !module {
                     iterator.add_end(Token::EndOfItem);
                 }
@@ -619,6 +901,12 @@ fn process_item(
         if info.token.kind() == TokenKind::KeywordModule {
             return Ok(true);
         }
+        if let_block && paren_level == 0 && info.token.kind() == TokenKind::KeywordIn {
+            if !first_token {
+                iterator.add_end(Token::EndOfItem);
+            }
+            return Ok(true);
+        }
         if info.token.kind() == TokenKind::LParen {
             paren_level += 1;
         }
@@ -632,9 +920,19 @@ fn process_item(
         if info.token.kind() == TokenKind::KeywordWhere
             || info.token.kind() == TokenKind::KeywordDo
             || info.token.kind() == TokenKind::KeywordOf
+            || info.token.kind() == TokenKind::KeywordLet
         {
             iterator.advance();
+            let let_token = info.token.kind() == TokenKind::KeywordLet;
             process_block(iterator, info, false, file_path)?;
+            if let_token && !iterator.is_done() {
+                // the closing in belongs to the let, regardless of its column
+                let next = iterator.peek();
+                if next.token.kind() == TokenKind::KeywordIn {
+                    iterator.result.push(next);
+                    iterator.advance();
+                }
+            }
         } else {
             iterator.advance();
         }
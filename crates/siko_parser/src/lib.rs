@@ -1,5 +1,7 @@
+pub mod doc_comment;
 pub mod error;
 mod expr;
+pub mod fixity;
 pub mod lexer;
 pub mod parser;
 pub mod token;
@@ -5,13 +5,18 @@ use super::util::report_parser_error;
 use super::util::report_unexpected_token;
 use super::util::ParenParseResult;
 use super::util::ParserErrorReason;
+use crate::doc_comment::DocComments;
 use crate::error::ParseError;
+use crate::fixity::FixityTable;
+use crate::fixity::MAX_PRECEDENCE;
 use crate::token::Token;
 use crate::token::TokenInfo;
 use crate::token::TokenKind;
 use siko_constants::get_implicit_module_list;
 use siko_constants::get_qualified_list_type_name;
 use siko_constants::BuiltinOperator;
+use siko_constants::ACTOR_HANDLES_CLASS_NAME;
+use siko_constants::STD_ACTOR_MODULE_NAME;
 use siko_location_info::filepath::FilePath;
 use siko_location_info::item::Item;
 use siko_location_info::item::ItemInfo;
@@ -31,6 +36,7 @@ use siko_syntax::data::Data;
 use siko_syntax::data::DerivedClass;
 use siko_syntax::data::Record;
 use siko_syntax::data::RecordField;
+use siko_syntax::data::TypeAlias;
 use siko_syntax::data::Variant;
 use siko_syntax::data::VariantId;
 use siko_syntax::export_import::EIGroup;
@@ -41,6 +47,9 @@ use siko_syntax::export_import::EIMember;
 use siko_syntax::export_import::EIMemberInfo;
 use siko_syntax::expr::Expr;
 use siko_syntax::expr::ExprId;
+use siko_syntax::fixity::Associativity;
+use siko_syntax::fixity::Fixity;
+use siko_syntax::fixity::FixityDeclaration;
 use siko_syntax::function::Function;
 use siko_syntax::function::FunctionBody;
 use siko_syntax::function::FunctionId;
@@ -65,15 +74,46 @@ enum FunctionOrFunctionType {
     FunctionType(String, FunctionTypeId),
 }
 
+// The clauses of a function are grouped while they follow each other, a clause
+// separated from the previous ones by another item starts a new definition
+fn add_function_clause(
+    functions: &mut BTreeMap<String, Vec<Vec<FunctionId>>>,
+    previous_function: &Option<String>,
+    name: String,
+    function_id: FunctionId,
+) {
+    let definitions = functions.entry(name.clone()).or_insert_with(|| Vec::new());
+    match definitions.last_mut() {
+        Some(clauses) if previous_function.as_ref() == Some(&name) => clauses.push(function_id),
+        _ => definitions.push(vec![function_id]),
+    }
+}
+
 fn parse_class_constraint(parser: &mut Parser) -> Result<Constraint, ParseError> {
     let start_index = parser.get_index();
+    // (a <- p) means that the actor a handles the protocol p
+    if parser.current_kind() == TokenKind::VarIdentifier {
+        let actor = parser.var_identifier("actor type arg")?;
+        parser.expect(TokenKind::Op(BuiltinOperator::Bind))?;
+        let protocol = parser.var_identifier("protocol type arg")?;
+        let end_index = parser.get_index();
+        let location_id = parser.get_location_id(start_index, end_index);
+        return Ok(Constraint {
+            class_name: format!("{}.{}", STD_ACTOR_MODULE_NAME, ACTOR_HANDLES_CLASS_NAME),
+            args: vec![actor, protocol],
+            location_id: location_id,
+        });
+    }
     let name = parser.parse_qualified_type_name()?;
-    let arg = parser.var_identifier("type arg")?;
+    let mut args = vec![parser.var_identifier("type arg")?];
+    while parser.current_kind() == TokenKind::VarIdentifier {
+        args.push(parser.var_identifier("type arg")?);
+    }
     let end_index = parser.get_index();
     let location_id = parser.get_location_id(start_index, end_index);
     let constraint = Constraint {
         class_name: name,
-        arg: arg,
+        args: args,
         location_id: location_id,
     };
     Ok(constraint)
@@ -85,7 +125,10 @@ pub struct Parser<'a> {
     index: usize,
     program: &'a mut Program,
     location_info: &'a mut LocationInfo,
+    fixities: &'a FixityTable,
+    doc_comments: &'a DocComments,
     temp_var_counter: Counter,
+    errors: Vec<ParseError>,
 }
 
 impl<'a> Parser<'a> {
@@ -94,6 +137,8 @@ impl<'a> Parser<'a> {
         tokens: &'a [TokenInfo],
         program: &'a mut Program,
         location_info: &'a mut LocationInfo,
+        fixities: &'a FixityTable,
+        doc_comments: &'a DocComments,
     ) -> Parser<'a> {
         Parser {
             file_path: file_path,
@@ -101,7 +146,10 @@ impl<'a> Parser<'a> {
             index: 0,
             program: program,
             location_info: location_info,
+            fixities: fixities,
+            doc_comments: doc_comments,
             temp_var_counter: Counter::new(),
+            errors: Vec::new(),
         }
     }
 
@@ -117,6 +165,48 @@ impl<'a> Parser<'a> {
         self.index
     }
 
+    // Returns the doc comment preceding the line of the token at the given index
+    fn get_doc(&self, index: usize) -> Option<String> {
+        let line = self.tokens[index].location.line;
+        self.doc_comments.get(line)
+    }
+
+    // Records the error and skips the item that started at start_index, nested blocks included.
+    // Stops after the EndOfItem closing the item or before the EndOfBlock/EndOfModule closing the
+    // enclosing block, so the caller can continue with the next item.
+    pub fn recover(&mut self, err: ParseError, start_index: usize) {
+        self.errors.push(err);
+        self.index = start_index;
+        let mut level = 0;
+        while let Some(token) = self.peek() {
+            match token.token.kind() {
+                TokenKind::KeywordWhere
+                | TokenKind::KeywordDo
+                | TokenKind::KeywordOf
+                | TokenKind::KeywordLet => {
+                    level += 1;
+                }
+                TokenKind::EndOfBlock => {
+                    if level == 0 {
+                        break;
+                    }
+                    level -= 1;
+                }
+                TokenKind::EndOfItem => {
+                    if level == 0 {
+                        self.index += 1;
+                        break;
+                    }
+                }
+                TokenKind::EndOfModule => {
+                    break;
+                }
+                _ => {}
+            }
+            self.index += 1;
+        }
+    }
+
     pub fn get_location_id(&mut self, start: usize, end: usize) -> LocationId {
         let mut set = LocationSet::new(self.file_path.clone());
         for token in &self.tokens[start..end] {
@@ -162,27 +252,173 @@ impl<'a> Parser<'a> {
         }
     }
 
+    pub fn peek_at(&self, offset: usize) -> Option<TokenInfo> {
+        self.tokens.get(self.index + offset).cloned()
+    }
+
+    pub fn get_fixity(&self, operator: &str) -> Fixity {
+        self.fixities.get(operator)
+    }
+
     pub fn get_temp_var_name(&mut self) -> String {
         format!("${}", self.temp_var_counter.next())
     }
 
     pub fn irrefutable_pattern_follows(&self) -> bool {
         let mut index = self.index;
+        // a bind nested in brackets belongs to a list comprehension
+        let mut depth = 0;
         while index < self.tokens.len() {
-            if self.tokens[index].token.kind() == TokenKind::EndOfItem {
-                return false;
-            }
-            if self.tokens[index].token.kind() == TokenKind::KeywordDo {
-                return false;
+            match self.tokens[index].token.kind() {
+                TokenKind::EndOfItem | TokenKind::KeywordDo => return false,
+                TokenKind::LBracket => depth += 1,
+                TokenKind::RBracket => depth -= 1,
+                TokenKind::Op(BuiltinOperator::Bind) if depth == 0 => return true,
+                _ => {}
             }
-            if self.tokens[index].token.kind() == TokenKind::Op(BuiltinOperator::Bind) {
-                return true;
+            index += 1;
+        }
+        false
+    }
+
+    // Looks for a '<-' in the current list comprehension qualifier
+    pub fn generator_follows(&self) -> bool {
+        let mut index = self.index;
+        let mut depth = 0;
+        while index < self.tokens.len() {
+            match self.tokens[index].token.kind() {
+                TokenKind::LParen | TokenKind::LBracket | TokenKind::LCurly => depth += 1,
+                TokenKind::RParen | TokenKind::RCurly => depth -= 1,
+                TokenKind::RBracket => {
+                    if depth == 0 {
+                        return false;
+                    }
+                    depth -= 1;
+                }
+                TokenKind::Comma | TokenKind::EndOfItem if depth == 0 => return false,
+                TokenKind::Op(BuiltinOperator::Bind) if depth == 0 => return true,
+                _ => {}
             }
             index += 1;
         }
         false
     }
 
+    fn operator_name_follows(&self) -> bool {
+        let kinds: Vec<_> = (0..3)
+            .map(|offset| self.peek_at(offset).map(|t| t.token.kind()))
+            .collect();
+        kinds[0] == Some(TokenKind::LParen)
+            && kinds[1] == Some(TokenKind::UserOperator)
+            && kinds[2] == Some(TokenKind::RParen)
+    }
+
+    pub fn parse_operator_name(&mut self) -> Result<String, ParseError> {
+        self.expect(TokenKind::LParen)?;
+        let token_info = self.peek().expect("Ran out of tokens");
+        if let Token::UserOperator(name) = token_info.token {
+            self.advance()?;
+            self.expect(TokenKind::RParen)?;
+            Ok(name)
+        } else {
+            report_unexpected_token(self, format!("operator"))
+        }
+    }
+
+    fn infix_definition_follows(&self) -> bool {
+        let mut index = self.index;
+        match self.tokens[index].token.kind() {
+            TokenKind::VarIdentifier | TokenKind::Wildcard => {
+                index += 1;
+            }
+            TokenKind::LParen => {
+                let mut level = 0;
+                while index < self.tokens.len() {
+                    match self.tokens[index].token.kind() {
+                        TokenKind::LParen => level += 1,
+                        TokenKind::RParen => level -= 1,
+                        TokenKind::EndOfItem => return false,
+                        _ => {}
+                    }
+                    index += 1;
+                    if level == 0 {
+                        break;
+                    }
+                }
+            }
+            _ => return false,
+        }
+        match self.tokens.get(index).map(|t| t.token.kind()) {
+            Some(TokenKind::UserOperator) | Some(TokenKind::Backtick) => true,
+            _ => false,
+        }
+    }
+
+    fn parse_infix_operator_name(&mut self) -> Result<String, ParseError> {
+        let token_info = self.peek().expect("Ran out of tokens");
+        match token_info.token {
+            Token::UserOperator(name) => {
+                self.advance()?;
+                Ok(name)
+            }
+            Token::Backtick => {
+                self.advance()?;
+                let name = self.var_identifier("function name")?;
+                self.expect(TokenKind::Backtick)?;
+                Ok(name)
+            }
+            _ => report_unexpected_token(self, format!("operator")),
+        }
+    }
+
+    fn parse_fixity_declaration(&mut self) -> Result<FixityDeclaration, ParseError> {
+        let start_index = self.get_index();
+        let associativity = match self.advance()?.token {
+            Token::KeywordInfixl => Associativity::Left,
+            Token::KeywordInfixr => Associativity::Right,
+            Token::KeywordInfix => Associativity::None,
+            _ => unreachable!(),
+        };
+        let token_info = self.peek().expect("Ran out of tokens");
+        let precedence = match token_info.token {
+            Token::IntegerLiteral(n) if n >= 0 && n as usize <= MAX_PRECEDENCE => {
+                self.advance()?;
+                n as usize
+            }
+            _ => {
+                return report_unexpected_token(
+                    self,
+                    format!("precedence between 0 and {}", MAX_PRECEDENCE),
+                );
+            }
+        };
+        let mut operators = Vec::new();
+        loop {
+            let token_info = self.peek().expect("Ran out of tokens");
+            let operator = match token_info.token {
+                Token::Op(op) if op.is_binary() => {
+                    self.advance()?;
+                    op.get_symbol().to_string()
+                }
+                _ => self.parse_infix_operator_name()?,
+            };
+            operators.push(operator);
+            if self.current(TokenKind::Comma) {
+                self.expect(TokenKind::Comma)?;
+            } else {
+                break;
+            }
+        }
+        let end_index = self.get_index();
+        let location_id = self.get_location_id(start_index, end_index);
+        self.expect(TokenKind::EndOfItem)?;
+        Ok(FixityDeclaration {
+            fixity: Fixity::new(associativity, precedence),
+            operators: operators,
+            location_id: location_id,
+        })
+    }
+
     fn constraint_follows(&self) -> bool {
         let mut index = self.index;
         while index < self.tokens.len() {
@@ -249,8 +485,11 @@ impl<'a> Parser<'a> {
         loop {
             match self.current_kind() {
                 TokenKind::LParen
+                | TokenKind::LBracket
                 | TokenKind::IntegerLiteral
                 | TokenKind::FloatLiteral
+                | TokenKind::Op(BuiltinOperator::Sub)
+                | TokenKind::CharLiteral
                 | TokenKind::StringLiteral
                 | TokenKind::VarIdentifier
                 | TokenKind::TypeIdentifier
@@ -459,6 +698,47 @@ impl<'a> Parser<'a> {
         Ok(from)
     }
 
+    fn parse_type_args(
+        &mut self,
+        allow_wildcard: bool,
+    ) -> Result<Vec<TypeSignatureId>, ParseError> {
+        let mut args = Vec::new();
+        loop {
+            match self.current_kind() {
+                TokenKind::TypeIdentifier => {
+                    let arg_start_index = self.get_index();
+                    let arg = self.parse_qualified_type_name()?;
+                    let arg = self
+                        .add_type_signature(TypeSignature::Named(arg, Vec::new()), arg_start_index);
+                    args.push(arg);
+                }
+                TokenKind::VarIdentifier => {
+                    let arg_start_index = self.get_index();
+                    let arg = self.var_identifier("type arg")?;
+                    let arg = self.add_type_signature(TypeSignature::TypeArg(arg), arg_start_index);
+                    args.push(arg);
+                }
+                TokenKind::LParen => {
+                    let arg = self.parse_tuple_type(allow_wildcard)?;
+                    args.push(arg);
+                }
+                TokenKind::LBracket => {
+                    let arg_start_index = self.get_index();
+                    self.expect(TokenKind::LBracket)?;
+                    let arg = self.parse_function_type(false, allow_wildcard)?;
+                    self.expect(TokenKind::RBracket)?;
+                    let ty = TypeSignature::Named(get_qualified_list_type_name(), vec![arg]);
+                    let id = self.add_type_signature(ty, arg_start_index);
+                    args.push(id);
+                }
+                _ => {
+                    break;
+                }
+            }
+        }
+        Ok(args)
+    }
+
     fn parse_type_part(
         &mut self,
         parsing_variant: bool,
@@ -480,45 +760,7 @@ impl<'a> Parser<'a> {
                 }
                 Token::TypeIdentifier(_) => {
                     let name = self.parse_qualified_type_name()?;
-                    let mut args = Vec::new();
-                    loop {
-                        match self.current_kind() {
-                            TokenKind::TypeIdentifier => {
-                                let arg_start_index = self.get_index();
-                                let arg = self.parse_qualified_type_name()?;
-                                let arg = self.add_type_signature(
-                                    TypeSignature::Named(arg, Vec::new()),
-                                    arg_start_index,
-                                );
-                                args.push(arg);
-                            }
-                            TokenKind::VarIdentifier => {
-                                let arg_start_index = self.get_index();
-                                let arg = self.var_identifier("type arg")?;
-                                let arg = self.add_type_signature(
-                                    TypeSignature::TypeArg(arg),
-                                    arg_start_index,
-                                );
-                                args.push(arg);
-                            }
-                            TokenKind::LParen => {
-                                let arg = self.parse_tuple_type(allow_wildcard)?;
-                                args.push(arg);
-                            }
-                            TokenKind::LBracket => {
-                                self.expect(TokenKind::LBracket)?;
-                                let arg = self.parse_function_type(false, allow_wildcard)?;
-                                self.expect(TokenKind::RBracket)?;
-                                let ty =
-                                    TypeSignature::Named(get_qualified_list_type_name(), vec![arg]);
-                                let id = self.add_type_signature(ty, start_index);
-                                args.push(id);
-                            }
-                            _ => {
-                                break;
-                            }
-                        }
-                    }
+                    let args = self.parse_type_args(allow_wildcard)?;
                     let ty = if parsing_variant {
                         TypeSignature::Variant(name, args)
                     } else {
@@ -529,7 +771,13 @@ impl<'a> Parser<'a> {
                 }
                 Token::VarIdentifier(_) => {
                     let name = self.var_identifier("type arg")?;
-                    let ty = TypeSignature::TypeArg(name);
+                    let args = self.parse_type_args(allow_wildcard)?;
+                    // a type argument applied to arguments is a type constructor variable
+                    let ty = if args.is_empty() {
+                        TypeSignature::TypeArg(name)
+                    } else {
+                        TypeSignature::TypeArgApplication(name, args)
+                    };
                     let id = self.add_type_signature(ty, start_index);
                     return Ok(id);
                 }
@@ -558,9 +806,20 @@ impl<'a> Parser<'a> {
 
     fn parse_function_or_function_type(&mut self) -> Result<FunctionOrFunctionType, ParseError> {
         let start_index = self.get_index();
-        let name = self.var_identifier("function name")?;
-        let name = name;
-        let args = self.parse_pattern_args()?;
+        let (name, args) = if self.operator_name_follows() {
+            let name = self.parse_operator_name()?;
+            let args = self.parse_pattern_args()?;
+            (name, args)
+        } else if self.infix_definition_follows() {
+            let left = parse_pattern(self)?;
+            let name = self.parse_infix_operator_name()?;
+            let right = parse_pattern(self)?;
+            (name, vec![left, right])
+        } else {
+            let name = self.var_identifier("function name")?;
+            let args = self.parse_pattern_args()?;
+            (name, args)
+        };
         if self.current(TokenKind::KeywordDoubleColon) {
             self.advance()?;
             let constraints = if self.constraint_follows() {
@@ -608,6 +867,7 @@ impl<'a> Parser<'a> {
                 full_type_signature_id: full_type_signature_id,
                 type_signature_id: type_signature_id,
                 location_id: location_id,
+                doc: self.get_doc(start_index),
             };
             self.expect(TokenKind::EndOfItem)?;
             self.program.function_types.add_item(id, function_type);
@@ -615,60 +875,59 @@ impl<'a> Parser<'a> {
         } else {
             let end_index = self.get_index();
             let location_id = self.get_location_id(start_index, end_index);
+            let guard = if self.current(TokenKind::KeywordIf) {
+                self.expect(TokenKind::KeywordIf)?;
+                Some(self.parse_expr()?)
+            } else {
+                None
+            };
             self.expect(TokenKind::Equal)?;
-            let mut temp_args = Vec::new();
             let body = if let Some(token) = self.peek() {
                 if token.token.kind() == TokenKind::KeywordExtern {
                     self.expect(TokenKind::KeywordExtern)?;
-                    for arg in args {
-                        let item_info = &self.program.patterns.get(&arg);
+                    for arg in &args {
+                        let item_info = &self.program.patterns.get(arg);
                         let pattern = &item_info.item;
                         let location_id = item_info.location_id;
-                        if let Pattern::Binding(n) = pattern {
-                            temp_args.push((n.clone(), location_id))
-                        } else {
-                            let location_set = self.location_info.get_item_location(&location_id);
-                            let (line, ranges) = location_set.lines.iter().next().unwrap();
-                            let location = Location {
-                                line: *line,
-                                span: Span {
-                                    start: ranges[0].start,
-                                    end: ranges[0].end,
-                                },
-                            };
-                            let err = ParseError::new(
-                                "Cannot use patterns in extern function arguments".to_string(),
-                                self.file_path.clone(),
-                                location,
-                            );
-                            return Err(err);
+                        match pattern {
+                            Pattern::Binding(_) => {}
+                            _ => {
+                                let location_set =
+                                    self.location_info.get_item_location(&location_id);
+                                let (line, ranges) = location_set.lines.iter().next().unwrap();
+                                let location = Location {
+                                    line: *line,
+                                    span: Span {
+                                        start: ranges[0].start,
+                                        end: ranges[0].end,
+                                    },
+                                };
+                                let err = ParseError::new(
+                                    "Cannot use patterns in extern function arguments".to_string(),
+                                    self.file_path.clone(),
+                                    location,
+                                );
+                                return Err(err);
+                            }
                         }
                     }
+                    if guard.is_some() {
+                        let reason = ParserErrorReason::Custom {
+                            msg: format!("extern function cannot have a guard"),
+                        };
+                        return report_parser_error(self, reason);
+                    }
                     FunctionBody::Extern
                 } else {
-                    let body_expr_id = self.parse_expr()?;
-                    if args.is_empty() {
-                        FunctionBody::Expr(body_expr_id)
-                    } else {
-                        let mut temp_arg_exprs = Vec::new();
-                        for arg in args.iter() {
-                            let location = self.get_program().patterns.get(arg).location_id;
-                            let temp_arg_name = self.get_temp_var_name();
-                            temp_args.push((temp_arg_name.clone(), location));
-                            let path_expr = Expr::Path(temp_arg_name);
-                            let path_expr_id = self.add_expr(path_expr, start_index);
-                            temp_arg_exprs.push(path_expr_id);
-                        }
-                        let tuple_expr = Expr::Tuple(temp_arg_exprs);
-                        let tuple_expr_id = self.add_expr(tuple_expr, start_index);
-                        let tuple_pattern = Pattern::Tuple(args.clone());
-                        let tuple_pattern_id = self.add_pattern(tuple_pattern, start_index);
-                        let bind_expr = Expr::Bind(tuple_pattern_id, tuple_expr_id);
-                        let bind_expr_id = self.add_expr(bind_expr, start_index);
-                        let do_expr = Expr::Do(vec![bind_expr_id, body_expr_id]);
-                        let do_expr_id = self.add_expr(do_expr, start_index);
-                        FunctionBody::Expr(do_expr_id)
+                    let mut body_expr_id = self.parse_expr()?;
+                    if self.current(TokenKind::KeywordWhere) {
+                        let where_start_index = self.get_index();
+                        self.expect(TokenKind::KeywordWhere)?;
+                        let local_functions = self.parse_local_functions()?;
+                        let expr = Expr::Let(local_functions, body_expr_id);
+                        body_expr_id = self.add_expr(expr, where_start_index);
                     }
+                    FunctionBody::Expr(body_expr_id)
                 }
             } else {
                 unreachable!()
@@ -678,15 +937,75 @@ impl<'a> Parser<'a> {
             let function = Function {
                 id: id,
                 name: name.clone(),
-                args: temp_args,
+                args: args,
+                guard: guard,
                 body: body,
                 location_id: location_id,
+                doc: self.get_doc(start_index),
             };
             self.program.functions.add_item(id, function);
             Ok(FunctionOrFunctionType::Function(name, id))
         }
     }
 
+    pub fn parse_local_functions(&mut self) -> Result<Vec<FunctionId>, ParseError> {
+        let mut local_functions = Vec::new();
+        while self.current_kind() != TokenKind::EndOfBlock {
+            let start_index = self.get_index();
+            let function_or_type = match self.parse_function_or_function_type() {
+                Ok(function_or_type) => function_or_type,
+                Err(err) => {
+                    self.recover(err, start_index);
+                    if self.get_index() == start_index {
+                        break;
+                    }
+                    continue;
+                }
+            };
+            match function_or_type {
+                FunctionOrFunctionType::Function(_, function_id) => {
+                    let function = self.program.functions.get(&function_id);
+                    if let FunctionBody::Extern = function.body {
+                        let location_id = function.location_id;
+                        let err = self.location_error(
+                            "Local functions cannot be extern".to_string(),
+                            location_id,
+                        );
+                        self.errors.push(err);
+                    }
+                    local_functions.push(function_id);
+                }
+                FunctionOrFunctionType::FunctionType(_, function_type_id) => {
+                    let location_id = self
+                        .program
+                        .function_types
+                        .get(&function_type_id)
+                        .location_id;
+                    let err = self.location_error(
+                        "Type signatures are not supported for local functions".to_string(),
+                        location_id,
+                    );
+                    self.errors.push(err);
+                }
+            }
+        }
+        self.expect(TokenKind::EndOfBlock)?;
+        Ok(local_functions)
+    }
+
+    fn location_error(&self, msg: String, location_id: LocationId) -> ParseError {
+        let location_set = self.location_info.get_item_location(&location_id);
+        let (line, ranges) = location_set.lines.iter().next().unwrap();
+        let location = Location {
+            line: *line,
+            span: Span {
+                start: ranges[0].start,
+                end: ranges[0].end,
+            },
+        };
+        ParseError::new(msg, self.file_path.clone(), location)
+    }
+
     fn parse_export_import_data_member(parser: &mut Parser) -> Result<EIMemberInfo, ParseError> {
         let start_index = parser.get_index();
         let member = if parser.current(TokenKind::DoubleDot) {
@@ -707,7 +1026,11 @@ impl<'a> Parser<'a> {
 
     fn parse_export_import_item(parser: &mut Parser) -> Result<EIItemInfo, ParseError> {
         let start_index = parser.get_index();
-        let name = parser.any_identifier("item")?;
+        let name = if parser.operator_name_follows() {
+            parser.parse_operator_name()?
+        } else {
+            parser.any_identifier("item")?
+        };
         let item = if parser.current(TokenKind::LParen) {
             let members = parser.parse_list0_in_parens(Parser::parse_export_import_data_member)?;
             let group = EIGroup {
@@ -729,7 +1052,11 @@ impl<'a> Parser<'a> {
 
     fn parse_hidden_item(parser: &mut Parser) -> Result<HiddenItem, ParseError> {
         let start_index = parser.get_index();
-        let name = parser.any_identifier("hidden item")?;
+        let name = if parser.operator_name_follows() {
+            parser.parse_operator_name()?
+        } else {
+            parser.any_identifier("hidden item")?
+        };
         let end_index = parser.get_index();
         let location_id = parser.get_location_id(start_index, end_index);
         Ok(HiddenItem {
@@ -786,6 +1113,7 @@ impl<'a> Parser<'a> {
             id: self.program.record_fields.get_id(),
             type_signature_id: type_signature_id,
             location_id: location_id,
+            doc: self.get_doc(start_index),
         };
         Ok(item)
     }
@@ -830,6 +1158,7 @@ impl<'a> Parser<'a> {
             location_id: location_id,
             external: false,
             derived_classes: derived_classes,
+            doc: self.get_doc(start_index),
         };
         Ok(record)
     }
@@ -847,6 +1176,7 @@ impl<'a> Parser<'a> {
             name: name,
             type_signature_id: type_signature_id,
             location_id: location_id,
+            doc: self.get_doc(variant_start_index),
         };
         self.program.variants.add_item(id, variant);
         Ok(id)
@@ -897,6 +1227,65 @@ impl<'a> Parser<'a> {
         }
     }
 
+    fn parse_type_alias(&mut self) -> Result<Data, ParseError> {
+        let start_index = self.get_index();
+        self.expect(TokenKind::KeywordType)?;
+        let name = self.type_identifier("type alias")?;
+        let args = self.parse_args()?;
+        self.expect(TokenKind::Equal)?;
+        let type_signature_id = self.parse_function_type(false, false)?;
+        let end_index = self.get_index();
+        let location_id = self.get_location_id(start_index, end_index);
+        let type_alias = TypeAlias {
+            name: name,
+            id: self.program.type_aliases.get_id(),
+            type_args: args,
+            type_signature_id: type_signature_id,
+            location_id: location_id,
+            doc: self.get_doc(start_index),
+        };
+        Ok(Data::TypeAlias(type_alias))
+    }
+
+    fn parse_newtype(&mut self) -> Result<Data, ParseError> {
+        let start_index = self.get_index();
+        self.expect(TokenKind::KeywordNewtype)?;
+        let name = self.type_identifier("newtype")?;
+        let args = self.parse_args()?;
+        self.expect(TokenKind::Equal)?;
+        let variant = self.parse_variant()?;
+        let end_index = self.get_index();
+        let location_id = self.get_location_id(start_index, end_index);
+        let variant_type_signature_id = self.program.variants.get(&variant).type_signature_id;
+        let variant_type_signature = self.program.type_signatures.get(&variant_type_signature_id);
+        let item_count = match &variant_type_signature.item {
+            TypeSignature::Variant(_, items) => items.len(),
+            _ => unreachable!(),
+        };
+        if item_count != 1 || self.current(TokenKind::Pipe) {
+            let err = self.location_error(
+                format!(
+                    "Newtype {} must have exactly one variant with exactly one item",
+                    name
+                ),
+                location_id,
+            );
+            return Err(err);
+        }
+        let derived_classes = self.parse_deriving()?;
+        let adt = Adt {
+            name: name,
+            id: self.program.adts.get_id(),
+            type_args: args,
+            variants: vec![variant],
+            location_id: location_id,
+            derived_classes: derived_classes,
+            doc: self.get_doc(start_index),
+            newtype: true,
+        };
+        Ok(Data::Adt(adt))
+    }
+
     fn parse_data(&mut self) -> Result<Data, ParseError> {
         let start_index = self.get_index();
         self.expect(TokenKind::KeywordData)?;
@@ -920,6 +1309,7 @@ impl<'a> Parser<'a> {
                     location_id: location_id,
                     external: true,
                     derived_classes: Vec::new(),
+                    doc: self.get_doc(start_index),
                 };
                 Ok(Data::Record(record))
             } else {
@@ -943,6 +1333,8 @@ impl<'a> Parser<'a> {
                     variants: variants,
                     location_id: location_id,
                     derived_classes: derived_classes,
+                    doc: self.get_doc(start_index),
+                    newtype: false,
                 };
                 Ok(Data::Adt(adt))
             }
@@ -957,6 +1349,8 @@ impl<'a> Parser<'a> {
                 variants: Vec::new(),
                 location_id: location_id,
                 derived_classes: derived_classes,
+                doc: self.get_doc(start_index),
+                newtype: false,
             };
             Ok(Data::Adt(adt))
         }
@@ -1041,6 +1435,7 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_class(&mut self, module: &mut Module) -> Result<Class, ParseError> {
+        let doc = self.get_doc(self.get_index());
         self.expect(TokenKind::KeywordClass)?;
         let constraints = if self.current_kind() == TokenKind::LParen {
             let cs = self.parse_list1_in_parens(parse_class_constraint)?;
@@ -1053,23 +1448,44 @@ impl<'a> Parser<'a> {
         let name = self.type_identifier("class name")?;
         let end_index = self.get_index();
         let class_location_id = self.get_location_id(start_index, end_index);
-        let arg = self.parse_function_type(false, false)?;
+        let args = self.parse_type_args(false)?;
+        if args.is_empty() {
+            self.var_identifier("class argument")?;
+        }
         let mut member_functions = BTreeMap::new();
         let mut member_function_types = BTreeMap::new();
+        let mut previous_function = None;
         if self.current_kind() == TokenKind::KeywordWhere {
             self.expect(TokenKind::KeywordWhere)?;
             while self.current_kind() != TokenKind::EndOfBlock {
-                let function_or_type = self.parse_function_or_function_type()?;
+                let member_start_index = self.get_index();
+                let function_or_type = match self.parse_function_or_function_type() {
+                    Ok(function_or_type) => function_or_type,
+                    Err(err) => {
+                        previous_function = None;
+                        self.recover(err, member_start_index);
+                        if self.get_index() == member_start_index {
+                            break;
+                        }
+                        continue;
+                    }
+                };
                 match function_or_type {
                     FunctionOrFunctionType::Function(name, function_id) => {
-                        let fs = member_functions.entry(name).or_insert_with(|| Vec::new());
-                        fs.push(function_id);
+                        add_function_clause(
+                            &mut member_functions,
+                            &previous_function,
+                            name.clone(),
+                            function_id,
+                        );
+                        previous_function = Some(name);
                     }
                     FunctionOrFunctionType::FunctionType(name, function_type_id) => {
                         let fs = member_function_types
                             .entry(name)
                             .or_insert_with(|| Vec::new());
                         fs.push(function_type_id);
+                        previous_function = None;
                     }
                 }
             }
@@ -1081,16 +1497,40 @@ impl<'a> Parser<'a> {
         let class = Class {
             id: id,
             name: name,
-            arg: arg,
+            args: args,
             constraints: constraints,
             member_functions: member_functions,
             member_function_types: member_function_types,
             location_id: class_location_id,
+            doc: doc,
         };
         Ok(class)
     }
 
+    // The instance head is parsed as a list of atomic types, one for each class
+    // argument. For single parameter classes the list is also read as a single
+    // applied type, e.g. `Result e` or `f a`.
+    fn combine_instance_args(
+        &mut self,
+        args: &Vec<TypeSignatureId>,
+        start_index: usize,
+    ) -> Option<TypeSignatureId> {
+        if args.len() == 1 {
+            return Some(args[0]);
+        }
+        let rest = args[1..].to_vec();
+        let ty = match &self.program.type_signatures.get(&args[0]).item {
+            TypeSignature::Named(name, items) if items.is_empty() => {
+                TypeSignature::Named(name.clone(), rest)
+            }
+            TypeSignature::TypeArg(name) => TypeSignature::TypeArgApplication(name.clone(), rest),
+            _ => return None,
+        };
+        Some(self.add_type_signature(ty, start_index))
+    }
+
     fn parse_instance(&mut self, module: &mut Module) -> Result<Instance, ParseError> {
+        let doc = self.get_doc(self.get_index());
         self.expect(TokenKind::KeywordInstance)?;
         let name = if self.current_kind() == TokenKind::KeywordAs {
             self.expect(TokenKind::KeywordAs)?;
@@ -1108,25 +1548,47 @@ impl<'a> Parser<'a> {
         };
         let class_name = self.type_identifier("class name")?;
         let start_index = self.get_index();
-        let type_signature_id = self.parse_function_type(false, false)?;
+        let args = self.parse_type_args(false)?;
+        if args.is_empty() {
+            self.type_identifier("instance type")?;
+        }
+        let type_signature_id = self.combine_instance_args(&args, start_index);
         let end_index = self.get_index();
         let instance_location_id = self.get_location_id(start_index, end_index);
         let mut member_functions = BTreeMap::new();
         let mut member_function_types = BTreeMap::new();
+        let mut previous_function = None;
         if self.current_kind() == TokenKind::KeywordWhere {
             self.expect(TokenKind::KeywordWhere)?;
             while self.current_kind() != TokenKind::EndOfBlock {
-                let function_or_type = self.parse_function_or_function_type()?;
+                let member_start_index = self.get_index();
+                let function_or_type = match self.parse_function_or_function_type() {
+                    Ok(function_or_type) => function_or_type,
+                    Err(err) => {
+                        previous_function = None;
+                        self.recover(err, member_start_index);
+                        if self.get_index() == member_start_index {
+                            break;
+                        }
+                        continue;
+                    }
+                };
                 match function_or_type {
                     FunctionOrFunctionType::Function(name, function_id) => {
-                        let fs = member_functions.entry(name).or_insert_with(|| Vec::new());
-                        fs.push(function_id);
+                        add_function_clause(
+                            &mut member_functions,
+                            &previous_function,
+                            name.clone(),
+                            function_id,
+                        );
+                        previous_function = Some(name);
                     }
                     FunctionOrFunctionType::FunctionType(name, function_type_id) => {
                         let fs = member_function_types
                             .entry(name)
                             .or_insert_with(|| Vec::new());
                         fs.push(function_type_id);
+                        previous_function = None;
                     }
                 }
             }
@@ -1140,10 +1602,12 @@ impl<'a> Parser<'a> {
             name: name,
             class_name: class_name,
             type_signature_id: type_signature_id,
+            args: args,
             constraints: constraints,
             member_functions: member_functions,
             member_function_types: member_function_types,
             location_id: instance_location_id,
+            doc: doc,
         };
         Ok(instance)
     }
@@ -1213,6 +1677,86 @@ impl<'a> Parser<'a> {
         Ok(actor)
     }
 
+    fn parse_module_item(
+        &mut self,
+        module: &mut Module,
+        previous_function: &mut Option<String>,
+    ) -> Result<(), ParseError> {
+        let token = self.peek().expect("Ran out of tokens");
+        let previous = previous_function.take();
+        match token.token.kind() {
+            TokenKind::KeywordImport => {
+                let import_id = self.program.imports.get_id();
+                let import = self.parse_import(import_id)?;
+                self.program.imports.add_item(import_id, import);
+                module.imports.push(import_id);
+            }
+            TokenKind::KeywordData | TokenKind::KeywordType | TokenKind::KeywordNewtype => {
+                let data = match token.token.kind() {
+                    TokenKind::KeywordType => self.parse_type_alias()?,
+                    TokenKind::KeywordNewtype => self.parse_newtype()?,
+                    _ => self.parse_data()?,
+                };
+                self.expect(TokenKind::EndOfItem)?;
+                match data {
+                    Data::Record(record) => {
+                        module.records.push(record.id);
+                        self.program.records.add_item(record.id, record);
+                    }
+                    Data::Adt(adt) => {
+                        module.adts.push(adt.id);
+                        self.program.adts.add_item(adt.id, adt);
+                    }
+                    Data::TypeAlias(type_alias) => {
+                        module.type_aliases.push(type_alias.id);
+                        self.program
+                            .type_aliases
+                            .add_item(type_alias.id, type_alias);
+                    }
+                }
+            }
+            TokenKind::KeywordClass => {
+                let class = self.parse_class(module)?;
+                self.program.classes.add_item(class.id, class);
+            }
+            TokenKind::KeywordInstance => {
+                let instance = self.parse_instance(module)?;
+                self.program.instances.add_item(instance.id, instance);
+            }
+            TokenKind::KeywordProtocol => {
+                let protocol = self.parse_protocol(module)?;
+                self.program.protocols.add_item(protocol.id, protocol);
+            }
+            TokenKind::KeywordActor => {
+                let actor = self.parse_actor(module)?;
+                self.program.actors.add_item(actor.id, actor);
+            }
+            TokenKind::KeywordInfixl | TokenKind::KeywordInfixr | TokenKind::KeywordInfix => {
+                // already in the fixity table, see FixityTable::collect
+                self.parse_fixity_declaration()?;
+            }
+            _ => match self.parse_function_or_function_type()? {
+                FunctionOrFunctionType::Function(name, function_id) => {
+                    add_function_clause(
+                        &mut module.functions,
+                        &previous,
+                        name.clone(),
+                        function_id,
+                    );
+                    *previous_function = Some(name);
+                }
+                FunctionOrFunctionType::FunctionType(name, function_type_id) => {
+                    let fs = module
+                        .function_types
+                        .entry(name)
+                        .or_insert_with(|| Vec::new());
+                    fs.push(function_type_id);
+                }
+            },
+        }
+        Ok(())
+    }
+
     fn parse_module(&mut self, id: ModuleId) -> Result<Module, ParseError> {
         self.expect(TokenKind::KeywordModule)?;
         let start_index = self.get_index();
@@ -1222,61 +1766,18 @@ impl<'a> Parser<'a> {
         let export_list = self.parse_export_import_list()?;
         let mut module = Module::new(name, id, location_id, export_list);
         self.expect(TokenKind::KeywordWhere)?;
+        let mut previous_function = None;
         loop {
             if let Some(token) = self.peek() {
-                match token.token.kind() {
-                    TokenKind::KeywordImport => {
-                        let import_id = self.program.imports.get_id();
-                        let import = self.parse_import(import_id)?;
-                        self.program.imports.add_item(import_id, import);
-                        module.imports.push(import_id);
-                    }
-                    TokenKind::KeywordData => {
-                        let data = self.parse_data()?;
-                        self.expect(TokenKind::EndOfItem)?;
-                        match data {
-                            Data::Record(record) => {
-                                module.records.push(record.id);
-                                self.program.records.add_item(record.id, record);
-                            }
-                            Data::Adt(adt) => {
-                                module.adts.push(adt.id);
-                                self.program.adts.add_item(adt.id, adt);
-                            }
-                        }
-                    }
-                    TokenKind::KeywordClass => {
-                        let class = self.parse_class(&mut module)?;
-                        self.program.classes.add_item(class.id, class);
-                    }
-                    TokenKind::KeywordInstance => {
-                        let instance = self.parse_instance(&mut module)?;
-                        self.program.instances.add_item(instance.id, instance);
-                    }
-                    TokenKind::KeywordProtocol => {
-                        let protocol = self.parse_protocol(&mut module)?;
-                        self.program.protocols.add_item(protocol.id, protocol);
-                    }
-                    TokenKind::KeywordActor => {
-                        let actor = self.parse_actor(&mut module)?;
-                        self.program.actors.add_item(actor.id, actor);
-                    }
-                    TokenKind::EndOfBlock => {
+                if token.token.kind() == TokenKind::EndOfBlock {
+                    break;
+                }
+                let start_index = self.get_index();
+                if let Err(err) = self.parse_module_item(&mut module, &mut previous_function) {
+                    self.recover(err, start_index);
+                    if self.get_index() == start_index {
                         break;
                     }
-                    _ => match self.parse_function_or_function_type()? {
-                        FunctionOrFunctionType::Function(name, function_id) => {
-                            let fs = module.functions.entry(name).or_insert_with(|| Vec::new());
-                            fs.push(function_id);
-                        }
-                        FunctionOrFunctionType::FunctionType(name, function_type_id) => {
-                            let fs = module
-                                .function_types
-                                .entry(name)
-                                .or_insert_with(|| Vec::new());
-                            fs.push(function_type_id);
-                        }
-                    },
                 }
             } else {
                 break;
@@ -1287,11 +1788,31 @@ impl<'a> Parser<'a> {
         Ok(module)
     }
 
-    pub fn parse(&mut self) -> Result<(), ParseError> {
+    // Skips a module whose header could not be parsed
+    fn skip_module(&mut self) {
+        while let Some(token) = self.peek() {
+            self.index += 1;
+            if token.token.kind() == TokenKind::EndOfModule {
+                break;
+            }
+        }
+    }
+
+    pub fn parse(&mut self) -> Result<(), Vec<ParseError>> {
         while !self.is_done() {
             let m_id = self.program.modules.get_id();
-            let module = self.parse_module(m_id)?;
-            self.program.modules.add_item(m_id, module);
+            match self.parse_module(m_id) {
+                Ok(module) => {
+                    self.program.modules.add_item(m_id, module);
+                }
+                Err(err) => {
+                    self.errors.push(err);
+                    self.skip_module();
+                }
+            }
+        }
+        if !self.errors.is_empty() {
+            return Err(std::mem::take(&mut self.errors));
         }
 
         let implicit_modules = get_implicit_module_list();
@@ -11,6 +11,8 @@ pub enum Token {
     CharLiteral(char),
     KeywordWhere,
     KeywordData,
+    KeywordType,
+    KeywordNewtype,
     KeywordModule,
     KeywordImport,
     KeywordIf,
@@ -29,7 +31,14 @@ pub enum Token {
     KeywordProtocol,
     KeywordActor,
     KeywordDeriving,
+    KeywordLet,
+    KeywordIn,
+    KeywordInfixl,
+    KeywordInfixr,
+    KeywordInfix,
     Op(BuiltinOperator),
+    UserOperator(String),
+    Backtick,
     Equal,
     Comma,
     LCurly,
@@ -43,10 +52,12 @@ pub enum Token {
     Lambda,
     Dot,
     DoubleDot,
+    At,
     Formatter,
     Wildcard,
     Colon,
     Apostrophe,
+    Comment(String),
     EndOfItem,
     EndOfBlock,
     EndOfModule,
@@ -71,6 +82,8 @@ impl Token {
             Token::CharLiteral(..) => TokenKind::CharLiteral,
             Token::KeywordWhere => TokenKind::KeywordWhere,
             Token::KeywordData => TokenKind::KeywordData,
+            Token::KeywordType => TokenKind::KeywordType,
+            Token::KeywordNewtype => TokenKind::KeywordNewtype,
             Token::KeywordModule => TokenKind::KeywordModule,
             Token::KeywordImport => TokenKind::KeywordImport,
             Token::KeywordIf => TokenKind::KeywordIf,
@@ -89,7 +102,14 @@ impl Token {
             Token::KeywordProtocol => TokenKind::KeywordProtocol,
             Token::KeywordActor => TokenKind::KeywordActor,
             Token::KeywordDeriving => TokenKind::KeywordDeriving,
+            Token::KeywordLet => TokenKind::KeywordLet,
+            Token::KeywordIn => TokenKind::KeywordIn,
+            Token::KeywordInfixl => TokenKind::KeywordInfixl,
+            Token::KeywordInfixr => TokenKind::KeywordInfixr,
+            Token::KeywordInfix => TokenKind::KeywordInfix,
             Token::Op(op) => TokenKind::Op(*op),
+            Token::UserOperator(..) => TokenKind::UserOperator,
+            Token::Backtick => TokenKind::Backtick,
             Token::Equal => TokenKind::Equal,
             Token::Comma => TokenKind::Comma,
             Token::LCurly => TokenKind::LCurly,
@@ -103,10 +123,12 @@ impl Token {
             Token::Lambda => TokenKind::Lambda,
             Token::Dot => TokenKind::Dot,
             Token::DoubleDot => TokenKind::DoubleDot,
+            Token::At => TokenKind::At,
             Token::Formatter => TokenKind::Formatter,
             Token::Wildcard => TokenKind::Wildcard,
             Token::Colon => TokenKind::Colon,
             Token::Apostrophe => TokenKind::Apostrophe,
+            Token::Comment(..) => TokenKind::Comment,
             Token::EndOfItem => TokenKind::EndOfItem,
             Token::EndOfBlock => TokenKind::EndOfBlock,
             Token::EndOfModule => TokenKind::EndOfModule,
@@ -124,6 +146,8 @@ pub enum TokenKind {
     CharLiteral,
     KeywordWhere,
     KeywordData,
+    KeywordType,
+    KeywordNewtype,
     KeywordModule,
     KeywordImport,
     KeywordIf,
@@ -142,7 +166,14 @@ pub enum TokenKind {
     KeywordProtocol,
     KeywordActor,
     KeywordDeriving,
+    KeywordLet,
+    KeywordIn,
+    KeywordInfixl,
+    KeywordInfixr,
+    KeywordInfix,
     Op(BuiltinOperator),
+    UserOperator,
+    Backtick,
     Equal,
     Comma,
     LCurly,
@@ -156,10 +187,12 @@ pub enum TokenKind {
     Lambda,
     Dot,
     DoubleDot,
+    At,
     Formatter,
     Wildcard,
     Colon,
     Apostrophe,
+    Comment,
     EndOfItem,
     EndOfBlock,
     EndOfModule,
@@ -184,7 +217,10 @@ impl TokenKind {
             TokenKind::KeywordDoubleColon => format!("::"),
             TokenKind::Colon => format!(":"),
             TokenKind::Comma => format!(","),
+            TokenKind::Backtick => format!("`"),
+            TokenKind::UserOperator => format!("operator"),
             TokenKind::DoubleDot => format!(".."),
+            TokenKind::At => format!("@"),
             TokenKind::StringLiteral => format!("string literal"),
             TokenKind::FloatLiteral => format!("float literal"),
             TokenKind::IntegerLiteral => format!("integer literal"),
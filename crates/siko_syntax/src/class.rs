@@ -8,11 +8,12 @@ use std::collections::BTreeMap;
 pub struct Class {
     pub id: ClassId,
     pub name: String,
-    pub arg: TypeSignatureId,
+    pub args: Vec<TypeSignatureId>,
     pub constraints: Vec<Constraint>,
-    pub member_functions: BTreeMap<String, Vec<FunctionId>>,
+    pub member_functions: BTreeMap<String, Vec<Vec<FunctionId>>>,
     pub member_function_types: BTreeMap<String, Vec<FunctionTypeId>>,
     pub location_id: LocationId,
+    pub doc: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -20,17 +21,19 @@ pub struct Instance {
     pub id: InstanceId,
     pub name: Option<String>,
     pub class_name: String,
-    pub type_signature_id: TypeSignatureId,
+    pub type_signature_id: Option<TypeSignatureId>,
+    pub args: Vec<TypeSignatureId>,
     pub constraints: Vec<Constraint>,
-    pub member_functions: BTreeMap<String, Vec<FunctionId>>,
+    pub member_functions: BTreeMap<String, Vec<Vec<FunctionId>>>,
     pub member_function_types: BTreeMap<String, Vec<FunctionTypeId>>,
     pub location_id: LocationId,
+    pub doc: Option<String>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Constraint {
     pub class_name: String,
-    pub arg: String,
+    pub args: Vec<String>,
     pub location_id: LocationId,
 }
 
@@ -10,6 +10,7 @@ pub struct DerivedClass {
 pub enum Data {
     Adt(Adt),
     Record(Record),
+    TypeAlias(TypeAlias),
 }
 
 #[derive(Debug, Clone)]
@@ -20,6 +21,8 @@ pub struct Adt {
     pub variants: Vec<VariantId>,
     pub location_id: LocationId,
     pub derived_classes: Vec<DerivedClass>,
+    pub doc: Option<String>,
+    pub newtype: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -28,6 +31,7 @@ pub struct Variant {
     pub id: VariantId,
     pub type_signature_id: TypeSignatureId,
     pub location_id: LocationId,
+    pub doc: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
@@ -61,6 +65,7 @@ pub struct Record {
     pub location_id: LocationId,
     pub external: bool,
     pub derived_classes: Vec<DerivedClass>,
+    pub doc: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
@@ -91,4 +96,26 @@ pub struct RecordField {
     pub id: RecordFieldId,
     pub type_signature_id: TypeSignatureId,
     pub location_id: LocationId,
+    pub doc: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TypeAlias {
+    pub name: String,
+    pub id: TypeAliasId,
+    pub type_args: Vec<(String, LocationId)>,
+    pub type_signature_id: TypeSignatureId,
+    pub location_id: LocationId,
+    pub doc: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub struct TypeAliasId {
+    pub id: usize,
+}
+
+impl From<usize> for TypeAliasId {
+    fn from(id: usize) -> TypeAliasId {
+        TypeAliasId { id: id }
+    }
 }
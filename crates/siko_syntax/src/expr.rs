@@ -1,3 +1,4 @@
+use crate::function::FunctionId;
 use crate::pattern::PatternId;
 use siko_constants::BuiltinOperator;
 use siko_location_info::location_id::LocationId;
@@ -67,6 +68,7 @@ pub enum Expr {
     CaseOf(ExprId, Vec<Case>),
     RecordInitialization(String, Vec<RecordConstructionItem>),
     RecordUpdate(String, Vec<RecordConstructionItem>),
+    Let(Vec<FunctionId>, ExprId),
 }
 
 impl fmt::Display for Expr {
@@ -105,6 +107,7 @@ impl fmt::Display for Expr {
             Expr::RecordUpdate(name, items) => {
                 write!(f, "RecordUpdate({}, {})", name, format_list(items))
             }
+            Expr::Let(functions, body) => write!(f, "Let({}, {})", format_list(functions), body),
         }
     }
 }
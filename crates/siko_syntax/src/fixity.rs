@@ -0,0 +1,48 @@
+use siko_location_info::location_id::LocationId;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Associativity {
+    Left,
+    Right,
+    None,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Fixity {
+    pub associativity: Associativity,
+    pub precedence: usize,
+}
+
+impl Fixity {
+    pub fn new(associativity: Associativity, precedence: usize) -> Fixity {
+        Fixity {
+            associativity: associativity,
+            precedence: precedence,
+        }
+    }
+}
+
+impl Default for Fixity {
+    fn default() -> Fixity {
+        Fixity::new(Associativity::Left, 9)
+    }
+}
+
+impl fmt::Display for Fixity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let keyword = match self.associativity {
+            Associativity::Left => "infixl",
+            Associativity::Right => "infixr",
+            Associativity::None => "infix",
+        };
+        write!(f, "{} {}", keyword, self.precedence)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FixityDeclaration {
+    pub fixity: Fixity,
+    pub operators: Vec<String>,
+    pub location_id: LocationId,
+}
@@ -1,7 +1,9 @@
 use crate::class::Constraint;
 use crate::expr::ExprId;
+use crate::pattern::PatternId;
 use crate::types::TypeSignatureId;
 use siko_location_info::location_id::LocationId;
+use std::fmt;
 
 #[derive(Debug, Clone)]
 pub enum FunctionBody {
@@ -18,15 +20,18 @@ pub struct FunctionType {
     pub full_type_signature_id: TypeSignatureId,
     pub type_signature_id: TypeSignatureId,
     pub location_id: LocationId,
+    pub doc: Option<String>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Function {
     pub id: FunctionId,
     pub name: String,
-    pub args: Vec<(String, LocationId)>,
+    pub args: Vec<PatternId>,
+    pub guard: Option<ExprId>,
     pub body: FunctionBody,
     pub location_id: LocationId,
+    pub doc: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
@@ -34,6 +39,12 @@ pub struct FunctionId {
     pub id: usize,
 }
 
+impl fmt::Display for FunctionId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}", self.id)
+    }
+}
+
 impl From<usize> for FunctionId {
     fn from(id: usize) -> FunctionId {
         FunctionId { id: id }
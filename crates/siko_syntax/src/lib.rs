@@ -3,6 +3,7 @@ pub mod class;
 pub mod data;
 pub mod export_import;
 pub mod expr;
+pub mod fixity;
 pub mod function;
 pub mod import;
 pub mod module;
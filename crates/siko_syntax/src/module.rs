@@ -4,6 +4,7 @@ use crate::class::ClassId;
 use crate::class::InstanceId;
 use crate::data::AdtId;
 use crate::data::RecordId;
+use crate::data::TypeAliasId;
 use crate::export_import::EIList;
 use crate::function::FunctionId;
 use crate::function::FunctionTypeId;
@@ -26,10 +27,11 @@ impl From<usize> for ModuleId {
 pub struct Module {
     pub name: String,
     pub id: ModuleId,
-    pub functions: BTreeMap<String, Vec<FunctionId>>,
+    pub functions: BTreeMap<String, Vec<Vec<FunctionId>>>,
     pub function_types: BTreeMap<String, Vec<FunctionTypeId>>,
     pub records: Vec<RecordId>,
     pub adts: Vec<AdtId>,
+    pub type_aliases: Vec<TypeAliasId>,
     pub classes: Vec<ClassId>,
     pub instances: Vec<InstanceId>,
     pub imports: Vec<ImportId>,
@@ -48,6 +50,7 @@ impl Module {
             function_types: BTreeMap::new(),
             records: Vec::new(),
             adts: Vec::new(),
+            type_aliases: Vec::new(),
             classes: Vec::new(),
             instances: Vec::new(),
             imports: Vec::new(),
@@ -40,5 +40,9 @@ pub enum Pattern {
     Typed(PatternId, TypeSignatureId),
     Record(String, Vec<RecordFieldPattern>),
     CharRange(char, char),
+    IntegerRange(i64, i64),
+    FloatLiteral(f64),
+    List(Vec<PatternId>, Option<PatternId>),
+    As(PatternId, String),
     Or(Vec<PatternId>),
 }
@@ -12,6 +12,8 @@ use crate::data::Record;
 use crate::data::RecordField;
 use crate::data::RecordFieldId;
 use crate::data::RecordId;
+use crate::data::TypeAlias;
+use crate::data::TypeAliasId;
 use crate::data::Variant;
 use crate::data::VariantId;
 use crate::expr::Expr;
@@ -39,6 +41,7 @@ pub struct Program {
     pub records: ItemContainer<RecordId, Record>,
     pub adts: ItemContainer<AdtId, Adt>,
     pub variants: ItemContainer<VariantId, Variant>,
+    pub type_aliases: ItemContainer<TypeAliasId, TypeAlias>,
     pub classes: ItemContainer<ClassId, Class>,
     pub instances: ItemContainer<InstanceId, Instance>,
     pub exprs: ItemContainer<ExprId, ItemInfo<Expr>>,
@@ -59,6 +62,7 @@ impl Program {
             records: ItemContainer::new(),
             adts: ItemContainer::new(),
             variants: ItemContainer::new(),
+            type_aliases: ItemContainer::new(),
             classes: ItemContainer::new(),
             instances: ItemContainer::new(),
             exprs: ItemContainer::new(),
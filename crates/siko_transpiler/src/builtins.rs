@@ -1,6 +1,7 @@
 use crate::types::ir_type_to_rust_type;
 use crate::util::get_ord_type_from_optional_ord;
 use crate::util::Indent;
+use siko_mir::data::RecordKind;
 use siko_mir::function::Function;
 use siko_mir::program::Program;
 use siko_mir::types::Type;
@@ -280,6 +281,41 @@ fn generate_string_builtins(
     Ok(())
 }
 
+fn generate_char_builtins(
+    module: &str,
+    output_file: &mut dyn Write,
+    program: &Program,
+    indent: &mut Indent,
+    original_name: &str,
+    result_ty: &Type,
+    result_ty_str: &str,
+) -> Result<()> {
+    indent.inc();
+    match original_name {
+        "opEq" => {
+            generate_opeq_builtin_body(output_file, indent, result_ty_str)?;
+        }
+        "show" => {
+            generate_show_builtin_body(output_file, indent, result_ty_str)?;
+        }
+        "partialCmp" => {
+            generate_partial_cmp_builtin_body(
+                output_file,
+                program,
+                indent,
+                result_ty,
+                result_ty_str,
+            )?;
+        }
+        "cmp" => {
+            generate_cmp_builtin_body(output_file, program, indent, result_ty_str)?;
+        }
+        _ => panic!("{}/{} not implemented", module, original_name),
+    }
+    indent.dec();
+    Ok(())
+}
+
 fn generate_map_builtins(
     _function: &Function,
     output_file: &mut dyn Write,
@@ -374,9 +410,10 @@ fn generate_map_builtins(
 fn generate_list_builtins(
     _function: &Function,
     output_file: &mut dyn Write,
-    _program: &Program,
+    program: &Program,
     indent: &mut Indent,
     original_name: &str,
+    result_ty: &Type,
     result_ty_str: &str,
     arg_types: Vec<String>,
 ) -> Result<()> {
@@ -426,11 +463,12 @@ fn generate_list_builtins(
             let list_iter_name = format!("{}_Iter", base_list_type[2]);
             let iter_trait_name =
                 result_ty_str.replace("crate::Iterator::", "crate::Iterator::Trait_");
+            let item_ty = get_iterator_item_type(result_ty, program);
 
             write!(output_file, "{}#[derive(Clone)]\n", indent)?;
             write!(output_file, "{}pub struct {} {{\n", indent, list_iter_name)?;
             indent.inc();
-            write!(output_file, "{}pub value: Vec<crate::Int::Int>,\n", indent)?;
+            write!(output_file, "{}pub value: Vec<{}>,\n", indent, item_ty)?;
             write!(output_file, "{}pub index: usize,\n", indent)?;
             indent.dec();
             write!(output_file, "{}}}\n", indent)?;
@@ -443,8 +481,8 @@ fn generate_list_builtins(
             indent.inc();
             write!(
                 output_file,
-                "{}fn next(&mut self) -> Option<crate::Int::Int> {{\n",
-                indent
+                "{}fn next(&mut self) -> Option<{}> {{\n",
+                indent, item_ty
             )?;
             indent.inc();
             write!(
@@ -513,6 +551,265 @@ fn generate_list_builtins(
     Ok(())
 }
 
+fn get_iterator_item_type(iterator_ty: &Type, program: &Program) -> String {
+    let id = iterator_ty.get_typedef_id();
+    match &program.typedefs.get(&id).get_record().kind {
+        RecordKind::External(_, args) => ir_type_to_rust_type(&args[0], program),
+        RecordKind::Normal => unreachable!(),
+    }
+}
+
+// Defines a local struct implementing the trait of the resulting iterator and returns an instance of it.
+// The fields are (name, type, initial value) triples, next_body is the body of the next method.
+fn write_iterator_struct(
+    output_file: &mut dyn Write,
+    program: &Program,
+    indent: &mut Indent,
+    struct_name: &str,
+    result_ty: &Type,
+    result_ty_str: &str,
+    fields: &[(&str, String, String)],
+    next_body: &[&str],
+) -> Result<()> {
+    let item_ty = get_iterator_item_type(result_ty, program);
+    let iter_trait_name = result_ty_str.replace("crate::Iterator::", "crate::Iterator::Trait_");
+    write!(output_file, "{}struct {} {{\n", indent, struct_name)?;
+    indent.inc();
+    for (name, ty, _) in fields {
+        write!(output_file, "{}pub {}: {},\n", indent, name, ty)?;
+    }
+    indent.dec();
+    write!(output_file, "{}}}\n", indent)?;
+
+    write!(output_file, "{}impl Clone for {} {{\n", indent, struct_name)?;
+    indent.inc();
+    write!(
+        output_file,
+        "{}fn clone(&self) -> {} {{\n",
+        indent, struct_name
+    )?;
+    indent.inc();
+    write!(output_file, "{}{} {{\n", indent, struct_name)?;
+    indent.inc();
+    for (name, _, _) in fields {
+        write!(output_file, "{}{}: self.{}.clone(),\n", indent, name, name)?;
+    }
+    indent.dec();
+    write!(output_file, "{}}}\n", indent)?;
+    indent.dec();
+    write!(output_file, "{}}}\n", indent)?;
+    indent.dec();
+    write!(output_file, "{}}}\n", indent)?;
+
+    write!(
+        output_file,
+        "{}impl {} for {} {{\n",
+        indent, iter_trait_name, struct_name
+    )?;
+    indent.inc();
+    write!(
+        output_file,
+        "{}fn next(&mut self) -> Option<{}> {{\n",
+        indent, item_ty
+    )?;
+    indent.inc();
+    for line in next_body {
+        write!(output_file, "{}{}\n", indent, line)?;
+    }
+    indent.dec();
+    write!(output_file, "{}}}\n", indent)?;
+    write!(
+        output_file,
+        "{}fn box_clone(&self) -> Box<dyn {}> {{\n",
+        indent, iter_trait_name
+    )?;
+    indent.inc();
+    write!(output_file, "{}Box::new(self.clone())\n", indent)?;
+    indent.dec();
+    write!(output_file, "{}}}\n", indent)?;
+    indent.dec();
+    write!(output_file, "{}}}\n", indent)?;
+
+    write!(output_file, "{}{} {{\n", indent, result_ty_str)?;
+    indent.inc();
+    write!(
+        output_file,
+        "{}value: Box::new({} {{\n",
+        indent, struct_name
+    )?;
+    indent.inc();
+    for (name, _, value) in fields {
+        write!(output_file, "{}{}: {},\n", indent, name, value)?;
+    }
+    indent.dec();
+    write!(output_file, "{}}}),\n", indent)?;
+    indent.dec();
+    write!(output_file, "{}}}", indent)?;
+    Ok(())
+}
+
+fn generate_iterator_builtins(
+    function: &Function,
+    output_file: &mut dyn Write,
+    program: &Program,
+    indent: &mut Indent,
+    original_name: &str,
+    result_ty: &Type,
+    result_ty_str: &str,
+    arg_types: Vec<String>,
+) -> Result<()> {
+    indent.inc();
+    let struct_name = to_first_uppercase(function.name.clone());
+    let arg_fields: Vec<_> = ["arg0", "arg1"]
+        .iter()
+        .zip(arg_types.iter())
+        .map(|(name, arg_type)| (*name, arg_type.clone(), name.to_string()))
+        .collect();
+    match original_name {
+        "map" => {
+            let next_body = [
+                "if let Some(value) = self.arg1.value.next() {",
+                "    Some(self.arg0.call(value))",
+                "} else {",
+                "    None",
+                "}",
+            ];
+            write_iterator_struct(
+                output_file,
+                program,
+                indent,
+                &struct_name,
+                result_ty,
+                result_ty_str,
+                &arg_fields[..],
+                &next_body,
+            )?;
+        }
+        "filter" => {
+            let mut fn_args = Vec::new();
+            function.function_type.get_args(&mut fn_args);
+            let bool_ty = ir_type_to_rust_type(&fn_args[0].get_result_type(1), program);
+            let keep = format!(
+                "        if let {}::True = self.arg0.call(value.clone()) {{ return Some(value); }}",
+                bool_ty
+            );
+            let next_body = [
+                "loop {",
+                "    if let Some(value) = self.arg1.value.next() {",
+                &keep,
+                "    } else {",
+                "        return None;",
+                "    }",
+                "}",
+            ];
+            write_iterator_struct(
+                output_file,
+                program,
+                indent,
+                &struct_name,
+                result_ty,
+                result_ty_str,
+                &arg_fields[..],
+                &next_body,
+            )?;
+        }
+        "flatMap" => {
+            let mut fields = arg_fields.clone();
+            fields.push((
+                "current",
+                format!("Option<{}>", result_ty_str),
+                format!("None"),
+            ));
+            let next_body = [
+                "loop {",
+                "    if let Some(current) = &mut self.current {",
+                "        if let Some(value) = current.value.next() {",
+                "            return Some(value);",
+                "        }",
+                "    }",
+                "    if let Some(value) = self.arg1.value.next() {",
+                "        self.current = Some(self.arg0.call(value));",
+                "    } else {",
+                "        return None;",
+                "    }",
+                "}",
+            ];
+            write_iterator_struct(
+                output_file,
+                program,
+                indent,
+                &struct_name,
+                result_ty,
+                result_ty_str,
+                &fields[..],
+                &next_body,
+            )?;
+        }
+        "take" => {
+            let next_body = [
+                "if self.arg0.value <= 0 {",
+                "    return None;",
+                "}",
+                "self.arg0.value -= 1;",
+                "self.arg1.value.next()",
+            ];
+            write_iterator_struct(
+                output_file,
+                program,
+                indent,
+                &struct_name,
+                result_ty,
+                result_ty_str,
+                &arg_fields[..],
+                &next_body,
+            )?;
+        }
+        "range" | "rangeFrom" => {
+            let end = if original_name == "range" {
+                format!("Some(arg2.value)")
+            } else {
+                format!("None")
+            };
+            let fields = [
+                (
+                    "current",
+                    format!("Option<i64>"),
+                    format!("Some(arg0.value)"),
+                ),
+                ("step", format!("i64"), format!("arg1.value")),
+                ("end", format!("Option<i64>"), end),
+            ];
+            let next_body = [
+                "let current = self.current?;",
+                "let done = match self.end {",
+                "    Some(end) if self.step < 0 => current < end,",
+                "    Some(end) => current > end,",
+                "    None => false,",
+                "};",
+                "if done {",
+                "    self.current = None;",
+                "    return None;",
+                "}",
+                "self.current = current.checked_add(self.step);",
+                "Some(crate::Int::Int { value: current })",
+            ];
+            write_iterator_struct(
+                output_file,
+                program,
+                indent,
+                &struct_name,
+                result_ty,
+                result_ty_str,
+                &fields,
+                &next_body,
+            )?;
+        }
+        _ => panic!("Iterator/{} not implemented", original_name),
+    }
+    indent.dec();
+    Ok(())
+}
+
 pub fn generate_builtin(
     function: &Function,
     output_file: &mut dyn Write,
@@ -546,6 +843,17 @@ pub fn generate_builtin(
                 result_ty_str,
             );
         }
+        "Char" => {
+            return generate_char_builtins(
+                function.module.as_ref(),
+                output_file,
+                program,
+                indent,
+                original_name,
+                result_ty,
+                result_ty_str,
+            );
+        }
         "Float" => {
             return generate_num_builtins(
                 function.module.as_ref(),
@@ -575,6 +883,19 @@ pub fn generate_builtin(
                 program,
                 indent,
                 original_name,
+                result_ty,
+                result_ty_str,
+                arg_types,
+            );
+        }
+        "Iterator" => {
+            return generate_iterator_builtins(
+                function,
+                output_file,
+                program,
+                indent,
+                original_name,
+                result_ty,
                 result_ty_str,
                 arg_types,
             );
@@ -643,96 +964,6 @@ pub fn generate_builtin(
                     )?;
                     write!(output_file, "{}{} {{ }}", indent, result_ty_str)?;
                 }
-                ("Iterator", "map") => {
-                    let struct_name = to_first_uppercase(function.name.clone());
-                    write!(output_file, "{}struct {} {{\n", indent, struct_name)?;
-                    indent.inc();
-                    for (index, arg_type) in arg_types.iter().enumerate() {
-                        write!(output_file, "{}pub arg{}: {},\n", indent, index, arg_type)?;
-                    }
-                    indent.dec();
-                    write!(output_file, "{}}}\n", indent)?;
-
-                    write!(output_file, "{}impl Clone for {} {{\n", indent, struct_name)?;
-                    indent.inc();
-                    write!(
-                        output_file,
-                        "{}fn clone(&self) -> {} {{\n",
-                        indent, struct_name
-                    )?;
-                    indent.inc();
-                    write!(output_file, "{}{} {{\n", indent, struct_name)?;
-                    indent.inc();
-                    write!(output_file, "{}arg0: self.arg0.clone(),\n", indent,)?;
-                    write!(output_file, "{}arg1: self.arg1.clone(),\n", indent,)?;
-                    indent.dec();
-                    write!(output_file, "{}}}\n", indent)?;
-                    indent.dec();
-                    write!(output_file, "{}}}\n", indent)?;
-                    indent.dec();
-                    write!(output_file, "{}}}\n", indent)?;
-
-                    let iter_id = result_ty.get_typedef_id();
-                    let iter = program.typedefs.get(&iter_id).get_record();
-                    let iter_trait_name = format!("Trait_{}", iter.name);
-
-                    write!(
-                        output_file,
-                        "{}impl {} for {} {{\n",
-                        indent, iter_trait_name, struct_name
-                    )?;
-                    indent.inc();
-                    write!(
-                        output_file,
-                        "{}fn next(&mut self) -> Option<crate::Int::Int> {{\n",
-                        indent
-                    )?;
-                    indent.inc();
-                    write!(
-                        output_file,
-                        "{}if let Some(value) = self.arg1.value.next() {{\n",
-                        indent
-                    )?;
-                    indent.inc();
-                    write!(output_file, "{}Some(self.arg0.call(value))\n", indent)?;
-                    indent.dec();
-                    write!(output_file, "{}}} else {{\n", indent)?;
-                    indent.inc();
-                    write!(output_file, "{}None\n", indent)?;
-                    indent.dec();
-                    write!(output_file, "{}}}\n", indent)?;
-                    indent.dec();
-                    write!(output_file, "{}}}\n", indent)?;
-                    write!(
-                        output_file,
-                        "{}fn box_clone(&self) -> Box<dyn {}> {{\n",
-                        indent, iter_trait_name
-                    )?;
-                    indent.inc();
-                    write!(output_file, "{}Box::new(self.clone())\n", indent)?;
-                    indent.dec();
-                    write!(output_file, "{}}}\n", indent)?;
-                    indent.dec();
-                    write!(output_file, "{}}}\n", indent)?;
-
-                    write!(output_file, "{}{} {{\n", indent, result_ty_str)?;
-                    indent.inc();
-                    write!(
-                        output_file,
-                        "{}value: Box::new({} {{\n",
-                        indent, struct_name
-                    )?;
-                    indent.inc();
-                    write!(output_file, "{}arg0: arg0,\n", indent,)?;
-                    write!(output_file, "{}arg1: arg1,\n", indent,)?;
-                    indent.dec();
-                    write!(output_file, "{}}}),\n", indent,)?;
-                    indent.dec();
-                    write!(output_file, "{}}}", indent)?;
-                }
-                ("Iterator", "filter") => {
-                    write!(output_file, "{}{} }}\n", indent, result_ty_str)?;
-                }
                 _ => panic!("{}/{} not implemented", function.module, function.name),
             }
         }
@@ -1,3 +1,6 @@
+use crate::pattern::is_complex_pattern;
+use crate::pattern::write_complex_bind;
+use crate::pattern::write_complex_case;
 use crate::pattern::write_pattern;
 use crate::types::ir_type_to_rust_type;
 use crate::util::arg_name;
@@ -76,19 +79,24 @@ pub fn write_expr(
             indent.dec();
         }
         Expr::Bind(pattern, rhs) => {
-            write!(output_file, "let ")?;
-            write_pattern(*pattern, output_file, program, indent)?;
-            write!(output_file, " = ")?;
-            write_expr(*rhs, output_file, program, indent)?;
-            write!(output_file, ";")?;
+            if is_complex_pattern(*pattern, program) {
+                write_complex_bind(*pattern, *rhs, output_file, program, indent)?;
+            } else {
+                write!(output_file, "let ")?;
+                write_pattern(*pattern, output_file, program, indent)?;
+                write!(output_file, " = ")?;
+                write_expr(*rhs, output_file, program, indent)?;
+                write!(output_file, ";")?;
+            }
             is_statement = true;
         }
         Expr::ExprValue(_, pattern_id) => {
             let pattern = &program.patterns.get(pattern_id).item;
-            if let Pattern::Binding(n) = pattern {
-                write!(output_file, "{}", n)?;
-            } else {
-                unreachable!();
+            match pattern {
+                Pattern::Binding(n) | Pattern::As(_, n) => {
+                    write!(output_file, "{}", n)?;
+                }
+                _ => unreachable!(),
             }
         }
         Expr::PartialFunctionCall(id, args) => {
@@ -144,7 +152,7 @@ pub fn write_expr(
         Expr::StringLiteral(s) => {
             let ty = program.get_expr_type(&expr_id);
             let ty = ir_type_to_rust_type(ty, program);
-            write!(output_file, "{} {{ value: \"{}\".to_string() }}", ty, s)?;
+            write!(output_file, "{} {{ value: {:?}.to_string() }}", ty, s)?;
         }
         Expr::FloatLiteral(f) => {
             let ty = program.get_expr_type(&expr_id);
@@ -154,12 +162,12 @@ pub fn write_expr(
         Expr::CharLiteral(c) => {
             let ty = program.get_expr_type(&expr_id);
             let ty = ir_type_to_rust_type(ty, program);
-            write!(output_file, "{} {{ value: '{}' }}", ty, c)?;
+            write!(output_file, "{} {{ value: {:?} }}", ty, c)?;
         }
         Expr::Formatter(fmt, args) => {
             let ty = program.get_expr_type(&expr_id);
             let ty = ir_type_to_rust_type(ty, program);
-            write!(output_file, "{} {{ value : format!(\"{}\"", ty, fmt)?;
+            write!(output_file, "{} {{ value : format!({:?}", ty, fmt)?;
             if !args.is_empty() {
                 write!(output_file, ",")?;
             }
@@ -179,10 +187,22 @@ pub fn write_expr(
             indent.inc();
             for case in cases {
                 write!(output_file, "{}", indent)?;
-                write_pattern(case.pattern_id, output_file, program, indent)?;
-                write!(output_file, " => {{")?;
-                write_expr(case.body, output_file, program, indent)?;
-                write!(output_file, "}}\n")?;
+                if is_complex_pattern(case.pattern_id, program) {
+                    write_complex_case(case.pattern_id, case.body, output_file, program, indent)?;
+                    write!(output_file, "\n")?;
+                } else {
+                    write_pattern(case.pattern_id, output_file, program, indent)?;
+                    write!(output_file, " => {{")?;
+                    write_expr(case.body, output_file, program, indent)?;
+                    write!(output_file, "}}\n")?;
+                }
+            }
+            // the pattern checker already proved exhaustiveness but rustc cannot see through the guards
+            if cases
+                .iter()
+                .any(|case| is_complex_pattern(case.pattern_id, program))
+            {
+                write!(output_file, "{}_ => unreachable!(),\n", indent)?;
             }
             indent.dec();
             write!(output_file, "{}}}", indent)?;
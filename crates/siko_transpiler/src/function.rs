@@ -214,7 +214,11 @@ pub fn write_function(
                 let adt = program.typedefs.get(id).get_adt();
                 let variant = &adt.variants[*index];
                 indent.inc();
-                write!(output_file, "{}{}::{}", indent, result_ty_str, variant.name)?;
+                if adt.newtype {
+                    write!(output_file, "{}{}", indent, result_ty_str)?;
+                } else {
+                    write!(output_file, "{}{}::{}", indent, result_ty_str, variant.name)?;
+                }
                 if function.arg_count > 0 {
                     let mut args = Vec::new();
                     for i in 0..function.arg_count {
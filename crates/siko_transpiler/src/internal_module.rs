@@ -1,5 +1,6 @@
 use crate::types::ir_type_to_rust_type;
 use crate::util::arg_name;
+use crate::util::get_module_name;
 use crate::util::Indent;
 use siko_constants::MIR_FUNCTION_TRAIT_NAME;
 use siko_mir::program::Program;
@@ -126,7 +127,7 @@ fn write_dyn_trait_impl_save_arg(
         } else {
             write!(
                 output_file,
-                "{}{}: self.{}.take(),\n",
+                "{}{}: self.{}.clone(),\n",
                 indent,
                 arg_name(index),
                 arg_name(index)
@@ -192,12 +193,14 @@ fn write_dyn_trait_impl_real_call(
     write!(
         output_file,
         "{}crate::{}::{}(",
-        indent, function.module, function.name
+        indent,
+        get_module_name(&function.module),
+        function.name
     )?;
     for index in 0..partial_function_call.fields.len() {
         write!(
             output_file,
-            "self.{}.take().expect(\"Missing arg\"), ",
+            "self.{}.clone().expect(\"Missing arg\"), ",
             arg_name(index)
         )?;
     }
@@ -1,9 +1,11 @@
 use crate::expr::write_expr;
 use crate::types::ir_type_to_rust_type;
 use crate::util::Indent;
+use siko_mir::expr::ExprId;
 use siko_mir::pattern::Pattern;
 use siko_mir::pattern::PatternId;
 use siko_mir::program::Program;
+use siko_mir::types::Type;
 use std::io::Result;
 use std::io::Write;
 
@@ -34,12 +36,16 @@ pub fn write_pattern(
             let ty = program.get_pattern_type(&pattern_id);
             let adt = program.typedefs.get(id).get_adt();
             let variant = &adt.variants[*index];
-            write!(
-                output_file,
-                "{}::{}",
-                ir_type_to_rust_type(ty, program),
-                variant.name
-            )?;
+            if adt.newtype {
+                write!(output_file, "{}", ir_type_to_rust_type(ty, program))?;
+            } else {
+                write!(
+                    output_file,
+                    "{}::{}",
+                    ir_type_to_rust_type(ty, program),
+                    variant.name
+                )?;
+            }
             if !items.is_empty() {
                 write!(output_file, "(")?;
                 for (index, item) in items.iter().enumerate() {
@@ -74,21 +80,300 @@ pub fn write_pattern(
         Pattern::CharLiteral(i) => {
             let ty = program.get_pattern_type(&pattern_id);
             let ty = ir_type_to_rust_type(ty, program);
-            write!(output_file, "{} {{ value: '{}' }}", ty, i)?;
+            write!(output_file, "{} {{ value: {:?} }}", ty, i)?;
         }
-        Pattern::CharRange(start, end) => {
-            write!(
-                output_file,
-                "p if std::ops::Range{{ start : '{}', end: ",
-                start
-            )?;
-            write!(output_file, "'{}'}}.contains(&p.value)", end)?;
+        Pattern::CharRange(..)
+        | Pattern::StringLiteral(_)
+        | Pattern::IntegerRange(..)
+        | Pattern::FloatLiteral(_)
+        | Pattern::List(..)
+        | Pattern::As(..) => {
+            // complex patterns are written by write_complex_case and write_complex_bind
+            unreachable!()
         }
-        Pattern::StringLiteral(s) => {
-            let ty = program.get_pattern_type(&pattern_id);
-            let ty = ir_type_to_rust_type(ty, program);
-            write!(output_file, "{} {{ value: {} }}", ty, s)?;
+    }
+    Ok(())
+}
+
+fn item_place(place: String, ty: &Type) -> String {
+    if let Type::Boxed(_) = ty {
+        format!("(*{})", place)
+    } else {
+        place
+    }
+}
+
+fn get_rest_place(rest: PatternId, place: &str, skipped: usize, program: &Program) -> String {
+    let ty = program.get_pattern_type(&rest);
+    format!(
+        "({} {{ value: {}.value[{}..].to_vec() }})",
+        ir_type_to_rust_type(ty, program),
+        place,
+        skipped
+    )
+}
+
+fn variant_match_arm(pattern_id: PatternId, program: &Program) -> String {
+    let pattern = &program.patterns.get(&pattern_id).item;
+    if let Pattern::Variant(id, index, items) = pattern {
+        let ty = program.get_pattern_type(&pattern_id);
+        let adt = program.typedefs.get(id).get_adt();
+        let variant = &adt.variants[*index];
+        let mut arm = format!("{}::{}", ir_type_to_rust_type(ty, program), variant.name);
+        if !items.is_empty() {
+            let names: Vec<_> = items.iter().map(|item| format!("p{}", item.id)).collect();
+            arm = format!("{}({})", arm, names.join(", "));
+        }
+        arm
+    } else {
+        unreachable!()
+    }
+}
+
+// Returns whether the pattern can be written as a Rust pattern over the generated types,
+// the rest is matched by binding the whole value and testing it in a match guard
+pub fn is_complex_pattern(pattern_id: PatternId, program: &Program) -> bool {
+    let pattern = &program.patterns.get(&pattern_id).item;
+    match pattern {
+        Pattern::Binding(_)
+        | Pattern::Wildcard
+        | Pattern::IntegerLiteral(_)
+        | Pattern::CharLiteral(_) => false,
+        Pattern::Record(_, items) | Pattern::Variant(_, _, items) => {
+            items.iter().any(|item| is_complex_pattern(*item, program))
+        }
+        Pattern::Guarded(inner, _) => is_complex_pattern(*inner, program),
+        Pattern::CharRange(..)
+        | Pattern::IntegerRange(..)
+        | Pattern::FloatLiteral(_)
+        | Pattern::StringLiteral(_)
+        | Pattern::List(..)
+        | Pattern::As(..) => true,
+    }
+}
+
+fn get_pattern_test(pattern_id: PatternId, place: String, program: &Program) -> Option<String> {
+    let pattern = &program.patterns.get(&pattern_id).item;
+    match pattern {
+        Pattern::Binding(_) | Pattern::Wildcard => None,
+        Pattern::As(inner, _) | Pattern::Guarded(inner, _) => {
+            get_pattern_test(*inner, place, program)
+        }
+        Pattern::IntegerLiteral(i) => Some(format!("{}.value == {}", place, i)),
+        Pattern::CharLiteral(c) => Some(format!("{}.value == {:?}", place, c)),
+        Pattern::StringLiteral(s) => Some(format!("{}.value == {:?}", place, s)),
+        Pattern::FloatLiteral(f) => Some(format!("{}.value == {:?}", place, f)),
+        Pattern::CharRange(start, end) => Some(format!(
+            "({:?}..{:?}).contains(&{}.value)",
+            start, end, place
+        )),
+        Pattern::IntegerRange(start, end) => {
+            Some(format!("({}..{}).contains(&{}.value)", start, end, place))
+        }
+        Pattern::Record(id, items) => {
+            let record = program.typedefs.get(id).get_record();
+            let tests: Vec<_> = items
+                .iter()
+                .enumerate()
+                .filter_map(|(index, item)| {
+                    let field = &record.fields[index];
+                    let place = item_place(format!("{}.{}", place, field.name), &field.ty);
+                    get_pattern_test(*item, place, program)
+                })
+                .collect();
+            if tests.is_empty() {
+                None
+            } else {
+                Some(tests.join(" && "))
+            }
+        }
+        Pattern::Variant(id, index, items) => {
+            let adt = program.typedefs.get(id).get_adt();
+            let variant = &adt.variants[*index];
+            let tests: Vec<_> = items
+                .iter()
+                .enumerate()
+                .filter_map(|(index, item)| {
+                    let item_ty = &variant.items[index];
+                    let place = if adt.newtype {
+                        item_place(format!("{}.0", place), item_ty)
+                    } else {
+                        item_place(format!("(*p{})", item.id), item_ty)
+                    };
+                    get_pattern_test(*item, place, program)
+                })
+                .collect();
+            if adt.newtype {
+                if tests.is_empty() {
+                    None
+                } else {
+                    Some(tests.join(" && "))
+                }
+            } else {
+                let test = if tests.is_empty() {
+                    format!("true")
+                } else {
+                    tests.join(" && ")
+                };
+                Some(format!(
+                    "match &{} {{ {} => {}, _ => false }}",
+                    place,
+                    variant_match_arm(pattern_id, program),
+                    test
+                ))
+            }
+        }
+        Pattern::List(items, rest) => {
+            let mut tests = Vec::new();
+            if rest.is_some() {
+                tests.push(format!("{}.value.len() >= {}", place, items.len()));
+            } else {
+                tests.push(format!("{}.value.len() == {}", place, items.len()));
+            }
+            for (index, item) in items.iter().enumerate() {
+                let place = format!("{}.value[{}]", place, index);
+                if let Some(test) = get_pattern_test(*item, place, program) {
+                    tests.push(test);
+                }
+            }
+            if let Some(rest) = rest {
+                let rest_place = get_rest_place(*rest, &place, items.len(), program);
+                if let Some(test) = get_pattern_test(*rest, rest_place, program) {
+                    tests.push(test);
+                }
+            }
+            Some(tests.join(" && "))
+        }
+    }
+}
+
+fn collect_bindings(
+    pattern_id: PatternId,
+    place: String,
+    program: &Program,
+    bindings: &mut Vec<(String, String)>,
+) {
+    let pattern = &program.patterns.get(&pattern_id).item;
+    match pattern {
+        Pattern::Binding(name) => {
+            bindings.push((name.clone(), format!("{}.clone()", place)));
+        }
+        Pattern::As(inner, name) => {
+            bindings.push((name.clone(), format!("{}.clone()", place)));
+            collect_bindings(*inner, place, program, bindings);
+        }
+        Pattern::Guarded(inner, _) => {
+            collect_bindings(*inner, place, program, bindings);
+        }
+        Pattern::Wildcard
+        | Pattern::IntegerLiteral(_)
+        | Pattern::CharLiteral(_)
+        | Pattern::StringLiteral(_)
+        | Pattern::FloatLiteral(_)
+        | Pattern::CharRange(..)
+        | Pattern::IntegerRange(..) => {}
+        Pattern::Record(id, items) => {
+            let record = program.typedefs.get(id).get_record();
+            for (index, item) in items.iter().enumerate() {
+                let field = &record.fields[index];
+                let place = item_place(format!("{}.{}", place, field.name), &field.ty);
+                collect_bindings(*item, place, program, bindings);
+            }
+        }
+        Pattern::Variant(id, index, items) => {
+            let adt = program.typedefs.get(id).get_adt();
+            let variant = &adt.variants[*index];
+            if adt.newtype {
+                for (index, item) in items.iter().enumerate() {
+                    let place = item_place(format!("{}.0", place), &variant.items[index]);
+                    collect_bindings(*item, place, program, bindings);
+                }
+            } else {
+                let mut item_bindings = Vec::new();
+                for (index, item) in items.iter().enumerate() {
+                    let place = item_place(format!("(*p{})", item.id), &variant.items[index]);
+                    collect_bindings(*item, place, program, &mut item_bindings);
+                }
+                let arm = variant_match_arm(pattern_id, program);
+                for (name, value) in item_bindings {
+                    let value = format!(
+                        "match &{} {{ {} => {}, _ => unreachable!() }}",
+                        place, arm, value
+                    );
+                    bindings.push((name, value));
+                }
+            }
+        }
+        Pattern::List(items, rest) => {
+            for (index, item) in items.iter().enumerate() {
+                let place = format!("{}.value[{}]", place, index);
+                collect_bindings(*item, place, program, bindings);
+            }
+            if let Some(rest) = rest {
+                let rest_place = get_rest_place(*rest, &place, items.len(), program);
+                collect_bindings(*rest, rest_place, program, bindings);
+            }
         }
     }
+}
+
+fn write_bindings(bindings: &Vec<(String, String)>, output_file: &mut dyn Write) -> Result<()> {
+    for (name, value) in bindings {
+        write!(output_file, "let {} = {}; ", name, value)?;
+    }
+    Ok(())
+}
+
+pub fn write_complex_case(
+    pattern_id: PatternId,
+    body: ExprId,
+    output_file: &mut dyn Write,
+    program: &Program,
+    indent: &mut Indent,
+) -> Result<()> {
+    let (pattern_id, guard) = match &program.patterns.get(&pattern_id).item {
+        Pattern::Guarded(inner, guard) => (*inner, Some(*guard)),
+        _ => (pattern_id, None),
+    };
+    let place = format!("pattern_{}", pattern_id.id);
+    let mut bindings = Vec::new();
+    collect_bindings(pattern_id, place.clone(), program, &mut bindings);
+    let test =
+        get_pattern_test(pattern_id, place.clone(), program).unwrap_or_else(|| format!("true"));
+    write!(output_file, "{} if {}", place, test)?;
+    if let Some(guard) = guard {
+        write!(output_file, " && {{ ")?;
+        write_bindings(&bindings, output_file)?;
+        write!(output_file, "match ")?;
+        write_expr(guard, output_file, program, indent)?;
+        let ty = program.get_expr_type(&guard);
+        let ty = ir_type_to_rust_type(ty, program);
+        write!(
+            output_file,
+            " {{ {}::True => true, {}::False => false }} }}",
+            ty, ty
+        )?;
+    }
+    write!(output_file, " => {{ ")?;
+    write_bindings(&bindings, output_file)?;
+    write_expr(body, output_file, program, indent)?;
+    write!(output_file, " }}")?;
+    Ok(())
+}
+
+pub fn write_complex_bind(
+    pattern_id: PatternId,
+    rhs: ExprId,
+    output_file: &mut dyn Write,
+    program: &Program,
+    indent: &mut Indent,
+) -> Result<()> {
+    let place = format!("pattern_{}", pattern_id.id);
+    let mut bindings = Vec::new();
+    collect_bindings(pattern_id, place.clone(), program, &mut bindings);
+    write!(output_file, "let {} = ", place)?;
+    write_expr(rhs, output_file, program, indent)?;
+    write!(output_file, "; ")?;
+    write_bindings(&bindings, output_file)?;
     Ok(())
 }
@@ -16,6 +16,17 @@ pub fn write_typedef(
 ) -> Result<()> {
     let typedef = program.typedefs.get(&typedef_id);
     match typedef {
+        TypeDef::Adt(adt) if adt.newtype => {
+            // a newtype has no runtime representation besides its single item
+            let item_ty = ir_type_to_rust_type(&adt.variants[0].items[0], program);
+            write!(output_file, "{}#[derive(Clone)]\n", indent)?;
+            write!(output_file, "{}#[repr(transparent)]\n", indent)?;
+            write!(
+                output_file,
+                "{}pub struct {}(pub {});\n",
+                indent, adt.name, item_ty
+            )?;
+        }
         TypeDef::Adt(adt) => {
             write!(output_file, "{}#[derive(Clone)]\n", indent)?;
             write!(output_file, "{}pub enum {} {{\n", indent, adt.name)?;
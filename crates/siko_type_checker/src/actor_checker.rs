@@ -0,0 +1,115 @@
+use crate::error::TypecheckError;
+use siko_ir::expr::Expr;
+use siko_ir::expr::ExprId;
+use siko_ir::function::FunctionId;
+use siko_ir::pattern::Pattern;
+use siko_ir::pattern::PatternId;
+use siko_ir::program::Program;
+use siko_ir::types::Type;
+use siko_ir::walker::Visitor;
+use siko_location_info::location_id::LocationId;
+
+// Checks that a message of the given type can be sent to an actor of the
+// given type, types that are not known yet are checked by the constraint of
+// send.
+pub fn check_message(
+    program: &Program,
+    actor_ty: &Type,
+    actor_location: LocationId,
+    message_ty: &Type,
+    message_location: LocationId,
+    errors: &mut Vec<TypecheckError>,
+) {
+    let actor = match actor_ty {
+        Type::Var(..) | Type::FixedTypeArg(..) | Type::Application(..) => None,
+        Type::Named(_, typedef_id, _) if program.get_actor_by_typedef(*typedef_id).is_some() => {
+            program.get_actor_by_typedef(*typedef_id)
+        }
+        _ => {
+            let err = TypecheckError::SendTargetNotActor(
+                actor_location,
+                actor_ty.get_resolved_type_string(program),
+            );
+            errors.push(err);
+            None
+        }
+    };
+    match message_ty {
+        Type::Var(..) | Type::FixedTypeArg(..) | Type::Application(..) => {}
+        Type::Named(_, typedef_id, _) if program.get_protocol_by_typedef(*typedef_id).is_some() => {
+            let protocol = program
+                .get_protocol_by_typedef(*typedef_id)
+                .expect("Protocol not found");
+            if let Some(actor) = actor {
+                if actor.get_handler(protocol.id).is_none() {
+                    let err = TypecheckError::ProtocolNotHandled(
+                        message_location,
+                        actor.name.clone(),
+                        protocol.name.clone(),
+                    );
+                    errors.push(err);
+                }
+            }
+        }
+        _ => {
+            let err = TypecheckError::SendMessageNotProtocol(
+                message_location,
+                message_ty.get_resolved_type_string(program),
+            );
+            errors.push(err);
+        }
+    }
+}
+
+pub struct ActorChecker<'a> {
+    program: &'a Program,
+    errors: &'a mut Vec<TypecheckError>,
+    send_function_id: FunctionId,
+}
+
+impl<'a> ActorChecker<'a> {
+    pub fn new(
+        program: &'a Program,
+        errors: &'a mut Vec<TypecheckError>,
+        send_function_id: FunctionId,
+    ) -> ActorChecker<'a> {
+        ActorChecker {
+            program: program,
+            errors: errors,
+            send_function_id: send_function_id,
+        }
+    }
+
+    fn check_send(&mut self, args: &[ExprId]) {
+        if let [actor_expr_id, message_expr_id] = args {
+            let actor_ty = self.program.get_expr_type(actor_expr_id);
+            let actor_location = self.program.exprs.get(actor_expr_id).location_id;
+            let message_ty = self.program.get_expr_type(message_expr_id);
+            let message_location = self.program.exprs.get(message_expr_id).location_id;
+            check_message(
+                self.program,
+                actor_ty,
+                actor_location,
+                message_ty,
+                message_location,
+                self.errors,
+            );
+        }
+    }
+}
+
+impl<'a> Visitor for ActorChecker<'a> {
+    fn get_program(&self) -> &Program {
+        self.program
+    }
+
+    fn visit_expr(&mut self, _: ExprId, expr: &Expr) {
+        if let Expr::StaticFunctionCall(function_id, args) = expr {
+            if *function_id == self.send_function_id {
+                self.check_send(args);
+            }
+        }
+    }
+
+    fn visit_pattern(&mut self, _: PatternId, _: &Pattern) {}
+}
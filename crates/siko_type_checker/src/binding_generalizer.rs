@@ -0,0 +1,349 @@
+use siko_ir::expr::Case;
+use siko_ir::expr::Expr;
+use siko_ir::expr::ExprId;
+use siko_ir::expr::RecordFieldValueExpr;
+use siko_ir::expr::RecordUpdateInfo;
+use siko_ir::function::Function;
+use siko_ir::function::FunctionId;
+use siko_ir::function::FunctionInfo;
+use siko_ir::pattern::BindGroup;
+use siko_ir::pattern::Pattern;
+use siko_ir::pattern::PatternId;
+use siko_ir::program::Program;
+use siko_ir::walker::walk_expr;
+use siko_ir::walker::Visitor;
+use siko_location_info::item::ItemInfo;
+use std::collections::BTreeMap;
+
+// Local bindings in do blocks whose right hand side is a syntactic value (a
+// lambda or a partially applied function over variables) are generalised by
+// giving every use of the binding its own copy of the value, lambdas included.
+// Each copy is typechecked and monomorphised on its own, so `id <- \x -> x`
+// can be used at several types. Anything else stays monomorphic (value
+// restriction).
+
+struct BindCollector<'a> {
+    program: &'a Program,
+    binds: Vec<(ExprId, PatternId, ExprId)>,
+    uses: Vec<(ExprId, PatternId)>,
+}
+
+impl<'a> Visitor for BindCollector<'a> {
+    fn get_program(&self) -> &Program {
+        &self.program
+    }
+
+    fn visit_expr(&mut self, expr_id: ExprId, expr: &Expr) {
+        match expr {
+            Expr::Bind(pattern_id, rhs) => {
+                self.binds.push((expr_id, *pattern_id, *rhs));
+            }
+            Expr::ExprValue(_, pattern_id) => {
+                self.uses.push((expr_id, *pattern_id));
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_pattern(&mut self, _: PatternId, _: &Pattern) {}
+}
+
+struct Copier {
+    source: FunctionId,
+    target: FunctionId,
+    exprs: BTreeMap<ExprId, ExprId>,
+    patterns: BTreeMap<PatternId, PatternId>,
+}
+
+impl Copier {
+    fn new(source: FunctionId, target: FunctionId) -> Copier {
+        Copier {
+            source: source,
+            target: target,
+            exprs: BTreeMap::new(),
+            patterns: BTreeMap::new(),
+        }
+    }
+
+    fn copy_exprs(&mut self, exprs: &Vec<ExprId>, program: &mut Program) -> Vec<ExprId> {
+        exprs
+            .iter()
+            .map(|expr_id| self.copy_expr(expr_id, program))
+            .collect()
+    }
+
+    fn copy_patterns(
+        &mut self,
+        patterns: &Vec<PatternId>,
+        program: &mut Program,
+    ) -> Vec<PatternId> {
+        patterns
+            .iter()
+            .map(|pattern_id| self.copy_pattern(pattern_id, program))
+            .collect()
+    }
+
+    fn copy_value(
+        &mut self,
+        value: &RecordFieldValueExpr,
+        program: &mut Program,
+    ) -> RecordFieldValueExpr {
+        RecordFieldValueExpr {
+            expr_id: self.copy_expr(&value.expr_id, program),
+            index: value.index,
+        }
+    }
+
+    fn copy_expr(&mut self, expr_id: &ExprId, program: &mut Program) -> ExprId {
+        let new_id = program.exprs.get_id();
+        // ExprValue refers back to the Bind or CaseOf that introduced it, so the
+        // mapping must exist before the children are copied.
+        self.exprs.insert(*expr_id, new_id);
+        let info = program.exprs.get(expr_id).clone();
+        let expr = self.copy_item(&info.item, program);
+        program
+            .exprs
+            .add_item(new_id, ItemInfo::new(expr, info.location_id));
+        new_id
+    }
+
+    fn copy_item(&mut self, expr: &Expr, program: &mut Program) -> Expr {
+        match expr {
+            Expr::StaticFunctionCall(id, args) => {
+                let id = copy_lambda(id, program);
+                Expr::StaticFunctionCall(id, self.copy_exprs(args, program))
+            }
+            Expr::DynamicFunctionCall(func_expr, args) => {
+                let func_expr = self.copy_expr(func_expr, program);
+                Expr::DynamicFunctionCall(func_expr, self.copy_exprs(args, program))
+            }
+            Expr::ClassFunctionCall(id, args) => {
+                Expr::ClassFunctionCall(*id, self.copy_exprs(args, program))
+            }
+            Expr::If(cond, true_branch, false_branch) => Expr::If(
+                self.copy_expr(cond, program),
+                self.copy_expr(true_branch, program),
+                self.copy_expr(false_branch, program),
+            ),
+            Expr::Tuple(items) => Expr::Tuple(self.copy_exprs(items, program)),
+            Expr::List(items) => Expr::List(self.copy_exprs(items, program)),
+            Expr::Do(items) => Expr::Do(self.copy_exprs(items, program)),
+            Expr::Bind(pattern_id, rhs) => {
+                let rhs = self.copy_expr(rhs, program);
+                Expr::Bind(self.copy_pattern(pattern_id, program), rhs)
+            }
+            Expr::ArgRef(arg_ref) => {
+                let mut arg_ref = *arg_ref;
+                if arg_ref.id == self.source {
+                    arg_ref.id = self.target;
+                }
+                Expr::ArgRef(arg_ref)
+            }
+            Expr::ExprValue(expr_id, pattern_id) => {
+                let expr_id = *self.exprs.get(expr_id).unwrap_or(expr_id);
+                let pattern_id = *self.patterns.get(pattern_id).unwrap_or(pattern_id);
+                Expr::ExprValue(expr_id, pattern_id)
+            }
+            Expr::FieldAccess(infos, receiver) => {
+                Expr::FieldAccess(infos.clone(), self.copy_expr(receiver, program))
+            }
+            Expr::TupleFieldAccess(index, receiver) => {
+                Expr::TupleFieldAccess(*index, self.copy_expr(receiver, program))
+            }
+            Expr::Formatter(fmt, args) => {
+                Expr::Formatter(fmt.clone(), self.copy_exprs(args, program))
+            }
+            Expr::CaseOf(body, cases, bind_groups) => {
+                let body = self.copy_expr(body, program);
+                let cases = cases
+                    .iter()
+                    .map(|case| Case {
+                        pattern_id: self.copy_pattern(&case.pattern_id, program),
+                        body: self.copy_expr(&case.body, program),
+                    })
+                    .collect();
+                let bind_groups = bind_groups
+                    .iter()
+                    .map(|bind_group| BindGroup {
+                        patterns: bind_group
+                            .patterns
+                            .iter()
+                            .map(|pattern_id| self.patterns[pattern_id])
+                            .collect(),
+                    })
+                    .collect();
+                Expr::CaseOf(body, cases, bind_groups)
+            }
+            Expr::RecordInitialization(id, values) => {
+                let values = values
+                    .iter()
+                    .map(|value| self.copy_value(value, program))
+                    .collect();
+                Expr::RecordInitialization(*id, values)
+            }
+            Expr::RecordUpdate(receiver, updates) => {
+                let receiver = self.copy_expr(receiver, program);
+                let updates = updates
+                    .iter()
+                    .map(|update| RecordUpdateInfo {
+                        record_id: update.record_id,
+                        items: update
+                            .items
+                            .iter()
+                            .map(|value| self.copy_value(value, program))
+                            .collect(),
+                    })
+                    .collect();
+                Expr::RecordUpdate(receiver, updates)
+            }
+            Expr::Hole(info) => {
+                let mut info = info.clone();
+                for (_, binding) in &mut info.bindings {
+                    *binding = self.copy_expr(binding, program);
+                }
+                Expr::Hole(info)
+            }
+            Expr::IntegerLiteral(_)
+            | Expr::FloatLiteral(_)
+            | Expr::StringLiteral(_)
+            | Expr::CharLiteral(_) => expr.clone(),
+        }
+    }
+
+    fn copy_pattern(&mut self, pattern_id: &PatternId, program: &mut Program) -> PatternId {
+        let new_id = program.patterns.get_id();
+        self.patterns.insert(*pattern_id, new_id);
+        let info = program.patterns.get(pattern_id).clone();
+        let pattern = match &info.item {
+            Pattern::Tuple(items) => Pattern::Tuple(self.copy_patterns(items, program)),
+            Pattern::Record(id, items) => Pattern::Record(*id, self.copy_patterns(items, program)),
+            Pattern::Variant(id, index, items) => {
+                Pattern::Variant(*id, *index, self.copy_patterns(items, program))
+            }
+            Pattern::Guarded(inner, guard) => {
+                let inner = self.copy_pattern(inner, program);
+                Pattern::Guarded(inner, self.copy_expr(guard, program))
+            }
+            Pattern::List(items, rest) => {
+                let items = self.copy_patterns(items, program);
+                let rest = rest.map(|rest| self.copy_pattern(&rest, program));
+                Pattern::List(items, rest)
+            }
+            Pattern::As(inner, name) => {
+                Pattern::As(self.copy_pattern(inner, program), name.clone())
+            }
+            Pattern::Typed(inner, type_signature_id) => {
+                Pattern::Typed(self.copy_pattern(inner, program), *type_signature_id)
+            }
+            pattern => pattern.clone(),
+        };
+        program
+            .patterns
+            .add_item(new_id, ItemInfo::new(pattern, info.location_id));
+        new_id
+    }
+}
+
+// Lambdas are typechecked together with their host function, so a lambda
+// shared between several uses would be monomorphic again.
+fn copy_lambda(function_id: &FunctionId, program: &mut Program) -> FunctionId {
+    let function = program.functions.get(function_id).clone();
+    let mut info = match function.info {
+        FunctionInfo::Lambda(info) => info,
+        _ => return *function_id,
+    };
+    let new_id = program.functions.get_id();
+    let mut copier = Copier::new(*function_id, new_id);
+    info.body = copier.copy_expr(&info.body, program);
+    let function = Function {
+        id: new_id,
+        arg_locations: function.arg_locations,
+        arg_names: function.arg_names,
+        arg_count: function.arg_count,
+        info: FunctionInfo::Lambda(info),
+    };
+    program.functions.add_item(new_id, function);
+    new_id
+}
+
+fn is_value(expr_id: &ExprId, program: &Program) -> bool {
+    match &program.exprs.get(expr_id).item {
+        Expr::ArgRef(_) => true,
+        Expr::ExprValue(_, _) => true,
+        Expr::IntegerLiteral(_) => true,
+        Expr::FloatLiteral(_) => true,
+        Expr::StringLiteral(_) => true,
+        Expr::CharLiteral(_) => true,
+        Expr::StaticFunctionCall(id, args) => is_partial_application(id, args, program),
+        _ => false,
+    }
+}
+
+fn is_partial_application(id: &FunctionId, args: &Vec<ExprId>, program: &Program) -> bool {
+    let function = program.functions.get(id);
+    function.arg_count > args.len() && args.iter().all(|arg| is_value(arg, program))
+}
+
+fn is_generalizable(pattern_id: &PatternId, rhs: &ExprId, program: &Program) -> bool {
+    match &program.patterns.get(pattern_id).item {
+        Pattern::Binding(_) => {}
+        _ => return false,
+    }
+    match &program.exprs.get(rhs).item {
+        Expr::StaticFunctionCall(id, args) => is_partial_application(id, args, program),
+        _ => false,
+    }
+}
+
+fn generalize_function(function_id: FunctionId, program: &mut Program) {
+    let body = match program.functions.get(&function_id).get_body() {
+        Some(body) => body,
+        None => return,
+    };
+    let mut collector = BindCollector {
+        program: program,
+        binds: Vec::new(),
+        uses: Vec::new(),
+    };
+    walk_expr(&body, &mut collector);
+    let binds = collector.binds;
+    let uses = collector.uses;
+    for (bind_expr_id, pattern_id, rhs) in binds {
+        let pattern_uses: Vec<_> = uses
+            .iter()
+            .filter(|(_, id)| *id == pattern_id)
+            .map(|(use_expr_id, _)| *use_expr_id)
+            .collect();
+        if pattern_uses.is_empty() || !is_generalizable(&pattern_id, &rhs, program) {
+            continue;
+        }
+        // the first use takes over the original value, the others get copies
+        let value = program.exprs.get(&rhs).item.clone();
+        for (index, use_expr_id) in pattern_uses.iter().enumerate() {
+            let value = if index == 0 {
+                value.clone()
+            } else {
+                let mut copier = Copier::new(function_id, function_id);
+                copier.copy_item(&value, program)
+            };
+            program.exprs.get_mut(use_expr_id).item = value;
+        }
+        program.exprs.get_mut(&bind_expr_id).item = Expr::Tuple(Vec::new());
+    }
+}
+
+pub fn generalize_local_bindings(program: &mut Program) {
+    // lambdas copied along the way are appended with higher ids and get
+    // processed in the next round
+    let mut processed = 0;
+    loop {
+        let functions: Vec<_> = program.functions.items.keys().cloned().collect();
+        if processed == functions.len() {
+            break;
+        }
+        for function_id in &functions[processed..] {
+            generalize_function(*function_id, program);
+        }
+        processed = functions.len();
+    }
+}
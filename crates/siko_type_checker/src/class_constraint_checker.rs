@@ -1,23 +1,34 @@
+use crate::actor_checker::check_message;
 use crate::common::FunctionTypeInfo;
 use crate::error::TypecheckError;
 use crate::type_info_provider::TypeInfoProvider;
 use crate::type_store::TypeStore;
 use crate::util::create_general_function_type_info;
+use crate::util::process_class_constraints;
+use siko_ir::class::ClassId;
 use siko_ir::expr::Expr;
 use siko_ir::expr::ExprId;
+use siko_ir::function::FunctionId;
+use siko_ir::function::FunctionInfo;
 use siko_ir::pattern::Pattern;
 use siko_ir::pattern::PatternId;
 use siko_ir::program::Program;
+use siko_ir::types::ResolverContext;
 use siko_ir::types::Type;
 use siko_ir::unifier::Unifier;
 use siko_ir::walker::Visitor;
 use siko_location_info::location_id::LocationId;
+use std::collections::BTreeMap;
 
 pub struct ClassConstraintChecker<'a> {
     program: &'a mut Program,
     type_store: &'a mut TypeStore,
     errors: &'a mut Vec<TypecheckError>,
     type_info_provider: &'a mut TypeInfoProvider,
+    givens: Vec<(ClassId, Type)>,
+    host_id: FunctionId,
+    actor_handles_class_id: Option<ClassId>,
+    actor_send_function_id: Option<FunctionId>,
 }
 
 impl<'a> ClassConstraintChecker<'a> {
@@ -26,12 +37,20 @@ impl<'a> ClassConstraintChecker<'a> {
         type_store: &'a mut TypeStore,
         errors: &'a mut Vec<TypecheckError>,
         type_info_provider: &'a mut TypeInfoProvider,
+        givens: Vec<(ClassId, Type)>,
+        host_id: FunctionId,
     ) -> ClassConstraintChecker<'a> {
+        let actor_handles_class_id = program.get_actor_handles_class_id();
+        let actor_send_function_id = program.get_actor_send_function_id();
         ClassConstraintChecker {
             program: program,
             type_store: type_store,
             errors: errors,
             type_info_provider: type_info_provider,
+            givens: givens,
+            host_id: host_id,
+            actor_handles_class_id: actor_handles_class_id,
+            actor_send_function_id: actor_send_function_id,
         }
     }
 
@@ -62,7 +81,7 @@ impl<'a> ClassConstraintChecker<'a> {
         if failed {
             let ty_str1 = ty1.get_resolved_type_string(self.program);
             let ty_str2 = ty2.get_resolved_type_string(self.program);
-            let err = TypecheckError::TypeMismatch(location, ty_str1, ty_str2);
+            let err = TypecheckError::TypeMismatch(location, ty_str1, ty_str2, Vec::new());
             self.errors.push(err);
         } else {
             if let Some(func_type_info) = func_type_info {
@@ -95,6 +114,162 @@ impl<'a> ClassConstraintChecker<'a> {
         let result_ty = func_type_info.function_type.get_result_type(args.len());
         self.unify(&result_ty, &expr_ty, location, Some(&mut func_type_info));
     }
+
+    fn constraint_string(&self, class_id: ClassId, ty: &Type) -> String {
+        let mut resolver_context = ResolverContext::new(self.program);
+        let items = match ty {
+            Type::Tuple(items) => items.clone(),
+            _ => vec![ty.clone()],
+        };
+        if Some(class_id) == self.actor_handles_class_id {
+            let items: Vec<_> = items
+                .iter()
+                .map(|item| item.get_resolved_type_string_with_context(&mut resolver_context))
+                .collect();
+            return items.join(" <- ");
+        }
+        let mut parts = vec![self.program.classes.get(&class_id).name.clone()];
+        for item in items {
+            let item = item.get_resolved_type_string_with_context(&mut resolver_context);
+            let wrapped = (item.starts_with('(') && item.ends_with(')'))
+                || (item.starts_with('[') && item.ends_with(']'));
+            if item.contains(' ') && !wrapped {
+                parts.push(format!("({})", item));
+            } else {
+                parts.push(item);
+            }
+        }
+        parts.join(" ")
+    }
+
+    fn check_class_constraint(
+        &mut self,
+        class_id: ClassId,
+        ty: &Type,
+        location: LocationId,
+    ) -> bool {
+        if !self.program.classes.get(&class_id).is_multi_param() {
+            let mut unifiers = Vec::new();
+            return self.program.instance_resolver.check_instance(
+                class_id,
+                ty,
+                location,
+                &mut unifiers,
+            );
+        }
+        for (given_class_id, given_ty) in &self.givens {
+            let mut unifier = self.program.get_unifier();
+            if *given_class_id == class_id && unifier.unify(ty, given_ty).is_ok() {
+                return true;
+            }
+        }
+        match self.program.instance_resolver.get_context(class_id, ty) {
+            Some(context) => context.iter().all(|(context_class_id, context_ty)| {
+                self.check_class_constraint(*context_class_id, context_ty, location)
+            }),
+            None => false,
+        }
+    }
+
+    // A constraint over the type variables of a function without type
+    // signature becomes a constraint of that function, its callers select the
+    // instance.
+    fn infer_constraint(&mut self, class_id: ClassId, ty: &Type) -> bool {
+        let host_type_info = self
+            .type_info_provider
+            .function_type_info_store
+            .get(&self.host_id);
+        if host_type_info.typed {
+            return false;
+        }
+        let mut host_vars = Vec::new();
+        host_type_info.function_type.collect_vars(&mut host_vars);
+        let mut vars = Vec::new();
+        ty.collect_vars(&mut vars);
+        if !vars.iter().all(|var| host_vars.contains(var)) {
+            return false;
+        }
+        let constraints = self
+            .type_info_provider
+            .inferred_constraints
+            .entry(self.host_id)
+            .or_insert_with(Vec::new);
+        if !constraints.contains(&(class_id, ty.clone())) {
+            constraints.push((class_id, ty.clone()));
+        }
+        true
+    }
+
+    // Constraints of multi parameter classes are not attached to a single type
+    // variable, they are checked once the types of the call are known. Type
+    // variables left in the class arguments mean that the instance cannot be
+    // selected, unless they belong to the type of the function.
+    fn check_multi_param_constraints(
+        &mut self,
+        args: &Vec<ExprId>,
+        function_type: &Type,
+        constraints: Vec<(ClassId, Type)>,
+        location: LocationId,
+        expr_id: ExprId,
+        is_send: bool,
+    ) {
+        let mut call_type = self.type_store.get_expr_type(&expr_id).clone();
+        for arg in args.iter().rev() {
+            let arg_type = self.type_store.get_expr_type(arg).clone();
+            call_type = Type::Function(Box::new(arg_type), Box::new(call_type));
+        }
+        // the call may use the type arguments of the callee itself, so the
+        // signature is instantiated with fresh variables first
+        let type_var_generator = &mut self.type_info_provider.type_var_generator;
+        let mut arg_map = BTreeMap::new();
+        let function_type = function_type
+            .duplicate(&mut arg_map, type_var_generator)
+            .remove_fixed_types();
+        let constraints: Vec<_> = constraints
+            .into_iter()
+            .map(|(class_id, ty)| {
+                let ty = ty.duplicate(&mut arg_map, type_var_generator);
+                (class_id, ty.remove_fixed_types())
+            })
+            .collect();
+        let mut unifier = self.program.get_unifier();
+        if unifier.unify(&function_type, &call_type).is_err() {
+            return;
+        }
+        for (class_id, ty) in constraints {
+            let ty = unifier.apply(&ty);
+            if ty.has_type_vars() {
+                if self.infer_constraint(class_id, &ty) {
+                    continue;
+                }
+                let candidates = self.program.instance_resolver.get_candidates(class_id, &ty);
+                let err = TypecheckError::AmbiguousInstance(
+                    self.constraint_string(class_id, &ty),
+                    location,
+                    candidates,
+                );
+                self.errors.push(err);
+            } else if Some(class_id) == self.actor_handles_class_id && ty.is_concrete_type() {
+                // the arguments of send are checked by the actor checker
+                if let (Type::Tuple(items), false) = (&ty, is_send) {
+                    check_message(
+                        self.program,
+                        &items[0],
+                        location,
+                        &items[1],
+                        location,
+                        self.errors,
+                    );
+                }
+            } else if !self.check_class_constraint(class_id, &ty, location) {
+                let err = TypecheckError::MissingInstance(
+                    self.constraint_string(class_id, &ty),
+                    location,
+                );
+                self.errors.push(err);
+            }
+        }
+    }
 }
 
 impl<'a> Visitor for ClassConstraintChecker<'a> {
@@ -110,6 +285,24 @@ impl<'a> Visitor for ClassConstraintChecker<'a> {
                     .type_info_provider
                     .get_class_member_type(class_member_id);
                 self.check_function_call(args, &class_member_type, location, expr_id);
+                let class_id = self.program.class_members.get(class_member_id).class_id;
+                if self.program.classes.get(&class_id).is_multi_param() {
+                    let (member_type, class_type) = self
+                        .program
+                        .class_member_types
+                        .get(class_member_id)
+                        .expect("Class member type not found")
+                        .clone();
+                    let constraints = vec![(class_id, class_type)];
+                    self.check_multi_param_constraints(
+                        args,
+                        &member_type,
+                        constraints,
+                        location,
+                        expr_id,
+                        false,
+                    );
+                }
             }
             Expr::DynamicFunctionCall(func_expr_id, args) => {
                 let function_type = self.type_store.get_expr_type(func_expr_id).clone();
@@ -131,6 +324,33 @@ impl<'a> Visitor for ClassConstraintChecker<'a> {
                     .clone()
                     .remove_fixed_types();
                 self.check_function_call(args, &func_type_info.function_type, location, expr_id);
+                let mut constraints = match &self.program.functions.get(id).info {
+                    FunctionInfo::NamedFunction(info) => process_class_constraints(
+                        &info.constraints,
+                        self.program,
+                        &mut self.type_info_provider.type_var_generator,
+                    ),
+                    _ => Vec::new(),
+                };
+                if let Some(inferred) = self.type_info_provider.inferred_constraints.get(id) {
+                    constraints.extend(inferred.iter().cloned());
+                }
+                if !constraints.is_empty() {
+                    let function_type = self
+                        .type_info_provider
+                        .function_type_info_store
+                        .get(id)
+                        .function_type
+                        .clone();
+                    self.check_multi_param_constraints(
+                        args,
+                        &function_type,
+                        constraints,
+                        location,
+                        expr_id,
+                        Some(*id) == self.actor_send_function_id,
+                    );
+                }
             }
             _ => {}
         }
@@ -1,5 +1,11 @@
 use siko_location_info::location_id::LocationId;
 
+#[derive(Debug)]
+pub enum MismatchTrace {
+    Signature(String, LocationId),
+    Binding(String, LocationId),
+}
+
 #[derive(Debug)]
 pub enum TypecheckError {
     ConflictingInstances(String, LocationId, LocationId),
@@ -9,7 +15,7 @@ pub enum TypecheckError {
     FunctionArgAndSignatureMismatch(String, usize, usize, LocationId, bool),
     MainNotFound,
     IncorrectTypeForMain(String, LocationId),
-    TypeMismatch(LocationId, String, String),
+    TypeMismatch(LocationId, String, String, Vec<MismatchTrace>),
     FunctionArgumentMismatch(LocationId, String, String),
     InvalidVariantPattern(LocationId, String, usize, usize),
     InvalidRecordPattern(LocationId, String, usize, usize),
@@ -17,9 +23,15 @@ pub enum TypecheckError {
     InvalidFormatString(LocationId),
     CyclicClassDependencies(LocationId, String),
     MissingInstance(String, LocationId),
+    AmbiguousInstance(String, LocationId, Vec<LocationId>),
     ClassNotAutoDerivable(String, LocationId),
-    UnreachablePattern(LocationId),
-    NonExhaustivePattern(LocationId),
+    UnreachablePattern(LocationId, LocationId),
+    NonExhaustivePattern(LocationId, Vec<String>),
+    ActorHandlerTypeMismatch(LocationId, String, String),
+    SendTargetNotActor(LocationId, String),
+    SendMessageNotProtocol(LocationId, String),
+    ProtocolNotHandled(LocationId, String, String),
+    TypedHole(String, LocationId, String, Vec<(String, String)>),
 }
 
 #[derive(Debug)]
@@ -1,3 +1,4 @@
+use crate::error::MismatchTrace;
 use crate::error::TypecheckError;
 use crate::type_info_provider::TypeInfoProvider;
 use crate::type_store::TypeStore;
@@ -5,6 +6,7 @@ use siko_ir::data_type_info::AdtTypeInfo;
 use siko_ir::expr::Expr;
 use siko_ir::expr::ExprId;
 use siko_ir::function::FunctionId;
+use siko_ir::function::FunctionInfo;
 use siko_ir::pattern::Pattern;
 use siko_ir::pattern::PatternId;
 use siko_ir::program::Program;
@@ -41,14 +43,28 @@ impl<'a> ExpressionChecker<'a> {
         }
     }
 
-    fn unify(&mut self, ty1: &Type, ty2: &Type, location: LocationId) -> Unifier {
+    // `sources` are the original types of the two sides, they lead back to
+    // the places that fixed the types if the unification fails.
+    fn unify_traced(
+        &mut self,
+        ty1: &Type,
+        ty2: &Type,
+        location: LocationId,
+        sources: Vec<Type>,
+        mut trace: Vec<MismatchTrace>,
+    ) -> Unifier {
         let mut unifier = Unifier::new(self.type_info_provider.type_var_generator.clone());
         if !unifier.unify(ty1, ty2).is_ok() {
             let ty_str1 = ty1.get_resolved_type_string(self.program);
             let ty_str2 = ty2.get_resolved_type_string(self.program);
-            let err = TypecheckError::TypeMismatch(location, ty_str1, ty_str2);
+            for (location, ty) in self.type_store.trace.explain(&sources, location) {
+                let ty_str = ty.get_resolved_type_string(self.program);
+                trace.push(MismatchTrace::Binding(ty_str, location));
+            }
+            let err = TypecheckError::TypeMismatch(location, ty_str1, ty_str2, trace);
             self.errors.push(err);
         } else {
+            self.type_store.trace.record(&unifier, location, &sources);
             self.type_store.apply(&unifier);
             for id in &self.group.items {
                 let info = self.type_info_provider.function_type_info_store.get_mut(id);
@@ -61,41 +77,99 @@ impl<'a> ExpressionChecker<'a> {
     pub fn match_expr_with(&mut self, expr_id: ExprId, ty: &Type) -> Unifier {
         let expr_ty = self.type_store.get_expr_type(&expr_id).clone();
         let location = self.program.exprs.get(&expr_id).location_id;
-        self.unify(ty, &expr_ty, location)
+        let sources = vec![self.type_store.get_initial_expr_type(&expr_id).clone()];
+        self.unify_traced(ty, &expr_ty, location, sources, Vec::new())
     }
 
     fn match_pattern_with(&mut self, pattern_id: PatternId, ty: &Type) {
         let pattern_ty = self.type_store.get_pattern_type(&pattern_id).clone();
         let location = self.program.patterns.get(&pattern_id).location_id;
-        self.unify(ty, &pattern_ty, location);
+        let sources = vec![self
+            .type_store
+            .get_initial_pattern_type(&pattern_id)
+            .clone()];
+        self.unify_traced(ty, &pattern_ty, location, sources, Vec::new());
     }
 
     fn match_expr_with_pattern(&mut self, expr_id: ExprId, pattern_id: PatternId) {
         let expr_ty = self.type_store.get_expr_type(&expr_id).clone();
         let pattern_ty = self.type_store.get_pattern_type(&pattern_id).clone();
         let location = self.program.patterns.get(&pattern_id).location_id;
-        self.unify(&expr_ty, &pattern_ty, location);
+        let sources = vec![
+            self.type_store.get_initial_expr_type(&expr_id).clone(),
+            self.type_store
+                .get_initial_pattern_type(&pattern_id)
+                .clone(),
+        ];
+        self.unify_traced(&expr_ty, &pattern_ty, location, sources, Vec::new());
     }
 
     fn match_exprs(&mut self, expr_id1: ExprId, expr_id2: ExprId) {
         let expr_ty1 = self.type_store.get_expr_type(&expr_id1).clone();
         let expr_ty2 = self.type_store.get_expr_type(&expr_id2).clone();
         let location = self.program.exprs.get(&expr_id2).location_id;
-        self.unify(&expr_ty1, &expr_ty2, location);
+        let sources = vec![
+            self.type_store.get_initial_expr_type(&expr_id1).clone(),
+            self.type_store.get_initial_expr_type(&expr_id2).clone(),
+        ];
+        self.unify_traced(&expr_ty1, &expr_ty2, location, sources, Vec::new());
     }
 
     fn match_patterns(&mut self, pattern_id1: PatternId, pattern_id2: PatternId) {
         let pattern_ty1 = self.type_store.get_pattern_type(&pattern_id1).clone();
         let pattern_ty2 = self.type_store.get_pattern_type(&pattern_id2).clone();
         let location = self.program.patterns.get(&pattern_id2).location_id;
-        self.unify(&pattern_ty1, &pattern_ty2, location);
+        let sources = vec![
+            self.type_store
+                .get_initial_pattern_type(&pattern_id1)
+                .clone(),
+            self.type_store
+                .get_initial_pattern_type(&pattern_id2)
+                .clone(),
+        ];
+        self.unify_traced(&pattern_ty1, &pattern_ty2, location, sources, Vec::new());
+    }
+
+    fn get_signature_trace(&self, expr_id: ExprId) -> Vec<MismatchTrace> {
+        let mut trace = Vec::new();
+        match &self.program.exprs.get(&expr_id).item {
+            Expr::StaticFunctionCall(function_id, _) => {
+                let function = self.program.functions.get(function_id);
+                if let FunctionInfo::NamedFunction(info) = &function.info {
+                    if info.type_signature.is_some() {
+                        trace.push(MismatchTrace::Signature(
+                            info.name.clone(),
+                            info.location_id,
+                        ));
+                    }
+                }
+            }
+            Expr::ClassFunctionCall(class_member_id, _) => {
+                let class_member = self.program.class_members.get(class_member_id);
+                trace.push(MismatchTrace::Signature(
+                    class_member.name.clone(),
+                    class_member.location_id,
+                ));
+            }
+            _ => {}
+        }
+        trace
     }
 
     fn check_function_call(&mut self, expr_id: ExprId, args: &Vec<ExprId>) {
         for (index, arg) in args.iter().enumerate() {
             let func_type_info = self.type_store.get_func_type_for_expr(&expr_id);
             let arg_type = &func_type_info.args[index].clone();
-            self.match_expr_with(*arg, &arg_type);
+            let arg_ty = self.type_store.get_expr_type(arg).clone();
+            let location = self.program.exprs.get(arg).location_id;
+            let sources = vec![
+                self.type_store
+                    .get_initial_func_arg_type(&expr_id, index)
+                    .clone(),
+                self.type_store.get_initial_expr_type(arg).clone(),
+            ];
+            let trace = self.get_signature_trace(expr_id);
+            self.unify_traced(arg_type, &arg_ty, location, sources, trace);
         }
     }
 
@@ -144,6 +218,7 @@ impl<'a> Visitor for ExpressionChecker<'a> {
                 self.check_function_call(expr_id, args);
             }
             Expr::CharLiteral(_) => {}
+            Expr::Hole(_) => {}
             Expr::DynamicFunctionCall(func_expr_id, args) => {
                 self.type_store
                     .remove_fixed_types_from_expr_type(&func_expr_id);
@@ -190,7 +265,12 @@ impl<'a> Visitor for ExpressionChecker<'a> {
                         let expected_type = format!("{}", all_records.join(" or "));
                         let found_type = receiver_ty.get_resolved_type_string(self.program);
                         let location = self.program.exprs.get(&receiver_expr_id).location_id;
-                        let err = TypecheckError::TypeMismatch(location, expected_type, found_type);
+                        let err = TypecheckError::TypeMismatch(
+                            location,
+                            expected_type,
+                            found_type,
+                            Vec::new(),
+                        );
                         self.errors.push(err);
                         return;
                     }
@@ -294,8 +374,12 @@ impl<'a> Visitor for ExpressionChecker<'a> {
                     None => {
                         let expected_type = format!("{}", expected_records.join(" or "));
                         let found_type = receiver_ty.get_resolved_type_string(self.program);
-                        let err =
-                            TypecheckError::TypeMismatch(location_id, expected_type, found_type);
+                        let err = TypecheckError::TypeMismatch(
+                            location_id,
+                            expected_type,
+                            found_type,
+                            Vec::new(),
+                        );
                         self.errors.push(err);
                     }
                 }
@@ -324,7 +408,8 @@ impl<'a> Visitor for ExpressionChecker<'a> {
                 let expected_type = format!("<tuple with at least {} item(s)>", index + 1);
                 let found_type = receiver_ty.get_resolved_type_string(self.program);
                 let location = self.program.exprs.get(&receiver_expr_id).location_id;
-                let err = TypecheckError::TypeMismatch(location, expected_type, found_type);
+                let err =
+                    TypecheckError::TypeMismatch(location, expected_type, found_type, Vec::new());
                 self.errors.push(err);
             }
         }
@@ -352,6 +437,24 @@ impl<'a> Visitor for ExpressionChecker<'a> {
             Pattern::StringLiteral(_) => {}
             Pattern::CharLiteral(_) => {}
             Pattern::CharRange(_, _) => {}
+            Pattern::IntegerRange(_, _) => {}
+            Pattern::FloatLiteral(_) => {}
+            Pattern::List(items, rest) => {
+                if let Some(first) = items.first() {
+                    let ty = self.type_store.get_pattern_type(first).clone();
+                    let ty = self.program.get_list_type(ty);
+                    self.match_pattern_with(pattern_id, &ty);
+                    for item in items {
+                        self.match_patterns(*first, *item);
+                    }
+                }
+                if let Some(rest) = rest {
+                    self.match_patterns(*rest, pattern_id);
+                }
+            }
+            Pattern::As(inner, _) => {
+                self.match_patterns(*inner, pattern_id);
+            }
             Pattern::Tuple(items) => {
                 let ty = self.type_store.get_pattern_type(&pattern_id).clone();
                 if let Type::Tuple(item_types) = ty {
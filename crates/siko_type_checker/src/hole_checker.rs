@@ -0,0 +1,124 @@
+use crate::error::TypecheckError;
+use crate::type_info_provider::TypeInfoProvider;
+use crate::type_store::TypeStore;
+use siko_ir::expr::Expr;
+use siko_ir::expr::ExprId;
+use siko_ir::expr::HoleInfo;
+use siko_ir::pattern::Pattern;
+use siko_ir::pattern::PatternId;
+use siko_ir::program::Program;
+use siko_ir::types::Type;
+use siko_ir::walker::Visitor;
+use siko_location_info::location_id::LocationId;
+
+const MAX_FUNCTION_FITS: usize = 10;
+
+pub struct HoleChecker<'a> {
+    program: &'a mut Program,
+    type_store: &'a TypeStore,
+    type_info_provider: &'a mut TypeInfoProvider,
+    errors: &'a mut Vec<TypecheckError>,
+}
+
+impl<'a> HoleChecker<'a> {
+    pub fn new(
+        program: &'a mut Program,
+        type_store: &'a TypeStore,
+        type_info_provider: &'a mut TypeInfoProvider,
+        errors: &'a mut Vec<TypecheckError>,
+    ) -> HoleChecker<'a> {
+        HoleChecker {
+            program: program,
+            type_store: type_store,
+            type_info_provider: type_info_provider,
+            errors: errors,
+        }
+    }
+
+    fn fits(&mut self, hole_ty: &Type, candidate_ty: &Type, location: LocationId) -> bool {
+        let mut unifier = self.program.get_unifier();
+        if unifier.unify(candidate_ty, hole_ty).is_err() {
+            return false;
+        }
+        unifier.get_constraints().iter().all(|constraint| {
+            let mut unifiers = Vec::new();
+            self.program.instance_resolver.check_instance(
+                constraint.class_id,
+                &constraint.ty,
+                location,
+                &mut unifiers,
+            )
+        })
+    }
+
+    fn check_hole(&mut self, expr_id: ExprId, info: &HoleInfo) {
+        let hole_ty = self.type_store.get_expr_type(&expr_id).clone();
+        let location = self.program.exprs.get(&expr_id).location_id;
+        let mut fits = Vec::new();
+        for (name, binding) in &info.bindings {
+            let binding_ty = self.type_store.get_expr_type(binding).clone();
+            if self.fits(&hole_ty, &binding_ty, location) {
+                fits.push((
+                    name.clone(),
+                    binding_ty.get_resolved_type_string(self.program),
+                ));
+            }
+        }
+        // every function fits a hole of unknown type, listing them would not help
+        if let Type::Var(..) = hole_ty {
+        } else {
+            let mut function_fits = Vec::new();
+            for (name, function_id) in &info.functions {
+                let function_type_info = self
+                    .type_info_provider
+                    .function_type_info_store
+                    .get(function_id);
+                if !function_type_info.typed {
+                    continue;
+                }
+                let ty = function_type_info.function_type.clone();
+                let candidate_ty = self
+                    .type_info_provider
+                    .get_function_type(function_id, true)
+                    .function_type;
+                if self.fits(&hole_ty, &candidate_ty, location) {
+                    function_fits.push((name.clone(), ty.get_resolved_type_string(self.program)));
+                }
+            }
+            for (name, class_member_id) in &info.class_members {
+                let candidate_ty = self
+                    .type_info_provider
+                    .get_class_member_type(class_member_id);
+                if self.fits(&hole_ty, &candidate_ty, location) {
+                    let ty =
+                        &self.type_info_provider.class_member_type_info_map[class_member_id].ty;
+                    function_fits.push((name.clone(), ty.get_resolved_type_string(self.program)));
+                }
+            }
+            function_fits.sort();
+            function_fits.truncate(MAX_FUNCTION_FITS);
+            fits.extend(function_fits);
+        }
+        let err = TypecheckError::TypedHole(
+            info.name.clone(),
+            location,
+            hole_ty.get_resolved_type_string(self.program),
+            fits,
+        );
+        self.errors.push(err);
+    }
+}
+
+impl<'a> Visitor for HoleChecker<'a> {
+    fn get_program(&self) -> &Program {
+        &self.program
+    }
+
+    fn visit_expr(&mut self, expr_id: ExprId, expr: &Expr) {
+        if let Expr::Hole(info) = expr {
+            self.check_hole(expr_id, info);
+        }
+    }
+
+    fn visit_pattern(&mut self, _: PatternId, _: &Pattern) {}
+}
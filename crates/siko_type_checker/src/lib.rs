@@ -1,12 +1,16 @@
+pub mod actor_checker;
+pub mod binding_generalizer;
 pub mod class_constraint_checker;
 pub mod common;
 pub mod error;
 pub mod expression_checker;
+pub mod hole_checker;
 pub mod instance_resolver;
 pub mod pattern_checker;
 pub mod type_info_provider;
 pub mod type_store;
 pub mod type_store_initializer;
+pub mod type_trace;
 pub mod typechecker;
 pub mod undefined_var_checker;
 pub mod util;
@@ -6,232 +6,445 @@ use siko_ir::pattern::Pattern;
 use siko_ir::pattern::PatternId;
 use siko_ir::program::Program;
 use siko_ir::walker::Visitor;
-use siko_location_info::location_id::LocationId;
-use siko_util::Counter;
-use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+#[derive(PartialOrd, Ord, PartialEq, Eq, Clone, Copy, Debug)]
+enum RangeKind {
+    Int,
+    Char,
+}
+
+impl RangeKind {
+    fn domain(&self) -> (i128, i128) {
+        match self {
+            RangeKind::Int => (i64::MIN as i128, i64::MAX as i128),
+            RangeKind::Char => (0, std::char::MAX as i128),
+        }
+    }
+}
 
 #[derive(PartialOrd, Ord, PartialEq, Eq, Clone, Debug)]
-enum ChoiceKind {
-    Binding,
+enum Constructor {
     Variant(TypeDefId, usize),
+    Tuple(usize),
+    Record(TypeDefId),
+    // inclusive bounds, literals are single element ranges
+    Range(RangeKind, i128, i128),
     String(String),
-    Int(i64),
-    Char(char),
+    Float(u64),
+    List(usize),
+    // lists with at least this many items, only used to describe missing patterns
+    ListPrefix(usize),
 }
 
-#[derive(Clone)]
-struct Choice {
-    first_location: Option<LocationId>,
-    sub_choices: BTreeMap<ChoiceKind, usize>,
-}
+impl Constructor {
+    fn arity(&self, program: &Program) -> usize {
+        match self {
+            Constructor::Variant(id, index) => program.typedefs.get(id).get_adt().variants[*index]
+                .items
+                .len(),
+            Constructor::Tuple(arity) => *arity,
+            Constructor::Record(id) => program.typedefs.get(id).get_record().fields.len(),
+            Constructor::List(len) | Constructor::ListPrefix(len) => *len,
+            Constructor::Range(..) | Constructor::String(_) | Constructor::Float(_) => 0,
+        }
+    }
 
-impl Choice {
-    fn new() -> Choice {
-        Choice {
-            first_location: None,
-            sub_choices: BTreeMap::new(),
+    fn covers(&self, other: &Constructor) -> bool {
+        match (self, other) {
+            (
+                Constructor::Range(kind, lo, hi),
+                Constructor::Range(other_kind, other_lo, other_hi),
+            ) => kind == other_kind && lo <= other_lo && other_hi <= hi,
+            _ => self == other,
         }
     }
 }
 
-struct CaseInfo {
-    choices: BTreeMap<usize, Choice>,
-    next_id: Counter,
+#[derive(Clone, Debug)]
+enum Pat {
+    Wildcard,
+    Constructor(Constructor, Vec<Pat>),
+    // a list with at least as many items as the prefix, i.e. [a, b, ..rest]
+    ListPrefix(Vec<Pat>),
 }
 
-impl CaseInfo {
-    fn new() -> CaseInfo {
-        CaseInfo {
-            choices: BTreeMap::new(),
-            next_id: Counter::new(),
+fn convert_pattern(pattern_id: &PatternId, program: &Program) -> Pat {
+    match &program.patterns.get(pattern_id).item {
+        Pattern::Binding(_) | Pattern::Wildcard => Pat::Wildcard,
+        Pattern::As(item, _) | Pattern::Typed(item, _) | Pattern::Guarded(item, _) => {
+            convert_pattern(item, program)
+        }
+        Pattern::Tuple(items) => {
+            let items: Vec<_> = items.iter().map(|i| convert_pattern(i, program)).collect();
+            Pat::Constructor(Constructor::Tuple(items.len()), items)
+        }
+        Pattern::Record(id, items) => {
+            let items = items.iter().map(|i| convert_pattern(i, program)).collect();
+            Pat::Constructor(Constructor::Record(*id), items)
+        }
+        Pattern::Variant(id, index, items) => {
+            let items = items.iter().map(|i| convert_pattern(i, program)).collect();
+            Pat::Constructor(Constructor::Variant(*id, *index), items)
+        }
+        Pattern::IntegerLiteral(i) => Pat::Constructor(
+            Constructor::Range(RangeKind::Int, *i as i128, *i as i128),
+            Vec::new(),
+        ),
+        Pattern::IntegerRange(start, end) => Pat::Constructor(
+            Constructor::Range(RangeKind::Int, *start as i128, *end as i128 - 1),
+            Vec::new(),
+        ),
+        Pattern::CharLiteral(c) => Pat::Constructor(
+            Constructor::Range(RangeKind::Char, *c as i128, *c as i128),
+            Vec::new(),
+        ),
+        Pattern::CharRange(start, end) => Pat::Constructor(
+            Constructor::Range(RangeKind::Char, *start as i128, *end as i128 - 1),
+            Vec::new(),
+        ),
+        Pattern::StringLiteral(s) => Pat::Constructor(Constructor::String(s.clone()), Vec::new()),
+        Pattern::FloatLiteral(f) => Pat::Constructor(Constructor::Float(f.to_bits()), Vec::new()),
+        Pattern::List(items, rest) => {
+            let mut items: Vec<_> = items.iter().map(|i| convert_pattern(i, program)).collect();
+            match rest {
+                Some(rest) => match convert_pattern(rest, program) {
+                    Pat::Wildcard => Pat::ListPrefix(items),
+                    Pat::ListPrefix(rest_items) => {
+                        items.extend(rest_items);
+                        Pat::ListPrefix(items)
+                    }
+                    Pat::Constructor(Constructor::List(_), rest_items) => {
+                        items.extend(rest_items);
+                        Pat::Constructor(Constructor::List(items.len()), items)
+                    }
+                    Pat::Constructor(..) => unreachable!(),
+                },
+                None => Pat::Constructor(Constructor::List(items.len()), items),
+            }
         }
     }
+}
 
-    fn add_first(&mut self) -> usize {
-        let id = self.next_id.next();
-        let choice = Choice::new();
-        self.choices.insert(id, choice);
-        id
-    }
-
-    fn get_choice(&self, id: usize) -> &Choice {
-        self.choices.get(&id).expect("Choice not found")
-    }
+fn wildcards(count: usize) -> Vec<Pat> {
+    std::iter::repeat(Pat::Wildcard).take(count).collect()
+}
 
-    fn check_choices(&self, first: usize, program: &Program, errors: &mut Vec<TypecheckError>) {
-        let choice = self.get_choice(first);
-        let mut bind_found = false;
-        let mut variants = Vec::new();
-        let mut constant_pattern_found = false;
-        for (kind, _) in &choice.sub_choices {
-            match kind {
-                ChoiceKind::Binding => {
-                    bind_found = true;
-                }
-                ChoiceKind::Variant(id, index) => {
-                    variants.push((id, index));
-                }
-                ChoiceKind::String(_) => {
-                    constant_pattern_found = true;
-                }
-                ChoiceKind::Int(_) => {
-                    constant_pattern_found = true;
-                }
-                ChoiceKind::Char(_) => {
-                    constant_pattern_found = true;
-                }
+fn specialize(row: &[Pat], constructor: &Constructor, program: &Program) -> Option<Vec<Pat>> {
+    let mut result = match &row[0] {
+        Pat::Wildcard => wildcards(constructor.arity(program)),
+        Pat::Constructor(c, items) => {
+            if c.covers(constructor) {
+                items.clone()
+            } else {
+                return None;
             }
         }
-        if !bind_found {
-            if !variants.is_empty() {
-                let typedef_id = variants[0].0;
-                let adt = program.typedefs.get(&typedef_id).get_adt();
-                if adt.variants.len() != variants.len() {
-                    let err = TypecheckError::NonExhaustivePattern(
-                        choice.first_location.expect("First location not found"),
-                    );
-                    errors.push(err);
-                }
-            }
-            if constant_pattern_found {
-                let err = TypecheckError::NonExhaustivePattern(
-                    choice.first_location.expect("First location not found"),
-                );
-                errors.push(err);
+        Pat::ListPrefix(items) => match constructor {
+            Constructor::List(len) | Constructor::ListPrefix(len) if *len >= items.len() => {
+                let mut items = items.clone();
+                items.extend(wildcards(len - items.len()));
+                items
             }
+            _ => return None,
+        },
+    };
+    result.extend(row[1..].iter().cloned());
+    Some(result)
+}
 
-            for (_, choice) in &choice.sub_choices {
-                if errors.is_empty() {
-                    self.check_choices(*choice, program, errors);
+// Splits the given range into pieces along the boundaries of the ranges found in the column,
+// every piece is either fully covered or not covered at all by each range in the column
+fn split_range(kind: RangeKind, lo: i128, hi: i128, heads: &[&Pat]) -> Vec<Constructor> {
+    let mut boundaries = BTreeSet::new();
+    boundaries.insert(lo);
+    boundaries.insert(hi + 1);
+    for head in heads {
+        if let Pat::Constructor(Constructor::Range(_, r_lo, r_hi), _) = head {
+            for b in &[*r_lo, *r_hi + 1] {
+                if lo < *b && *b <= hi {
+                    boundaries.insert(*b);
                 }
             }
         }
     }
+    let boundaries: Vec<_> = boundaries.into_iter().collect();
+    boundaries
+        .windows(2)
+        .map(|w| Constructor::Range(kind, w[0], w[1] - 1))
+        .collect()
+}
 
-    fn add_choice(
-        &mut self,
-        parent: usize,
-        kind: ChoiceKind,
-        choice_added: &mut bool,
-        location_id: LocationId,
-    ) -> usize {
-        let parent = self
-            .choices
-            .get_mut(&parent)
-            .expect("Parent choice not found");
-        let mut newly_added = false;
-        let new_id = self.next_id.next();
-        if parent.first_location.is_none() {
-            parent.first_location = Some(location_id);
+fn max_list_len(heads: &[&Pat]) -> usize {
+    heads
+        .iter()
+        .map(|head| match head {
+            Pat::Constructor(Constructor::List(len), _) => *len,
+            Pat::ListPrefix(items) => items.len(),
+            _ => 0,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+// Lists longer than any list pattern in the column are all matched the same way,
+// so they are represented by a single prefix constructor
+fn list_constructors(min: usize, max: usize) -> Vec<Constructor> {
+    let mut constructors: Vec<_> = (min..=max).map(|len| Constructor::List(len)).collect();
+    constructors.push(Constructor::ListPrefix(max + 1));
+    constructors
+}
+
+fn is_covered(constructor: &Constructor, heads: &[&Pat]) -> bool {
+    heads.iter().any(|head| match head {
+        Pat::Wildcard => false,
+        Pat::Constructor(c, _) => c.covers(constructor),
+        Pat::ListPrefix(items) => match constructor {
+            Constructor::List(len) | Constructor::ListPrefix(len) => *len >= items.len(),
+            _ => false,
+        },
+    })
+}
+
+// Returns every constructor of the column's type, ranges are split along the boundaries found
+// in the column, None is returned if the column has no constructors or they cannot be listed
+fn all_constructors(heads: &[&Pat], program: &Program) -> Option<Vec<Constructor>> {
+    let first = heads.iter().find(|head| match head {
+        Pat::Wildcard => false,
+        _ => true,
+    })?;
+    let all = match first {
+        Pat::Wildcard => unreachable!(),
+        Pat::Constructor(Constructor::Variant(id, _), _) => {
+            let adt = program.typedefs.get(id).get_adt();
+            (0..adt.variants.len())
+                .map(|index| Constructor::Variant(*id, index))
+                .collect()
         }
-        if let Some(bind_id) = parent.sub_choices.get(&ChoiceKind::Binding) {
-            return *bind_id;
+        Pat::Constructor(Constructor::Tuple(arity), _) => vec![Constructor::Tuple(*arity)],
+        Pat::Constructor(Constructor::Record(id), _) => vec![Constructor::Record(*id)],
+        Pat::Constructor(Constructor::Range(kind, _, _), _) => {
+            let (lo, hi) = kind.domain();
+            split_range(*kind, lo, hi, heads)
         }
-        let id = *parent.sub_choices.entry(kind).or_insert_with(|| {
-            newly_added = true;
-            new_id
-        });
-        if newly_added {
-            let choice = Choice::new();
-            self.choices.insert(id, choice);
-            *choice_added = true;
+        Pat::Constructor(Constructor::String(_), _)
+        | Pat::Constructor(Constructor::Float(_), _) => {
+            return None;
         }
-        id
+        Pat::Constructor(Constructor::List(_), _)
+        | Pat::Constructor(Constructor::ListPrefix(_), _)
+        | Pat::ListPrefix(_) => list_constructors(0, max_list_len(heads)),
+    };
+    Some(all)
+}
+
+// Returns every constructor of the column's type if all of them appear in the column,
+// if some are missing then only the wildcard rows matter, None is returned
+fn complete_signature(heads: &[&Pat], program: &Program) -> Option<Vec<Constructor>> {
+    let all = all_constructors(heads, program)?;
+    if all.iter().all(|c| is_covered(c, heads)) {
+        Some(all)
+    } else {
+        None
     }
+}
 
-    fn check_pattern(
-        &mut self,
-        pattern_id: &PatternId,
-        program: &Program,
-        parent: usize,
-        choice_added: &mut bool,
-    ) -> usize {
-        let item_info = program.patterns.get(pattern_id);
-        match &item_info.item {
-            Pattern::Binding(_) => {
-                return self.add_choice(
-                    parent,
-                    ChoiceKind::Binding,
-                    choice_added,
-                    item_info.location_id,
-                );
-            }
-            Pattern::Wildcard => {
-                return self.add_choice(
-                    parent,
-                    ChoiceKind::Binding,
-                    choice_added,
-                    item_info.location_id,
-                );
+// Maranget's usefulness check, a row is useful if there is a value that it matches but
+// none of the rows in the matrix do
+fn is_useful(matrix: &Vec<Vec<Pat>>, row: &Vec<Pat>, program: &Program) -> bool {
+    if row.is_empty() {
+        return matrix.is_empty();
+    }
+    let heads: Vec<&Pat> = matrix.iter().map(|r| &r[0]).collect();
+    let constructors = match &row[0] {
+        Pat::Wildcard => match complete_signature(&heads, program) {
+            Some(constructors) => constructors,
+            None => {
+                let default: Vec<_> = matrix
+                    .iter()
+                    .filter(|r| match r[0] {
+                        Pat::Wildcard => true,
+                        _ => false,
+                    })
+                    .map(|r| r[1..].to_vec())
+                    .collect();
+                return is_useful(&default, &row[1..].to_vec(), program);
             }
-            Pattern::Variant(id, index, items) => {
-                let mut id = self.add_choice(
-                    parent,
-                    ChoiceKind::Variant(*id, *index),
-                    choice_added,
-                    item_info.location_id,
-                );
-                for item in items {
-                    id = self.check_pattern(item, program, id, choice_added);
+        },
+        Pat::Constructor(Constructor::Range(kind, lo, hi), _) => {
+            split_range(*kind, *lo, *hi, &heads)
+        }
+        Pat::Constructor(c, _) => vec![c.clone()],
+        Pat::ListPrefix(items) => {
+            let max = std::cmp::max(max_list_len(&heads), items.len());
+            list_constructors(items.len(), max)
+        }
+    };
+    constructors.iter().any(|c| {
+        let specialized: Vec<_> = matrix
+            .iter()
+            .filter_map(|r| specialize(r, c, program))
+            .collect();
+        match specialize(row, c, program) {
+            Some(row) => is_useful(&specialized, &row, program),
+            None => false,
+        }
+    })
+}
+
+const MAX_MISSING_PATTERNS: usize = 5;
+
+fn default_matrix(matrix: &Vec<Vec<Pat>>) -> Vec<Vec<Pat>> {
+    matrix
+        .iter()
+        .filter(|r| match r[0] {
+            Pat::Wildcard => true,
+            _ => false,
+        })
+        .map(|r| r[1..].to_vec())
+        .collect()
+}
+
+// Collects the values that no row of the matrix matches, every returned row has one pattern per column
+fn missing_patterns(
+    matrix: &Vec<Vec<Pat>>,
+    column_count: usize,
+    program: &Program,
+) -> Vec<Vec<Pat>> {
+    if column_count == 0 {
+        return if matrix.is_empty() {
+            vec![Vec::new()]
+        } else {
+            Vec::new()
+        };
+    }
+    let heads: Vec<&Pat> = matrix.iter().map(|r| &r[0]).collect();
+    // every constructor is tried, even the ones not in the column, so that the missing values
+    // are listed together, the missing pieces of ranges are listed as ranges
+    let mut result = Vec::new();
+    match all_constructors(&heads, program) {
+        Some(constructors) => {
+            for c in constructors {
+                let arity = c.arity(program);
+                let specialized: Vec<_> = matrix
+                    .iter()
+                    .filter_map(|r| specialize(r, &c, program))
+                    .collect();
+                for mut row in missing_patterns(&specialized, arity + column_count - 1, program) {
+                    let items: Vec<_> = row.drain(..arity).collect();
+                    row.insert(0, Pat::Constructor(c.clone(), items));
+                    result.push(row);
+                }
+                if result.len() > MAX_MISSING_PATTERNS {
+                    break;
                 }
-                id
             }
-            Pattern::StringLiteral(s) => {
-                return self.add_choice(
-                    parent,
-                    ChoiceKind::String(s.clone()),
-                    choice_added,
-                    item_info.location_id,
-                );
+        }
+        None => {
+            // the missing strings cannot be listed, one that is not in the column stands for them
+            let head = match missing_string(&heads) {
+                Some(s) => Pat::Constructor(Constructor::String(s), Vec::new()),
+                None => Pat::Wildcard,
+            };
+            for mut row in missing_patterns(&default_matrix(matrix), column_count - 1, program) {
+                row.insert(0, head.clone());
+                result.push(row);
             }
-            Pattern::IntegerLiteral(i) => {
-                return self.add_choice(
-                    parent,
-                    ChoiceKind::Int(i.clone()),
-                    choice_added,
-                    item_info.location_id,
-                );
+        }
+    }
+    result
+}
+
+// Returns a string that none of the string literals in the column match,
+// None is returned if the column has no string literals
+fn missing_string(heads: &[&Pat]) -> Option<String> {
+    let literals: BTreeSet<_> = heads
+        .iter()
+        .filter_map(|head| match head {
+            Pat::Constructor(Constructor::String(s), _) => Some(s),
+            _ => None,
+        })
+        .collect();
+    if literals.is_empty() {
+        return None;
+    }
+    (0..)
+        .map(|len| "a".repeat(len))
+        .find(|s| !literals.contains(s))
+}
+
+fn format_pattern(pat: &Pat, program: &Program, nested: bool) -> String {
+    let format_items = |items: &Vec<Pat>| -> Vec<String> {
+        items
+            .iter()
+            .map(|item| format_pattern(item, program, true))
+            .collect()
+    };
+    let with_args = |name: String, items: &Vec<Pat>| -> String {
+        if items.is_empty() {
+            name
+        } else {
+            let s = format!("{} {}", name, format_items(items).join(" "));
+            if nested {
+                format!("({})", s)
+            } else {
+                s
             }
-            Pattern::CharLiteral(i) => {
-                return self.add_choice(
-                    parent,
-                    ChoiceKind::Char(i.clone()),
-                    choice_added,
-                    item_info.location_id,
-                );
+        }
+    };
+    match pat {
+        Pat::Wildcard => format!("_"),
+        Pat::ListPrefix(items) => format_list_prefix(format_items(items)),
+        Pat::Constructor(c, items) => match c {
+            Constructor::Variant(id, index) => {
+                let adt = program.typedefs.get(id).get_adt();
+                with_args(adt.variants[*index].name.clone(), items)
             }
-            Pattern::Tuple(items) => {
-                let mut id = parent;
-                for item in items {
-                    id = self.check_pattern(item, program, id, choice_added);
-                }
-                id
+            Constructor::Record(id) => {
+                let record = program.typedefs.get(id).get_record();
+                with_args(record.name.clone(), items)
             }
-            Pattern::Record(_, items) => {
-                let mut id = parent;
-                for item in items {
-                    id = self.check_pattern(item, program, id, choice_added);
+            Constructor::Tuple(_) => format!("({})", format_items(items).join(", ")),
+            Constructor::List(_) => format!("[{}]", format_items(items).join(", ")),
+            Constructor::ListPrefix(_) => format_list_prefix(format_items(items)),
+            Constructor::Range(kind, lo, hi) => {
+                let format_value = |v: i128| match kind {
+                    RangeKind::Int => format!("{}", v),
+                    RangeKind::Char => match std::char::from_u32(v as u32) {
+                        Some(c) => format!("{:?}", c),
+                        None => format!("'\\u{{{:x}}}'", v),
+                    },
+                };
+                // the pieces reaching the ends of the domain are shown as open ranges
+                let (min, max) = kind.domain();
+                let s = if lo == hi {
+                    format_value(*lo)
+                } else if *lo == min && *hi == max {
+                    return format!("_");
+                } else if *lo == min {
+                    format!("..{}", format_value(*hi + 1))
+                } else if *hi == max {
+                    format!("{}..", format_value(*lo))
+                } else {
+                    format!("{}..{}", format_value(*lo), format_value(*hi + 1))
+                };
+                if nested && (*lo < 0 || lo != hi) {
+                    format!("({})", s)
+                } else {
+                    s
                 }
-                id
             }
-            Pattern::Typed(item, _) => {
-                return self.check_pattern(item, program, parent, choice_added);
-            }
-            Pattern::CharRange(..) => {
-                // FIXME: add checks for char ranges
-                return 0;
-            }
-            Pattern::Guarded(..) => {
-                // FIXME:
-                // We cannot check at compile time whether the guard will return True or not,
-                // so guarded patterns are ignored for now, we assume that they fail
-                // This can be improved by checking the guard expression
-                return 0;
-            }
-        }
+            Constructor::String(s) => format!("{:?}", s),
+            Constructor::Float(bits) => format!("{:?}", f64::from_bits(*bits)),
+        },
     }
 }
 
+fn format_list_prefix(mut items: Vec<String>) -> String {
+    items.push(format!(".."));
+    format!("[{}]", items.join(", "))
+}
+
 pub struct PatternChecker<'a> {
     program: &'a Program,
     errors: &'a mut Vec<TypecheckError>,
@@ -253,28 +466,49 @@ impl<'a> Visitor for PatternChecker<'a> {
 
     fn visit_expr(&mut self, _: ExprId, expr: &Expr) {
         if let Expr::CaseOf(_, cases, _) = expr {
-            let mut case_info = CaseInfo::new();
-            let first = case_info.add_first();
+            let mut matrix = Vec::new();
+            let mut locations = Vec::new();
             for case in cases {
-                let mut choice_added = false;
-
-                let id = case_info.check_pattern(
-                    &case.pattern_id,
-                    &self.program,
-                    first,
-                    &mut choice_added,
-                );
-                if id == 0 {
-                    // guarded pattern, ignore it
-                } else {
-                    let location = self.program.patterns.get(&case.pattern_id).location_id;
-                    if !choice_added {
-                        let err = TypecheckError::UnreachablePattern(location);
-                        self.errors.push(err);
-                    }
+                let row = vec![convert_pattern(&case.pattern_id, &self.program)];
+                let location = self.program.patterns.get(&case.pattern_id).location_id;
+                if !is_useful(&matrix, &row, &self.program) {
+                    // blame a single earlier arm if there is one covering this one on its own,
+                    // otherwise the arm completing the coverage
+                    let single = matrix
+                        .iter()
+                        .position(|r| !is_useful(&vec![r.clone()], &row, &self.program));
+                    let index = single.or_else(|| {
+                        (0..matrix.len())
+                            .find(|i| !is_useful(&matrix[..=*i].to_vec(), &row, &self.program))
+                    });
+                    // a pattern matching nothing is blamed on itself
+                    let covering = index.map(|index| locations[index]).unwrap_or(location);
+                    let err = TypecheckError::UnreachablePattern(location, covering);
+                    self.errors.push(err);
+                }
+                // We cannot check at compile time whether the guard will return True or not,
+                // so guarded patterns are assumed to fail
+                if let Pattern::Guarded(..) = &self.program.patterns.get(&case.pattern_id).item {
+                    continue;
+                }
+                matrix.push(row);
+                locations.push(location);
+            }
+            let missing = missing_patterns(&matrix, 1, &self.program);
+            if !missing.is_empty() {
+                let mut patterns: Vec<_> = missing
+                    .iter()
+                    .take(MAX_MISSING_PATTERNS)
+                    .map(|row| format_pattern(&row[0], &self.program, false))
+                    .collect();
+                if missing.len() > MAX_MISSING_PATTERNS {
+                    patterns.push(format!("..."));
                 }
+                let first = cases.first().expect("Empty case");
+                let location = self.program.patterns.get(&first.pattern_id).location_id;
+                let err = TypecheckError::NonExhaustivePattern(location, patterns);
+                self.errors.push(err);
             }
-            case_info.check_choices(first, &self.program, self.errors);
         }
     }
 
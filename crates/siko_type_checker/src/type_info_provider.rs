@@ -1,6 +1,7 @@
 use crate::common::ClassMemberTypeInfo;
 use crate::common::FunctionTypeInfo;
 use crate::common::FunctionTypeInfoStore;
+use siko_ir::class::ClassId;
 use siko_ir::class::ClassMemberId;
 use siko_ir::data::TypeDefId;
 use siko_ir::data_type_info::AdtTypeInfo;
@@ -16,6 +17,8 @@ pub struct TypeInfoProvider {
     pub adt_type_info_map: BTreeMap<TypeDefId, AdtTypeInfo>,
     pub function_type_info_store: FunctionTypeInfoStore,
     pub record_type_info_map: BTreeMap<TypeDefId, RecordTypeInfo>,
+    // multi parameter class constraints of the functions without type signature
+    pub inferred_constraints: BTreeMap<FunctionId, Vec<(ClassId, Type)>>,
 }
 
 impl TypeInfoProvider {
@@ -26,6 +29,7 @@ impl TypeInfoProvider {
             adt_type_info_map: BTreeMap::new(),
             function_type_info_store: FunctionTypeInfoStore::new(),
             record_type_info_map: BTreeMap::new(),
+            inferred_constraints: BTreeMap::new(),
         }
     }
 
@@ -1,4 +1,5 @@
 use crate::common::FunctionTypeInfo;
+use crate::type_trace::TypeTrace;
 use siko_ir::data_type_info::AdtTypeInfo;
 use siko_ir::data_type_info::RecordTypeInfo;
 use siko_ir::expr::ExprId;
@@ -85,6 +86,10 @@ impl fmt::Display for PatternTypeState {
 pub struct TypeStore {
     expr_types: BTreeMap<ExprId, ExpressionTypeState>,
     pattern_types: BTreeMap<PatternId, PatternTypeState>,
+    initial_expr_types: BTreeMap<ExprId, Type>,
+    initial_func_arg_types: BTreeMap<ExprId, Vec<Type>>,
+    initial_pattern_types: BTreeMap<PatternId, Type>,
+    pub trace: TypeTrace,
 }
 
 impl TypeStore {
@@ -92,10 +97,15 @@ impl TypeStore {
         TypeStore {
             expr_types: BTreeMap::new(),
             pattern_types: BTreeMap::new(),
+            initial_expr_types: BTreeMap::new(),
+            initial_func_arg_types: BTreeMap::new(),
+            initial_pattern_types: BTreeMap::new(),
+            trace: TypeTrace::new(),
         }
     }
 
     pub fn initialize_expr(&mut self, expr_id: ExprId, ty: Type) {
+        self.initial_expr_types.insert(expr_id, ty.clone());
         let r = self
             .expr_types
             .insert(expr_id, ExpressionTypeState::ExprType(ty));
@@ -108,6 +118,9 @@ impl TypeStore {
         ty: Type,
         func_ty: FunctionTypeInfo,
     ) {
+        self.initial_expr_types.insert(expr_id, ty.clone());
+        self.initial_func_arg_types
+            .insert(expr_id, func_ty.args.clone());
         let r = self
             .expr_types
             .insert(expr_id, ExpressionTypeState::FunctionCall(func_ty, ty));
@@ -120,6 +133,7 @@ impl TypeStore {
         ty: Type,
         record_type_info: RecordTypeInfo,
     ) {
+        self.initial_expr_types.insert(expr_id, ty.clone());
         let r = self.expr_types.insert(
             expr_id,
             ExpressionTypeState::RecordInitialization(record_type_info, ty),
@@ -128,6 +142,7 @@ impl TypeStore {
     }
 
     pub fn initialize_pattern(&mut self, pattern_id: PatternId, ty: Type) {
+        self.initial_pattern_types.insert(pattern_id, ty.clone());
         self.pattern_types
             .insert(pattern_id, PatternTypeState::PatternType(ty));
     }
@@ -138,6 +153,7 @@ impl TypeStore {
         ty: Type,
         adt_type_info: AdtTypeInfo,
     ) {
+        self.initial_pattern_types.insert(pattern_id, ty.clone());
         self.pattern_types
             .insert(pattern_id, PatternTypeState::VariantType(adt_type_info, ty));
     }
@@ -148,6 +164,7 @@ impl TypeStore {
         ty: Type,
         record_type_info: RecordTypeInfo,
     ) {
+        self.initial_pattern_types.insert(pattern_id, ty.clone());
         self.pattern_types.insert(
             pattern_id,
             PatternTypeState::RecordType(record_type_info, ty),
@@ -162,6 +179,25 @@ impl TypeStore {
         }
     }
 
+    pub fn get_initial_expr_type(&self, expr_id: &ExprId) -> &Type {
+        self.initial_expr_types
+            .get(expr_id)
+            .expect("Expr type not found")
+    }
+
+    pub fn get_initial_func_arg_type(&self, expr_id: &ExprId, index: usize) -> &Type {
+        &self
+            .initial_func_arg_types
+            .get(expr_id)
+            .expect("Expr type not found")[index]
+    }
+
+    pub fn get_initial_pattern_type(&self, pattern_id: &PatternId) -> &Type {
+        self.initial_pattern_types
+            .get(pattern_id)
+            .expect("Pattern type not found")
+    }
+
     pub fn get_func_type_for_expr(&self, expr_id: &ExprId) -> &FunctionTypeInfo {
         match self.expr_types.get(expr_id).expect("Expr type not found") {
             ExpressionTypeState::ExprType(_) => unreachable!(),
@@ -122,6 +122,13 @@ impl<'a> Visitor for TypeStoreInitializer<'a> {
                     .get_new_type_var();
                 self.type_store.initialize_expr(expr_id, ty);
             }
+            Expr::Hole(_) => {
+                let ty = self
+                    .type_info_provider
+                    .type_var_generator
+                    .get_new_type_var();
+                self.type_store.initialize_expr(expr_id, ty);
+            }
             Expr::ExprValue(_, _) => {
                 let ty = self
                     .type_info_provider
@@ -265,6 +272,29 @@ impl<'a> Visitor for TypeStoreInitializer<'a> {
                 self.type_store
                     .initialize_pattern(pattern_id, self.program.get_char_type());
             }
+            Pattern::IntegerRange(_, _) => {
+                self.type_store
+                    .initialize_pattern(pattern_id, self.program.get_int_type());
+            }
+            Pattern::FloatLiteral(_) => {
+                self.type_store
+                    .initialize_pattern(pattern_id, self.program.get_float_type());
+            }
+            Pattern::List(_, _) => {
+                let ty = self.program.get_list_type(
+                    self.type_info_provider
+                        .type_var_generator
+                        .get_new_type_var(),
+                );
+                self.type_store.initialize_pattern(pattern_id, ty);
+            }
+            Pattern::As(_, _) => {
+                let ty = self
+                    .type_info_provider
+                    .type_var_generator
+                    .get_new_type_var();
+                self.type_store.initialize_pattern(pattern_id, ty);
+            }
             Pattern::Record(typedef_id, fields) => {
                 let record_type_info = self.type_info_provider.get_record_type_info(typedef_id);
                 if record_type_info.field_types.len() != fields.len() {
@@ -0,0 +1,86 @@
+use siko_ir::types::Type;
+use siko_ir::unifier::Unifier;
+use siko_location_info::location_id::LocationId;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+// Remembers where every type variable got bound, together with the original
+// (not yet substituted) types of the two sides of that unification. Following
+// these links from the sides of a failed unification leads back to the
+// expressions that fixed the conflicting types.
+
+struct Binding {
+    order: usize,
+    location: LocationId,
+    ty: Type,
+    sources: Vec<Type>,
+}
+
+pub struct TypeTrace {
+    bindings: BTreeMap<usize, Binding>,
+}
+
+impl Default for TypeTrace {
+    fn default() -> TypeTrace {
+        TypeTrace::new()
+    }
+}
+
+impl TypeTrace {
+    pub fn new() -> TypeTrace {
+        TypeTrace {
+            bindings: BTreeMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, unifier: &Unifier, location: LocationId, sources: &Vec<Type>) {
+        for (index, ty) in unifier.get_substitution().get_bindings() {
+            let order = self.bindings.len();
+            self.bindings.entry(*index).or_insert_with(|| Binding {
+                order: order,
+                location: location,
+                ty: ty.clone(),
+                sources: sources.clone(),
+            });
+        }
+    }
+
+    // Returns the locations where a type variable reachable from the given
+    // types was first bound to a type without variables. Later bindings to the
+    // same type are just copies, they are not reported.
+    pub fn explain(&self, sources: &Vec<Type>, location: LocationId) -> Vec<(LocationId, Type)> {
+        let mut pending = Vec::new();
+        for source in sources {
+            source.collect_vars(&mut pending);
+        }
+        let mut visited = BTreeSet::new();
+        let mut found = Vec::new();
+        while let Some(index) = pending.pop() {
+            if !visited.insert(index) {
+                continue;
+            }
+            if let Some(binding) = self.bindings.get(&index) {
+                if !binding.ty.has_type_vars() {
+                    found.push((binding.order, binding.location, binding.ty.clone()));
+                }
+                binding.ty.collect_vars(&mut pending);
+                for source in &binding.sources {
+                    source.collect_vars(&mut pending);
+                }
+            }
+        }
+        found.sort_by_key(|(order, _, _)| *order);
+        let mut locations = BTreeSet::new();
+        locations.insert(location);
+        let mut types = Vec::new();
+        let mut result = Vec::new();
+        for (_, location, ty) in found {
+            if types.contains(&ty) || !locations.insert(location) {
+                continue;
+            }
+            types.push(ty.clone());
+            result.push((location, ty));
+        }
+        result
+    }
+}
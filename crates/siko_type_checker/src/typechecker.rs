@@ -1,9 +1,12 @@
+use crate::actor_checker::ActorChecker;
+use crate::binding_generalizer::generalize_local_bindings;
 use crate::class_constraint_checker::ClassConstraintChecker;
 use crate::common::ClassMemberTypeInfo;
 use crate::common::FunctionTypeInfo;
 use crate::error::Error;
 use crate::error::TypecheckError;
 use crate::expression_checker::ExpressionChecker;
+use crate::hole_checker::HoleChecker;
 use crate::instance_resolver::check_conflicts;
 use crate::instance_resolver::check_instance_dependencies;
 use crate::pattern_checker::PatternChecker;
@@ -12,6 +15,7 @@ use crate::type_store::TypeStore;
 use crate::type_store_initializer::TypeStoreInitializer;
 use crate::undefined_var_checker::UndefinedVarChecker;
 use crate::util::create_general_function_type;
+use crate::util::process_class_constraints;
 use crate::util::process_type_signature;
 use siko_ir::class::ClassId;
 use siko_ir::data::TypeDef;
@@ -23,6 +27,8 @@ use siko_ir::expr::ExprId;
 use siko_ir::function::Function;
 use siko_ir::function::FunctionId;
 use siko_ir::function::FunctionInfo;
+use siko_ir::function::LambdaInfo;
+use siko_ir::function::LocalFunctionInfo;
 use siko_ir::function::NamedFunctionKind;
 use siko_ir::program::Program;
 use siko_ir::type_var_generator::TypeVarGenerator;
@@ -33,11 +39,23 @@ use siko_ir::walker::walk_expr;
 use siko_util::dependency_processor::DependencyGroup;
 use std::collections::BTreeMap;
 
-pub struct Typechecker {}
+pub struct Typechecker {
+    main_required: bool,
+}
 
 impl Typechecker {
     pub fn new() -> Typechecker {
-        Typechecker {}
+        Typechecker {
+            main_required: true,
+        }
+    }
+
+    // Checks programs that are not run through their main function, like the
+    // modules of a REPL session
+    pub fn without_main() -> Typechecker {
+        Typechecker {
+            main_required: false,
+        }
     }
 
     fn process_derived_instances(
@@ -330,7 +348,11 @@ impl Typechecker {
                 let err = TypecheckError::CyclicClassDependencies(class.location_id, path);
                 errors.push(err);
             }
-            let ty = ty.add_constraints(&class.constraints);
+            let ty = if class.is_multi_param() {
+                ty
+            } else {
+                ty.add_constraints(&class.constraints)
+            };
             // println!("class type {}", ty);
             class_types.insert(*class_id, ty);
         }
@@ -345,6 +367,13 @@ impl Typechecker {
                 *instance_id,
                 instance.location_id,
             );
+
+            let context =
+                process_class_constraints(&instance.constraints, program, type_var_generator)
+                    .into_iter()
+                    .map(|(class_id, ty)| (class_id, ty.remove_fixed_types()))
+                    .collect();
+            program.instance_resolver.add_context(*instance_id, context);
         }
     }
 
@@ -465,12 +494,36 @@ impl Typechecker {
                         .function_type_info_store
                         .add(*id, func_type_info);
                 }
-                FunctionInfo::Lambda(i) => {
+                FunctionInfo::ActorConstructor(i) => {
+                    let actor = program.actors.get(&i.actor_id);
+                    let adt_type_info = type_info_provider.get_adt_type_info(&i.type_id);
+                    let state_type = process_type_signature(
+                        actor.state_type_signature,
+                        program,
+                        type_var_generator,
+                    );
+                    let func_type_info = FunctionTypeInfo {
+                        displayed_name: format!("{}_ctor", actor.name),
+                        args: vec![state_type.clone()],
+                        typed: true,
+                        result: adt_type_info.adt_type.clone(),
+                        function_type: Type::Function(
+                            Box::new(state_type),
+                            Box::new(adt_type_info.adt_type),
+                        ),
+                        body: None,
+                    };
+                    type_info_provider
+                        .function_type_info_store
+                        .add(*id, func_type_info);
+                }
+                FunctionInfo::Lambda(LambdaInfo { body, .. })
+                | FunctionInfo::LocalFunction(LocalFunctionInfo { body, .. }) => {
                     let displayed_name = format!("{}", function.info);
                     let func_type_info = self.register_untyped_function(
                         displayed_name,
                         function,
-                        i.body,
+                        *body,
                         type_var_generator,
                     );
                     type_info_provider
@@ -567,11 +620,62 @@ impl Typechecker {
                     unreachable!();
                 }
             }
-        } else {
+        } else if self.main_required {
             errors.push(TypecheckError::MainNotFound);
         }
     }
 
+    fn check_actors(
+        &self,
+        program: &Program,
+        errors: &mut Vec<TypecheckError>,
+        type_info_provider: &mut TypeInfoProvider,
+    ) {
+        for (_, actor) in &program.actors.items {
+            let state_type = process_type_signature(
+                actor.state_type_signature,
+                program,
+                &mut type_info_provider.type_var_generator,
+            );
+            for handler in &actor.handlers {
+                let protocol = program.protocols.get(&handler.protocol_id);
+                let message_type = process_type_signature(
+                    protocol.type_signature,
+                    program,
+                    &mut type_info_provider.type_var_generator,
+                );
+                let expected_type = Type::Function(
+                    Box::new(state_type.clone()),
+                    Box::new(Type::Function(
+                        Box::new(message_type),
+                        Box::new(state_type.clone()),
+                    )),
+                );
+                let handler_type = type_info_provider
+                    .get_function_type(&handler.function_id, true)
+                    .function_type;
+                let mut unifier = program.get_unifier();
+                if unifier.unify(&expected_type, &handler_type).is_err() {
+                    let err = TypecheckError::ActorHandlerTypeMismatch(
+                        handler.location_id,
+                        expected_type.get_resolved_type_string(program),
+                        handler_type.get_resolved_type_string(program),
+                    );
+                    errors.push(err);
+                }
+            }
+        }
+
+        if let Some(send_function_id) = program.get_actor_send_function_id() {
+            for (_, function) in &program.functions.items {
+                if let Some(body) = function.get_body() {
+                    let mut actor_checker = ActorChecker::new(program, errors, send_function_id);
+                    walk_expr(&body, &mut actor_checker);
+                }
+            }
+        }
+    }
+
     fn init_expr_types<'a>(
         &self,
         function_id: &FunctionId,
@@ -633,6 +737,23 @@ impl Typechecker {
         walk_expr(&body, &mut undef_var_checker);
     }
 
+    fn check_holes<'a>(
+        &self,
+        function_id: &FunctionId,
+        errors: &'a mut Vec<TypecheckError>,
+        type_store: &'a mut TypeStore,
+        type_info_provider: &'a mut TypeInfoProvider,
+        program: &'a mut Program,
+    ) {
+        let body = type_info_provider
+            .function_type_info_store
+            .get(function_id)
+            .body
+            .expect("body not found");
+        let mut hole_checker = HoleChecker::new(program, type_store, type_info_provider, errors);
+        walk_expr(&body, &mut hole_checker);
+    }
+
     fn check_patterns<'a>(
         &self,
         function_id: &FunctionId,
@@ -656,8 +777,27 @@ impl Typechecker {
     ) {
         let function_type_info = type_info_provider.function_type_info_store.get(function_id);
         let body = function_type_info.body.expect("body not found");
-        let mut class_constraint_checker =
-            ClassConstraintChecker::new(program, type_store, errors, type_info_provider);
+        // lambdas can rely on the constraints of their host function
+        let mut host_id = *function_id;
+        while let Some(id) = program.functions.get(&host_id).get_lambda_host() {
+            host_id = id;
+        }
+        let givens = match &program.functions.get(&host_id).info {
+            FunctionInfo::NamedFunction(info) => process_class_constraints(
+                &info.constraints,
+                program,
+                &mut type_info_provider.type_var_generator,
+            ),
+            _ => Vec::new(),
+        };
+        let mut class_constraint_checker = ClassConstraintChecker::new(
+            program,
+            type_store,
+            errors,
+            type_info_provider,
+            givens,
+            host_id,
+        );
         walk_expr(&body, &mut class_constraint_checker);
     }
 
@@ -691,6 +831,10 @@ impl Typechecker {
             );
         }
 
+        for function in &group.items {
+            self.check_holes(function, errors, type_store, type_info_provider, program);
+        }
+
         if !errors.is_empty() {
             return;
         }
@@ -780,6 +924,8 @@ impl Typechecker {
             return Err(Error::typecheck_err(errors));
         }
 
+        generalize_local_bindings(program);
+
         self.process_functions(
             program,
             &mut type_var_generator,
@@ -816,6 +962,12 @@ impl Typechecker {
             return Err(Error::typecheck_err(errors));
         }
 
+        self.check_actors(program, &mut errors, &mut type_info_provider);
+
+        if !errors.is_empty() {
+            return Err(Error::typecheck_err(errors));
+        }
+
         self.check_main(program, &mut errors, &type_info_provider);
 
         if !errors.is_empty() {
@@ -1,5 +1,7 @@
 use crate::common::FunctionTypeInfo;
 use crate::error::TypecheckError;
+use siko_ir::class::ClassConstraint;
+use siko_ir::class::ClassId;
 use siko_ir::program::Program;
 use siko_ir::type_signature::TypeSignature;
 use siko_ir::type_signature::TypeSignatureId;
@@ -44,6 +46,20 @@ pub fn create_general_function_type_info(
     }
 }
 
+pub fn process_class_constraints(
+    constraints: &Vec<ClassConstraint>,
+    program: &Program,
+    type_var_generator: &mut TypeVarGenerator,
+) -> Vec<(ClassId, Type)> {
+    constraints
+        .iter()
+        .map(|constraint| {
+            let ty = process_type_signature(constraint.type_signature, program, type_var_generator);
+            (constraint.class_id, ty)
+        })
+        .collect()
+}
+
 pub fn process_type_signature(
     type_signature_id: TypeSignatureId,
     program: &Program,
@@ -76,6 +92,14 @@ pub fn process_type_signature(
             constraints.sort();
             Type::FixedTypeArg(name.clone(), *index, constraints)
         }
+        TypeSignature::Application(head, items) => {
+            let head = process_type_signature(*head, program, type_var_generator);
+            let items: Vec<_> = items
+                .iter()
+                .map(|item| process_type_signature(*item, program, type_var_generator))
+                .collect();
+            Type::apply_args(head, items)
+        }
         TypeSignature::Variant(..) => panic!("Variant should not appear here"),
         TypeSignature::Wildcard => type_var_generator.get_new_type_var(),
     }
@@ -1,12 +1,14 @@
 use std::collections::BTreeSet;
 use std::env;
 use std::fs;
+use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
+use std::process::Stdio;
 use walkdir::WalkDir;
 
-fn process_dir(arg: String, inputs: &mut Vec<(PathBuf, String)>) -> bool {
+fn process_dir(arg: String, test_file: &str, inputs: &mut Vec<(PathBuf, String)>) -> bool {
     let path = Path::new(&arg);
     if !path.exists() {
         let path_str = format!("{}", path.display());
@@ -18,7 +20,7 @@ fn process_dir(arg: String, inputs: &mut Vec<(PathBuf, String)>) -> bool {
             let entry = entry.unwrap();
             if entry.path().is_file() {
                 if let Some(filename) = entry.path().file_name() {
-                    if filename == "main.sk" {
+                    if filename == test_file {
                         let parent_dir = entry.path().parent().expect("Parent dir not found");
                         let testcase_name = parent_dir.file_name().expect("TC name not found");
                         let source_path = PathBuf::from(parent_dir);
@@ -39,11 +41,121 @@ fn process_dir(arg: String, inputs: &mut Vec<(PathBuf, String)>) -> bool {
 
 fn print_usage() {
     println!("Usage:");
-    println!("SikoTester SIKOC SIKO_STD COMP_DIR RUST_COMP_DIR SUCCESS_DIRFAIL_DIR");
+    println!("SikoTester SIKOC SIKO_STD COMP_DIR RUST_COMP_DIR SUCCESS_DIR FAIL_DIR [TOOL_DIR]");
+}
+
+// The last field of every line is a measured duration, it is replaced so the
+// output does not change between runs
+fn mask_durations(content: &str) -> String {
+    let mut masked = String::new();
+    for line in content.lines() {
+        match line.rfind(' ') {
+            Some(index) => masked += &format!("{} N\n", &line[..index]),
+            None => masked += &format!("{}\n", line),
+        }
+    }
+    masked
+}
+
+// Messages of the debug adapter protocol are framed by their length, the
+// lengths are computed again after the paths in the messages were replaced
+fn frame_messages(text: &str) -> String {
+    const HEADER: &str = "Content-Length: ";
+    if !text.starts_with(HEADER) {
+        return text.to_string();
+    }
+    let mut framed = String::new();
+    for message in text.split(HEADER).skip(1) {
+        let body = match message.find("\r\n\r\n") {
+            Some(index) => &message[index + 4..],
+            None => message,
+        };
+        framed += &format!("{}{}\r\n\r\n{}", HEADER, body.len(), body);
+    }
+    framed
+}
+
+// Runs siko with the arguments on the first line of the command file of the
+// test, the input file is piped into it. The following lines of the command
+// file select what goes into the .output file of the test: stdout, stderr or
+// files written into the scratch dir of the test, durations of the files
+// marked as timed are masked.
+fn run_tool_test(sikoc: &str, siko_std: &str, comp_dir: &str, t: &Path, tc_name: &str) -> bool {
+    let command = fs::read_to_string(t.join("command")).expect("command file read failed");
+    let scratch_dir = format!("{}/{}", comp_dir, tc_name);
+    let _ = fs::remove_dir_all(&scratch_dir);
+    fs::create_dir_all(&scratch_dir).expect("scratch dir create failed");
+    if t.join("main.sk").exists() {
+        fs::copy(t.join("main.sk"), format!("{}/main.sk", scratch_dir)).expect("copy failed");
+    }
+    let test_dir = format!("{}", t.display());
+    let mut lines = command.lines();
+    let args: Vec<_> = lines
+        .next()
+        .unwrap_or("")
+        .split_whitespace()
+        .map(|arg| {
+            arg.replace("{std}", siko_std)
+                .replace("{test}", &test_dir)
+                .replace("{dir}", &scratch_dir)
+        })
+        .collect();
+    let input = fs::read(t.join("input")).unwrap_or_default();
+    let mut child = Command::new(sikoc)
+        .args(&args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to execute process");
+    child
+        .stdin
+        .take()
+        .expect("stdin not found")
+        .write_all(&input)
+        .expect("input write failed");
+    let output = child.wait_with_output().expect("failed to execute process");
+    // absolute paths depend on where the repository is
+    let mut replacements = Vec::new();
+    if let Ok(absolute_dir) = fs::canonicalize(t) {
+        replacements.push((format!("{}", absolute_dir.display()), "{test}"));
+    }
+    if let Ok(absolute_dir) = fs::canonicalize(&scratch_dir) {
+        replacements.push((format!("{}", absolute_dir.display()), "{dir}"));
+    }
+    let normalize = |text: &str| {
+        let mut text = text.to_string();
+        for (from, to) in &replacements {
+            text = text.replace(from, to);
+        }
+        frame_messages(&text)
+    };
+    let mut result = String::new();
+    for line in lines {
+        let mut parts = line.split_whitespace();
+        match (parts.next(), parts.next()) {
+            (Some("stdout"), _) => result += &normalize(&String::from_utf8_lossy(&output.stdout)),
+            (Some("stderr"), _) => result += &normalize(&String::from_utf8_lossy(&output.stderr)),
+            (Some(file), timed) => {
+                let path = format!("{}/{}", scratch_dir, file);
+                let content = fs::read_to_string(path).unwrap_or(format!("missing\n"));
+                let content = if timed == Some("timed") {
+                    mask_durations(&content)
+                } else {
+                    normalize(&content)
+                };
+                result += &format!("--- {}\n{}", file, content);
+            }
+            (None, _) => {}
+        }
+    }
+    let output_filename = format!("{}/{}.output", test_dir, tc_name);
+    fs::write(output_filename, result).expect("output file write failed");
+    output.status.success()
 }
 
 fn process_args(args: Vec<String>) -> bool {
-    if args.len() != 6 {
+    if args.len() != 6 && args.len() != 7 {
         print_usage();
         return false;
     }
@@ -54,9 +166,13 @@ fn process_args(args: Vec<String>) -> bool {
     let success_dir = args[4].clone();
     let fail_dir = args[5].clone();
     let mut success_files = Vec::new();
-    process_dir(success_dir, &mut success_files);
+    process_dir(success_dir, "main.sk", &mut success_files);
     let mut fail_files = Vec::new();
-    process_dir(fail_dir, &mut fail_files);
+    process_dir(fail_dir, "main.sk", &mut fail_files);
+    let mut tool_files = Vec::new();
+    if let Some(tool_dir) = args.get(6) {
+        process_dir(tool_dir.clone(), "command", &mut tool_files);
+    }
     let mut success_count = 0;
     let mut fail_count = 0;
     let mut failed_tcs = BTreeSet::new();
@@ -76,6 +192,11 @@ fn process_args(args: Vec<String>) -> bool {
         } else {
             print!("OK");
         }
+        if s.join("interpreter_only").exists() {
+            success_count += 1;
+            println!("");
+            continue;
+        }
         //println!("Compiling {}", s.display());
         let rs_output_file = format!("{}/{}.rs", comp_dir, tc_name);
         let rustc_output_file = format!("{}/{}", rust_comp_dir.clone(), tc_name);
@@ -141,6 +262,18 @@ fn process_args(args: Vec<String>) -> bool {
         }
     }
 
+    for (t, tc_name) in tool_files {
+        print!("TC-T: {} ", tc_name);
+        if run_tool_test(&sikoc, &siko_std, &comp_dir, &t, &tc_name) {
+            success_count += 1;
+            println!("OK");
+        } else {
+            fail_count += 1;
+            failed_tcs.insert(tc_name.clone());
+            println!("Fail")
+        }
+    }
+
     println!("Total: {}/{}", success_count + fail_count, fail_count);
     if !failed_tcs.is_empty() {
         for tc in failed_tcs {
@@ -4,8 +4,10 @@ set -e
 
 ./build.sh
 
+./siko fmt --check std
+
 mkdir -p comp
 mkdir -p rust_comp
 
 cd siko_tester
-cargo run -- ../siko ../std ../comp ../rust_comp ../tests/success/ ../tests/fail/
+cargo run -- ../siko ../std ../comp ../rust_comp ../tests/success/ ../tests/fail/ ../tests/tool/
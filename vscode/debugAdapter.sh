@@ -0,0 +1,3 @@
+#!/bin/sh
+# The debug adapter is built into the compiler, siko must be in the PATH
+exec siko dap